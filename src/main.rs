@@ -1,33 +1,198 @@
-use std::{
-    env::args,
-    fs,
-    io::{stdin, stdout},
-};
+use std::{env::args, fs, io, path::Path, process::ExitCode};
 
-use sustlang::{RunningScript, Script};
+use sustlang::{RunningScript, Sandbox, Script, ScriptOutcome};
 
-fn main() {
+fn parse_file(filename: &str) -> Option<Script> {
+    if filename.ends_with(".sustc") {
+        let bytes = match fs::read(filename) {
+            Ok(i) => i,
+            Err(e) => {
+                eprintln!("error: can't read `{}`: {}", filename, e);
+                return None;
+            }
+        };
+        return match Script::from_bytes(&bytes) {
+            Ok(i) => Some(i),
+            Err(e) => {
+                eprintln!("error ({:?}): {}", e, e);
+                None
+            }
+        };
+    }
+
+    // `-` is the script read from stdin
+    let parsed = if filename == "-" {
+        Script::parse_reader(io::stdin().lock())
+    } else {
+        match fs::read_to_string(filename) {
+            Ok(text) => Script::parse(text),
+            Err(e) => {
+                eprintln!("error: can't read `{}`: {}", filename, e);
+                return None;
+            }
+        }
+    };
+
+    match parsed {
+        Ok(i) => Some(i),
+        Err((e, c)) => {
+            eprintln!("error ({:?}): {} line: {}", e, e, c);
+            None
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn export(outcome: &ScriptOutcome, path: &str) -> bool {
+    if let Err(e) = outcome.export_globals(Path::new(path)) {
+        eprintln!("error ({:?}): {}", e, e);
+        return false;
+    }
+    true
+}
+
+#[cfg(not(feature = "serde"))]
+fn export(_outcome: &ScriptOutcome, _path: &str) -> bool {
+    eprintln!("error: --export-globals requires the serde feature");
+    false
+}
+
+const USAGE: &str = "usage: sustlang [--dry-run] [--export-globals file] [--set name=value] \
+[--globals file] [--sandbox name] script [args...]
+       sustlang compile script [output]";
+
+fn main() -> ExitCode {
     let args: Vec<String> = args().collect();
 
+    if args.get(1).is_some_and(|arg| arg == "compile") {
+        let Some(filename) = args.get(2).cloned() else {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        };
+        let output = match args.get(3) {
+            Some(i) => i.clone(),
+            None => Path::new(&filename)
+                .with_extension("sustc")
+                .to_string_lossy()
+                .to_string(),
+        };
+
+        let Some(script) = parse_file(&filename) else {
+            return ExitCode::FAILURE;
+        };
+        if let Err(e) = fs::write(&output, script.to_bytes()) {
+            eprintln!("error: can't write `{}`: {}", output, e);
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let mut args = &args[..];
+    let mut dry_run = false;
+    let mut export_globals = None;
+    let mut assignments = Vec::new();
+    let mut globals_files = Vec::new();
+    let mut sandbox = Sandbox::Full;
+    loop {
+        let Some(arg) = args.get(1) else {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        };
+        match arg.as_str() {
+            "--export-globals" | "--set" | "--globals" | "--sandbox" if args.len() < 3 => {
+                eprintln!("error: `{}` requires a value", arg);
+                return ExitCode::FAILURE;
+            }
+            "--dry-run" => {
+                dry_run = true;
+                args = &args[1..];
+            }
+            "--export-globals" => {
+                export_globals = Some(args[2].clone());
+                args = &args[2..];
+            }
+            "--set" => {
+                assignments.push(args[2].clone());
+                args = &args[2..];
+            }
+            "--globals" => {
+                globals_files.push(args[2].clone());
+                args = &args[2..];
+            }
+            "--sandbox" => {
+                sandbox = match Sandbox::from_name(&args[2]) {
+                    Some(i) => i,
+                    None => {
+                        eprintln!("error: unknown sandbox `{}`", args[2]);
+                        return ExitCode::FAILURE;
+                    }
+                };
+                args = &args[2..];
+            }
+            _ => break,
+        }
+    }
+
     let filename = args[1].clone();
     let args = args[1..].to_vec();
 
-    let script = match Script::parse(fs::read_to_string(filename).unwrap()) {
-        Ok(i) => i,
-        Err((e, c)) => {
-            println!("error ({:?}) line: {}", e, c);
-            return;
-        }
+    let script = match parse_file(&filename) {
+        Some(i) => i,
+        None => return ExitCode::FAILURE,
     };
 
-    let mut running_script = RunningScript::new(script);
-    running_script
-        .set_standard_vars(args, Box::new(stdout()), Box::new(stdin()))
-        .unwrap();
-    match running_script.run() {
-        Ok(_) => {}
-        Err((e, c)) => {
-            println!("error ({:?}) command: {:?}", e, c);
+    let mut builder = RunningScript::builder(script)
+        .args(args)
+        .script_path(&filename)
+        .sandbox(sandbox);
+    for file in globals_files {
+        match fs::read_to_string(&file) {
+            Ok(json) => builder = builder.globals_json(json),
+            Err(e) => {
+                eprintln!("error: can't read `{}`: {}", file, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    for assignment in assignments {
+        builder = builder.global_assignment(assignment);
+    }
+    if dry_run {
+        builder = builder.dry_run(true).audit_hook(|event| {
+            eprintln!(
+                "dry run: {} {} (line {})",
+                event.command.to_name(),
+                event.target,
+                event.line
+            );
+        });
+    }
+
+    let running_script = match builder.build() {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("error ({:?}): {}", e, e);
+            return ExitCode::FAILURE;
         }
     };
+    match running_script.run_with_traceback() {
+        Ok(outcome) => {
+            if let Some(path) = export_globals {
+                if !export(&outcome, &path) {
+                    return ExitCode::FAILURE;
+                }
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error ({:?}): {} command: {:?}", e.error, e, e.command);
+            for e in &e.thread_errors {
+                eprintln!(
+                    "thread error ({:?}): {} command: {:?}",
+                    e.error, e, e.command
+                );
+            }
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,31 @@
+use arboard::Clipboard;
+
+use super::script::ScriptError;
+
+use std::sync::Mutex;
+
+/// Clipboard of the interpreter, kept open so the copied text stays available
+/// to other applications while the program is running
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+fn with_clipboard<T>(
+    action: impl FnOnce(&mut Clipboard) -> Result<T, arboard::Error>,
+) -> Result<T, ScriptError> {
+    let mut clipboard = CLIPBOARD.lock().unwrap();
+    if clipboard.is_none() {
+        *clipboard = Some(Clipboard::new().map_err(|_| ScriptError::ClipboardError)?);
+    }
+    action(clipboard.as_mut().unwrap()).map_err(|_| ScriptError::ClipboardError)
+}
+
+/// Text in the clipboard, empty if there is no text
+pub fn clipboard_get() -> Result<String, ScriptError> {
+    with_clipboard(|clipboard| match clipboard.get_text() {
+        Err(arboard::Error::ContentNotAvailable) => Ok(String::new()),
+        text => text,
+    })
+}
+
+pub fn clipboard_set(text: &str) -> Result<(), ScriptError> {
+    with_clipboard(|clipboard| clipboard.set_text(text))
+}
@@ -1,1007 +1,3580 @@
-use bytebuffer::ByteBuffer;
+use rand::seq::SliceRandom;
 use rand::Rng;
-
-use crate::{variable, FileOutStream, IgnoreResult};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[cfg(feature = "notify")]
+use crate::notify;
+#[cfg(feature = "serial")]
+use crate::open_serial;
+#[cfg(feature = "mqtt")]
+use crate::MqttClient;
+#[cfg(feature = "clipboard")]
+use crate::{clipboard_get, clipboard_set};
+use crate::{
+    form_urlencode, format_time, hash_hex, http_request, multipart_body, network_time,
+    parse_http_url, parse_time, read_http_request, style_text, write_http_response, BufferStream,
+    FileOutMode, FileOutStream, FileStream, FileStreamMode, HttpResponse, IgnoreResult,
+};
+#[cfg(feature = "mail")]
+use crate::{send_mail, MailServer};
+#[cfg(feature = "gzip")]
+use crate::{GzipInStream, GzipOutStream};
 
 use super::super::command::CommandType;
-use super::super::script::{RunningScript, ScriptError};
+use super::super::script::{
+    Access, CallStack, Function, HttpSession, RateLimiter, RunningScript, ScriptError,
+    ScriptThread, Symbol, VarPath,
+};
 use super::super::var::{VarType, Variable};
 
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fmt::{self, Debug};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::{self, Stdio};
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
 use std::time::Duration;
-use std::{fs, thread};
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone)]
 pub struct Command {
     pub command_type: CommandType,
     pub args: Vec<String>,
     pub line: usize,
+    /// File the command came from if it is not the script itself (imported code),
+    /// `line` is the line in that file
+    pub file: Option<Arc<str>>,
+    /// `args` as variable paths, split and interned once when the command is created
+    paths: Arc<Vec<VarPath>>,
+}
+
+impl Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Command")
+            .field("command_type", &self.command_type)
+            .field("args", &self.args)
+            .field("line", &self.line)
+            .field("file", &self.file)
+            .finish()
+    }
+}
+
+/// How long `GET_NETWORK_TIME` waits for the answer of the server
+const NETWORK_TIME_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long `HTTP_GET` and `HTTP_REQUEST` wait for the connection and for each read
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Buffer size of `READ`, it reads at most this many bytes at once
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// One `read` of the stream, repeated if it was interrupted by a signal
+fn read_some(stream: &mut dyn Read, buffer: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match stream.read(buffer) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            read => return read,
+        }
+    }
+}
+
+/// `BREAK` in the loop body or in an `IF` inside it, see `Function::execute_block`
+fn is_loop_break(error: &(ScriptError, Command)) -> bool {
+    matches!(error.0, ScriptError::BreakOutsideLoopError)
+        && error.1.command_type == CommandType::Break
 }
 
 impl Command {
     pub fn new(command_type: CommandType, line: usize, args: Vec<String>) -> Command {
         Command {
             command_type,
+            paths: Arc::new(args.iter().map(|arg| VarPath::parse(arg)).collect()),
             args,
             line,
+            file: None,
         }
     }
 
-    pub fn execute(
+    /// Argument `index` as a variable path
+    fn path(&self, index: usize) -> Result<&VarPath, (ScriptError, Command)> {
+        self.paths
+            .get(index)
+            .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))
+    }
+
+    /// Where the command is written: `file:line` for imported code, `line N` for the script itself
+    pub fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}:{}", file, self.line),
+            None => format!("line {}", self.line),
+        }
+    }
+
+    /// Bytes read from a stream as a value of the type of `var`: `string`, `bytes` or `list[char]`
+    fn bytes_to_var(
+        &self,
+        buffer: Vec<u8>,
+        var: &Variable,
+    ) -> Result<Variable, (ScriptError, Command)> {
+        match var {
+            Variable::List(VarType::List(item_type), _) if **item_type == VarType::Char => {
+                Ok(Variable::from_list(
+                    Some(
+                        String::from_utf8(buffer)
+                            .map_err(|_| (ScriptError::StringUTF8Error, self.clone()))?
+                            .chars()
+                            .map(|f| Variable::from_char(Some(f)))
+                            .collect(),
+                    ),
+                    VarType::Char,
+                ))
+            }
+            Variable::String(_, _) => Ok(Variable::from_str(Some(
+                String::from_utf8(buffer)
+                    .map_err(|_| (ScriptError::StringUTF8Error, self.clone()))?,
+            ))),
+            Variable::Bytes(_, _) => Ok(Variable::from_bytes(Some(buffer))),
+            var => Err((
+                ScriptError::type_mismatch("string, bytes or list[char]", var),
+                self.clone(),
+            )),
+        }
+    }
+
+    /// Bytes to write from `var`: `string`, `char`, `bytes` or `list[char]`
+    fn var_to_bytes(&self, var: &Variable) -> Result<Vec<u8>, (ScriptError, Command)> {
+        if let Variable::Bytes(_, Some(bytes)) = var {
+            Ok(bytes.as_ref().clone())
+        } else if var.get_type() == VarType::List(Box::new(VarType::Char)) {
+            let mut text = String::new();
+            for ele in var.as_list().map_err(|f| (f, self.clone()))?.iter() {
+                text.push(ele.as_char().map_err(|f| (f, self.clone()))?);
+            }
+            Ok(text.into_bytes())
+        } else if let Variable::String(_, Some(string)) = var {
+            Ok(string.as_bytes().to_vec())
+        } else if let Variable::Char(_, Some(value)) = var {
+            Ok(value.to_string().into_bytes())
+        } else {
+            Err((
+                ScriptError::type_mismatch("string, char, bytes or list[char]", var),
+                self.clone(),
+            ))
+        }
+    }
+
+    /// Entries of `map[string,string]` sorted by key, so the output doesn't depend on the map order
+    fn string_pairs(
+        &self,
+        var: &Variable,
+    ) -> Result<Vec<(String, String)>, (ScriptError, Command)> {
+        let mut pairs = Vec::new();
+        for (key, value) in var.as_map().map_err(|f| (f, self.clone()))?.iter() {
+            pairs.push((
+                key.as_str().map_err(|f| (f, self.clone()))?,
+                value.as_str().map_err(|f| (f, self.clone()))?,
+            ));
+        }
+        pairs.sort();
+        Ok(pairs)
+    }
+
+    /// Request of `HTTP_GET` and `HTTP_REQUEST`, in a dry run nothing is sent and the answer
+    /// has status 0 and no body
+    fn send_http(
         &self,
-        script: Arc<Mutex<RunningScript>>,
+        script: &RunningScript,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+        session: Option<&HttpSession>,
+    ) -> Result<HttpResponse, (ScriptError, Command)> {
+        let (authority, _) = parse_http_url(url).map_err(|f| (f, self.clone()))?;
+        script.access(Access::NetworkClient, &authority, self)?;
+        if script.is_dry_run() {
+            return Ok(HttpResponse {
+                status: 0,
+                headers: HashMap::new(),
+                set_cookies: Vec::new(),
+                body: Vec::new(),
+            });
+        }
+
+        // headers of the command replace the session headers with the same name
+        let mut all_headers: Vec<(String, String)> = match session {
+            Some(session) => session
+                .request_headers()
+                .into_iter()
+                .filter(|(name, _)| !headers.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)))
+                .collect(),
+            None => Vec::new(),
+        };
+        all_headers.extend_from_slice(headers);
+
+        let response = http_request(method, url, &all_headers, body, HTTP_TIMEOUT)
+            .map_err(|f| (f, self.clone()))?;
+        if let Some(session) = session {
+            for set_cookie in &response.set_cookies {
+                session.store_cookie(set_cookie);
+            }
+        }
+        Ok(response)
+    }
+
+    /// Session of the optional `session_var` argument of `HTTP_GET` and `HTTP_REQUEST`
+    fn http_session_arg(
+        &self,
+        script: &RunningScript,
+        index: usize,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Option<Arc<HttpSession>>, (ScriptError, Command)> {
+        let Some(session_var) = self.args.get(index) else {
+            return Ok(None);
+        };
+        let session = script
+            .get_var(session_var.clone(), locals)
+            .map_err(|f| (f, self.clone()))?
+            .as_http_session()
+            .map_err(|f| (f, self.clone()))?;
+        Ok(Some(session))
+    }
+
+    /// Write the answer of `HTTP_GET` or `HTTP_REQUEST` to the result variables
+    fn set_http_response(
+        &self,
+        response: HttpResponse,
+        vars: (String, String, Option<String>),
+        script: &RunningScript,
         global: bool,
-        locals: &mut HashMap<String, Variable>,
-        temp_vars: &mut Vec<String>,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        let (status_var, body_var, headers_var) = vars;
+
+        let body_type = script
+            .get_var(body_var.clone(), locals)
+            .map_err(|f| (f, self.clone()))?;
+        let body = self.bytes_to_var(response.body, &body_type)?;
+
+        script
+            .set_var(
+                status_var,
+                Variable::from_int(Some(response.status as isize)),
+                global,
+                false,
+                locals,
+            )
+            .map_err(|f| (f, self.clone()))?;
+        script
+            .set_var(body_var, body, global, false, locals)
+            .map_err(|f| (f, self.clone()))?;
+        if let Some(headers_var) = headers_var.filter(|name| name != "null") {
+            let headers = response
+                .headers
+                .into_iter()
+                .map(|(k, v)| (Variable::from_str(Some(k)), Variable::from_str(Some(v))))
+                .collect();
+            script
+                .set_var(
+                    headers_var,
+                    Variable::from_map(Some(headers), VarType::String, VarType::String),
+                    global,
+                    false,
+                    locals,
+                )
+                .map_err(|f| (f, self.clone()))?;
+        }
+        Ok(())
+    }
+
+    /// Accept clients on `addr_var:port_var` (the first two arguments) of `OPEN_TCP_LISTENER`
+    /// or `OPEN_HTTP_LISTENER`, every client is passed to `handle` in a separate thread.
+    /// Limits `[max_connections_var]` and `[backlog_var]` are the arguments after the function.
+    /// Errors of `handle` are written to `cerr`.
+    fn listen(
+        &self,
+        script: Arc<RunningScript>,
+        locals: &mut HashMap<Symbol, Variable>,
+        handle: impl Fn(Arc<RunningScript>, TcpStream) -> Result<(), (ScriptError, Command)>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<(), (ScriptError, Command)> {
+        let handle = Arc::new(handle);
+        let addr_var = self.path(0)?;
+        let port_var = self.path(1)?;
+
+        let addr = script
+            .get_path(addr_var, locals)
+            .map_err(|f| (f, self.clone()))?
+            .as_str()
+            .map_err(|f| (f, self.clone()))?;
+        let port = script
+            .get_path(port_var, locals)
+            .map_err(|f| (f, self.clone()))?
+            .as_int()
+            .map_err(|f| (f, self.clone()))?;
+
+        let mut limits = Vec::new();
+        for name in self.args.get(3..).unwrap_or_default() {
+            let limit = script
+                .get_var(name.clone(), locals)
+                .map_err(|f| (f, self.clone()))?
+                .as_int()
+                .map_err(|f| (f, self.clone()))?;
+            if limit < 0 {
+                return Err((ScriptError::RangeError, self.clone()));
+            }
+            limits.push(limit as usize);
+        }
+        let max_connections = limits.first().copied().unwrap_or(0);
+        let backlog = limits.get(1).copied().unwrap_or(0);
+
+        let port = u16::try_from(port).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+        script.access(Access::NetworkServer, &format!("{}:{}", addr, port), self)?;
+        // no clients come in a dry run, so the listener stops right away
+        if script.is_dry_run() {
+            return Ok(());
+        }
+        let listener = TcpListener::bind((addr.as_str(), port))
+            .map_err(|_| (ScriptError::NetworkError, self.clone()))?;
+
+        // (active connections, connections waiting for a free slot)
+        let queue: Arc<Mutex<(usize, VecDeque<TcpStream>)>> =
+            Arc::new(Mutex::new((0, VecDeque::new())));
+
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+
+            {
+                let mut queue = queue.lock().unwrap();
+                if max_connections != 0 && queue.0 >= max_connections {
+                    // a client beyond the backlog is disconnected, as the docs of the commands say
+                    if queue.1.len() < backlog {
+                        queue.1.push_back(stream);
+                    }
+                    continue;
+                }
+                queue.0 += 1;
+            }
+
+            let handle = handle.clone();
+            let local_script = script.clone();
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let mut stream = Some(stream);
+                while let Some(client) = stream {
+                    if let Err(error) = handle(local_script.clone(), client) {
+                        local_script.report_error(&error);
+                    }
+
+                    let mut queue = queue.lock().unwrap();
+                    stream = queue.1.pop_front();
+                    if stream.is_none() {
+                        queue.0 -= 1;
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Call `accept_func(string,int,in_stream,out_stream)` of `OPEN_TCP_LISTENER` for one client
+    fn accept_tcp_client(
+        func: &Function,
+        script: Arc<RunningScript>,
+        stream: TcpStream,
+    ) -> Result<(), (ScriptError, Command)> {
+        let command = Command::new(CommandType::OpenTcpListener, 0, vec![func.name.clone()]);
+
+        let peer = stream
+            .peer_addr()
+            .map_err(|_| (ScriptError::NetworkError, command.clone()))?;
+        let (in_stream, out_stream) = script
+            .get_network_streams()
+            .register(stream)
+            .map_err(|f| (f, command.clone()))?;
+
+        func.execute(
+            script,
+            "null".to_string(),
+            vec![
+                Variable::from_str(Some(peer.ip().to_string())),
+                Variable::from_int(Some(peer.port() as isize)),
+                in_stream,
+                out_stream,
+            ],
+            false,
+        )?;
+
+        Ok(())
+    }
+
+    /// Timeout in milliseconds from the `int` variable `name`, `0` means no timeout
+    fn timeout_millis(
+        &self,
+        script: &RunningScript,
+        name: &str,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Option<Duration>, (ScriptError, Command)> {
+        let millis = script
+            .get_var(name.to_string(), locals)
+            .map_err(|f| (f, self.clone()))?
+            .as_int()
+            .map_err(|f| (f, self.clone()))?;
+        let millis = u64::try_from(millis).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+        Ok((millis > 0).then(|| Duration::from_millis(millis)))
+    }
+
+    /// Connect to the first address of `host:port` that answers, `timeout` is for each address
+    fn connect_tcp(
+        &self,
+        host: &str,
+        port: u16,
+        timeout: Option<Duration>,
+    ) -> Result<TcpStream, (ScriptError, Command)> {
+        let addrs = (host, port)
+            .to_socket_addrs()
+            .map_err(|_| (ScriptError::DnsError(host.to_string()), self.clone()))?;
+
+        let mut error = ScriptError::DnsError(host.to_string());
+        for addr in addrs {
+            let stream = match timeout {
+                Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                None => TcpStream::connect(addr),
+            };
+            match stream {
+                Ok(stream) => return Ok(stream),
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                    error = ScriptError::StreamTimeoutError
+                }
+                Err(_) => error = ScriptError::NetworkError,
+            }
+        }
+        Err((error, self.clone()))
+    }
+
+    /// Read one request of `OPEN_HTTP_LISTENER`, call `handler_func(string,string,map[string,string],string)`
+    /// with it and write the result of the function as the response
+    fn accept_http_client(
+        func: &Function,
+        script: Arc<RunningScript>,
+        mut stream: TcpStream,
+    ) -> Result<(), (ScriptError, Command)> {
+        let command = Command::new(CommandType::OpenHttpListener, 0, vec![func.name.clone()]);
+
+        let mut reader = BufReader::new(
+            stream
+                .try_clone()
+                .map_err(|_| (ScriptError::NetworkError, command.clone()))?,
+        );
+        let request = read_http_request(&mut reader).map_err(|f| (f, command.clone()))?;
+        let Ok(body) = String::from_utf8(request.body) else {
+            return write_http_response(&mut stream, 400, &[], b"").map_err(|f| (f, command));
+        };
+
+        let headers = request
+            .headers
+            .into_iter()
+            .map(|(k, v)| (Variable::from_str(Some(k)), Variable::from_str(Some(v))))
+            .collect();
+        let result = func.execute(
+            script,
+            "null".to_string(),
+            vec![
+                Variable::from_str(Some(request.method)),
+                Variable::from_str(Some(request.path)),
+                Variable::from_map(Some(headers), VarType::String, VarType::String),
+                Variable::from_str(Some(body)),
+            ],
+            false,
+        );
+
+        let mut status = 200;
+        let mut headers = Vec::new();
+        let mut body = Vec::new();
+        let response = result.and_then(|response| {
+            for (key, value) in command.string_pairs(&response)? {
+                match key.as_str() {
+                    "status" => {
+                        status = value
+                            .parse()
+                            .ok()
+                            .filter(|status| (100..1000).contains(status))
+                            .ok_or_else(|| (ScriptError::ParseVarError, command.clone()))?;
+                    }
+                    "body" => body = value.into_bytes(),
+                    _ => headers.push((key, value)),
+                }
+            }
+            Ok(())
+        });
+
+        if let Err(error) = response {
+            write_http_response(&mut stream, 500, &[], b"").ignore();
+            return Err(error);
+        }
+        write_http_response(&mut stream, status, &headers, &body).map_err(|f| (f, command))
+    }
+
+    /// `SET_ADD`, `SET_REMOVE`, `SET_HAS`, `SET_UNION` and `SET_INTERSECT`
+    fn execute_set(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
     ) -> Result<(), (ScriptError, Command)> {
         match self.command_type {
-            CommandType::InitVar => {
-                let type_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let type_var = VarType::from_name(&type_var).map_err(|f| (f, self.clone()))?;
-                let name_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::SetAdd | CommandType::SetRemove => {
+                let set_var_name = self.path(0)?;
+                let value_var = self.path(1)?;
+
+                let set_var = script
+                    .get_path(set_var_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let set = set_var.as_set().map_err(|f| (f, self.clone()))?;
+                let value_type = set_var.get_set_type().map_err(|f| (f, self.clone()))?;
+                let value = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                if value.get_type() != value_type {
+                    return Err((
+                        ScriptError::type_mismatch(value_type.to_name(), &value),
+                        self.clone(),
+                    ));
+                }
 
+                // the variable holds the set too, empty it so the set isn't copied
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        name_var,
-                        Variable::empty_var(type_var).map_err(|f| (f, self.clone()))?,
+                    .set_path(
+                        set_var_name,
+                        Variable::from_set(None, value_type.clone()),
                         global,
-                        true,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+                drop(set_var);
+                let mut set = Arc::unwrap_or_clone(set);
+                if let CommandType::SetAdd = self.command_type {
+                    set.insert(value);
+                } else {
+                    set.remove(&value);
+                }
+
+                script
+                    .set_path(
+                        set_var_name,
+                        Variable::from_set(Some(set), value_type),
+                        global,
+                        false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::SetVar => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let value_var = self.args[1..].join(" ");
+            CommandType::SetHas => {
+                let set_var = self.path(0)?;
+                let value_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
-                let type_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(name_var.clone(), &mut locals.clone())
+                let set = script
+                    .get_path(set_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .get_type();
-                let var =
-                    Variable::parse_var(type_var, value_var).map_err(|f| (f, self.clone()))?;
+                    .as_set()
+                    .map_err(|f| (f, self.clone()))?;
+                let value = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(name_var, var, global, false, locals)
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(set.contains(&value))),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::TempVar => {
-                let type_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let name_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let value_var = self.args[2..].join(" ");
+            CommandType::SetUnion | CommandType::SetIntersect => {
+                let set_var = self.path(0)?;
+                let other_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let set_var = script
+                    .get_path(set_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let set = set_var.as_set().map_err(|f| (f, self.clone()))?;
+                let value_type = set_var.get_set_type().map_err(|f| (f, self.clone()))?;
+                let other_var = script
+                    .get_path(other_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                if other_var.get_type() != set_var.get_type() {
+                    return Err((
+                        ScriptError::type_mismatch(set_var.get_type().to_name(), &other_var),
+                        self.clone(),
+                    ));
+                }
+                let other = other_var.as_set().map_err(|f| (f, self.clone()))?;
+
+                let result = if let CommandType::SetUnion = self.command_type {
+                    set.union(&other).cloned().collect()
+                } else {
+                    set.intersection(&other).cloned().collect()
+                };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        name_var.clone(),
-                        Variable::parse_var(
-                            VarType::from_name(&type_var).map_err(|f| (f, self.clone()))?,
-                            value_var,
-                        )
-                        .map_err(|f| (f, self.clone()))?,
+                    .set_path(
+                        result_var,
+                        Variable::from_set(Some(result), value_type),
                         global,
-                        true,
+                        false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
-
-                temp_vars.push(name_var);
             }
-            CommandType::MoveVar => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let target_var = self
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// `COUNT_IF`, `SUM_LIST` and `AVG_LIST`
+    fn execute_aggregate(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::CountIf => {
+                let list_var = self.path(0)?;
+                let func_name = self
                     .args
                     .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let result_var = self.path(2)?;
 
-                let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var.clone(), locals)
+                let list_var = script
+                    .get_path(list_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_list()
                     .map_err(|f| (f, self.clone()))?;
 
-                script
-                    .lock()
-                    .unwrap()
-                    .set_var(target_var, var, global, false, locals)
+                let func = script
+                    .resolve_function(func_name, locals)
                     .map_err(|f| (f, self.clone()))?;
+
+                let mut count = 0;
+
+                for i in list_var.iter() {
+                    if func
+                        .execute(script.clone(), "null".to_string(), vec![i.clone()], false)?
+                        .as_bool()
+                        .map_err(|f| (f, self.clone()))?
+                    {
+                        count += 1;
+                    }
+                }
+
                 script
-                    .lock()
-                    .unwrap()
-                    .drop_var(source_var, locals)
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(count)),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::CopyVar => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let target_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::SumList => {
+                let list_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var.clone(), locals)
+                let list_var = script
+                    .get_path(list_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
+                let result = match list_var.get_list_type().map_err(|f| (f, self.clone()))? {
+                    VarType::Integer => {
+                        let mut sum = 0;
+                        for i in list_var.as_list().map_err(|f| (f, self.clone()))?.iter() {
+                            sum += i.as_int().map_err(|f| (f, self.clone()))?;
+                        }
+                        Variable::from_int(Some(sum))
+                    }
+                    VarType::Float => {
+                        let mut sum = 0.0;
+                        for i in list_var.as_list().map_err(|f| (f, self.clone()))?.iter() {
+                            sum += i.as_float().map_err(|f| (f, self.clone()))?;
+                        }
+                        Variable::from_float(Some(sum))
+                    }
+                    _ => {
+                        return Err((
+                            ScriptError::type_mismatch("list[integer] or list[float]", &list_var),
+                            self.clone(),
+                        ));
+                    }
+                };
+
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(target_var, var, global, false, locals)
+                    .set_path(result_var, result, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::DropVar => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::AvgList => {
+                let list_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                script
-                    .lock()
-                    .unwrap()
-                    .drop_var(name_var, locals)
+                let list_var = script
+                    .get_path(list_var, locals)
                     .map_err(|f| (f, self.clone()))?;
-            }
-            CommandType::HasVar => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let list_type = list_var.get_list_type().map_err(|f| (f, self.clone()))?;
+                let list_var = list_var.as_list().map_err(|f| (f, self.clone()))?;
+
+                if list_type != VarType::Integer && list_type != VarType::Float {
+                    return Err((
+                        ScriptError::TypeMismatchError {
+                            expected: String::from("list[integer] or list[float]"),
+                            actual: VarType::List(Box::new(list_type.clone())).to_name(),
+                        },
+                        self.clone(),
+                    ));
+                }
 
-                let result = script.lock().unwrap().get_var(name_var, locals).is_ok();
+                let mut sum = 0.0;
+                for i in list_var.iter() {
+                    sum += match list_type {
+                        VarType::Integer => i.as_int().map_err(|f| (f, self.clone()))? as f64,
+                        VarType::Float => i.as_float().map_err(|f| (f, self.clone()))?,
+                        _ => {
+                            return Err((
+                                ScriptError::type_mismatch("integer or float", i),
+                                self.clone(),
+                            ));
+                        }
+                    };
+                }
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        Variable::from_bool(Some(result)),
+                        Variable::from_float(Some(sum / list_var.len() as f64)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::AddStr => {
-                let var_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            _ => unreachable!(),
+        }
 
-                let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?;
-                let other_var: String = if let Variable::List(VarType::Char, Some(list)) = other_var
-                {
-                    let mut bytes = Vec::new();
-                    for ele in list {
-                        bytes.push(ele.as_char().map_err(|f| (f, self.clone()))?);
-                    }
-                    String::from_utf8(bytes)
-                        .or(Err(ScriptError::StringUTF8Error))
-                        .map_err(|f| (f, self.clone()))?
-                } else if let Variable::String(_, Some(string)) = other_var {
-                    string
-                } else if let Variable::Char(_, Some(value)) = other_var {
-                    String::from_utf8(vec![value])
-                        .or(Err(ScriptError::StringUTF8Error))
-                        .map_err(|f| (f, self.clone()))?
-                } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
+        Ok(())
+    }
+
+    /// Time, date, duration and timer commands
+    fn execute_time(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::TimeNow => {
+                let result_var = self.path(0)?;
+
+                let now = script.get_clock().now_millis();
+                let now = match script.get_path(result_var, locals) {
+                    Ok(Variable::DateTime(_, _)) => Variable::from_date_time(Some(now as i64)),
+                    _ => Variable::from_int(Some(now as isize)),
                 };
 
-                let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var_name.clone(), locals)
+                script
+                    .set_path(result_var, now, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::TimeFormat => {
+                let time_var = self.path(0)?;
+                let pattern_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let time = script
+                    .get_path(time_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let pattern = script
+                    .get_path(pattern_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
+                let text = format_time(time as i64, &pattern)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?;
+
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        var_name.clone(),
-                        Variable::from_str(Some(var.clone() + &other_var)),
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(text)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::Write => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-
-                let text = script
-                    .lock()
-                    .unwrap()
-                    .get_var(name_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?;
-                let text: Vec<u8> = if let Variable::List(VarType::Char, Some(list)) = text {
-                    let mut bytes = Vec::new();
-                    for ele in list {
-                        bytes.push(ele.as_char().map_err(|f| (f, self.clone()))?);
-                    }
-                    bytes
-                } else if let Variable::String(_, Some(string)) = text {
-                    string.as_bytes().to_vec()
-                } else if let Variable::Char(_, Some(value)) = text {
-                    vec![value]
-                } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
-                };
+            CommandType::TimeDiff => {
+                let end_var = self.path(0)?;
+                let start_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
-                let stream = script
-                    .lock()
-                    .unwrap()
-                    .get_var(stream_var.clone(), locals)
+                let end = script
+                    .get_path(end_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_out_stream()
+                    .as_int()
                     .map_err(|f| (f, self.clone()))?;
-                stream.lock().unwrap().write_all(&text).unwrap();
-            }
-            CommandType::UseFunc => {
-                let func_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_name = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let args_names = self.args[2..].to_vec();
-
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
+                let start = script
+                    .get_path(start_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
                     .map_err(|f| (f, self.clone()))?;
 
-                let mut args = Vec::new();
-                for name in args_names {
-                    args.push(
-                        script
-                            .lock()
-                            .unwrap()
-                            .get_var(name, locals)
-                            .map_err(|f| (f, self.clone()))?,
-                    );
-                }
+                let diff = end
+                    .checked_sub(start)
+                    .ok_or_else(|| (ScriptError::RangeError, self.clone()))?;
 
-                func.execute(script.clone(), result_name, args, false)?;
-            }
-            CommandType::Return => {
-                return Ok(());
-            }
-            CommandType::For => {
-                let func_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let start_index = script
-                    .lock()
-                    .unwrap()
-                    .get_var(
-                        self.args
-                            .get(1)
-                            .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                            .clone(),
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(diff)),
+                        global,
+                        false,
                         locals,
                     )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::DateParse => {
+                let text_var = self.path(0)?;
+                let pattern_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let text = script
+                    .get_path(text_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_int()
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
-                let end_index = script
-                    .lock()
-                    .unwrap()
-                    .get_var(
-                        self.args
-                            .get(2)
-                            .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                            .clone(),
-                        locals,
-                    )
+                let pattern = script
+                    .get_path(pattern_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_int()
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
-                    .map_err(|f| (f, self.clone()))?;
+                let date = parse_time(&text, &pattern)
+                    .ok_or_else(|| (ScriptError::ParseVarError, self.clone()))?;
 
-                for index in start_index..=end_index {
-                    func.execute(
-                        script.clone(),
-                        "null".to_string(),
-                        vec![Variable::from_int(Some(index))],
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_date_time(Some(date)),
+                        global,
                         false,
-                    )?;
-                }
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ToString => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::DateFormat => {
+                let date_var = self.path(0)?;
+                let pattern_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
-                let source_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var, locals)
+                let date = script
+                    .get_path(date_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_date_time()
+                    .map_err(|f| (f, self.clone()))?;
+                let pattern = script
+                    .get_path(pattern_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
-                let result = source_var.to_string().map_err(|f| (f, self.clone()))?;
+                let text = format_time(date, &pattern)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        Variable::from_str(Some(result)),
+                        Variable::from_str(Some(text)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ToChars => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
+            CommandType::DateAdd => {
+                let date_var = self.path(0)?;
+                let millis_var = self
                     .args
                     .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
-                let source_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var, locals)
+                let date = script
+                    .get_path(date_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_date_time()
                     .map_err(|f| (f, self.clone()))?;
-
-                let result = source_var
-                    .as_str()
+                let millis = match script
+                    .get_var(millis_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_bytes()
-                    .iter()
-                    .map(|f| Variable::from_char(Some(*f)))
-                    .collect();
-                let result =
-                    Variable::from_list(Some(result), VarType::List(Box::new(VarType::Char)));
+                {
+                    Variable::Integer(_, Some(v)) => v as i64,
+                    Variable::Duration(_, Some(v)) => v,
+                    millis_var => {
+                        return Err((
+                            ScriptError::type_mismatch("integer or duration", &millis_var),
+                            self.clone(),
+                        ));
+                    }
+                };
+
+                let date = date
+                    .checked_add(millis)
+                    .ok_or_else(|| (ScriptError::RangeError, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(result_var, result, global, false, locals)
+                    .set_path(
+                        date_var,
+                        Variable::from_date_time(Some(date)),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ToInteger => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
+            CommandType::DurationMs | CommandType::DurationSec | CommandType::DurationMin => {
+                let value_var = self
                     .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let result_var = self.path(1)?;
 
-                let source_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var, locals)
+                let unit = match self.command_type {
+                    CommandType::DurationSec => 1000,
+                    CommandType::DurationMin => 60_000,
+                    _ => 1,
+                };
+                let millis = match script
+                    .get_var(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                {
+                    Variable::Integer(_, Some(v)) => (v as i64).checked_mul(unit),
+                    Variable::Float(_, Some(v)) => {
+                        let millis = (v * unit as f64).round();
+                        (millis.is_finite() && millis.abs() < i64::MAX as f64)
+                            .then_some(millis as i64)
+                    }
+                    value_var => {
+                        return Err((
+                            ScriptError::type_mismatch("integer or float", &value_var),
+                            self.clone(),
+                        ));
+                    }
+                }
+                .ok_or_else(|| (ScriptError::RangeError, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_duration(Some(millis)),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::DurationToMs => {
+                let duration_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let result = source_var
-                    .as_str()
+                let millis = script
+                    .get_path(duration_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .parse::<isize>()
-                    .or(Err(ScriptError::ParseVarError))
+                    .as_duration()
                     .map_err(|f| (f, self.clone()))?;
-                let result = Variable::from_int(Some(result));
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(result_var, result, global, false, locals)
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(millis as isize)),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ToFloat => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
+            CommandType::Measure => {
+                let func_name = self
                     .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let result_var = self.path(1)?;
 
-                let source_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var, locals)
+                let func = script
+                    .resolve_function(func_name, locals)
                     .map_err(|f| (f, self.clone()))?;
 
-                let result = source_var
+                let clock = script.get_clock();
+                let start = clock.monotonic_millis();
+                func.execute(script.clone(), "null".to_string(), vec![], false)?;
+                let elapsed = clock.monotonic_millis().saturating_sub(start);
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(elapsed as isize)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::TimerStart => {
+                let name_var = self.path(0)?;
+
+                let name = script
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?
                     .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let now = script.get_clock().monotonic_millis();
+                script.get_timers().lock().unwrap().insert(name, now);
+            }
+            CommandType::TimerStop => {
+                let name_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let name = script
+                    .get_path(name_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .parse::<f64>()
-                    .or(Err(ScriptError::ParseVarError))
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
-                let result = Variable::from_float(Some(result));
 
-                script
+                let start = script
+                    .get_timers()
                     .lock()
                     .unwrap()
-                    .set_var(result_var, result, global, false, locals)
+                    .remove(&name)
+                    .ok_or_else(|| (ScriptError::KeyNotFoundError(name), self.clone()))?;
+                let elapsed = script.get_clock().monotonic_millis().saturating_sub(start);
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(elapsed as isize)),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ToBool => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Threads, locks, atomics and rate limits
+    fn execute_thread(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::NewThread
+            | CommandType::NewThreadHandle
+            | CommandType::NewIsolatedThread => {
+                let func_name = self
                     .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
-                let source_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var, locals)
-                    .map_err(|f| (f, self.clone()))?;
+                let (thread_var, args_names) = match self.command_type {
+                    CommandType::NewThread => (
+                        "null".to_string(),
+                        self.args.get(1..).unwrap_or_default().to_vec(),
+                    ),
+                    _ => (
+                        self.args.get(1).cloned().unwrap_or("null".to_string()),
+                        self.args.get(2..).unwrap_or_default().to_vec(),
+                    ),
+                };
 
-                let result = if let Variable::List(_, Some(value)) = source_var {
-                    !value.is_empty()
-                } else if let Variable::String(_, Some(value)) = source_var {
-                    value == "true" || value == "1"
-                } else if let Variable::Char(_, Some(value)) = source_var {
-                    value != 0
-                } else if let Variable::Integer(_, Some(value)) = source_var {
-                    value != 0
-                } else if let Variable::Float(_, Some(value)) = source_var {
-                    value != 0.0
-                } else if let Variable::Bool(_, Some(value)) = source_var {
-                    value
-                } else if let Variable::Map(_, Some(value)) = source_var {
-                    !value.is_empty()
-                } else if let Variable::Optional(_, Some(value)) = source_var {
-                    value.is_some()
-                } else if let Variable::Null(_) = source_var {
-                    false
-                } else if let Variable::OutStream(_, Some(_)) = source_var {
-                    true
-                } else if let Variable::InStream(_, Some(_)) = source_var {
-                    true
-                } else {
-                    false
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .clone();
+
+                let mut args = Vec::new();
+                for name in args_names {
+                    args.push(
+                        script
+                            .get_var(name, locals)
+                            .map_err(|f| (f, self.clone()))?,
+                    );
+                }
+
+                let local_script = match self.command_type {
+                    CommandType::NewIsolatedThread => Arc::new(script.isolated()),
+                    _ => script.clone(),
                 };
+                let handle = Arc::new(Mutex::new(ScriptThread::default()));
+                let local_handle = handle.clone();
+                let join_handle = thread::spawn(move || {
+                    if let Err(error) = func.execute(local_script, "null".to_string(), args, false)
+                    {
+                        let mut thread = local_handle.lock().unwrap();
+                        thread.error = Some(error);
+                        thread.traceback = CallStack::take();
+                    }
+                });
 
-                script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        result_var,
-                        Variable::from_bool(Some(result)),
-                        global,
-                        false,
-                        locals,
-                    )
+                handle.lock().unwrap().handle = Some(join_handle);
+                script.add_thread(handle.clone());
+
+                if thread_var != "null" {
+                    script
+                        .set_var(
+                            thread_var,
+                            Variable::from_thread(Some(handle)),
+                            global,
+                            false,
+                            locals,
+                        )
+                        .map_err(|f| (f, self.clone()))?;
+                }
+            }
+            CommandType::JoinThread => {
+                let thread_var = self.path(0)?;
+
+                let handle = script
+                    .get_path(thread_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_thread()
                     .map_err(|f| (f, self.clone()))?;
+
+                let join_handle = handle.lock().unwrap().handle.take();
+                if let Some(join_handle) = join_handle {
+                    join_handle
+                        .join()
+                        .map_err(|_| (ScriptError::ThreadPanicError, self.clone()))?;
+                }
+
+                let error = handle.lock().unwrap().take_error();
+                if let Some(error) = error {
+                    return Err(error);
+                }
             }
-            CommandType::ToChar => {
-                let source_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::ThreadIsFinished => {
+                let thread_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let source_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(source_var, locals)
+                let handle = script
+                    .get_path(thread_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_thread()
                     .map_err(|f| (f, self.clone()))?;
 
-                let result = if let Variable::String(_, Some(value)) = source_var {
-                    value.as_bytes()[0]
-                } else if let Variable::Char(_, Some(value)) = source_var {
-                    value
-                } else if let Variable::Integer(_, Some(value)) = source_var {
-                    value as u8
-                } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
-                };
+                let finished = handle.lock().unwrap().is_finished();
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        Variable::from_char(Some(result)),
+                        Variable::from_bool(Some(finished)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::GetSymbol => {
-                let str_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let index_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-
-                let str_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(str_var, locals)
-                    .map_err(|f| (f, self.clone()))?;
-                let index_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(index_var, locals)
-                    .map_err(|f| (f, self.clone()))?;
-
-                let index = index_var.as_int().map_err(|f| (f, self.clone()))?;
-
-                let result = if let Variable::String(_, Some(value)) = str_var {
-                    value.as_bytes()[index as usize]
-                } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
-                };
+            CommandType::LockNew => {
+                let lock_var = self.path(0)?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        result_var,
-                        Variable::from_char(Some(result)),
+                    .set_path(
+                        lock_var,
+                        Variable::from_lock(Some(Arc::new((Mutex::new(false), Condvar::new())))),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::GetItem => {
-                let list_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let index_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::LockAcquire => {
+                let lock_var = self.path(0)?;
 
-                let list_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(list_var, locals)
+                let lock = script
+                    .get_path(lock_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_lock()
                     .map_err(|f| (f, self.clone()))?;
-                let index_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(index_var, locals)
+
+                let (locked, condvar) = &*lock;
+                let mut locked = locked.lock().unwrap();
+                while *locked {
+                    locked = condvar.wait(locked).unwrap();
+                }
+                *locked = true;
+            }
+            CommandType::LockRelease => {
+                let lock_var = self.path(0)?;
+
+                let lock = script
+                    .get_path(lock_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_lock()
                     .map_err(|f| (f, self.clone()))?;
 
-                let index = index_var.as_int().map_err(|f| (f, self.clone()))?;
+                let (locked, condvar) = &*lock;
+                *locked.lock().unwrap() = false;
+                condvar.notify_one();
+            }
+            CommandType::AtomicNew => {
+                let atomic_var = self.path(0)?;
 
-                let result = if let Variable::List(_, Some(value)) = list_var {
-                    value[index as usize].clone()
-                } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
+                let value = match self.args.get(1) {
+                    Some(value_var) => script
+                        .get_var(value_var.clone(), locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_int()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => 0,
                 };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(result_var, result, global, false, locals)
+                    .set_path(
+                        atomic_var,
+                        Variable::from_atomic(Some(Arc::new(AtomicIsize::new(value)))),
+                        global,
+                        false,
+                        locals,
+                    )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::GetValue => {
-                let map_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let key_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::AtomicAdd => {
+                let atomic_var = self.path(0)?;
+                let value_var = self.path(1)?;
 
-                let map_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(map_var, locals)
+                let atomic = script
+                    .get_path(atomic_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_atomic()
                     .map_err(|f| (f, self.clone()))?;
-                let key_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(key_var, locals)
+                let value = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
                     .map_err(|f| (f, self.clone()))?;
 
-                let result = if let Variable::Map(_, Some(value)) = map_var {
-                    value[&key_var].clone()
-                } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
-                };
+                // `fetch_add` wraps around on overflow, the new value has to wrap the same way
+                let result = atomic
+                    .fetch_add(value, Ordering::SeqCst)
+                    .wrapping_add(value);
 
-                script
-                    .lock()
-                    .unwrap()
-                    .set_var(result_var, result, global, false, locals)
-                    .map_err(|f| (f, self.clone()))?;
+                if let Some(result_var) = self.args.get(2) {
+                    script
+                        .set_var(
+                            result_var.clone(),
+                            Variable::from_int(Some(result)),
+                            global,
+                            false,
+                            locals,
+                        )
+                        .map_err(|f| (f, self.clone()))?;
+                }
             }
-            CommandType::ListSize => {
-                let list_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::AtomicGet => {
+                let atomic_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let list_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(list_var, locals)
+                let atomic = script
+                    .get_path(atomic_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_atomic()
                     .map_err(|f| (f, self.clone()))?;
-                let list_size = list_var.as_list().map_err(|f| (f, self.clone()))?.len();
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        Variable::from_int(Some(list_size as isize)),
+                        Variable::from_int(Some(atomic.load(Ordering::SeqCst))),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::MapSize => {
-                let map_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::RateLimit => {
+                let limiter_var = self.path(0)?;
+                let permits_var = self.path(1)?;
 
-                let map_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(map_var, locals)
+                let permits = script
+                    .get_path(permits_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
                     .map_err(|f| (f, self.clone()))?;
-                let map_size = map_var.as_list().map_err(|f| (f, self.clone()))?.len();
+                if permits <= 0 {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        result_var,
-                        Variable::from_int(Some(map_size as isize)),
+                    .set_path(
+                        limiter_var,
+                        Variable::from_limiter(Some(Arc::new(RateLimiter::new(permits as u64)))),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::StringSize => {
-                let string_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::Acquire => {
+                let limiter_var = self.path(0)?;
 
-                let string_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(string_var, locals)
+                let limiter = script
+                    .get_path(limiter_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_limiter()
+                    .map_err(|f| (f, self.clone()))?;
+                let permits = match self.args.get(1) {
+                    Some(permits_var) => script
+                        .get_var(permits_var.clone(), locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_int()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => 1,
+                };
+                let permits =
+                    u64::try_from(permits).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+
+                let clock = script.get_clock();
+                let wait = limiter.reserve(permits, clock.monotonic_millis());
+                if !wait.is_zero() {
+                    clock.sleep(wait);
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// HTTP, mail, MQTT and DNS commands
+    fn execute_network(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::HttpFormEncode => {
+                let fields_var = self.path(0)?;
+                let body_var = self.path(1)?;
+                let content_type_var = self.path(2)?;
+
+                let fields = script
+                    .get_path(fields_var, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let string_size = string_var.as_list().map_err(|f| (f, self.clone()))?.len();
+                let fields = self.string_pairs(&fields)?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        result_var,
-                        Variable::from_int(Some(string_size as isize)),
+                    .set_path(
+                        body_var,
+                        Variable::from_str(Some(form_urlencode(&fields))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(
+                        content_type_var,
+                        Variable::from_str(Some("application/x-www-form-urlencoded".to_string())),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ForMap => {
-                let func_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let map_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::MultipartBuild => {
+                let fields_var = self.path(0)?;
+                let files_var = self.path(1)?;
+                let body_var = self.path(2)?;
+                let content_type_var = self.path(3)?;
 
-                let map_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(map_var, locals)
+                let fields = script
+                    .get_path(fields_var, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let map_var = map_var.as_map().map_err(|f| (f, self.clone()))?;
-
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
+                let fields = self.string_pairs(&fields)?;
+                let files = script
+                    .get_path(files_var, locals)
                     .map_err(|f| (f, self.clone()))?;
+                let files = self.string_pairs(&files)?;
 
-                for (k, v) in map_var {
-                    func.execute(script.clone(), "null".to_string(), vec![k, v], false)?;
+                for (_, path) in &files {
+                    script.access(Access::FileRead, path, self)?;
                 }
-            }
-            CommandType::ForList => {
-                let func_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+
+                let boundary = format!("sustlang-{:016x}", rand::thread_rng().gen::<u64>());
+                let body =
+                    multipart_body(&fields, &files, &boundary).map_err(|f| (f, self.clone()))?;
+
+                let body_type = script
+                    .get_path(body_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let body = self.bytes_to_var(body, &body_type)?;
+
+                script
+                    .set_path(body_var, body, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(
+                        content_type_var,
+                        Variable::from_str(Some(format!(
+                            "multipart/form-data; boundary={}",
+                            boundary
+                        ))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::HttpSessionNew => {
+                let session_var = self.path(0)?;
+
+                script
+                    .set_path(
+                        session_var,
+                        Variable::from_http_session(Some(Arc::new(HttpSession::new()))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::HttpSessionSetHeader => {
+                let session_var = self.path(0)?;
+                let name_var = self.path(1)?;
+                let value_var = self.path(2)?;
+
+                let session = script
+                    .get_path(session_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_http_session()
+                    .map_err(|f| (f, self.clone()))?;
+                let name_var = script
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let value_var = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                session.set_header(&name_var, &value_var);
+            }
+            CommandType::HttpSessionStoreCookie => {
+                let session_var = self.path(0)?;
+                let set_cookie_var = self.path(1)?;
+
+                let session = script
+                    .get_path(session_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_http_session()
+                    .map_err(|f| (f, self.clone()))?;
+                let set_cookie_var = script
+                    .get_path(set_cookie_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                session.store_cookie(&set_cookie_var);
+            }
+            CommandType::HttpSessionHeaders => {
+                let session_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let session = script
+                    .get_path(session_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_http_session()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let headers = session.request_headers();
+                script
+                    .set_path(result_var, Variable::from(headers), global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "mail")]
+            CommandType::SendMail => {
+                let server_var = self.path(0)?;
+                let port_var = self.path(1)?;
+                let user_var = self.path(2)?;
+                let pass_var = self.path(3)?;
+                let to_var = self.path(4)?;
+                let subject_var = self.path(5)?;
+                let body_var = self.path(6)?;
+
+                let server_var = script
+                    .get_path(server_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let port_var = script
+                    .get_path(port_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let user_var = script
+                    .get_path(user_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let pass_var = script
+                    .get_path(pass_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let to_var = script
+                    .get_path(to_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let subject_var = script
+                    .get_path(subject_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let body_var = script
+                    .get_path(body_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let server = MailServer {
+                    host: server_var,
+                    port: u16::try_from(port_var)
+                        .map_err(|_| (ScriptError::RangeError, self.clone()))?,
+                    user: user_var,
+                    pass: pass_var,
+                };
+                script.access(
+                    Access::NetworkClient,
+                    &format!("{}:{}", server.host, server.port),
+                    self,
+                )?;
+                if !script.is_dry_run() {
+                    send_mail(&server, &to_var, &subject_var, &body_var)
+                        .map_err(|f| (f, self.clone()))?;
+                }
+            }
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttConnect => {
+                let host_var = self.path(0)?;
+                let port_var = self.path(1)?;
+                let client_id_var = self.path(2)?;
+                let mqtt_var = self.path(3)?;
+
+                let host_var = script
+                    .get_path(host_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let port_var = script
+                    .get_path(port_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let client_id_var = script
+                    .get_path(client_id_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let port =
+                    u16::try_from(port_var).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+                script.access(
+                    Access::NetworkClient,
+                    &format!("{}:{}", host_var, port),
+                    self,
+                )?;
+                let client = if script.is_dry_run() {
+                    MqttClient::simulated()
+                } else {
+                    MqttClient::connect(&host_var, port, &client_id_var, script.clone())
+                        .map_err(|f| (f, self.clone()))?
+                };
+
+                script
+                    .set_path(
+                        mqtt_var,
+                        Variable::from_mqtt(Some(Arc::new(client))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttPublish => {
+                let mqtt_var = self.path(0)?;
+                let topic_var = self.path(1)?;
+                let payload_var = self.path(2)?;
+
+                let mqtt_var = script
+                    .get_path(mqtt_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_mqtt()
+                    .map_err(|f| (f, self.clone()))?;
+                let topic_var = script
+                    .get_path(topic_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let payload_var = script
+                    .get_path(payload_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                mqtt_var
+                    .publish(&topic_var, payload_var.as_bytes())
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttSubscribe => {
+                let mqtt_var = self.path(0)?;
+                let topic_var = self.path(1)?;
+                let func_name = self
+                    .args
+                    .get(2)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
-                let list_var = self
+
+                let mqtt_var = script
+                    .get_path(mqtt_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_mqtt()
+                    .map_err(|f| (f, self.clone()))?;
+                let topic_var = script
+                    .get_path(topic_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                mqtt_var
+                    .subscribe(&topic_var, func.clone())
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::HttpGet => {
+                let url_var = self.path(0)?;
+                let status_var = self
                     .args
                     .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let body_var = self
+                    .args
+                    .get(2)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+
+                let url_var = script
+                    .get_path(url_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let session = self.http_session_arg(&script, 3, locals)?;
+
+                let response =
+                    self.send_http(&script, "GET", &url_var, &[], &[], session.as_deref())?;
+                self.set_http_response(
+                    response,
+                    (status_var, body_var, None),
+                    &script,
+                    global,
+                    locals,
+                )?;
+            }
+            CommandType::HttpRequest => {
+                let method_var = self.path(0)?;
+                let url_var = self.path(1)?;
+                let headers_var = self.path(2)?;
+                let request_body_var = self.path(3)?;
+                let status_var = self
+                    .args
+                    .get(4)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let body_var = self
+                    .args
+                    .get(5)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let response_headers_var = self.args.get(6).cloned();
+
+                let method_var = script
+                    .get_path(method_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let url_var = script
+                    .get_path(url_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let headers = self.string_pairs(
+                    &script
+                        .get_path(headers_var, locals)
+                        .map_err(|f| (f, self.clone()))?,
+                )?;
+                let request_body = self.var_to_bytes(
+                    &script
+                        .get_path(request_body_var, locals)
+                        .map_err(|f| (f, self.clone()))?,
+                )?;
+
+                let session = self.http_session_arg(&script, 7, locals)?;
+
+                let response = self.send_http(
+                    &script,
+                    &method_var,
+                    &url_var,
+                    &headers,
+                    &request_body,
+                    session.as_deref(),
+                )?;
+                self.set_http_response(
+                    response,
+                    (status_var, body_var, response_headers_var),
+                    &script,
+                    global,
+                    locals,
+                )?;
+            }
+            CommandType::DnsResolve => {
+                let host_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let host_var = script
+                    .get_path(host_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::NetworkClient, &host_var, self)?;
+                let addrs = (host_var.as_str(), 0)
+                    .to_socket_addrs()
+                    .map_err(|_| (ScriptError::DnsError(host_var.clone()), self.clone()))?;
+
+                // the resolver gives an address for every socket type, so they repeat
+                let mut ips: Vec<String> = Vec::new();
+                for addr in addrs {
+                    let ip = addr.ip().to_string();
+                    if !ips.contains(&ip) {
+                        ips.push(ip);
+                    }
+                }
+                if ips.is_empty() {
+                    return Err((ScriptError::DnsError(host_var), self.clone()));
+                }
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_list(
+                            Some(
+                                ips.into_iter()
+                                    .map(|ip| Variable::from_str(Some(ip)))
+                                    .collect(),
+                            ),
+                            VarType::String,
+                        ),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::GetNetworkTime => {
+                let server_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let server_var = script
+                    .get_path(server_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::NetworkClient, &server_var, self)?;
+                let now = if script.is_dry_run() {
+                    script.get_clock().now_millis()
+                } else {
+                    network_time(&server_var, NETWORK_TIME_TIMEOUT)
+                        .map_err(|f| (f, self.clone()))?
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(now as isize)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Clipboard, notifications, environment variables and processes
+    fn execute_system(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            #[cfg(feature = "clipboard")]
+            CommandType::ClipboardGet => {
+                let result_var = self.path(0)?;
+
+                script.access(Access::Desktop, "clipboard", self)?;
+                let text = clipboard_get().map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(text)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "clipboard")]
+            CommandType::ClipboardSet => {
+                let text_var = self.path(0)?;
+
+                let text_var = script
+                    .get_path(text_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::Desktop, "clipboard", self)?;
+                clipboard_set(&text_var).map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "notify")]
+            CommandType::Notify => {
+                let title_var = self.path(0)?;
+                let body_var = self.path(1)?;
+
+                let title_var = script
+                    .get_path(title_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let body_var = script
+                    .get_path(body_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::Desktop, "notification", self)?;
+                notify(&title_var, &body_var).map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::EnvGet => {
+                let name_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let name_var = script
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::Environment, &name_var, self)?;
+                let value = env::var_os(&name_var)
+                    .map(|value| Variable::from_str(Some(value.to_string_lossy().to_string())));
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_optional(Some(value), VarType::String),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::EnvSet => {
+                let name_var = self.path(0)?;
+                let value_var = self.path(1)?;
+
+                let name_var = script
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let value_var = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                // set_var panics on these names, so they are reported as bad arguments
+                if name_var.is_empty() || name_var.contains(['=', '\0']) || value_var.contains('\0')
+                {
+                    return Err((ScriptError::CommandArgsInvalidError, self.clone()));
+                }
+                script.access(Access::Environment, &name_var, self)?;
+                env::set_var(name_var, value_var);
+            }
+            CommandType::Spawn => {
+                let cmd_var = self.path(0)?;
+                let args_var = self.path(1)?;
+                let exit_code_var = self.path(2)?;
+                let stdout_stream = self.path(3)?;
+                let stdin_stream = self.path(4)?;
+
+                let cmd_var = script
+                    .get_path(cmd_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let mut args = Vec::new();
+                for arg in script
+                    .get_path(args_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_list()
+                    .map_err(|f| (f, self.clone()))?
+                    .iter()
+                {
+                    args.push(arg.as_str().map_err(|f| (f, self.clone()))?);
+                }
+
+                let mut cmdline = vec![cmd_var.clone()];
+                cmdline.extend(args.iter().cloned());
+                script.access(Access::Process, &cmdline.join(" "), self)?;
+
+                let (reader, writer, exit_code) = if script.is_dry_run() {
+                    (
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(io::empty())))),
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(io::sink())))),
+                        Arc::new(AtomicIsize::new(0)),
+                    )
+                } else {
+                    let mut child = process::Command::new(&cmd_var)
+                        .args(&args)
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::piped())
+                        .spawn()
+                        .map_err(|e| match e.kind() {
+                            io::ErrorKind::NotFound => (
+                                ScriptError::FileNotFoundError(cmd_var.clone()),
+                                self.clone(),
+                            ),
+                            _ => (ScriptError::ProcessError(cmd_var.clone()), self.clone()),
+                        })?;
+                    let (Some(stdout), Some(stdin)) = (child.stdout.take(), child.stdin.take())
+                    else {
+                        return Err((ScriptError::ProcessError(cmd_var), self.clone()));
+                    };
+
+                    // -1 until the process exits, also when it is killed by a signal
+                    let exit_code = Arc::new(AtomicIsize::new(-1));
+                    let waiter_code = exit_code.clone();
+                    thread::spawn(move || {
+                        let code = child.wait().ok().and_then(|s| s.code()).unwrap_or(-1);
+                        waiter_code.store(code as isize, Ordering::SeqCst);
+                    });
+
+                    (
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(stdout)))),
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(stdin)))),
+                        exit_code,
+                    )
+                };
+
+                script
+                    .set_path(
+                        exit_code_var,
+                        Variable::from_atomic(Some(exit_code)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(stdout_stream, reader, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(stdin_stream, writer, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Random numbers
+    fn execute_random(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::Random => {
+                let min_var = self.path(0)?;
+                let max_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let min_var = script
+                    .get_path(min_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let max_var = script
+                    .get_path(max_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                if min_var > max_var {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+                let result = script.with_rng(|rng| rng.gen_range(min_var..=max_var));
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::RandomFloat => {
+                let result_var = self.path(0)?;
+
+                let result = script.with_rng(|rng| rng.gen::<f64>());
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_float(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::RandomBytes => {
+                let count_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let count = script
+                    .get_path(count_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let count =
+                    usize::try_from(count).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+
+                let mut bytes = vec![0u8; count];
+                script.with_rng(|rng| rng.fill(&mut bytes[..]));
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_bytes(Some(bytes)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::RandomChoice => {
+                let list_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let list = script
+                    .get_path(list_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_list()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let item = script
+                    .with_rng(|rng| list.choose(rng).cloned())
+                    .ok_or_else(|| {
+                        (
+                            ScriptError::IndexOutOfBoundsError { index: 0, len: 0 },
+                            self.clone(),
+                        )
+                    })?;
+
+                script
+                    .set_path(result_var, item, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::RandomSeed => {
+                let seed_var = self.path(0)?;
+
+                let seed = script
+                    .get_path(seed_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.set_random_seed(seed as u64);
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Opening files, sockets and other streams, `SEEK` and `TELL`
+    fn execute_stream(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::OpenFileIn => {
+                let path_var = self.path(0)?;
+                let stream_var = self.path(1)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::FileRead, &path_var, self)?;
+                let file = File::open(&path_var)
+                    .map_err(|e| (ScriptError::file_read(path_var, &e), self.clone()))?;
+
+                script
+                    .set_path(
+                        stream_var,
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(BufReader::new(file))))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::OpenFileOut => {
+                let path_var = self.path(0)?;
+                let stream_var = self.path(1)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let mode = match self.args.get(2) {
+                    Some(mode_var) => {
+                        let mode = script
+                            .get_var(mode_var.clone(), locals)
+                            .map_err(|f| (f, self.clone()))?
+                            .as_str()
+                            .map_err(|f| (f, self.clone()))?;
+                        FileOutMode::from_name(&mode)
+                            .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    }
+                    None => FileOutMode::Append,
+                };
+
+                script.access(Access::FileWrite, &path_var, self)?;
+                let result: Arc<Mutex<dyn Write + Send>> = if script.is_dry_run() {
+                    Arc::new(Mutex::new(io::sink()))
+                } else {
+                    Arc::new(Mutex::new(FileOutStream::open(&path_var, mode).map_err(
+                        |_| (ScriptError::FileWriteError(path_var.clone()), self.clone()),
+                    )?))
+                };
+
+                script
+                    .set_path(
+                        stream_var,
+                        Variable::from_out_stream(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::OpenFile => {
+                let path_var = self.path(0)?;
+                let stream_var = self.path(1)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let mode = match self.args.get(2) {
+                    Some(mode_var) => {
+                        let mode = script
+                            .get_var(mode_var.clone(), locals)
+                            .map_err(|f| (f, self.clone()))?
+                            .as_str()
+                            .map_err(|f| (f, self.clone()))?;
+                        FileStreamMode::from_name(&mode)
+                            .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    }
+                    None => FileStreamMode::Read,
+                };
+
+                let access = if mode == FileStreamMode::Read {
+                    Access::FileRead
+                } else {
+                    Access::FileWrite
+                };
+                script.access(access, &path_var, self)?;
+                let file = if script.is_dry_run() && mode != FileStreamMode::Read {
+                    FileStream::simulated(&path_var, mode)
+                } else {
+                    FileStream::open(&path_var, mode)
+                }
+                .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        stream_var,
+                        Variable::from_file_stream(Some(Arc::new(Mutex::new(file)))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::Seek => {
+                let stream_var = self.path(0)?;
+                let offset_var = self.path(1)?;
+                let whence_var = self.path(2)?;
+
+                let stream_var = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_file_stream()
+                    .map_err(|f| (f, self.clone()))?;
+                let offset_var = script
+                    .get_path(offset_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let whence_var = script
+                    .get_path(whence_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                stream_var
+                    .lock()
+                    .unwrap()
+                    .seek_to(offset_var as i64, &whence_var)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::Tell => {
+                let stream_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let stream_var = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_file_stream()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let position = stream_var
+                    .lock()
+                    .unwrap()
+                    .position()
+                    .map_err(|f| (f, self.clone()))?;
+                let position = isize::try_from(position)
+                    .map_err(|_| (ScriptError::RangeError, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(position)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "serial")]
+            CommandType::OpenSerial => {
+                let path_var = self.path(0)?;
+                let baud_var = self.path(1)?;
+                let in_stream = self.path(2)?;
+                let out_stream = self.path(3)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let baud_var = script
+                    .get_path(baud_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let baud_rate =
+                    u32::try_from(baud_var).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+                script.access(Access::FileWrite, &path_var, self)?;
+                let (reader, writer) = if script.is_dry_run() {
+                    (
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(io::empty())))),
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(io::sink())))),
+                    )
+                } else {
+                    let (reader, writer) =
+                        open_serial(&path_var, baud_rate).map_err(|f| (f, self.clone()))?;
+                    (
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(reader)))),
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(writer)))),
+                    )
+                };
+
+                script
+                    .set_path(in_stream, reader, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(out_stream, writer, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::OpenBuffer => {
+                let in_stream = self.path(0)?;
+                let out_stream = self.path(1)?;
+
+                let buffer = BufferStream::new();
+
+                script
+                    .set_path(
+                        in_stream,
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(buffer.clone())))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(
+                        out_stream,
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(buffer)))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "gzip")]
+            CommandType::GzipWrapOut => {
+                let stream_var = self.path(0)?;
+                let gzip_stream = self.path(1)?;
+
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_out_stream()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        gzip_stream,
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(GzipOutStream::new(
+                            stream,
+                        ))))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            #[cfg(feature = "gzip")]
+            CommandType::GunzipWrapIn => {
+                let stream_var = self.path(0)?;
+                let plain_stream = self.path(1)?;
+
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_in_stream()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        plain_stream,
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(GzipInStream::new(
+                            stream,
+                        ))))),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::OpenTcpConnection => {
+                let addr_var = self.path(0)?;
+                let port_var = self.path(1)?;
+                let in_stream = self.path(2)?;
+                let out_stream = self.path(3)?;
+
+                let addr = script
+                    .get_path(addr_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let port = script
+                    .get_path(port_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let timeout = match self.args.get(4) {
+                    Some(timeout_var) => self.timeout_millis(&script, timeout_var, locals)?,
+                    None => None,
+                };
+
+                let port =
+                    u16::try_from(port).map_err(|_| (ScriptError::RangeError, self.clone()))?;
+                script.access(Access::NetworkClient, &format!("{}:{}", addr, port), self)?;
+                let (reader, writer) = if script.is_dry_run() {
+                    (
+                        Variable::from_in_stream(Some(Arc::new(Mutex::new(io::empty())))),
+                        Variable::from_out_stream(Some(Arc::new(Mutex::new(io::sink())))),
+                    )
+                } else {
+                    let stream = self.connect_tcp(&addr, port, timeout)?;
+                    stream
+                        .set_read_timeout(timeout)
+                        .and_then(|_| stream.set_write_timeout(timeout))
+                        .map_err(|_| (ScriptError::NetworkError, self.clone()))?;
+                    script
+                        .get_network_streams()
+                        .register(stream)
+                        .map_err(|f| (f, self.clone()))?
+                };
+
+                script
+                    .set_path(in_stream, reader, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(out_stream, writer, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::SetStreamTimeout => {
+                let stream_var = self.path(0)?;
+                let millis_var = self
+                    .args
+                    .get(1)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+
+                let timeout = self.timeout_millis(&script, &millis_var, locals)?;
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                // streams of a dry run lead nowhere and never wait
+                if !script.is_dry_run() {
+                    script
+                        .get_network_streams()
+                        .set_timeout(&stream, timeout)
+                        .map_err(|f| (f, self.clone()))?;
+                }
+            }
+            CommandType::OpenTcpListener => {
+                let accept_func = self
+                    .args
+                    .get(2)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let func = script
+                    .resolve_function(accept_func, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .clone();
+
+                self.listen(script, locals, move |script, client| {
+                    Command::accept_tcp_client(&func, script, client)
+                })?;
+            }
+            CommandType::OpenHttpListener => {
+                let handler_func = self
+                    .args
+                    .get(2)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let func = script
+                    .resolve_function(handler_func, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .clone();
+
+                let map_type = VarType::Map(Box::new(VarType::String), Box::new(VarType::String));
+                if func.result_type != map_type {
+                    return Err((
+                        ScriptError::TypeMismatchError {
+                            expected: map_type.to_name(),
+                            actual: func.result_type.to_name(),
+                        },
+                        self.clone(),
+                    ));
+                }
+
+                self.listen(script, locals, move |script, client| {
+                    Command::accept_http_client(&func, script, client)
+                })?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Commands of the file system
+    fn execute_file(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::FileExists | CommandType::IsFolder => {
+                let path_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::FileRead, &path_var, self)?;
+                let path = Path::new(&path_var);
+                let result = if let CommandType::IsFolder = self.command_type {
+                    path.is_dir()
+                } else {
+                    path.exists()
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::FileDelete => {
+                let path_var = self.path(0)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::FileWrite, &path_var, self)?;
+                if script.is_dry_run() {
+                    if !Path::new(&path_var).exists() {
+                        return Err((ScriptError::FileNotFoundError(path_var), self.clone()));
+                    }
+                    return Ok(());
+                }
+                let removed = if Path::new(&path_var).is_dir() {
+                    fs::remove_dir(&path_var)
+                } else {
+                    fs::remove_file(&path_var)
+                };
+                removed.map_err(|e| (ScriptError::file_write(path_var, &e), self.clone()))?;
+            }
+            CommandType::FileCopy | CommandType::FileRename => {
+                let from_var = self.path(0)?;
+                let to_var = self.path(1)?;
+
+                let from_var = script
+                    .get_path(from_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let to_var = script
+                    .get_path(to_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                // a renamed file is removed from its old path
+                let from_access = if self.command_type == CommandType::FileCopy {
+                    Access::FileRead
+                } else {
+                    Access::FileWrite
+                };
+                script.access(from_access, &from_var, self)?;
+                script.access(Access::FileWrite, &to_var, self)?;
+                if !Path::new(&from_var).exists() {
+                    return Err((ScriptError::FileNotFoundError(from_var), self.clone()));
+                }
+                if script.is_dry_run() {
+                    return Ok(());
+                }
+                let result = if let CommandType::FileCopy = self.command_type {
+                    fs::copy(&from_var, &to_var).map(|_| ())
+                } else {
+                    fs::rename(&from_var, &to_var)
+                };
+                result.map_err(|e| (ScriptError::file_write(to_var, &e), self.clone()))?;
+            }
+            CommandType::MakeDir => {
+                let path_var = self.path(0)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::FileWrite, &path_var, self)?;
+                if !script.is_dry_run() {
+                    fs::create_dir_all(&path_var)
+                        .map_err(|e| (ScriptError::file_write(path_var, &e), self.clone()))?;
+                }
+            }
+            CommandType::ListDir => {
+                let path_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let path_var = script
+                    .get_path(path_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script.access(Access::FileRead, &path_var, self)?;
+                let mut paths = Vec::new();
+                for entry in fs::read_dir(&path_var)
+                    .map_err(|e| (ScriptError::file_read(path_var.clone(), &e), self.clone()))?
+                {
+                    let entry = entry.map_err(|e| {
+                        (ScriptError::file_read(path_var.clone(), &e), self.clone())
+                    })?;
+                    paths.push(entry.path().to_string_lossy().to_string());
+                }
+                paths.sort();
+
+                script
+                    .set_path(result_var, Variable::from(paths), global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Loops and `BREAK`
+    fn execute_loop(
+        &self,
+        script: Arc<RunningScript>,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::For => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let start_index = script
+                    .get_var(
+                        self.args
+                            .get(1)
+                            .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                            .clone(),
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let end_index = script
+                    .get_var(
+                        self.args
+                            .get(2)
+                            .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                            .clone(),
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                for index in start_index..=end_index {
+                    match func.execute_block(script.clone(), vec![Variable::from_int(Some(index))])
+                    {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => {
+                            result?;
+                        }
+                    }
+                }
+            }
+            CommandType::ForMap => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let map_var = self.path(1)?;
+
+                let map_var = script
+                    .get_path(map_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let map_var = map_var.as_map().map_err(|f| (f, self.clone()))?;
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                for (k, v) in map_var.iter() {
+                    match func.execute_block(script.clone(), vec![k.clone(), v.clone()]) {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => {
+                            result?;
+                        }
+                    }
+                }
+            }
+            CommandType::ForList => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let list_var = self.path(1)?;
+
+                let list_var = script
+                    .get_path(list_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let list_var = list_var.as_list().map_err(|f| (f, self.clone()))?;
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                for i in list_var.iter() {
+                    match func.execute_block(script.clone(), vec![i.clone()]) {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => {
+                            result?;
+                        }
+                    }
+                }
+            }
+            CommandType::ForString => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let string_var = self.path(1)?;
+
+                let string_var = script
+                    .get_path(string_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let string_var = string_var.as_str().map_err(|f| (f, self.clone()))?;
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                for c in string_var.chars() {
+                    match func.execute_block(script.clone(), vec![Variable::from_char(Some(c))]) {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => {
+                            result?;
+                        }
+                    }
+                }
+            }
+            CommandType::While => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                if func.result_type != VarType::Bool {
+                    return Err((
+                        ScriptError::TypeMismatchError {
+                            expected: String::from("bool"),
+                            actual: func.result_type.to_name(),
+                        },
+                        self.clone(),
+                    ));
+                }
+
+                loop {
+                    let condition = match func.execute_block(script.clone(), vec![]) {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => result?,
+                    };
+
+                    if !matches!(condition, Variable::Bool(_, Some(true))) {
+                        break;
+                    }
+                }
+            }
+            CommandType::DoWhile => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+                let bool_var = self.path(1)?;
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                loop {
+                    match func.execute_block(script.clone(), vec![]) {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => {
+                            result?;
+                        }
+                    }
+
+                    let condition = script
+                        .get_path(bool_var, locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_bool()
+                        .map_err(|f| (f, self.clone()))?;
+
+                    if !condition {
+                        break;
+                    }
+                }
+            }
+            CommandType::Loop => {
+                let func_name = self
+                    .args
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .clone();
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                loop {
+                    match func.execute_block(script.clone(), vec![]) {
+                        Err(error) if is_loop_break(&error) => break,
+                        result => {
+                            result?;
+                        }
+                    }
+                }
+            }
+            CommandType::Break => {
+                return Err((ScriptError::BreakOutsideLoopError, self.clone()));
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Conversions between types
+    fn execute_convert(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::ToString => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = source_var.to_string().map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToChars => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = source_var
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?
+                    .chars()
+                    .map(|f| Variable::from_char(Some(f)))
+                    .collect();
+                let result = Variable::from_list(Some(result), VarType::Char);
+
+                script
+                    .set_path(result_var, result, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToInteger => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = source_var
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?
+                    .parse::<isize>()
+                    .or(Err(ScriptError::ParseVarError))
+                    .map_err(|f| (f, self.clone()))?;
+                let result = Variable::from_int(Some(result));
+
+                script
+                    .set_path(result_var, result, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToIntegerRadix => {
+                let source_var = self.path(0)?;
+                let radix_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let radix = script
+                    .get_path(radix_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                if !(2..=36).contains(&radix) {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+
+                let result = isize::from_str_radix(&source_var, radix as u32)
+                    .or(Err(ScriptError::ParseVarError))
+                    .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::JsonParse => {
+                let str_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let text = script
+                    .get_path(str_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let result_type = script
+                    .get_path(result_var, locals)
+                    .ok()
+                    .map(|v| v.get_type())
+                    .filter(|t| *t != VarType::Null);
+
+                let result =
+                    Variable::from_json(&text, result_type).map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(result_var, result, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::JsonStringify => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let text = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .to_json()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(text)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::Hash => {
+                let algo_var = self.path(0)?;
+                let input_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let algo = script
+                    .get_path(algo_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let input = script
+                    .get_path(input_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let input = self.var_to_bytes(&input)?;
+
+                let hash = hash_hex(&algo, &input)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(hash)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToStringRadix => {
+                let source_var = self.path(0)?;
+                let radix_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let value = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let radix = script
+                    .get_path(radix_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+
+                if !(2..=36).contains(&radix) {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+
+                let mut digits = Vec::new();
+                let mut rest = value.unsigned_abs();
+                loop {
+                    digits.push(
+                        char::from_digit((rest % radix as usize) as u32, radix as u32)
+                            .ok_or_else(|| (ScriptError::RangeError, self.clone()))?,
+                    );
+                    rest /= radix as usize;
+                    if rest == 0 {
+                        break;
+                    }
+                }
+                if value < 0 {
+                    digits.push('-');
+                }
+                let result: String = digits.into_iter().rev().collect();
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToFloat => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = source_var
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?
+                    .parse::<f64>()
+                    .or(Err(ScriptError::ParseVarError))
+                    .map_err(|f| (f, self.clone()))?;
+                let result = Variable::from_float(Some(result));
+
+                script
+                    .set_path(result_var, result, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::FormatFloat => {
+                let var = self.path(0)?;
+                let precision_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let value = match script
+                    .get_path(var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                {
+                    Variable::Float(_, Some(v)) => v,
+                    Variable::Integer(_, Some(v)) => v as f64,
+                    value => {
+                        return Err((
+                            ScriptError::type_mismatch("integer or float", &value),
+                            self.clone(),
+                        ));
+                    }
+                };
+                let precision = script
+                    .get_path(precision_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let mode = match self.args.get(3) {
+                    Some(mode_var) => script
+                        .get_var(mode_var.clone(), locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_str()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => String::from("fixed"),
+                };
+
+                if precision < 0 {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+                let precision = precision as usize;
+
+                let result = match mode.as_str() {
+                    "fixed" => format!("{:.*}", precision, value),
+                    "scientific" => format!("{:.*e}", precision, value),
+                    _ => {
+                        return Err((ScriptError::ParseVarError, self.clone()));
+                    }
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_str(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToBool => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = if let Variable::List(_, Some(value)) = source_var {
+                    !value.is_empty()
+                } else if let Variable::String(_, Some(value)) = source_var {
+                    *value == "true" || *value == "1"
+                } else if let Variable::Char(_, Some(value)) = source_var {
+                    value != '\0'
+                } else if let Variable::Integer(_, Some(value)) = source_var {
+                    value != 0
+                } else if let Variable::Float(_, Some(value)) = source_var {
+                    value != 0.0
+                } else if let Variable::Bool(_, Some(value)) = source_var {
+                    value
+                } else if let Variable::Map(_, Some(value)) = source_var {
+                    !value.is_empty()
+                } else if let Variable::Optional(_, Some(value)) = source_var {
+                    value.is_some()
+                } else if let Variable::Null(_) = source_var {
+                    false
+                } else if let Variable::OutStream(_, Some(_)) = source_var {
+                    true
+                } else {
+                    #[cfg(feature = "mqtt")]
+                    let client = matches!(source_var, Variable::Mqtt(_, Some(_)));
+                    #[cfg(not(feature = "mqtt"))]
+                    let client = false;
+
+                    client
+                        || matches!(
+                            source_var,
+                            Variable::InStream(_, Some(_))
+                                | Variable::FileStream(_, Some(_))
+                                | Variable::Func(_, Some(_))
+                                | Variable::Thread(_, Some(_))
+                                | Variable::Lock(_, Some(_))
+                                | Variable::Atomic(_, Some(_))
+                                | Variable::Limiter(_, Some(_))
+                                | Variable::HttpSession(_, Some(_))
+                        )
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ToChar => {
+                let source_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let source_var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = if let Variable::String(_, Some(value)) = &source_var {
+                    value.chars().next().ok_or_else(|| {
+                        (
+                            ScriptError::IndexOutOfBoundsError { index: 0, len: 0 },
+                            self.clone(),
+                        )
+                    })?
+                } else if let Variable::Char(_, Some(value)) = source_var {
+                    value
+                } else if let Variable::Integer(_, Some(value)) = source_var {
+                    u32::try_from(value)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| (ScriptError::RangeError, self.clone()))?
+                } else {
+                    return Err((
+                        ScriptError::type_mismatch("string, char or integer", &source_var),
+                        self.clone(),
+                    ));
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_char(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::GetSymbol => {
+                let str_var = self.path(0)?;
+                let index_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let str_var = script
+                    .get_path(str_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let index_var = script
+                    .get_path(index_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let index = index_var.as_int().map_err(|f| (f, self.clone()))?;
+
+                let result = if let Variable::String(_, Some(value)) = &str_var {
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|i| value.chars().nth(i))
+                        .ok_or_else(|| {
+                            (
+                                ScriptError::IndexOutOfBoundsError {
+                                    index,
+                                    len: value.chars().count(),
+                                },
+                                self.clone(),
+                            )
+                        })?
+                } else {
+                    return Err((ScriptError::type_mismatch("string", &str_var), self.clone()));
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_char(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Items and sizes of lists, maps and strings
+    fn execute_collection(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::GetItem | CommandType::GetItemOpt => {
+                let list_var = self.path(0)?;
+                let index_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let list_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(list_var, locals)
+                    .get_path(list_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let index_var = script
+                    .get_path(index_var, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let list_var = list_var.as_list().map_err(|f| (f, self.clone()))?;
 
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
+                let index = index_var.as_int().map_err(|f| (f, self.clone()))?;
+
+                let value = list_var.as_list().map_err(|f| (f, self.clone()))?;
+                let item = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| value.get(i))
+                    .cloned();
+
+                let result = if let CommandType::GetItemOpt = self.command_type {
+                    let item_type = list_var.get_list_type().map_err(|f| (f, self.clone()))?;
+                    Variable::from_optional(Some(item), item_type)
+                } else {
+                    item.ok_or_else(|| {
+                        (
+                            ScriptError::IndexOutOfBoundsError {
+                                index,
+                                len: value.len(),
+                            },
+                            self.clone(),
+                        )
+                    })?
+                };
+
+                script
+                    .set_path(result_var, result, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::GetValue | CommandType::GetValueOpt => {
+                let map_var = self.path(0)?;
+                let key_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
-                for i in list_var {
-                    func.execute(script.clone(), "null".to_string(), vec![i], false)?;
-                }
+                let map_var = script
+                    .get_path(map_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let key_var = script
+                    .get_path(key_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let value = map_var.as_map().map_err(|f| (f, self.clone()))?;
+                let item = value.get(&key_var).cloned();
+
+                let result = if let CommandType::GetValueOpt = self.command_type {
+                    let (_, value_type) = map_var.get_map_types().map_err(|f| (f, self.clone()))?;
+                    Variable::from_optional(Some(item), value_type)
+                } else {
+                    item.ok_or_else(|| {
+                        (
+                            ScriptError::KeyNotFoundError(key_var.to_string().unwrap_or_default()),
+                            self.clone(),
+                        )
+                    })?
+                };
+
+                script
+                    .set_path(result_var, result, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ForString => {
-                let func_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let string_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::GetPathOpt => {
+                let path = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let string_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(string_var, locals)
+                let value = script
+                    .get_path_opt(path, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let string_var = string_var.as_str().map_err(|f| (f, self.clone()))?;
 
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
+                let result = match value {
+                    Some(value) => {
+                        let value_type = value.get_type();
+                        Variable::from_optional(Some(Some(value)), value_type)
+                    }
+                    None => Variable::from_optional(
+                        Some(None),
+                        script
+                            .get_path(result_var, locals)
+                            .map_err(|f| (f, self.clone()))?
+                            .get_option_type()
+                            .map_err(|f| (f, self.clone()))?,
+                    ),
+                };
+
+                script
+                    .set_path(result_var, result, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::ListSize => {
+                let list_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                for c in string_var.as_bytes() {
-                    func.execute(
-                        script.clone(),
-                        "null".to_string(),
-                        vec![Variable::from_char(Some(*c))],
+                let list_var = script
+                    .get_path(list_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let list_size = list_var.as_list().map_err(|f| (f, self.clone()))?.len();
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(list_size as isize)),
+                        global,
                         false,
-                    )?;
-                }
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::While => {
-                let func_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::MapSize => {
+                let map_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
+                let map_size = script
+                    .get_path(map_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .clone();
+                    .as_map()
+                    .map_err(|f| (f, self.clone()))?
+                    .len();
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        "while".to_string(),
-                        Variable::from_bool(Some(true)),
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(map_size as isize)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::StringSize
+            | CommandType::StringSizeBytes
+            | CommandType::StringSizeGraphemes => {
+                let string_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                loop {
-                    func.execute(script.clone(), "while".to_string(), vec![], false)?;
-
-                    let condition = script
-                        .lock()
-                        .unwrap()
-                        .get_var("while".to_string(), locals)
-                        .map_err(|f| (f, self.clone()))?
-                        .as_bool()
-                        .map_err(|f| (f, self.clone()))?;
+                let string_var = script
+                    .get_path(string_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let string_size = match self.command_type {
+                    CommandType::StringSizeBytes => string_var.len(),
+                    CommandType::StringSizeGraphemes => string_var.graphemes(true).count(),
+                    _ => string_var.chars().count(),
+                };
 
-                    if !condition {
-                        break;
-                    }
-                }
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(string_size as isize)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
             }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Comparisons and logic
+    fn execute_compare(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
             CommandType::Equals => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let var = self.path(0)?;
+                let other_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var, locals)
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?;
                 let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
+                    .get_path(other_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(var == other_var)),
                         global,
@@ -1011,31 +3584,15 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::More => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let var = self.path(0)?;
+                let other_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var, locals)
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?;
                 let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
+                    .get_path(other_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
                 let result = if let Variable::Float(_, Some(v1)) = var {
@@ -1044,9 +3601,12 @@ impl Command {
                     } else if let Variable::Integer(_, Some(v2)) = other_var {
                         v1 > v2 as f64
                     } else if let Variable::Char(_, Some(v2)) = other_var {
-                        v1 > v2 as f64
+                        v1 > u32::from(v2) as f64
                     } else {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or char", &other_var),
+                            self.clone(),
+                        ));
                     }
                 } else if let Variable::Integer(_, Some(v1)) = var {
                     if let Variable::Float(_, Some(v2)) = other_var {
@@ -1056,26 +3616,54 @@ impl Command {
                     } else if let Variable::Char(_, Some(v2)) = other_var {
                         v1 > v2 as isize
                     } else {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or char", &other_var),
+                            self.clone(),
+                        ));
                     }
                 } else if let Variable::Char(_, Some(v1)) = var {
                     if let Variable::Float(_, Some(v2)) = other_var {
-                        v1 as f64 > v2
+                        u32::from(v1) as f64 > v2
                     } else if let Variable::Integer(_, Some(v2)) = other_var {
                         v1 as isize > v2
                     } else if let Variable::Char(_, Some(v2)) = other_var {
                         v1 > v2
                     } else {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or char", &other_var),
+                            self.clone(),
+                        ));
+                    }
+                } else if let Variable::DateTime(_, Some(v1)) = var {
+                    if let Variable::DateTime(_, Some(v2)) = other_var {
+                        v1 > v2
+                    } else {
+                        return Err((
+                            ScriptError::type_mismatch("datetime", &other_var),
+                            self.clone(),
+                        ));
+                    }
+                } else if let Variable::Duration(_, Some(v1)) = var {
+                    if let Variable::Duration(_, Some(v2)) = other_var {
+                        v1 > v2
+                    } else {
+                        return Err((
+                            ScriptError::type_mismatch("duration", &other_var),
+                            self.clone(),
+                        ));
                     }
                 } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
+                    return Err((
+                        ScriptError::type_mismatch(
+                            "integer, float, char, datetime or duration",
+                            &var,
+                        ),
+                        self.clone(),
+                    ));
                 };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(result)),
                         global,
@@ -1085,31 +3673,15 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::Less => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let var = self.path(0)?;
+                let other_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var, locals)
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?;
                 let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
+                    .get_path(other_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
                 let result = if let Variable::Float(_, Some(v1)) = var {
@@ -1118,9 +3690,12 @@ impl Command {
                     } else if let Variable::Integer(_, Some(v2)) = other_var {
                         v1 < v2 as f64
                     } else if let Variable::Char(_, Some(v2)) = other_var {
-                        v1 < v2 as f64
+                        v1 < u32::from(v2) as f64
                     } else {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or char", &other_var),
+                            self.clone(),
+                        ));
                     }
                 } else if let Variable::Integer(_, Some(v1)) = var {
                     if let Variable::Float(_, Some(v2)) = other_var {
@@ -1130,26 +3705,54 @@ impl Command {
                     } else if let Variable::Char(_, Some(v2)) = other_var {
                         v1 < v2 as isize
                     } else {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or char", &other_var),
+                            self.clone(),
+                        ));
                     }
                 } else if let Variable::Char(_, Some(v1)) = var {
                     if let Variable::Float(_, Some(v2)) = other_var {
-                        (v1 as f64) < v2
+                        (u32::from(v1) as f64) < v2
                     } else if let Variable::Integer(_, Some(v2)) = other_var {
                         (v1 as isize) < v2
                     } else if let Variable::Char(_, Some(v2)) = other_var {
                         v1 < v2
                     } else {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or char", &other_var),
+                            self.clone(),
+                        ));
+                    }
+                } else if let Variable::DateTime(_, Some(v1)) = var {
+                    if let Variable::DateTime(_, Some(v2)) = other_var {
+                        v1 < v2
+                    } else {
+                        return Err((
+                            ScriptError::type_mismatch("datetime", &other_var),
+                            self.clone(),
+                        ));
+                    }
+                } else if let Variable::Duration(_, Some(v1)) = var {
+                    if let Variable::Duration(_, Some(v2)) = other_var {
+                        v1 < v2
+                    } else {
+                        return Err((
+                            ScriptError::type_mismatch("duration", &other_var),
+                            self.clone(),
+                        ));
                     }
                 } else {
-                    return Err((ScriptError::TypeMismatchError, self.clone()));
+                    return Err((
+                        ScriptError::type_mismatch(
+                            "integer, float, char, datetime or duration",
+                            &var,
+                        ),
+                        self.clone(),
+                    ));
                 };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(result)),
                         global,
@@ -1159,41 +3762,23 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::And => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let var = self.path(0)?;
+                let other_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var, locals)
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_bool()
                     .map_err(|f| (f, self.clone()))?;
                 let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
+                    .get_path(other_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_bool()
                     .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(var && other_var)),
                         global,
@@ -1203,41 +3788,23 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::Or => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let var = self.path(0)?;
+                let other_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var, locals)
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_bool()
                     .map_err(|f| (f, self.clone()))?;
                 let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
+                    .get_path(other_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_bool()
                     .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(var || other_var)),
                         global,
@@ -1247,29 +3814,17 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::Not => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let var = self.path(0)?;
+                let result_var = self.path(1)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var, locals)
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_bool()
                     .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(!var)),
                         global,
@@ -1278,72 +3833,38 @@ impl Command {
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::If => {
-                let bool_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let func_name = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-
-                let func = script
-                    .lock()
-                    .unwrap()
-                    .get_function(func_name)
-                    .map_err(|f| (f, self.clone()))?;
-
-                let bool_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(bool_var, locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_bool()
-                    .map_err(|f| (f, self.clone()))?;
-
-                if bool_var {
-                    func.execute(script.clone(), "null".to_string(), vec![], false)?;
-                }
-            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// `HAS_*` and `IS_*` checks
+    fn execute_check(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
             CommandType::HasStr => {
-                let string_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let substring = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let string_var = self.path(0)?;
+                let substring = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let string_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(string_var, locals)
+                    .get_path(string_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_str()
                     .map_err(|f| (f, self.clone()))?;
                 let substring = script
-                    .lock()
-                    .unwrap()
-                    .get_var(substring, locals)
+                    .get_path(substring, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(string_var.contains(&substring))),
                         global,
@@ -1353,39 +3874,21 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::HasItem => {
-                let list_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let item_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let list_var = self.path(0)?;
+                let item_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let list_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(list_var, locals)
+                    .get_path(list_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_list()
                     .map_err(|f| (f, self.clone()))?;
                 let item_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(item_var, locals)
+                    .get_path(item_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(list_var.contains(&item_var))),
                         global,
@@ -1395,58 +3898,34 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::HasEntry => {
-                let map_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let key_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let value_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(3)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let map_var = self.path(0)?;
+                let key_var = self.path(1)?;
+                let value_var = self.path(2)?;
+                let result_var = self.path(3)?;
 
                 let map_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(map_var, locals)
+                    .get_path(map_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_map()
                     .map_err(|f| (f, self.clone()))?;
                 let key_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(key_var, locals)
+                    .get_path(key_var, locals)
                     .map_err(|f| (f, self.clone()))?;
                 let value_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(value_var, locals)
+                    .get_path(value_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
                 let mut has = false;
 
-                for (k, v) in map_var {
-                    if k == key_var && v == value_var {
+                for (k, v) in map_var.iter() {
+                    if *k == key_var && *v == value_var {
                         has = true;
                         break;
                     }
                 }
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(has)),
                         global,
@@ -1456,48 +3935,30 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::HasKey => {
-                let map_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let key_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let map_var = self.path(0)?;
+                let key_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
                 let map_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(map_var, locals)
+                    .get_path(map_var, locals)
                     .map_err(|f| (f, self.clone()))?
                     .as_map()
                     .map_err(|f| (f, self.clone()))?;
                 let key_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(key_var, locals)
+                    .get_path(key_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
                 let mut has = false;
 
-                for (k, _) in map_var {
-                    if k == key_var {
+                for k in map_var.keys() {
+                    if *k == key_var {
                         has = true;
                         break;
                     }
                 }
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
                         Variable::from_bool(Some(has)),
                         global,
@@ -1507,886 +3968,1531 @@ impl Command {
                     .map_err(|f| (f, self.clone()))?;
             }
             CommandType::HasValue => {
-                let map_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let value_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+                let map_var = self.path(0)?;
+                let value_var = self.path(1)?;
+                let result_var = self.path(2)?;
+
+                let map_var = script
+                    .get_path(map_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_map()
+                    .map_err(|f| (f, self.clone()))?;
+                let value_var = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let mut has = false;
+
+                for v in map_var.values() {
+                    if *v == value_var {
+                        has = true;
+                        break;
+                    }
+                }
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(has)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::HasOptional => {
+                let optional_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let optional_var = script
+                    .get_path(optional_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_option()
+                    .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(optional_var.is_some())),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::IsDigit
+            | CommandType::IsAlpha
+            | CommandType::IsWhitespace
+            | CommandType::IsUpper
+            | CommandType::IsLower => {
+                let char_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let char_var = script
+                    .get_path(char_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_char()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let result = match self.command_type {
+                    CommandType::IsDigit => char_var.is_ascii_digit(),
+                    CommandType::IsAlpha => char_var.is_alphabetic(),
+                    CommandType::IsWhitespace => char_var.is_whitespace(),
+                    CommandType::IsUpper => char_var.is_uppercase(),
+                    CommandType::IsLower => char_var.is_lowercase(),
+                    _ => unreachable!(),
+                };
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Slices of strings, lists and bytes
+    fn execute_slice(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::SubStr => {
+                let str_var_name = self.path(0)?;
+                let start_index = self.path(1)?;
+                let end_index = self.args.get(2).cloned();
+
+                let str_var = script
+                    .get_path(str_var_name, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
+                let start_index = script
+                    .get_path(start_index, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let end_index = match end_index {
+                    Some(end_index) => script
+                        .get_var(end_index, locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_int()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => str_var.chars().count() as isize,
+                };
+
+                if start_index < 0
+                    || end_index < start_index
+                    || end_index as usize > str_var.chars().count()
+                {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+
+                let result: String = str_var
+                    .chars()
+                    .skip(start_index as usize)
+                    .take((end_index - start_index) as usize)
+                    .collect();
+
+                script
+                    .set_path(
+                        str_var_name,
+                        Variable::from_str(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::SubList => {
+                let list_var_name = self.path(0)?;
+                let start_index = self.path(1)?;
+                let end_index = self.args.get(2).cloned();
+
+                let list_var = script
+                    .get_path(list_var_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let list = list_var.as_list().map_err(|f| (f, self.clone()))?;
+                let start_index = script
+                    .get_path(start_index, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let end_index = match end_index {
+                    Some(end_index) => script
+                        .get_var(end_index, locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_int()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => list.len() as isize,
+                };
+
+                if start_index < 0 || end_index < start_index {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+
+                let result = list
+                    .get(start_index as usize..end_index as usize)
+                    .ok_or_else(|| (ScriptError::RangeError, self.clone()))?
+                    .to_vec();
+
+                script
+                    .set_path(
+                        list_var_name,
+                        Variable::from_list(
+                            Some(result),
+                            list_var.get_list_type().map_err(|f| (f, self.clone()))?,
+                        ),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::BytesSlice => {
+                let bytes_var_name = self.path(0)?;
+                let start_index = self.path(1)?;
+                let end_index = self.args.get(2).cloned();
+
+                let bytes = script
+                    .get_path(bytes_var_name, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_bytes()
+                    .map_err(|f| (f, self.clone()))?;
+                let start_index = script
+                    .get_path(start_index, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let end_index = match end_index {
+                    Some(end_index) => script
+                        .get_var(end_index, locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_int()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => bytes.len() as isize,
+                };
+
+                if start_index < 0 || end_index < start_index {
+                    return Err((ScriptError::RangeError, self.clone()));
+                }
+
+                let result = bytes
+                    .get(start_index as usize..end_index as usize)
+                    .ok_or_else(|| (ScriptError::RangeError, self.clone()))?
+                    .to_vec();
+
+                script
+                    .set_path(
+                        bytes_var_name,
+                        Variable::from_bytes(Some(result)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::BytesConcat => {
+                let bytes_var_name = self.path(0)?;
+                let value_var = self.path(1)?;
+
+                let bytes = script
+                    .get_path(bytes_var_name, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_bytes()
+                    .map_err(|f| (f, self.clone()))?;
+                let value = script
+                    .get_path(value_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let value = self.var_to_bytes(&value)?;
+
+                let mut bytes = Arc::unwrap_or_clone(bytes);
+                bytes.extend_from_slice(&value);
+
+                script
+                    .set_path(
+                        bytes_var_name,
+                        Variable::from_bytes(Some(bytes)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::BytesLen => {
+                let bytes_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let len = script
+                    .get_path(bytes_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_bytes()
+                    .map_err(|f| (f, self.clone()))?
+                    .len();
+
+                script
+                    .set_path(
+                        result_var,
+                        Variable::from_int(Some(len as isize)),
+                        global,
+                        false,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Reading streams, `PROMPT` and `STYLE_TEXT`
+    fn execute_read(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::ReadLine => {
+                let name_var = self.path(0)?;
+                let stream_var = self.path(1)?;
 
-                let map_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(map_var, locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_map()
+                let var = script
+                    .get_path(name_var, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let value_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(value_var, locals)
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_in_stream()
                     .map_err(|f| (f, self.clone()))?;
 
-                let mut has = false;
-
-                for (_, v) in map_var {
-                    if v == value_var {
-                        has = true;
+                let mut line: Vec<u8> = Vec::new();
+                let mut buffer = [0; 1];
+                let mut ended = false;
+                let mut stream = stream.lock().unwrap();
+                while read_some(&mut *stream, &mut buffer)
+                    .map_err(|e| (ScriptError::stream_read(&e), self.clone()))?
+                    > 0
+                {
+                    if buffer[0] == b'\n' {
+                        ended = true;
                         break;
                     }
+                    line.push(buffer[0]);
+                }
+                drop(stream);
+
+                let eof = line.is_empty() && !ended;
+                if line.last() == Some(&b'\r') {
+                    line.pop();
                 }
 
+                let value = if let Variable::Optional(_, _) = var {
+                    let line_type = var.get_option_type().map_err(|f| (f, self.clone()))?;
+                    let line = if eof {
+                        None
+                    } else {
+                        let line_var = Variable::not_inited_var(line_type.clone())
+                            .map_err(|f| (f, self.clone()))?;
+                        Some(self.bytes_to_var(line, &line_var)?)
+                    };
+                    Variable::from_optional(Some(line), line_type)
+                } else {
+                    self.bytes_to_var(line, &var)?
+                };
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        result_var,
-                        Variable::from_bool(Some(has)),
-                        global,
-                        false,
-                        locals,
-                    )
+                    .set_path(name_var, value, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::HasOptional => {
-                let optional_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::Prompt => {
+                let prompt_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let optional_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(optional_var, locals)
+                let prompt = script
+                    .get_path(prompt_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_option()
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
+                let edited = script.get_line_editor().lock().unwrap().read_line(&prompt);
+                let line = match edited {
+                    Some(line) => line.map_err(|f| (f, self.clone()))?,
+                    None => {
+                        let cout = script
+                            .get_var(String::from("cout"), locals)
+                            .map_err(|f| (f, self.clone()))?
+                            .as_out_stream()
+                            .map_err(|f| (f, self.clone()))?;
+                        let mut cout = cout.lock().unwrap();
+                        cout.write_all(prompt.as_bytes())
+                            .and_then(|_| cout.flush())
+                            .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
+
+                        let cin = script
+                            .get_var(String::from("cin"), locals)
+                            .map_err(|f| (f, self.clone()))?
+                            .as_in_stream()
+                            .map_err(|f| (f, self.clone()))?;
+                        let mut cin = cin.lock().unwrap();
+
+                        let mut line = Vec::new();
+                        let mut buffer = [0; 1];
+                        while cin
+                            .read(&mut buffer)
+                            .map_err(|e| (ScriptError::stream_read(&e), self.clone()))?
+                            > 0
+                        {
+                            if buffer[0] == b'\n' {
+                                break;
+                            }
+                            line.push(buffer[0]);
+                        }
+                        if line.last() == Some(&b'\r') {
+                            line.pop();
+                        }
+
+                        String::from_utf8(line)
+                            .or(Err((ScriptError::StringUTF8Error, self.clone())))?
+                    }
+                };
+
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        Variable::from_bool(Some(optional_var.is_some())),
+                        Variable::from_str(Some(line)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::UnpackOptional => {
-                let optional_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
+            CommandType::StyleText => {
+                let text_var = self.path(0)?;
+                let style = self
                     .args
                     .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let result_var = self.path(2)?;
 
-                let optional_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(optional_var, locals)
+                let text = script
+                    .get_path(text_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_option()
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
+                // styles are checked even when they are not applied
+                let styled = style_text(&text, &style)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?;
+                let result = if script.is_styled_output() {
+                    styled
+                } else {
+                    text
+                };
+
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        optional_var
-                            .ok_or(ScriptError::ParseVarError)
-                            .map_err(|f| (f, self.clone()))?
-                            .as_mut()
-                            .clone(),
+                        Variable::from_str(Some(result)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::Sleep => {
-                let time_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::ReadChar => {
+                let name_var = self.path(0)?;
+                let stream_var = self.path(1)?;
 
-                let time_var = match script
-                    .lock()
-                    .unwrap()
-                    .get_var(time_var, locals)
+                let stream = script
+                    .get_path(stream_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                {
-                    Variable::Integer(_, Some(v)) => Duration::from_millis(v as u64),
-                    Variable::Float(_, Some(v)) => Duration::from_millis(v as u64),
-                    _ => {
-                        return Err((ScriptError::TypeMismatchError, self.clone()));
-                    }
+                    .as_in_stream()
+                    .map_err(|f| (f, self.clone()))?;
+
+                let mut stream = stream.lock().unwrap();
+                let mut buffer = [0; 4];
+                let read = stream
+                    .read(&mut buffer[..1])
+                    .map_err(|e| (ScriptError::stream_read(&e), self.clone()))?
+                    > 0;
+                let variable = if read {
+                    // the first byte of UTF-8 tells how many bytes the symbol has
+                    let len = match buffer[0] {
+                        0xF0.. => 4,
+                        0xE0.. => 3,
+                        0xC0.. => 2,
+                        _ => 1,
+                    };
+                    stream
+                        .read_exact(&mut buffer[1..len])
+                        .map_err(|e| (ScriptError::stream_read(&e), self.clone()))?;
+                    let symbol = std::str::from_utf8(&buffer[..len])
+                        .map_err(|_| (ScriptError::StringUTF8Error, self.clone()))?;
+                    Variable::from_char(symbol.chars().next())
+                } else {
+                    Variable::from_char(None)
                 };
 
-                thread::sleep(time_var);
+                script
+                    .set_path(name_var, variable, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::AddInt => {
-                let var_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::Read | CommandType::ReadAll => {
+                let name_var = self.path(0)?;
+                let stream_var = self.path(1)?;
 
-                let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_int()
-                    .map_err(|f| (f, self.clone()))?;
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var_name.clone(), locals)
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let stream = script
+                    .get_path(stream_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_int()
+                    .as_in_stream()
                     .map_err(|f| (f, self.clone()))?;
 
+                let mut buffer: Vec<u8> = Vec::new();
+                let mut stream = stream.lock().unwrap();
+                let read = if let CommandType::ReadAll = self.command_type {
+                    stream.read_to_end(&mut buffer)
+                } else {
+                    buffer.resize(READ_BUFFER_SIZE, 0);
+                    read_some(&mut *stream, &mut buffer)
+                };
+                let read = read.map_err(|e| (ScriptError::stream_read(&e), self.clone()))?;
+                buffer.truncate(read);
+
+                let value = self.bytes_to_var(buffer, &var)?;
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        var_name,
-                        Variable::from_int(Some(var + other_var)),
-                        global,
-                        false,
-                        locals,
-                    )
+                    .set_path(name_var, value, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::AddFloat => {
-                let var_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let other_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::ReadLength | CommandType::ReadSome => {
+                let name_var = self.path(0)?;
+                let size_var = self.path(1)?;
+                let stream_var = self.path(2)?;
 
-                let other_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(other_var, locals)
+                let var = script
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let size_var = script
+                    .get_path(size_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_float()
+                    .as_int()
                     .map_err(|f| (f, self.clone()))?;
-                let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var_name.clone(), locals)
+                let stream = script
+                    .get_path(stream_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_float()
+                    .as_in_stream()
                     .map_err(|f| (f, self.clone()))?;
 
+                let size = usize::try_from(size_var)
+                    .map_err(|_| (ScriptError::RangeError, self.clone()))?;
+
+                let mut buffer: Vec<u8> = vec![0; size];
+                let mut stream = stream.lock().unwrap();
+                let read = if let CommandType::ReadSome = self.command_type {
+                    read_some(&mut *stream, &mut buffer)
+                } else {
+                    stream.read_exact(&mut buffer).map(|_| size)
+                };
+                let read = read.map_err(|e| (ScriptError::stream_read(&e), self.clone()))?;
+                drop(stream);
+                buffer.truncate(read);
+
+                let value = self.bytes_to_var(buffer, &var)?;
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        var_name,
-                        Variable::from_float(Some(var + other_var)),
-                        global,
-                        false,
-                        locals,
-                    )
-                    .map_err(|f| (f, self.clone()))?;
+                    .set_path(name_var, value, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                if let CommandType::ReadSome = self.command_type {
+                    let result_var = self.path(3)?;
+                    script
+                        .set_path(
+                            result_var,
+                            Variable::from_int(Some(read as isize)),
+                            global,
+                            false,
+                            locals,
+                        )
+                        .map_err(|f| (f, self.clone()))?;
+                }
             }
-            CommandType::SubStr => {
-                let str_var_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let start_index = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let end_index = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::Pipe => {
+                let in_stream = self.path(0)?;
+                let out_stream = self.path(1)?;
 
-                let str_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(str_var_name.clone(), locals)
+                let in_stream = script
+                    .get_path(in_stream, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_str()
+                    .as_in_stream()
                     .map_err(|f| (f, self.clone()))?;
-                let start_index = script
-                    .lock()
-                    .unwrap()
-                    .get_var(start_index, locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_int()
-                    .map_err(|f| (f, self.clone()))? as usize;
-                let end_index = script
-                    .lock()
-                    .unwrap()
-                    .get_var(end_index, locals)
+                let out_stream = script
+                    .get_path(out_stream, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_int()
-                    .map_err(|f| (f, self.clone()))? as usize;
-
-                script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        str_var_name,
-                        Variable::from_str(Some(str_var[start_index..end_index].to_string())),
-                        global,
-                        false,
-                        locals,
-                    )
+                    .as_out_stream()
                     .map_err(|f| (f, self.clone()))?;
+
+                let mut left = match self.args.get(2) {
+                    Some(limit_var) => {
+                        let limit = script
+                            .get_var(limit_var.clone(), locals)
+                            .map_err(|f| (f, self.clone()))?
+                            .as_int()
+                            .map_err(|f| (f, self.clone()))?;
+                        Some(
+                            usize::try_from(limit)
+                                .map_err(|_| (ScriptError::RangeError, self.clone()))?,
+                        )
+                    }
+                    None => None,
+                };
+
+                let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+                while left != Some(0) {
+                    let size = left.map_or(READ_BUFFER_SIZE, |left| left.min(READ_BUFFER_SIZE));
+                    let read = read_some(&mut *in_stream.lock().unwrap(), &mut buffer[..size])
+                        .map_err(|e| (ScriptError::stream_read(&e), self.clone()))?;
+                    if read == 0 {
+                        break;
+                    }
+                    out_stream
+                        .lock()
+                        .unwrap()
+                        .write_all(&buffer[..read])
+                        .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
+                    left = left.map(|left| left - read);
+                }
             }
-            CommandType::SubList => {
-                let list_var_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let start_index = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let end_index = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            _ => unreachable!(),
+        }
 
-                let list_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(list_var_name.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?;
-                let start_index = script
-                    .lock()
-                    .unwrap()
-                    .get_var(start_index, locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_int()
-                    .map_err(|f| (f, self.clone()))? as usize;
-                let end_index = script
-                    .lock()
-                    .unwrap()
-                    .get_var(end_index, locals)
+        Ok(())
+    }
+
+    /// Optionals
+    fn execute_optional(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::UnpackOptional => {
+                let optional_var = self.path(0)?;
+                let result_var = self.path(1)?;
+
+                let optional_var = script
+                    .get_path(optional_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_int()
-                    .map_err(|f| (f, self.clone()))? as usize;
+                    .as_option()
+                    .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        list_var_name,
-                        Variable::from_list(
-                            Some(
-                                list_var.as_list().map_err(|f| (f, self.clone()))?
-                                    [start_index..end_index]
-                                    .to_vec(),
-                            ),
-                            list_var.get_type(),
-                        ),
+                    .set_path(
+                        result_var,
+                        optional_var
+                            .ok_or(ScriptError::ParseVarError)
+                            .map_err(|f| (f, self.clone()))?
+                            .as_mut()
+                            .clone(),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ReadLine => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::PackOptional => {
+                let var = self.path(0)?;
+                let result_var = self.path(1)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(name_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?;
-                let stream = script
-                    .lock()
-                    .unwrap()
-                    .get_var(stream_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_in_stream()
+                    .get_path(var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
-                let mut line = String::new();
-                let mut buffer = [0; 1];
-                while stream
-                    .lock()
-                    .unwrap()
-                    .read(&mut buffer)
-                    .map_err(|_| (ScriptError::StreamReadError, self.clone()))?
-                    > 0
-                {
-                    if buffer[0] == b'\n' {
-                        break;
-                    }
-                    line.push(buffer[0] as char);
-                }
+                let result = Variable::from_optional(Some(Some(var.clone())), var.get_type());
 
-                let buffer = line.as_bytes().to_vec();
+                script
+                    .set_path(result_var, result, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::NoneOptional => {
+                let var_name = self.path(0)?;
+
+                let var = script
+                    .get_path(var_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        name_var,
-                        match var {
-                            Variable::List(VarType::Char, _) => Variable::from_list(
-                                Some(
-                                    buffer
-                                        .iter()
-                                        .map(|f| Variable::from_char(Some(*f)))
-                                        .collect(),
-                                ),
-                                VarType::List(Box::new(VarType::Char)),
-                            ),
-                            Variable::String(_, _) => Variable::from_str(Some(
-                                String::from_utf8(buffer)
-                                    .or(Err(ScriptError::StringUTF8Error))
-                                    .map_err(|f| (f, self.clone()))?,
-                            )),
-                            _ => {
-                                return Err((ScriptError::TypeMismatchError, self.clone()));
-                            }
-                        },
+                    .set_path(
+                        var_name,
+                        Variable::from_optional(
+                            Some(None),
+                            var.get_option_type().map_err(|f| (f, self.clone()))?,
+                        ),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ReadChar => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::UnpackOr => {
+                let optional_var = self.path(0)?;
+                let default_var = self.path(1)?;
+                let result_var = self.path(2)?;
 
-                let stream = script
-                    .lock()
-                    .unwrap()
-                    .get_var(stream_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_in_stream()
+                let optional_var = script
+                    .get_path(optional_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let default_var = script
+                    .get_path(default_var, locals)
                     .map_err(|f| (f, self.clone()))?;
 
-                let mut buffer = [0; 1];
-                let read = stream
-                    .lock()
-                    .unwrap()
-                    .read(&mut buffer)
-                    .map_err(|_| (ScriptError::StreamReadError, self.clone()))?
-                    > 0;
-                let variable = if read {
-                    Variable::from_char(Some(buffer[0]))
-                } else {
-                    Variable::from_char(None)
+                if default_var.get_type()
+                    != optional_var
+                        .get_option_type()
+                        .map_err(|f| (f, self.clone()))?
+                {
+                    return Err((
+                        ScriptError::TypeMismatchError {
+                            expected: optional_var
+                                .get_option_type()
+                                .map_err(|f| (f, self.clone()))?
+                                .to_name(),
+                            actual: default_var.get_type().to_name(),
+                        },
+                        self.clone(),
+                    ));
+                }
+
+                let result = match optional_var.as_option().map_err(|f| (f, self.clone()))? {
+                    Some(value) => *value,
+                    None => default_var,
                 };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(name_var, variable, global, false, locals)
+                    .set_path(result_var, result, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::Read => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
+            CommandType::OptionalMap => {
+                let optional_var = self.path(0)?;
+                let func_name = self
                     .args
                     .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let result_var = self.path(2)?;
 
-                let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(name_var.clone(), locals)
+                let func = script
+                    .resolve_function(func_name, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let stream = script
-                    .lock()
-                    .unwrap()
-                    .get_var(stream_var.clone(), locals)
+
+                let optional_var = script
+                    .get_path(optional_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_in_stream()
+                    .as_option()
                     .map_err(|f| (f, self.clone()))?;
 
-                let mut buffer: Vec<u8> = Vec::new();
-                stream.lock().unwrap().read(&mut buffer).unwrap();
+                let result = match optional_var {
+                    Some(value) => Some(func.execute(
+                        script.clone(),
+                        "null".to_string(),
+                        vec![*value],
+                        false,
+                    )?),
+                    None => None,
+                };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        name_var,
-                        match var {
-                            Variable::List(VarType::Char, _) => Variable::from_list(
-                                Some(
-                                    buffer
-                                        .iter()
-                                        .map(|f| Variable::from_char(Some(*f)))
-                                        .collect(),
-                                ),
-                                VarType::List(Box::new(VarType::Char)),
-                            ),
-                            Variable::String(_, _) => Variable::from_str(Some(
-                                String::from_utf8(buffer)
-                                    .or(Err(ScriptError::StringUTF8Error))
-                                    .map_err(|f| (f, self.clone()))?,
-                            )),
-                            _ => {
-                                return Err((ScriptError::TypeMismatchError, self.clone()));
-                            }
-                        },
+                    .set_path(
+                        result_var,
+                        Variable::from_optional(Some(result), func.result_type.clone()),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ReadLength => {
-                let name_var = self
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Run `action` of this command, a panic inside it becomes `InternalPanicError`
+    /// instead of unwinding into the host
+    pub fn catch_panic<T>(
+        &self,
+        action: impl FnOnce() -> Result<T, (ScriptError, Command)>,
+    ) -> Result<T, (ScriptError, Command)> {
+        panic::catch_unwind(AssertUnwindSafe(action)).unwrap_or_else(|payload| {
+            let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else {
+                String::from("unknown panic")
+            };
+            Err((
+                ScriptError::InternalPanicError {
+                    command: self.command_type.to_name().to_string(),
+                    line: self.line,
+                    message,
+                },
+                self.clone(),
+            ))
+        })
+    }
+
+    pub fn execute(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+        temp_vars: &mut Vec<String>,
+    ) -> Result<(), (ScriptError, Command)> {
+        self.catch_panic(|| self.execute_command(script, global, locals, temp_vars))
+    }
+
+    /// Groups of commands are run by their own `execute_*` methods, so the stack frame
+    /// of this one doesn't grow with every command and nested calls don't overflow the stack
+    fn execute_command(
+        &self,
+        script: Arc<RunningScript>,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+        temp_vars: &mut Vec<String>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match self.command_type {
+            CommandType::InitVar => {
+                let type_var = self
                     .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
-                let size_var = self
+                let type_var = VarType::from_name(&type_var).map_err(|f| (f, self.clone()))?;
+                let name_var = self.path(1)?;
+
+                script
+                    .set_path(
+                        name_var,
+                        Variable::empty_var(type_var).map_err(|f| (f, self.clone()))?,
+                        global,
+                        true,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::SetVar => {
+                let name_var = self.path(0)?;
+                let value_var = self.args[1..].join(" ");
+
+                let type_var = script
+                    .get_path(name_var, &mut locals.clone())
+                    .map_err(|f| (f, self.clone()))?
+                    .get_type();
+                let var =
+                    Variable::parse_var(type_var, value_var).map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(name_var, var, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::TempVar => {
+                let type_var = self
                     .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
-                let stream_var = self
+                let name_var = self
                     .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .get(1)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let value_var = self.args[2..].join(" ");
+
+                script
+                    .set_var(
+                        name_var.clone(),
+                        Variable::parse_var(
+                            VarType::from_name(&type_var).map_err(|f| (f, self.clone()))?,
+                            value_var,
+                        )
+                        .map_err(|f| (f, self.clone()))?,
+                        global,
+                        true,
+                        locals,
+                    )
+                    .map_err(|f| (f, self.clone()))?;
+
+                temp_vars.push(name_var);
+            }
+            CommandType::MoveVar => {
+                let source_var = self.path(0)?;
+                let target_var = self.path(1)?;
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(name_var.clone(), locals)
+                    .get_path(source_var, locals)
                     .map_err(|f| (f, self.clone()))?;
-                let size_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(size_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_int()
+
+                script
+                    .set_path(target_var, var, global, false, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                script
+                    .drop_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::CopyVar => {
+                let source_var = self.path(0)?;
+                let target_var = self.path(1)?;
+
+                let var = script
+                    .get_path(source_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                script
+                    .set_path(target_var, var, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::DropVar => {
+                let name_var = self.path(0)?;
+
+                if let Ok(Variable::OutStream(_, Some(stream))) = script.get_path(name_var, locals)
+                {
+                    stream
+                        .lock()
+                        .unwrap()
+                        .flush()
+                        .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
+                }
+
+                script
+                    .drop_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::Flush => {
+                let stream_var = self.path(0)?;
+
                 let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_out_stream()
+                    .map_err(|f| (f, self.clone()))?;
+                stream
                     .lock()
                     .unwrap()
-                    .get_var(stream_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_in_stream()
+                    .flush()
+                    .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
+            }
+            CommandType::Close => {
+                let stream_var = self.path(0)?;
+
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                match &stream {
+                    Variable::OutStream(_, Some(stream)) => stream
+                        .lock()
+                        .unwrap()
+                        .flush()
+                        .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?,
+                    Variable::InStream(_, Some(_)) | Variable::FileStream(_, Some(_)) => {}
+                    _ => {
+                        return Err((
+                            ScriptError::type_mismatch("in_stream or out_stream", &stream),
+                            self.clone(),
+                        ));
+                    }
+                }
+
+                let closed =
+                    Variable::not_inited_var(stream.get_type()).map_err(|f| (f, self.clone()))?;
+                script
+                    .set_path(stream_var, closed, global, false, locals)
                     .map_err(|f| (f, self.clone()))?;
+            }
+            CommandType::HasVar => {
+                let name_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let mut buffer: Vec<u8> = Vec::with_capacity(size_var as usize);
-                stream.lock().unwrap().read_exact(&mut buffer).unwrap();
+                let result = script.get_path(name_var, locals).is_ok();
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        name_var,
-                        match var {
-                            Variable::List(VarType::Char, _) => Variable::from_list(
-                                Some(
-                                    buffer
-                                        .iter()
-                                        .map(|f| Variable::from_char(Some(*f)))
-                                        .collect(),
-                                ),
-                                VarType::List(Box::new(VarType::Char)),
-                            ),
-                            Variable::String(_, _) => Variable::from_str(Some(
-                                String::from_utf8(buffer)
-                                    .or(Err(ScriptError::StringUTF8Error))
-                                    .map_err(|f| (f, self.clone()))?,
-                            )),
-                            _ => {
-                                return Err((ScriptError::TypeMismatchError, self.clone()));
-                            }
-                        },
+                    .set_path(
+                        result_var,
+                        Variable::from_bool(Some(result)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::ReadAll => {
-                let name_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::AddStr => {
+                let var_name = self.path(0)?;
+                let other_var = self.path(1)?;
+
+                let other_var = script
+                    .get_path(other_var, locals)
+                    .map_err(|f| (f, self.clone()))?;
+                let other_var: String =
+                    if let Variable::List(VarType::List(item_type), Some(list)) = &other_var {
+                        if **item_type != VarType::Char {
+                            return Err((
+                                ScriptError::type_mismatch(
+                                    "string, char or list[char]",
+                                    &other_var,
+                                ),
+                                self.clone(),
+                            ));
+                        }
+                        let mut text = String::new();
+                        for ele in list.iter() {
+                            text.push(ele.as_char().map_err(|f| (f, self.clone()))?);
+                        }
+                        text
+                    } else if let Variable::String(_, Some(string)) = other_var {
+                        string.as_ref().clone()
+                    } else if let Variable::Char(_, Some(value)) = other_var {
+                        value.to_string()
+                    } else {
+                        return Err((
+                            ScriptError::type_mismatch("string, char or list[char]", &other_var),
+                            self.clone(),
+                        ));
+                    };
 
                 let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(name_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?;
-                let stream = script
-                    .lock()
-                    .unwrap()
-                    .get_var(stream_var.clone(), locals)
+                    .get_path(var_name, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_in_stream()
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
-                let mut buffer: Vec<u8> = Vec::new();
-                stream.lock().unwrap().read_to_end(&mut buffer).unwrap();
-
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        name_var,
-                        match var {
-                            Variable::List(VarType::Char, _) => Variable::from_list(
-                                Some(
-                                    buffer
-                                        .iter()
-                                        .map(|f| Variable::from_char(Some(*f)))
-                                        .collect(),
-                                ),
-                                VarType::List(Box::new(VarType::Char)),
-                            ),
-                            Variable::String(_, _) => Variable::from_str(Some(
-                                String::from_utf8(buffer)
-                                    .or(Err(ScriptError::StringUTF8Error))
-                                    .map_err(|f| (f, self.clone()))?,
-                            )),
-                            _ => {
-                                return Err((ScriptError::TypeMismatchError, self.clone()));
-                            }
-                        },
+                    .set_path(
+                        var_name,
+                        Variable::from_str(Some(var.clone() + &other_var)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::PackOptional => {
-                let var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::Write | CommandType::WriteLine => {
+                let name_var = self.path(0)?;
+                let stream_var = self.path(1)?;
 
-                let var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(var.clone(), locals)
+                let text = script
+                    .get_path(name_var, locals)
                     .map_err(|f| (f, self.clone()))?;
+                let mut text = self.var_to_bytes(&text)?;
+                if let CommandType::WriteLine = self.command_type {
+                    text.push(b'\n');
+                }
 
-                let result = Variable::from_optional(Some(Some(var.clone())), var.get_type());
-
-                script
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_out_stream()
+                    .map_err(|f| (f, self.clone()))?;
+                stream
                     .lock()
                     .unwrap()
-                    .set_var(result_var, result, global, false, locals)
-                    .map_err(|f| (f, self.clone()))?;
+                    .write_all(&text)
+                    .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
             }
-            CommandType::NoneOptional => {
-                let var_name = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::PrintPretty => {
+                let name_var = self.path(0)?;
+                let stream_var = self.path(1)?;
 
-                let var = script
+                let text = script
+                    .get_path(name_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .to_pretty_string(2)
+                    .map_err(|f| (f, self.clone()))?
+                    + "\n";
+
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_out_stream()
+                    .map_err(|f| (f, self.clone()))?;
+                stream
                     .lock()
                     .unwrap()
-                    .get_var(var_name.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?;
+                    .write_all(text.as_bytes())
+                    .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
+            }
+            CommandType::DumpVars => {
+                let stream_var = self.path(0)?;
 
-                script
+                let prefix = match self.args.get(1) {
+                    Some(prefix_var) => script
+                        .get_var(prefix_var.clone(), locals)
+                        .map_err(|f| (f, self.clone()))?
+                        .as_str()
+                        .map_err(|f| (f, self.clone()))?,
+                    None => String::new(),
+                };
+
+                let mut globals: Vec<(String, Variable)> =
+                    script.get_globals().into_iter().collect();
+                globals.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut local_vars: Vec<(String, Variable)> = locals
+                    .iter()
+                    .map(|(id, var)| (id.variable_name(), var.clone()))
+                    .collect();
+                local_vars.sort_by(|a, b| a.0.cmp(&b.0));
+
+                let mut text = String::new();
+                for (scope, vars) in [("global", globals), ("local", local_vars)] {
+                    for (name, var) in vars {
+                        let value = if var.is_initialized() {
+                            var.to_pretty_string(2).map_err(|f| (f, self.clone()))?
+                        } else {
+                            String::from("not inited")
+                        };
+                        text.push_str(&format!(
+                            "{}{} {}: {} = {}\n",
+                            prefix,
+                            scope,
+                            name,
+                            var.get_type().to_name(),
+                            value
+                        ));
+                    }
+                }
+
+                let stream = script
+                    .get_path(stream_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_out_stream()
+                    .map_err(|f| (f, self.clone()))?;
+                stream
                     .lock()
                     .unwrap()
-                    .set_var(
-                        var_name,
-                        Variable::from_optional(
-                            Some(None),
-                            var.get_option_type().map_err(|f| (f, self.clone()))?,
-                        ),
-                        global,
-                        false,
-                        locals,
-                    )
-                    .map_err(|f| (f, self.clone()))?;
+                    .write_all(text.as_bytes())
+                    .map_err(|e| (ScriptError::stream_write(&e), self.clone()))?;
             }
-            CommandType::NewThread => {
+            CommandType::UseFunc => {
                 let func_name = self
                     .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
+                let result_var = self.path(1)?;
+                let arg_paths = &self.paths[2..];
+
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let mut args = Vec::new();
+                for path in arg_paths {
+                    args.push(
+                        script
+                            .get_path(path, locals)
+                            .map_err(|f| (f, self.clone()))?,
+                    );
+                }
+
+                let result = func.execute(script.clone(), "null".to_string(), args, false)?;
+
+                if self.args[1] != "null" {
+                    script
+                        .set_path(result_var, result, global, false, locals)
+                        .map_err(|f| (f, self.clone()))?;
+                }
+            }
+            CommandType::CallDynamic => {
+                let func_name_var = self.path(0)?;
+                let result_var = self.path(1)?;
+                let arg_paths = &self.paths[2..];
+
+                let func_name = script
+                    .get_path(func_name_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_str()
+                    .map_err(|f| (f, self.clone()))?;
 
                 let func = script
-                    .lock()
-                    .unwrap()
                     .get_function(func_name)
                     .map_err(|f| (f, self.clone()))?;
 
-                let local_script = script.clone();
-                thread::spawn(move || {
-                    match func.execute(local_script, "null".to_string(), vec![], false) {
-                        Ok(_) => {}
-                        Err((e, c)) => {
-                            println!("error ({:?}) command: {:?}", e, c);
-                        }
-                    };
-                });
+                let mut args = Vec::new();
+                for path in arg_paths {
+                    args.push(
+                        script
+                            .get_path(path, locals)
+                            .map_err(|f| (f, self.clone()))?,
+                    );
+                }
+
+                let result = func.execute(script.clone(), "null".to_string(), args, false)?;
+
+                if self.args[1] != "null" {
+                    script
+                        .set_path(result_var, result, global, false, locals)
+                        .map_err(|f| (f, self.clone()))?;
+                }
             }
-            CommandType::Random => {
-                let min_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let max_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let result_var = self
+            CommandType::MemoFunc => {
+                let func_name = self
                     .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
-                let min_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(min_var.clone(), locals)
-                    .map_err(|f| (f, self.clone()))?
-                    .as_int()
+                let func = script
+                    .resolve_function(func_name, locals)
                     .map_err(|f| (f, self.clone()))?;
+                script.get_memo_cache().mark(&func.name);
+            }
+            CommandType::HasFunc => {
+                let func_name_var = self.path(0)?;
+                let result_var = self.path(1)?;
 
-                let max_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(max_var.clone(), locals)
+                let func_name = script
+                    .get_path(func_name_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_int()
+                    .as_str()
                     .map_err(|f| (f, self.clone()))?;
 
-                let result = rand::thread_rng().gen_range(min_var..=max_var);
+                let result = script.get_function(func_name).is_ok();
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
+                    .set_path(
                         result_var,
-                        Variable::from_int(Some(result)),
+                        Variable::from_bool(Some(result)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::Import => {
-                let script_path_var = self
+            CommandType::Return => {
+                return Ok(());
+            }
+            CommandType::For
+            | CommandType::ForMap
+            | CommandType::ForList
+            | CommandType::ForString
+            | CommandType::While
+            | CommandType::DoWhile
+            | CommandType::Loop
+            | CommandType::Break => self.execute_loop(script, locals)?,
+            CommandType::ToString
+            | CommandType::ToChars
+            | CommandType::ToInteger
+            | CommandType::ToIntegerRadix
+            | CommandType::JsonParse
+            | CommandType::JsonStringify
+            | CommandType::Hash
+            | CommandType::ToStringRadix
+            | CommandType::ToFloat
+            | CommandType::FormatFloat
+            | CommandType::ToBool
+            | CommandType::ToChar
+            | CommandType::GetSymbol => self.execute_convert(script, global, locals)?,
+            CommandType::GetItem
+            | CommandType::GetItemOpt
+            | CommandType::GetValue
+            | CommandType::GetValueOpt
+            | CommandType::GetPathOpt
+            | CommandType::ListSize
+            | CommandType::MapSize
+            | CommandType::StringSize
+            | CommandType::StringSizeBytes
+            | CommandType::StringSizeGraphemes => {
+                self.execute_collection(script, global, locals)?
+            }
+            CommandType::SetAdd
+            | CommandType::SetRemove
+            | CommandType::SetHas
+            | CommandType::SetUnion
+            | CommandType::SetIntersect => self.execute_set(script, global, locals)?,
+            CommandType::CountIf | CommandType::SumList | CommandType::AvgList => {
+                self.execute_aggregate(script, global, locals)?
+            }
+            CommandType::Equals
+            | CommandType::More
+            | CommandType::Less
+            | CommandType::And
+            | CommandType::Or
+            | CommandType::Not => self.execute_compare(script, global, locals)?,
+            CommandType::If => {
+                let bool_var = self.path(0)?;
+                let func_name = self
                     .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .get(1)
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
-                // TODO: write logic
+                let func = script
+                    .resolve_function(func_name, locals)
+                    .map_err(|f| (f, self.clone()))?;
+
+                let bool_var = script
+                    .get_path(bool_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_bool()
+                    .map_err(|f| (f, self.clone()))?;
+
+                if bool_var {
+                    func.execute_block(script.clone(), vec![])?;
+                }
             }
-            CommandType::ImportText => {
-                let script_text_var = self
+            CommandType::HasStr
+            | CommandType::HasItem
+            | CommandType::HasEntry
+            | CommandType::HasKey
+            | CommandType::HasValue
+            | CommandType::HasOptional
+            | CommandType::IsDigit
+            | CommandType::IsAlpha
+            | CommandType::IsWhitespace
+            | CommandType::IsUpper
+            | CommandType::IsLower => self.execute_check(script, global, locals)?,
+            CommandType::UnpackOptional
+            | CommandType::PackOptional
+            | CommandType::NoneOptional
+            | CommandType::UnpackOr
+            | CommandType::OptionalMap => self.execute_optional(script, global, locals)?,
+            CommandType::Sleep => {
+                let time_var = self
                     .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
-                // TODO: write logic
+                let time_var = match script
+                    .get_var(time_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                {
+                    Variable::Integer(_, Some(v)) => Duration::from_millis(v as u64),
+                    Variable::Float(_, Some(v)) => Duration::from_millis(v as u64),
+                    Variable::Duration(_, Some(v)) => Duration::from_millis(
+                        u64::try_from(v).map_err(|_| (ScriptError::RangeError, self.clone()))?,
+                    ),
+                    time_var => {
+                        return Err((
+                            ScriptError::type_mismatch("integer, float or duration", &time_var),
+                            self.clone(),
+                        ));
+                    }
+                };
+
+                let clock = script.get_clock();
+                clock.sleep(time_var);
             }
-            CommandType::OpenFileIn => {
-                let path_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
+            CommandType::SleepUntil => {
+                let deadline_var = self
                     .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
-                let path_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(path_var.clone(), locals)
+                let deadline = match script
+                    .get_var(deadline_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_str()
+                {
+                    Variable::DateTime(_, Some(v)) => v as i128,
+                    Variable::Integer(_, Some(v)) => v as i128,
+                    deadline_var => {
+                        return Err((
+                            ScriptError::type_mismatch("datetime or integer", &deadline_var),
+                            self.clone(),
+                        ));
+                    }
+                };
+
+                // the wait is counted from the deadline and not from the previous wake up,
+                // so a periodic task doesn't drift by the time of its work
+                let clock = script.get_clock();
+                let left = deadline - clock.now_millis() as i128;
+                if left > 0 {
+                    clock.sleep(Duration::from_millis(left as u64));
+                }
+            }
+            CommandType::AddInt => {
+                let var_name = self.path(0)?;
+                let other_var = self.path(1)?;
+
+                let other_var = script
+                    .get_path(other_var, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
+                    .map_err(|f| (f, self.clone()))?;
+                let var = script
+                    .get_path(var_name, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_int()
                     .map_err(|f| (f, self.clone()))?;
 
-                let result =
-                    fs::read(path_var).map_err(|_| (ScriptError::FileReadError, self.clone()))?;
+                let checked = script.get_pragmas().checked_arithmetic();
+                let result = if checked {
+                    var.checked_add(other_var)
+                        .ok_or_else(|| (ScriptError::RangeError, self.clone()))?
+                } else {
+                    var + other_var
+                };
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        stream_var,
-                        Variable::from_in_stream(Some(Arc::new(Mutex::new(
-                            ByteBuffer::from_bytes(&result),
-                        )))),
+                    .set_path(
+                        var_name,
+                        Variable::from_int(Some(result)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::OpenFileOut => {
-                let path_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let stream_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
+            CommandType::AddFloat => {
+                let var_name = self.path(0)?;
+                let other_var = self.path(1)?;
 
-                let path_var = script
-                    .lock()
-                    .unwrap()
-                    .get_var(path_var.clone(), locals)
+                let other_var = script
+                    .get_path(other_var, locals)
                     .map_err(|f| (f, self.clone()))?
-                    .as_str()
+                    .as_float()
+                    .map_err(|f| (f, self.clone()))?;
+                let var = script
+                    .get_path(var_name, locals)
+                    .map_err(|f| (f, self.clone()))?
+                    .as_float()
                     .map_err(|f| (f, self.clone()))?;
-
-                let bytes = fs::read(path_var.clone())
-                    .map_err(|_| (ScriptError::FileWriteError, self.clone()))?;
-                let result = FileOutStream::new(path_var, bytes);
 
                 script
-                    .lock()
-                    .unwrap()
-                    .set_var(
-                        stream_var,
-                        Variable::from_out_stream(Some(Arc::new(Mutex::new(result)))),
+                    .set_path(
+                        var_name,
+                        Variable::from_float(Some(var + other_var)),
                         global,
                         false,
                         locals,
                     )
                     .map_err(|f| (f, self.clone()))?;
             }
-            CommandType::OpenTcpConnection => {
-                let addr_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let port_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let in_stream = self
-                    .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let out_stream = self
+            CommandType::SubStr
+            | CommandType::SubList
+            | CommandType::BytesSlice
+            | CommandType::BytesConcat
+            | CommandType::BytesLen => self.execute_slice(script, global, locals)?,
+            CommandType::ReadLine
+            | CommandType::Prompt
+            | CommandType::StyleText
+            | CommandType::ReadChar
+            | CommandType::Read
+            | CommandType::ReadAll
+            | CommandType::ReadLength
+            | CommandType::ReadSome
+            | CommandType::Pipe => self.execute_read(script, global, locals)?,
+            CommandType::TimeNow
+            | CommandType::TimeFormat
+            | CommandType::TimeDiff
+            | CommandType::DateParse
+            | CommandType::DateFormat
+            | CommandType::DateAdd
+            | CommandType::DurationMs
+            | CommandType::DurationSec
+            | CommandType::DurationMin
+            | CommandType::DurationToMs
+            | CommandType::Measure
+            | CommandType::TimerStart
+            | CommandType::TimerStop => self.execute_time(script, global, locals)?,
+            CommandType::NewThread
+            | CommandType::NewThreadHandle
+            | CommandType::NewIsolatedThread
+            | CommandType::JoinThread
+            | CommandType::ThreadIsFinished
+            | CommandType::LockNew
+            | CommandType::LockAcquire
+            | CommandType::LockRelease
+            | CommandType::AtomicNew
+            | CommandType::AtomicAdd
+            | CommandType::AtomicGet
+            | CommandType::RateLimit
+            | CommandType::Acquire => self.execute_thread(script, global, locals)?,
+            CommandType::HttpFormEncode
+            | CommandType::MultipartBuild
+            | CommandType::HttpSessionNew
+            | CommandType::HttpSessionSetHeader
+            | CommandType::HttpSessionStoreCookie
+            | CommandType::HttpSessionHeaders
+            | CommandType::HttpGet
+            | CommandType::HttpRequest
+            | CommandType::DnsResolve
+            | CommandType::GetNetworkTime => self.execute_network(script, global, locals)?,
+            #[cfg(feature = "mail")]
+            CommandType::SendMail => self.execute_network(script, global, locals)?,
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttConnect | CommandType::MqttPublish | CommandType::MqttSubscribe => {
+                self.execute_network(script, global, locals)?
+            }
+            #[cfg(feature = "clipboard")]
+            CommandType::ClipboardGet | CommandType::ClipboardSet => {
+                self.execute_system(script, global, locals)?
+            }
+            #[cfg(feature = "notify")]
+            CommandType::Notify => self.execute_system(script, global, locals)?,
+            CommandType::EnvGet | CommandType::EnvSet | CommandType::Spawn => {
+                self.execute_system(script, global, locals)?
+            }
+            CommandType::Random
+            | CommandType::RandomFloat
+            | CommandType::RandomBytes
+            | CommandType::RandomChoice
+            | CommandType::RandomSeed => self.execute_random(script, global, locals)?,
+            CommandType::Import => {
+                let _script_path_var = self
                     .args
-                    .get(3)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
                 // TODO: write logic
             }
-            CommandType::OpenTcpListener => {
-                let addr_var = self
-                    .args
-                    .get(0)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let port_var = self
-                    .args
-                    .get(1)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
-                    .clone();
-                let accept_func = self
+            CommandType::ImportText => {
+                let _script_text_var = self
                     .args
-                    .get(2)
-                    .ok_or((ScriptError::CommandArgsInvalidError, self.clone()))?
+                    .first()
+                    .ok_or_else(|| (ScriptError::CommandArgsInvalidError, self.clone()))?
                     .clone();
 
                 // TODO: write logic
             }
+            CommandType::OpenFileIn
+            | CommandType::OpenFileOut
+            | CommandType::OpenFile
+            | CommandType::Seek
+            | CommandType::Tell
+            | CommandType::OpenBuffer
+            | CommandType::OpenTcpConnection
+            | CommandType::SetStreamTimeout
+            | CommandType::OpenTcpListener
+            | CommandType::OpenHttpListener => self.execute_stream(script, global, locals)?,
+            #[cfg(feature = "serial")]
+            CommandType::OpenSerial => self.execute_stream(script, global, locals)?,
+            #[cfg(feature = "gzip")]
+            CommandType::GzipWrapOut | CommandType::GunzipWrapIn => {
+                self.execute_stream(script, global, locals)?
+            }
+            CommandType::FileExists
+            | CommandType::IsFolder
+            | CommandType::FileDelete
+            | CommandType::FileCopy
+            | CommandType::FileRename
+            | CommandType::MakeDir
+            | CommandType::ListDir => self.execute_file(script, global, locals)?,
             _ => {}
         }
 
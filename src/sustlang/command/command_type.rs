@@ -32,7 +32,7 @@ pub enum CommandType {
     /// Параметры: `source_var`, `target_var`
     CopyVar,
 
-    /// Дропнуть переменную `name_var`
+    /// Дропнуть переменную `name_var`, если это `out_stream`, то перед этим в него записывается все, что в нем накопилось
     ///
     /// Название: DROP_VAR \
     /// Параметры: `name_var`
@@ -44,19 +44,37 @@ pub enum CommandType {
     /// Параметры: `name_var`, `result_var`
     HasVar,
 
+    /// Объявить тип `alias_name`, который дальше можно использовать вместо `type`. Алиасы подставляются при парсинге скрипта
+    ///
+    /// Название: TYPE_ALIAS \
+    /// Параметры: `alias_name`, `type`
+    TypeAlias,
+
+    /// Задать поле `key` заголовка скрипта. Строки `META` должны идти в начале скрипта до остальных команд
+    ///
+    /// Название: META \
+    /// Параметры: `key`, `value`
+    Meta,
+
+    /// Включить новую семантику языка для этого скрипта, например `PRAGMA lang 2`. Строки `PRAGMA` должны идти в начале скрипта до остальных команд
+    ///
+    /// Название: PRAGMA \
+    /// Параметры: `key`, `value`
+    Pragma,
+
     /// Скопировать значение переменной с `source_var` в `result_var`, переводя в `string`
     ///
     /// Название: TO_STRING \
     /// Параметры: `source_var`, `result_var`
     ToString,
 
-    /// Скопировать строку `str_var` в `result_var`, переводя в `list[char]`
+    /// Скопировать строку `str_var` в `result_var`, переводя в `list[char]` (по символу юникода на `char`)
     ///
     /// Название: TO_CHARS \
     /// Параметры: `source_var`, `result_var`
     ToChars,
 
-    /// Скопировать строку `source_var` (тип переменной: `string`/`integer`) в `result_var`, переводя в `char`
+    /// Скопировать первый символ строки `source_var` или символ с кодом `source_var: integer` в `result_var`, переводя в `char`
     ///
     /// Название: TO_CHAR \
     /// Параметры: `source_var`, `result_var`
@@ -68,19 +86,55 @@ pub enum CommandType {
     /// Параметры: `source_var`, `result_var`
     ToInteger,
 
+    /// Скопировать строку `source_var` в `result_var`, переводя в `integer` в системе счисления `radix_var` (тип переменной: `int`, от 2 до 36)
+    ///
+    /// Название: TO_INTEGER_RADIX \
+    /// Параметры: `source_var`, `radix_var`, `result_var`
+    ToIntegerRadix,
+
+    /// Скопировать число `source_var` в `result_var`, переводя в `string` в системе счисления `radix_var` (тип переменной: `int`, от 2 до 36)
+    ///
+    /// Название: TO_STRING_RADIX \
+    /// Параметры: `source_var`, `radix_var`, `result_var`
+    ToStringRadix,
+
+    /// Посчитать хеш `input_var` (тип переменной: `string`/`list[char]`) алгоритмом `algo_var` (тип переменной: `string`: `sha256`, `md5` или `crc32`) и записать его в `result_var` (тип переменной: `string`) в виде hex строки
+    ///
+    /// Название: HASH \
+    /// Параметры: `algo_var`, `input_var`, `result_var`
+    Hash,
+
+    /// Разобрать json из строки `str_var` и записать в `result_var`: объекты становятся `map[string,...]`, массивы - `list`, `null` - пустым `optional`. Если `result_var` уже есть, то json читается в ее тип, иначе тип определяется по данным
+    ///
+    /// Название: JSON_PARSE \
+    /// Параметры: `str_var`, `result_var`
+    JsonParse,
+
+    /// Записать переменную `source_var` в `result_var` строкой в формате json (стримы, потоки и блокировки записываются как `null`)
+    ///
+    /// Название: JSON_STRINGIFY \
+    /// Параметры: `source_var`, `result_var`
+    JsonStringify,
+
     /// Скопировать строку `source_var` в `result_var`, переводя в `float`
     ///
     /// Название: TO_FLOAT \
     /// Параметры: `source_var`, `result_var`
     ToFloat,
 
+    /// Записать число `var` (тип переменной: `float`/`int`) в `result_var` строкой с `precision_var` (тип переменной: `int`) знаками после запятой, `mode_var` (тип переменной: `string`, необязательный параметр) - `fixed` (по умолчанию) или `scientific`
+    ///
+    /// Название: FORMAT_FLOAT \
+    /// Параметры: `var`, `precision_var`, `result_var`, `[mode_var]`
+    FormatFloat,
+
     /// Скопировать строку `source_var` (тип переменной: `string`/`integer`) в `result_var`, переводя в `bool`
     ///
     /// Название: TO_BOOL \
     /// Параметры: `source_var`, `result_var`
     ToBool,
 
-    /// Скопировать символ из строки `str_var` по индексу `index_var` и записать в `result_var`
+    /// Скопировать символ из строки `str_var` по индексу `index_var` (в символах юникода) и записать в `result_var`
     ///
     /// Название: GET_SYMBOL \
     /// Параметры: `str_var`, `index_var`, `result_var`
@@ -98,6 +152,24 @@ pub enum CommandType {
     /// Параметры: `map_var`, key_var`, `result_var`
     GetValue,
 
+    /// Скопировать предмет из списка `list_var` по индексу `index_var` и упаковать его в `optional` в `result_var`, если индекс за пределами списка, то в `result_var` записывается `none`
+    ///
+    /// Название: GET_ITEM_OPT \
+    /// Параметры: `list_var`, `index_var`, `result_var`
+    GetItemOpt,
+
+    /// Скопировать предмет из мапы `map_var` по ключу `key_var` и упаковать его в `optional` в `result_var`, если ключа нет в мапе, то в `result_var` записывается `none`
+    ///
+    /// Название: GET_VALUE_OPT \
+    /// Параметры: `map_var`, `key_var`, `result_var`
+    GetValueOpt,
+
+    /// Достать значение по пути `path` (например `config.server.port`) и упаковать его в `optional` в `result_var`, если путь проходит через пустой `optional`, отсутствующий ключ мапы или индекс за пределами списка, то в `result_var` записывается `none`
+    ///
+    /// Название: GET_PATH_OPT \
+    /// Параметры: `path`, `result_var`
+    GetPathOpt,
+
     /// Прибавить к числу `var` значение `other_var`
     ///
     /// Название: ADD_INT \
@@ -116,54 +188,126 @@ pub enum CommandType {
     /// Параметры: `var`, `other_var`
     AddStr,
 
-    /// Сделать подстроку из строки `str_var` и сохранить туда же
+    /// Сделать подстроку из строки `str_var` с индекса `start_index` включительно до `end_index` не включительно (если `end_index` нет, то до конца строки) и сохранить туда же, при выходе индексов за пределы строки - ошибка `RangeError`. Индексы в символах юникода
     ///
     /// Название: SUB_STR \
-    /// Параметры: `str_var`, `start_index`, `end_index`
+    /// Параметры: `str_var`, `start_index`, `[end_index]`
     SubStr,
 
-    /// Сделать подсписок из списка `list_var` и сохранить туда же
+    /// Сделать подсписок из списка `list_var` с индекса `start_index` включительно до `end_index` не включительно (если `end_index` нет, то до конца списка) и сохранить туда же, при выходе индексов за пределы списка - ошибка `RangeError`
     ///
     /// Название: SUB_LIST \
-    /// Параметры: `list_var`, `start_index`, `end_index`
+    /// Параметры: `list_var`, `start_index`, `[end_index]`
     SubList,
 
+    /// Оставить в `bytes_var: bytes` байты с индекса `start_index` включительно до `end_index` не включительно (если `end_index` нет, то до конца), при выходе индексов за пределы - ошибка `RangeError`
+    ///
+    /// Название: BYTES_SLICE \
+    /// Параметры: `bytes_var`, `start_index`, `[end_index]`
+    BytesSlice,
+
+    /// Дописать в конец `bytes_var: bytes` байты `value_var` (тип переменной: `bytes`, `string`, `char` или `list[char]`)
+    ///
+    /// Название: BYTES_CONCAT \
+    /// Параметры: `bytes_var`, `value_var`
+    BytesConcat,
+
+    /// Записать в `result_var: int` количество байтов в `bytes_var: bytes`
+    ///
+    /// Название: BYTES_LEN \
+    /// Параметры: `bytes_var`, `result_var`
+    BytesLen,
+
     /// Вывести переменную `name_var` в `stream_var`
     ///
     /// Название: WRITE \
     /// Параметры: `name_var`, `stream_var`
     Write,
 
+    /// Вывести переменную `name_var` в `stream_var` и перевести строку
+    ///
+    /// Название: WRITE_LINE \
+    /// Параметры: `name_var`, `stream_var`
+    WriteLine,
+
+    /// Записать все, что накопилось в `stream_var` (например, в стриме файла из `OPEN_FILE_OUT`)
+    ///
+    /// Название: FLUSH \
+    /// Параметры: `stream_var`
+    Flush,
+
+    /// Закрыть стрим `stream_var`: `out_stream` перед этим сбрасывается как во `FLUSH`, переменная становится неинициализированной, а сам стрим закрывается, когда на него не остается других ссылок
+    ///
+    /// Название: CLOSE \
+    /// Параметры: `stream_var`
+    Close,
+
+    /// Вывести переменную `name_var` в `stream_var` в читаемом виде: вложенные списки и мапы с отступами, ключи мап отсортированы, в конце переход на следующую строку
+    ///
+    /// Название: PRINT_PRETTY \
+    /// Параметры: `name_var`, `stream_var`
+    PrintPretty,
+
+    /// Вывести в `stream_var` все глобальные и локальные переменные с названием, типом и значением в читаемом виде, каждая строка начинается с текста из `prefix_var` (тип переменной: `string`, необязательный параметр)
+    ///
+    /// Название: DUMP_VARS \
+    /// Параметры: `stream_var`, `[prefix_var]`
+    DumpVars,
+
     /// Прочитать с `stream_var` ровно `length_var` байтов в переменную `name_var` типа `string`/`list[char]`
     ///
     /// Название: READ_LENGTH \
     /// Параметры: `name_var`, `length_var`, `stream_var`
     ReadLength,
 
+    /// Прочитать с `stream_var` не больше `length_var` байтов в переменную `name_var` типа `string`/`list[char]` и записать количество прочитанных байтов в `result_var` (`0` - стрим закончился)
+    ///
+    /// Название: READ_SOME \
+    /// Параметры: `name_var`, `length_var`, `stream_var`, `result_var`
+    ReadSome,
+
+    /// Копировать байты из `in_stream` в `out_stream` кусками фиксированного размера, пока `in_stream` не закончится или не будет скопировано `limit_var` байтов (тип переменной: `int`, необязательный параметр)
+    ///
+    /// Название: PIPE \
+    /// Параметры: `in_stream`, `out_stream`, `[limit_var]`
+    Pipe,
+
     /// Прочитать с `stream_var` все имеющиеся байты в переменную `name_var` типа `string`/`list[char]`
     ///
     /// Название: READ_ALL \
     /// Параметры: `name_var`, `stream_var`
     ReadAll,
 
-    /// Прочитать с `stream_var` в переменную `name_var` типа `list[char]`/`string`
+    /// Прочитать с `stream_var` имеющиеся байты (не больше 4096) в переменную `name_var` типа `list[char]`/`string`
     ///
     /// Название: READ \
     /// Параметры: `name_var`, `stream_var`
     Read,
 
-    /// Прочитать с `stream_var` один символ в переменную `name_var` типа `char`
+    /// Прочитать с `stream_var` один символ UTF-8 в переменную `name_var` типа `char`
     ///
     /// Название: READ_CHAR \
     /// Параметры: `name_var`, `stream_var`
     ReadChar,
 
-    /// Прочитать с `stream_var` одну строку в переменную `name_var` типа `list[char]`/`string`
+    /// Прочитать с `stream_var` одну строку (без `\n` и `\r` в конце) в переменную `name_var` типа `list[char]`/`string`, если `name_var` имеет тип `optional[string]`/`optional[list[char]]`, то по достижении конца стрима в нее записывается `none`
     ///
     /// Название: READ_LINE \
     /// Параметры: `name_var`, `stream_var`
     ReadLine,
 
+    /// Написать `prompt_var` (тип переменной: `string`) в `cout` и прочитать строку из `cin` в переменную `result_var` типа `string`. Если программа запущена в терминале и интерпретатор собран с фичей `readline`, то строку можно редактировать, а введенные строки запоминаются в историю
+    ///
+    /// Название: PROMPT \
+    /// Параметры: `prompt_var`, `result_var`
+    Prompt,
+
+    /// Оформить текст `text_var` (тип переменной: `string`) стилями `style` через запятую (`bold`, `dim`, `italic`, `underline`, цвета `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan`, `white` и цвета фона `bg_red` и т.д.) и записать в `result_var`. Если `cout` не терминал или задана переменная окружения `NO_COLOR`, то текст записывается без изменений
+    ///
+    /// Название: STYLE_TEXT \
+    /// Параметры: `text_var`, `style`, `result_var`
+    StyleText,
+
     /// Функция `func` (с единственным аргументом с типом `int`) вызывается с `start_index` до `end_index` включительно, `start_index` и `end_index` это названия переменных
     ///
     /// Название: FOR \
@@ -182,54 +326,429 @@ pub enum CommandType {
     /// Параметры: `func(any)`, `list_var`
     ForList,
 
-    /// Функция `func` (с результатом `bool`) вызывается, пока функция выдает `true`
+    /// Функция `func` (с результатом `bool`) вызывается, пока функция выдает `true` (результат функции - переменная `result`)
     ///
     /// Название: WHILE \
     /// Параметры: `func -> bool`
     While,
 
+    /// Функция `func` вызывается один раз, а затем снова, пока переменная `bool_var` равна `true` (условие проверяется после каждого вызова)
+    ///
+    /// Название: DO_WHILE \
+    /// Параметры: `func`, `bool_var`
+    DoWhile,
+
+    /// Функция `func` вызывается бесконечно, пока внутри неё не будет вызван `BREAK`. `RETURN` в `func` заканчивает только текущий вызов, а не весь цикл
+    ///
+    /// Название: LOOP \
+    /// Параметры: `func`
+    Loop,
+
     /// Открыть файл по пути `path_var` (`path_var`, `stream_var` - переменные) для чтения и записать стрим для чтения в переменную `stream_var`
     ///
     /// Название: OPEN_FILE_IN \
     /// Параметры: `path_var`, `stream_var`
     OpenFileIn,
 
-    /// Открыть файл по пути `path_var` (`path_var`, `stream_var` - переменные) для записи и записать стрим для записи в переменную `stream_var`
+    /// Открыть файл по пути `path_var` (`path_var`, `stream_var` - переменные) для записи и записать стрим для записи в переменную `stream_var`.
+    /// Режим `mode_var` (тип переменной: `string`, необязательный параметр): `append` (по умолчанию) - дописывать в конец файла, `truncate` - стереть старое содержимое, `create_new` - файл не должен существовать. Если файла нет, то он создается
     ///
     /// Название: OPEN_FILE_OUT \
-    /// Параметры: `path_var`, `stream_var`
+    /// Параметры: `path_var`, `stream_var`, `[mode_var]`
     OpenFileOut,
 
-    /// Подключиться по `addr_var:port_var` (`addr_var: string`, `port_var: int`, `in_stream: in_stream`, `out_stream: out_stream` - переменные) и записать стримы для чтения и записи в `in_stream` и `out_stream`
+    /// Открыть файл по пути `path_var` (`path_var`, `stream_var` - переменные) и записать в переменную `stream_var` стрим файла (тип переменной: `file_stream`), который можно читать, писать и перемещать по файлу через `SEEK`.
+    /// Режим `mode_var` (тип переменной: `string`, необязательный параметр): `read` (по умолчанию) - только чтение, `write` - чтение и запись, `truncate` - стереть старое содержимое, `create_new` - файл не должен существовать. В режимах записи файл создается, если его нет
+    ///
+    /// Название: OPEN_FILE \
+    /// Параметры: `path_var`, `stream_var`, `[mode_var]`
+    OpenFile,
+
+    /// Переместиться в стриме файла `stream_var` (тип переменной: `file_stream`) на `offset_var` байт (тип переменной: `int`) от `whence_var` (тип переменной: `string`): `start` - начало файла, `current` - текущая позиция, `end` - конец файла
+    ///
+    /// Название: SEEK \
+    /// Параметры: `stream_var`, `offset_var`, `whence_var`
+    Seek,
+
+    /// Записать текущую позицию в стриме файла `stream_var` (тип переменной: `file_stream`) в `result_var` (тип переменной: `int`)
+    ///
+    /// Название: TELL \
+    /// Параметры: `stream_var`, `result_var`
+    Tell,
+
+    /// Подключиться по `addr_var:port_var` (`addr_var: string`, `port_var: int`, `in_stream: in_stream`, `out_stream: out_stream` - переменные) и записать стримы для чтения и записи в `in_stream` и `out_stream`. `timeout_var` (тип переменной: `int`) - таймаут подключения, чтения и записи в миллисекундах, по умолчанию таймаута нет
     ///
     /// Название: OPEN_TCP_CONNECTION \
-    /// Параметры: `addr_var`, `port_var`, `in_stream`, `out_stream`
+    /// Параметры: `addr_var`, `port_var`, `in_stream`, `out_stream`, `[timeout_var]`
     OpenTcpConnection,
 
-    /// Ожидание подключений с `addr_var:port_var` (`addr_var: string`, `port_var: int` - переменные), при подключениях вызывается функция `accept_func`
+    /// Задать таймаут чтения и записи `millis_var` (тип переменной: `int`, 0 - без таймаута) в миллисекундах для стрима TCP подключения `stream_var`, после таймаута команды чтения и записи выдают ошибку `StreamTimeoutError`
+    ///
+    /// Название: SET_STREAM_TIMEOUT \
+    /// Параметры: `stream_var`, `millis_var`
+    SetStreamTimeout,
+
+    /// Создать буфер в памяти и записать стримы для чтения и записи этого буфера в `in_stream` и `out_stream`: записанное в `out_stream` можно прочитать из `in_stream`. Если буфер пуст, то чтение возвращает конец стрима
+    ///
+    /// Название: OPEN_BUFFER \
+    /// Параметры: `in_stream`, `out_stream`
+    OpenBuffer,
+
+    /// Записать в `gzip_stream` (тип переменной: `out_stream`) стрим, который сжимает записанное в gzip и пишет в `stream_var` (тип переменной: `out_stream`/`file_stream`). Конец данных gzip записывается, когда стрим закрывается
+    ///
+    /// Название: GZIP_WRAP_OUT \
+    /// Параметры: `stream_var`, `gzip_stream`
+    #[cfg(feature = "gzip")]
+    GzipWrapOut,
+
+    /// Записать в `plain_stream` (тип переменной: `in_stream`) стрим, который читает данные gzip из `stream_var` (тип переменной: `in_stream`/`file_stream`) и распаковывает их
+    ///
+    /// Название: GUNZIP_WRAP_IN \
+    /// Параметры: `stream_var`, `plain_stream`
+    #[cfg(feature = "gzip")]
+    GunzipWrapIn,
+
+    /// Открыть последовательный порт `path_var` (тип переменной: `string`) со скоростью `baud_var` (тип переменной: `int`) и записать стримы для чтения и записи в `in_stream` и `out_stream`
+    ///
+    /// Название: OPEN_SERIAL \
+    /// Параметры: `path_var`, `baud_var`, `in_stream`, `out_stream`
+    #[cfg(feature = "serial")]
+    OpenSerial,
+
+    /// Ожидание подключений с `addr_var:port_var` (`addr_var: string`, `port_var: int` - переменные), при каждом подключении в отдельном потоке вызывается функция `accept_func` с адресом, портом и стримами клиента.
+    /// Одновременно обрабатывается не больше `max_connections_var` клиентов (тип переменной: `int`, 0 или нет параметра - без ограничения),
+    /// остальные ждут в очереди длиной `backlog_var` (тип переменной: `int`, по умолчанию 0), клиенты сверх очереди отключаются.
+    /// Ошибки `accept_func` пишутся в `cerr`, сервер при этом продолжает работу
     ///
     /// Название: OPEN_TCP_LISTENER \
-    /// Параметры: `addr_var`, `port_var`, `accept_func(string,int,in_stream,out_stream)`
+    /// Параметры: `addr_var`, `port_var`, `accept_func(string,int,in_stream,out_stream)`, `[max_connections_var]`, `[backlog_var]`
     OpenTcpListener,
 
-    /// Ждать миллисекунд из переменной `time_var` (тип переменной: int)
+    /// HTTP сервер на `addr_var:port_var`, для каждого запроса в отдельном потоке вызывается функция `handler_func(string,string,map[string,string],string) -> map[string,string]` с методом, путем, заголовками и телом запроса. Ключи `status` (по умолчанию 200) и `body` результата - код и тело ответа, остальные ключи - заголовки ответа, на ошибку функции отправляется ответ 500, а сама ошибка пишется в `cerr`. Ограничения `max_connections_var` и `backlog_var` как в `OPEN_TCP_LISTENER`
+    ///
+    /// Название: OPEN_HTTP_LISTENER \
+    /// Параметры: `addr_var`, `port_var`, `handler_func`, `[max_connections_var]`, `[backlog_var]`
+    OpenHttpListener,
+
+    /// Ждать миллисекунд из переменной `time_var` (тип переменной: int) или время `time_var: duration`
     ///
     /// Название: SLEEP \
     /// Параметры: `time_var`
     Sleep,
 
-    /// Вызвать функцию `func` в новом потоке
+    /// Ждать до времени `deadline_var: datetime` (или `int`, миллисекунды с начала эпохи unix), если оно уже прошло, то не ждать
+    ///
+    /// Название: SLEEP_UNTIL \
+    /// Параметры: `deadline_var`
+    SleepUntil,
+
+    /// Записать в `result_var` текущее время в миллисекундах с начала эпохи unix (`int`) или как `datetime`
+    ///
+    /// Название: TIME_NOW \
+    /// Параметры: `result_var`
+    TimeNow,
+
+    /// Записать в `result_var: string` время `time_var: int` (миллисекунды с начала эпохи unix, UTC) по шаблону `pattern_var: string`
+    ///
+    /// Название: TIME_FORMAT \
+    /// Параметры: `time_var`, `pattern_var`, `result_var`
+    TimeFormat,
+
+    /// Записать в `result_var: int` разницу `end_var - start_var` в миллисекундах
+    ///
+    /// Название: TIME_DIFF \
+    /// Параметры: `end_var`, `start_var`, `result_var`
+    TimeDiff,
+
+    /// Разобрать текст `text_var: string` по шаблону `pattern_var: string` (коды как в `TIME_FORMAT`) и записать в `result_var: datetime`
+    ///
+    /// Название: DATE_PARSE \
+    /// Параметры: `text_var`, `pattern_var`, `result_var`
+    DateParse,
+
+    /// Записать в `result_var: string` дату `date_var: datetime` по шаблону `pattern_var: string` (коды как в `TIME_FORMAT`)
+    ///
+    /// Название: DATE_FORMAT \
+    /// Параметры: `date_var`, `pattern_var`, `result_var`
+    DateFormat,
+
+    /// Прибавить к дате `date_var: datetime` миллисекунды `millis_var: int` или время `millis_var: duration`
+    ///
+    /// Название: DATE_ADD \
+    /// Параметры: `date_var`, `millis_var`
+    DateAdd,
+
+    /// Записать в `result_var: duration` время из миллисекунд `value_var: int`
+    ///
+    /// Название: DURATION_MS \
+    /// Параметры: `value_var`, `result_var`
+    DurationMs,
+
+    /// Записать в `result_var: duration` время из секунд `value_var` (тип переменной: `int` или `float`)
+    ///
+    /// Название: DURATION_SEC \
+    /// Параметры: `value_var`, `result_var`
+    DurationSec,
+
+    /// Записать в `result_var: duration` время из минут `value_var` (тип переменной: `int` или `float`)
+    ///
+    /// Название: DURATION_MIN \
+    /// Параметры: `value_var`, `result_var`
+    DurationMin,
+
+    /// Записать в `result_var: int` сколько миллисекунд во времени `duration_var: duration`
+    ///
+    /// Название: DURATION_TO_MS \
+    /// Параметры: `duration_var`, `result_var`
+    DurationToMs,
+
+    /// Вызвать функцию `func` без аргументов и записать в `result_var` время ее выполнения в миллисекундах (по монотонным часам, на него не влияет перевод системного времени)
+    ///
+    /// Название: MEASURE \
+    /// Параметры: `func`, `result_var`
+    Measure,
+
+    /// Запустить таймер с именем `name_var: string` (по монотонным часам), таймер с тем же именем запускается заново
+    ///
+    /// Название: TIMER_START \
+    /// Параметры: `name_var`
+    TimerStart,
+
+    /// Остановить таймер с именем `name_var: string` и записать в `result_var: int` сколько миллисекунд прошло с `TIMER_START`
+    ///
+    /// Название: TIMER_STOP \
+    /// Параметры: `name_var`, `result_var`
+    TimerStop,
+
+    /// Вызвать функцию `func` в новом потоке, значения переменных `[arg_var1] ... [arg_varN]` копируются и передаются в функцию как аргументы, по завершении программы интерпретатор ждет завершения всех потоков
     ///
     /// Название: NEW_THREAD \
-    /// Параметры: `func`
+    /// Параметры: `func`, `[arg_var1] ... [arg_varN]`
     NewThread,
 
+    /// То же, что и `NEW_THREAD`, но поток записывается в `thread_var` (тип переменной: `thread`, если название `thread_var` - `null`, то поток никуда не записывается)
+    ///
+    /// Название: NEW_THREAD_HANDLE \
+    /// Параметры: `func`, `thread_var`, `[arg_var1] ... [arg_varN]`
+    NewThreadHandle,
+
+    /// То же, что и `NEW_THREAD_HANDLE`, но поток получает копию глобальных переменных на момент запуска: изменения глобальных переменных в потоке не видны остальной программе и наоборот, общими остаются только потоки ввода/вывода
+    ///
+    /// Название: NEW_ISOLATED_THREAD \
+    /// Параметры: `func`, `[thread_var]`, `[arg_var1] ... [arg_varN]`
+    NewIsolatedThread,
+
+    /// Ждать, пока поток `thread_var` (тип переменной: `thread`) завершится, если функция потока завершилась с ошибкой, то `JOIN_THREAD` выдает эту ошибку (ошибки потоков, которые никто не дождался, выдаются по завершении программы)
+    ///
+    /// Название: JOIN_THREAD \
+    /// Параметры: `thread_var`
+    JoinThread,
+
+    /// Узнать, завершился ли поток `thread_var` (тип переменной: `thread`) и записать результат в `result_var`
+    ///
+    /// Название: THREAD_IS_FINISHED \
+    /// Параметры: `thread_var`, `result_var`
+    ThreadIsFinished,
+
+    /// Создать новую блокировку и записать ее в `lock_var`
+    ///
+    /// Название: LOCK_NEW \
+    /// Параметры: `lock_var`
+    LockNew,
+
+    /// Захватить блокировку `lock_var` (тип переменной: `lock`), если она уже захвачена, то ждать ее освобождения
+    ///
+    /// Название: LOCK_ACQUIRE \
+    /// Параметры: `lock_var`
+    LockAcquire,
+
+    /// Освободить блокировку `lock_var` (тип переменной: `lock`)
+    ///
+    /// Название: LOCK_RELEASE \
+    /// Параметры: `lock_var`
+    LockRelease,
+
+    /// Создать новый атомарный счетчик со значением `value_var` (тип переменной: `int`, необязательный параметр, по умолчанию 0) и записать его в `atomic_var`
+    ///
+    /// Название: ATOMIC_NEW \
+    /// Параметры: `atomic_var`, `[value_var]`
+    AtomicNew,
+
+    /// Атомарно прибавить к счетчику `atomic_var` (тип переменной: `atomic`) значение `value_var` (тип переменной: `int`) и записать новое значение в `result_var` (необязательный параметр), при переполнении счетчик переходит через край
+    ///
+    /// Название: ATOMIC_ADD \
+    /// Параметры: `atomic_var`, `value_var`, `[result_var]`
+    AtomicAdd,
+
+    /// Записать текущее значение счетчика `atomic_var` (тип переменной: `atomic`) в `result_var`
+    ///
+    /// Название: ATOMIC_GET \
+    /// Параметры: `atomic_var`, `result_var`
+    AtomicGet,
+
+    /// Создать ограничитель частоты, который выдает `permits_var` разрешений в секунду (тип переменной: `int`), и записать его в `limiter_var`
+    ///
+    /// Название: RATE_LIMIT \
+    /// Параметры: `limiter_var`, `permits_var`
+    RateLimit,
+
+    /// Получить `permits_var` разрешений (тип переменной: `int`, необязательный параметр, по умолчанию 1) от ограничителя `limiter_var` (тип переменной: `limiter`), если разрешений не хватает, то ждать, пока они появятся
+    ///
+    /// Название: ACQUIRE \
+    /// Параметры: `limiter_var`, `[permits_var]`
+    Acquire,
+
+    /// Закодировать поля `fields_var` (тип переменной: `map[string,string]`) как `application/x-www-form-urlencoded` и записать тело в `body_var`, а заголовок `Content-Type` - в `content_type_var`
+    ///
+    /// Название: HTTP_FORM_ENCODE \
+    /// Параметры: `fields_var`, `body_var`, `content_type_var`
+    HttpFormEncode,
+
+    /// Собрать тело `multipart/form-data` из полей `fields_var` и файлов `files_var` (имя поля - путь к файлу, тип обеих переменных: `map[string,string]`) и записать его в `body_var` типа `string`/`list[char]`, а заголовок `Content-Type` с границей - в `content_type_var`
+    ///
+    /// Название: MULTIPART_BUILD \
+    /// Параметры: `fields_var`, `files_var`, `body_var`, `content_type_var`
+    MultipartBuild,
+
+    /// Создать новую HTTP сессию (куки и заголовки, общие для всех запросов) и записать ее в `session_var`
+    ///
+    /// Название: HTTP_SESSION_NEW \
+    /// Параметры: `session_var`
+    HttpSessionNew,
+
+    /// Установить заголовок `name_var` со значением `value_var` для всех запросов сессии `session_var` (тип переменной: `http_session`), пустое значение удаляет заголовок
+    ///
+    /// Название: HTTP_SESSION_SET_HEADER \
+    /// Параметры: `session_var`, `name_var`, `value_var`
+    HttpSessionSetHeader,
+
+    /// Запомнить в сессии `session_var` (тип переменной: `http_session`) куку из значения заголовка `Set-Cookie` в `set_cookie_var`, кука с `Max-Age=0` или пустым значением удаляется
+    ///
+    /// Название: HTTP_SESSION_STORE_COOKIE \
+    /// Параметры: `session_var`, `set_cookie_var`
+    HttpSessionStoreCookie,
+
+    /// Записать в `result_var` (тип переменной: `map[string,string]`) заголовки для запроса в сессии `session_var` (тип переменной: `http_session`): заголовки сессии и `Cookie` со всеми куками
+    ///
+    /// Название: HTTP_SESSION_HEADERS \
+    /// Параметры: `session_var`, `result_var`
+    HttpSessionHeaders,
+
+    /// Узнать точное время у SNTP сервера `server_var` (тип переменной: `string`, `host` или `host:port`, порт по умолчанию 123) и записать его в `result_var` (тип переменной: `int`) в миллисекундах с начала эпохи unix
+    ///
+    /// Название: GET_NETWORK_TIME \
+    /// Параметры: `server_var`, `result_var`
+    GetNetworkTime,
+
+    /// Узнать IP адреса хоста `host_var` (тип переменной: `string`) и записать их в `result_var` (тип переменной: `list[string]`), если адрес не найден - ошибка `DnsError`
+    ///
+    /// Название: DNS_RESOLVE \
+    /// Параметры: `host_var`, `result_var`
+    DnsResolve,
+
+    /// Отправить GET запрос по адресу `url_var` (тип переменной: `string`, только `http://`) и записать код ответа в `status_var` (тип переменной: `int`), а тело ответа в `body_var` типа `string`/`list[char]`. С сессией `session_var` (тип переменной: `http_session`, необязательный параметр) отправляются ее заголовки и куки, а куки из `Set-Cookie` ответа запоминаются в ней
+    ///
+    /// Название: HTTP_GET \
+    /// Параметры: `url_var`, `status_var`, `body_var`, `[session_var]`
+    HttpGet,
+
+    /// Отправить запрос с методом `method_var` по адресу `url_var` (тип переменных: `string`, только `http://`) с заголовками `headers_var` (тип переменной: `map[string,string]`) и телом `request_body_var` (тип переменной: `string`/`list[char]`). Код ответа записывается в `status_var` (тип переменной: `int`), тело ответа в `body_var` типа `string`/`list[char]`, а заголовки ответа в `response_headers_var` (тип переменной: `map[string,string]`, необязательный параметр, `null` - не записывать) с названиями в нижнем регистре. С сессией `session_var` (тип переменной: `http_session`, необязательный параметр) отправляются ее заголовки и куки, а куки из `Set-Cookie` ответа запоминаются в ней, заголовки из `headers_var` заменяют заголовки сессии с тем же названием
+    ///
+    /// Название: HTTP_REQUEST \
+    /// Параметры: `method_var`, `url_var`, `headers_var`, `request_body_var`, `status_var`, `body_var`, `[response_headers_var]`, `[session_var]`
+    HttpRequest,
+
+    /// Записать в `result_var` (тип переменной: `optional[string]`) значение переменной окружения `name_var` (тип переменной: `string`), если переменной нет, то пустой optional
+    ///
+    /// Название: ENV_GET \
+    /// Параметры: `name_var`, `result_var`
+    EnvGet,
+
+    /// Установить переменной окружения `name_var` значение `value_var` (тип переменных: `string`), изменение видно процессам, запущенным после этого
+    ///
+    /// Название: ENV_SET \
+    /// Параметры: `name_var`, `value_var`
+    EnvSet,
+
+    /// Запустить программу `cmd_var` (тип переменной: `string`) с аргументами `args_var` (тип переменной: `list[string]`). В `stdout_stream` (тип переменной: `in_stream`) записывается поток вывода процесса, в `stdin_stream` (тип переменной: `out_stream`) поток ввода. В `exit_code_var` (тип переменной: `atomic`) записывается -1, после завершения процесса в нем будет код выхода
+    ///
+    /// Название: SPAWN \
+    /// Параметры: `cmd_var`, `args_var`, `exit_code_var`, `stdout_stream`, `stdin_stream`
+    Spawn,
+
+    /// Отправить письмо с темой `subject_var` и текстом `body_var` на адрес `to_var` через SMTP сервер `server_var:port_var` с логином `user_var` и паролем `pass_var`, логин также используется как адрес отправителя. На порту 465 используется TLS, на остальных - STARTTLS (все параметры - переменные, `port_var: int`, остальные `string`)
+    ///
+    /// Название: SEND_MAIL \
+    /// Параметры: `server_var`, `port_var`, `user_var`, `pass_var`, `to_var`, `subject_var`, `body_var`
+    #[cfg(feature = "mail")]
+    SendMail,
+
+    /// Записать текст из буфера обмена в `result_var` (тип переменной: `string`), если в буфере нет текста, то записывается пустая строка
+    ///
+    /// Название: CLIPBOARD_GET \
+    /// Параметры: `result_var`
+    #[cfg(feature = "clipboard")]
+    ClipboardGet,
+
+    /// Скопировать текст `text_var` (тип переменной: `string`) в буфер обмена
+    ///
+    /// Название: CLIPBOARD_SET \
+    /// Параметры: `text_var`
+    #[cfg(feature = "clipboard")]
+    ClipboardSet,
+
+    /// Показать уведомление с заголовком `title_var` и текстом `body_var` (тип переменных: `string`)
+    ///
+    /// Название: NOTIFY \
+    /// Параметры: `title_var`, `body_var`
+    #[cfg(feature = "notify")]
+    Notify,
+
+    /// Подключиться к MQTT брокеру `host_var:port_var` с идентификатором клиента `client_id_var` (`port_var: int`, остальные `string`) и записать подключение в `mqtt_var` (тип переменной: `mqtt`)
+    ///
+    /// Название: MQTT_CONNECT \
+    /// Параметры: `host_var`, `port_var`, `client_id_var`, `mqtt_var`
+    #[cfg(feature = "mqtt")]
+    MqttConnect,
+
+    /// Отправить сообщение `payload_var` (тип переменной: `string`) в топик `topic_var` (тип переменной: `string`) через подключение `mqtt_var` (тип переменной: `mqtt`)
+    ///
+    /// Название: MQTT_PUBLISH \
+    /// Параметры: `mqtt_var`, `topic_var`, `payload_var`
+    #[cfg(feature = "mqtt")]
+    MqttPublish,
+
+    /// Подписаться через подключение `mqtt_var` (тип переменной: `mqtt`) на топики `topic_var` (тип переменной: `string`, можно использовать `+` и `#`), функция `handler_func(string)` вызывается с текстом каждого сообщения в отдельном потоке, ее ошибки пишутся в `cerr`
+    ///
+    /// Название: MQTT_SUBSCRIBE \
+    /// Параметры: `mqtt_var`, `topic_var`, `handler_func`
+    #[cfg(feature = "mqtt")]
+    MqttSubscribe,
+
     /// Функция `func` вызывается с переданными аргументами и устанавливает результат в переменную `result_var`
     ///
     /// Название: USE_FUNC \
     /// Параметры: `func_name`, `result_var`, `[arg_var1] ... [arg_varN]`
     UseFunc,
 
+    /// Функция с названием из переменной `func_name_var` (тип переменной: `string`) вызывается с переданными аргументами и устанавливает результат в переменную `result_var`, если функции нет - ошибка `FunctionUnknownError`
+    ///
+    /// Название: CALL_DYNAMIC \
+    /// Параметры: `func_name_var`, `result_var`, `[arg_var1] ... [arg_varN]`
+    CallDynamic,
+
+    /// Узнать, существует ли функция с названием из переменной `func_name_var` (тип переменной: `string`) и записать результат в `result_var`
+    ///
+    /// Название: HAS_FUNC \
+    /// Параметры: `func_name_var`, `result_var`
+    HasFunc,
+
+    /// Запоминать результаты функции `func_name` по значениям аргументов: повторный вызов с теми же аргументами не выполняет функцию, а выдает сохраненный результат. Подходит только для функций без побочных эффектов
+    ///
+    /// Название: MEMO_FUNC \
+    /// Параметры: `func_name`
+    MemoFunc,
+
     /// Создать функцию с типом результата `result_type`, названием `func_name` и аргументами `[arg_name_1 arg_type] ... [arg_name_N arg_type]`. Установить результат переменной можно изменив переменную `result` внутри функции. Все команды после этой и до `FUNC_END` будут командами функции. Функции внутри функций не могут быть.
     ///
     /// Название: FUNC \
@@ -241,6 +760,11 @@ pub enum CommandType {
     /// Название: RETURN
     Return,
 
+    /// Досрочно выйти из цикла (`LOOP`, `DO_WHILE`, `WHILE`, `FOR`, `FOR_LIST`, `FOR_MAP`, `FOR_STRING`). Работает в функции цикла и в функциях `IF` внутри неё, `BREAK` в функции, вызванной через `USE_FUNC` или другую команду, завершается ошибкой `BreakOutsideLoopError`
+    ///
+    /// Название: BREAK
+    Break,
+
     /// Маркер, что команды функции тут заканчиваются
     ///
     /// Название: FUNC_END
@@ -324,6 +848,36 @@ pub enum CommandType {
     /// Параметры: `optional_var`, `result_var`
     HasOptional,
 
+    /// Узнать, является ли символ `char_var` (тип переменной: `char`) цифрой и записать результат в `result_var`
+    ///
+    /// Название: IS_DIGIT \
+    /// Параметры: `char_var`, `result_var`
+    IsDigit,
+
+    /// Узнать, является ли символ `char_var` (тип переменной: `char`) буквой и записать результат в `result_var`
+    ///
+    /// Название: IS_ALPHA \
+    /// Параметры: `char_var`, `result_var`
+    IsAlpha,
+
+    /// Узнать, является ли символ `char_var` (тип переменной: `char`) пробельным символом и записать результат в `result_var`
+    ///
+    /// Название: IS_WHITESPACE \
+    /// Параметры: `char_var`, `result_var`
+    IsWhitespace,
+
+    /// Узнать, является ли символ `char_var` (тип переменной: `char`) заглавной буквой и записать результат в `result_var`
+    ///
+    /// Название: IS_UPPER \
+    /// Параметры: `char_var`, `result_var`
+    IsUpper,
+
+    /// Узнать, является ли символ `char_var` (тип переменной: `char`) строчной буквой и записать результат в `result_var`
+    ///
+    /// Название: IS_LOWER \
+    /// Параметры: `char_var`, `result_var`
+    IsLower,
+
     /// Достать данные из `optional_var` и установить в `result_var`
     ///
     /// Название: UNPACK_OPTIONAL \
@@ -342,24 +896,96 @@ pub enum CommandType {
     /// Параметры: `var`
     NoneOptional,
 
+    /// Достать данные из `optional_var` и установить в `result_var`, если `optional_var` пустой, то установить значение `default_var`
+    ///
+    /// Название: UNPACK_OR \
+    /// Параметры: `optional_var`, `default_var`, `result_var`
+    UnpackOr,
+
+    /// Если `optional_var` не пустой, то вызвать функцию `func` с его данными и упаковать результат в `optional` в `result_var`, иначе установить в `result_var` пустой `optional`
+    ///
+    /// Название: OPTIONAL_MAP \
+    /// Параметры: `optional_var`, `func`, `result_var`
+    OptionalMap,
+
     /// Получить размер списка и записать в переменную `result_var` типа `int`
     ///
     /// Название: LIST_SIZE \
     /// Параметры: `list_var`, `result_var`
     ListSize,
 
-    /// Получить размер строки и записать в переменную `result_var` типа `int`
+    /// Получить размер мапы и записать в переменную `result_var` типа `int`
     ///
     /// Название: MAP_SIZE \
     /// Параметры: `map_var`, `result_var`
     MapSize,
 
-    /// Получить размер мапы и записать в переменную `result_var` типа `int`
+    /// Получить размер строки в символах юникода и записать в переменную `result_var` типа `int`
     ///
     /// Название: STRING_SIZE \
     /// Параметры: `string_var`, `result_var`
     StringSize,
 
+    /// Получить размер строки в байтах UTF-8 и записать в переменную `result_var` типа `int`
+    ///
+    /// Название: STRING_SIZE_BYTES \
+    /// Параметры: `string_var`, `result_var`
+    StringSizeBytes,
+
+    /// Получить размер строки в графемах (видимых символах, например буква с ударением или эмодзи из нескольких символов юникода) и записать в переменную `result_var` типа `int`
+    ///
+    /// Название: STRING_SIZE_GRAPHEMES \
+    /// Параметры: `string_var`, `result_var`
+    StringSizeGraphemes,
+
+    /// Добавить значение `value_var` в множество `set_var` (тип значения должен совпадать с типом множества)
+    ///
+    /// Название: SET_ADD \
+    /// Параметры: `set_var`, `value_var`
+    SetAdd,
+
+    /// Убрать значение `value_var` из множества `set_var`, если его там нет, то ничего не меняется
+    ///
+    /// Название: SET_REMOVE \
+    /// Параметры: `set_var`, `value_var`
+    SetRemove,
+
+    /// Узнать, есть ли в множестве `set_var` значение `value_var` и записать результат в `result_var`
+    ///
+    /// Название: SET_HAS \
+    /// Параметры: `set_var`, `value_var`, `result_var`
+    SetHas,
+
+    /// Записать в `result_var` объединение множеств `set_var` и `other_var` одного типа
+    ///
+    /// Название: SET_UNION \
+    /// Параметры: `set_var`, `other_var`, `result_var`
+    SetUnion,
+
+    /// Записать в `result_var` пересечение множеств `set_var` и `other_var` одного типа
+    ///
+    /// Название: SET_INTERSECT \
+    /// Параметры: `set_var`, `other_var`, `result_var`
+    SetIntersect,
+
+    /// Функция `predicate_func` (с единственным аргументом и результатом `bool`) вызывается для каждого предмета списка `list_var`, количество предметов, для которых она выдала `true`, записывается в `result_var` типа `int`
+    ///
+    /// Название: COUNT_IF \
+    /// Параметры: `list_var`, `predicate_func(any) -> bool`, `result_var`
+    CountIf,
+
+    /// Получить сумму предметов списка `list_var` (тип переменной: `list[int]`/`list[float]`) и записать в `result_var` (тип переменной: `int`/`float`)
+    ///
+    /// Название: SUM_LIST \
+    /// Параметры: `list_var`, `result_var`
+    SumList,
+
+    /// Получить среднее значение предметов списка `list_var` (тип переменной: `list[int]`/`list[float]`) и записать в `result_var` типа `float`, для пустого списка результат `NaN`
+    ///
+    /// Название: AVG_LIST \
+    /// Параметры: `list_var`, `result_var`
+    AvgList,
+
     /// Функция `func` вызывается для каждого символа строки `string_var`
     ///
     /// Название: FOR_STRING \
@@ -378,32 +1004,272 @@ pub enum CommandType {
     /// Параметры: `script_text_var`
     ImportText,
 
-    /// Получить рандомное число от `min_var: int` до `max_var: int` включительно и записать в `result_var: int`
+    /// Получить рандомное число от `min_var: int` до `max_var: int` включительно и записать в `result_var: int`, если `min_var` больше `max_var`, то выдается ошибка `RangeError`
     ///
     /// Название: RANDOM \
     /// Параметры: `min_var`, `max_var`, `result_var`
     Random,
 
-    /// Узнать существует ли файл по пути `path_var` и записать результат в `result_var`
+    /// Получить рандомное дробное число от 0 включительно до 1 и записать в `result_var: float`
+    ///
+    /// Название: RANDOM_FLOAT \
+    /// Параметры: `result_var`
+    RandomFloat,
+
+    /// Получить `count_var: int` рандомных байт и записать в `result_var: bytes`
+    ///
+    /// Название: RANDOM_BYTES \
+    /// Параметры: `count_var`, `result_var`
+    RandomBytes,
+
+    /// Выбрать рандомный элемент списка `list_var` и записать в `result_var`
+    ///
+    /// Название: RANDOM_CHOICE \
+    /// Параметры: `list_var`, `result_var`
+    RandomChoice,
+
+    /// Задать зерно `seed_var: int` для рандома, после этого `RANDOM*` выдают одни и те же числа при каждом запуске
+    ///
+    /// Название: RANDOM_SEED \
+    /// Параметры: `seed_var`
+    RandomSeed,
+
+    /// Узнать существует ли файл или папка по пути `path_var` и записать результат в `result_var: bool`
     ///
     /// Название: FILE_EXISTS \
     /// Параметры: `path_var`, `result_var`
     FileExists,
 
-    /// Узнать является ли папкой `path_var` и записать результат в `result_var`
+    /// Узнать является ли папкой `path_var` и записать результат в `result_var: bool`
     ///
     /// Название: IS_FOLDER \
     /// Параметры: `path_var`, `result_var`
     IsFolder,
 
-    /// Получить все пути до файлов внутри папки `path_var` и записать результат в `result_var: list[string]`
+    /// Получить все пути до файлов внутри папки `path_var` (в порядке сортировки) и записать результат в `result_var: list[string]`
     ///
-    /// Название: FOLDER_LIST \
+    /// Название: LIST_DIR (старое название: FOLDER_LIST) \
     /// Параметры: `path_var`, `result_var`
-    FolderList,
+    ListDir,
+
+    /// Удалить файл или пустую папку по пути `path_var`
+    ///
+    /// Название: FILE_DELETE \
+    /// Параметры: `path_var`
+    FileDelete,
+
+    /// Скопировать файл `from_var` в `to_var`, если `to_var` уже есть, то он перезаписывается
+    ///
+    /// Название: FILE_COPY \
+    /// Параметры: `from_var`, `to_var`
+    FileCopy,
+
+    /// Переименовать (переместить) файл или папку `from_var` в `to_var`
+    ///
+    /// Название: FILE_RENAME \
+    /// Параметры: `from_var`, `to_var`
+    FileRename,
+
+    /// Создать папку `path_var` вместе со всеми родительскими папками, если их нет
+    ///
+    /// Название: MKDIR \
+    /// Параметры: `path_var`
+    MakeDir,
 }
 
 impl CommandType {
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            CommandType::InitVar => "INIT_VAR",
+            CommandType::SetVar => "SET_VAR",
+            CommandType::TempVar => "TEMP_VAR",
+            CommandType::MoveVar => "MOVE_VAR",
+            CommandType::CopyVar => "COPY_VAR",
+            CommandType::DropVar => "DROP_VAR",
+            CommandType::HasVar => "HAS_VAR",
+            CommandType::TypeAlias => "TYPE_ALIAS",
+            CommandType::Meta => "META",
+            CommandType::Pragma => "PRAGMA",
+            CommandType::ToString => "TO_STRING",
+            CommandType::ToChars => "TO_CHARS",
+            CommandType::ToInteger => "TO_INTEGER",
+            CommandType::ToIntegerRadix => "TO_INTEGER_RADIX",
+            CommandType::ToStringRadix => "TO_STRING_RADIX",
+            CommandType::Hash => "HASH",
+            CommandType::JsonParse => "JSON_PARSE",
+            CommandType::JsonStringify => "JSON_STRINGIFY",
+            CommandType::ToFloat => "TO_FLOAT",
+            CommandType::FormatFloat => "FORMAT_FLOAT",
+            CommandType::ToChar => "TO_CHAR",
+            CommandType::ToBool => "TO_BOOL",
+            CommandType::GetSymbol => "GET_SYMBOL",
+            CommandType::GetItem => "GET_ITEM",
+            CommandType::GetValue => "GET_VALUE",
+            CommandType::GetItemOpt => "GET_ITEM_OPT",
+            CommandType::GetValueOpt => "GET_VALUE_OPT",
+            CommandType::GetPathOpt => "GET_PATH_OPT",
+            CommandType::AddInt => "ADD_INT",
+            CommandType::AddFloat => "ADD_FLOAT",
+            CommandType::AddStr => "ADD_STR",
+            CommandType::SubStr => "SUB_STR",
+            CommandType::SubList => "SUB_LIST",
+            CommandType::BytesSlice => "BYTES_SLICE",
+            CommandType::BytesConcat => "BYTES_CONCAT",
+            CommandType::BytesLen => "BYTES_LEN",
+            CommandType::ListSize => "LIST_SIZE",
+            CommandType::MapSize => "MAP_SIZE",
+            CommandType::StringSize => "STRING_SIZE",
+            CommandType::StringSizeBytes => "STRING_SIZE_BYTES",
+            CommandType::StringSizeGraphemes => "STRING_SIZE_GRAPHEMES",
+            CommandType::SetAdd => "SET_ADD",
+            CommandType::SetRemove => "SET_REMOVE",
+            CommandType::SetHas => "SET_HAS",
+            CommandType::SetUnion => "SET_UNION",
+            CommandType::SetIntersect => "SET_INTERSECT",
+            CommandType::CountIf => "COUNT_IF",
+            CommandType::SumList => "SUM_LIST",
+            CommandType::AvgList => "AVG_LIST",
+            CommandType::Write => "WRITE",
+            CommandType::WriteLine => "WRITE_LINE",
+            CommandType::Flush => "FLUSH",
+            CommandType::Close => "CLOSE",
+            CommandType::PrintPretty => "PRINT_PRETTY",
+            CommandType::DumpVars => "DUMP_VARS",
+            CommandType::Read => "READ",
+            CommandType::ReadAll => "READ_ALL",
+            CommandType::ReadLine => "READ_LINE",
+            CommandType::Prompt => "PROMPT",
+            CommandType::StyleText => "STYLE_TEXT",
+            CommandType::ReadChar => "READ_CHAR",
+            CommandType::ReadLength => "READ_LENGTH",
+            CommandType::ReadSome => "READ_SOME",
+            CommandType::Pipe => "PIPE",
+            CommandType::For => "FOR",
+            CommandType::ForMap => "FOR_MAP",
+            CommandType::ForList => "FOR_LIST",
+            CommandType::ForString => "FOR_STRING",
+            CommandType::While => "WHILE",
+            CommandType::DoWhile => "DO_WHILE",
+            CommandType::Loop => "LOOP",
+            CommandType::OpenFileIn => "OPEN_FILE_IN",
+            CommandType::OpenFileOut => "OPEN_FILE_OUT",
+            CommandType::OpenFile => "OPEN_FILE",
+            CommandType::Seek => "SEEK",
+            CommandType::Tell => "TELL",
+            CommandType::OpenTcpConnection => "OPEN_TCP_CONNECTION",
+            CommandType::SetStreamTimeout => "SET_STREAM_TIMEOUT",
+            CommandType::OpenBuffer => "OPEN_BUFFER",
+            #[cfg(feature = "gzip")]
+            CommandType::GzipWrapOut => "GZIP_WRAP_OUT",
+            #[cfg(feature = "gzip")]
+            CommandType::GunzipWrapIn => "GUNZIP_WRAP_IN",
+            #[cfg(feature = "serial")]
+            CommandType::OpenSerial => "OPEN_SERIAL",
+            CommandType::OpenTcpListener => "OPEN_TCP_LISTENER",
+            CommandType::OpenHttpListener => "OPEN_HTTP_LISTENER",
+            CommandType::Sleep => "SLEEP",
+            CommandType::SleepUntil => "SLEEP_UNTIL",
+            CommandType::TimeNow => "TIME_NOW",
+            CommandType::TimeFormat => "TIME_FORMAT",
+            CommandType::TimeDiff => "TIME_DIFF",
+            CommandType::DateParse => "DATE_PARSE",
+            CommandType::DateFormat => "DATE_FORMAT",
+            CommandType::DateAdd => "DATE_ADD",
+            CommandType::DurationMs => "DURATION_MS",
+            CommandType::DurationSec => "DURATION_SEC",
+            CommandType::DurationMin => "DURATION_MIN",
+            CommandType::DurationToMs => "DURATION_TO_MS",
+            CommandType::Measure => "MEASURE",
+            CommandType::TimerStart => "TIMER_START",
+            CommandType::TimerStop => "TIMER_STOP",
+            CommandType::NewThread => "NEW_THREAD",
+            CommandType::NewThreadHandle => "NEW_THREAD_HANDLE",
+            CommandType::NewIsolatedThread => "NEW_ISOLATED_THREAD",
+            CommandType::JoinThread => "JOIN_THREAD",
+            CommandType::ThreadIsFinished => "THREAD_IS_FINISHED",
+            CommandType::LockNew => "LOCK_NEW",
+            CommandType::LockAcquire => "LOCK_ACQUIRE",
+            CommandType::LockRelease => "LOCK_RELEASE",
+            CommandType::AtomicNew => "ATOMIC_NEW",
+            CommandType::AtomicAdd => "ATOMIC_ADD",
+            CommandType::AtomicGet => "ATOMIC_GET",
+            CommandType::RateLimit => "RATE_LIMIT",
+            CommandType::Acquire => "ACQUIRE",
+            CommandType::HttpFormEncode => "HTTP_FORM_ENCODE",
+            CommandType::MultipartBuild => "MULTIPART_BUILD",
+            CommandType::HttpSessionNew => "HTTP_SESSION_NEW",
+            CommandType::HttpSessionSetHeader => "HTTP_SESSION_SET_HEADER",
+            CommandType::HttpSessionStoreCookie => "HTTP_SESSION_STORE_COOKIE",
+            CommandType::HttpSessionHeaders => "HTTP_SESSION_HEADERS",
+            CommandType::GetNetworkTime => "GET_NETWORK_TIME",
+            CommandType::DnsResolve => "DNS_RESOLVE",
+            CommandType::HttpGet => "HTTP_GET",
+            CommandType::HttpRequest => "HTTP_REQUEST",
+            CommandType::EnvGet => "ENV_GET",
+            CommandType::EnvSet => "ENV_SET",
+            CommandType::Spawn => "SPAWN",
+            #[cfg(feature = "mail")]
+            CommandType::SendMail => "SEND_MAIL",
+            #[cfg(feature = "clipboard")]
+            CommandType::ClipboardGet => "CLIPBOARD_GET",
+            #[cfg(feature = "clipboard")]
+            CommandType::ClipboardSet => "CLIPBOARD_SET",
+            #[cfg(feature = "notify")]
+            CommandType::Notify => "NOTIFY",
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttConnect => "MQTT_CONNECT",
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttPublish => "MQTT_PUBLISH",
+            #[cfg(feature = "mqtt")]
+            CommandType::MqttSubscribe => "MQTT_SUBSCRIBE",
+            CommandType::UseFunc => "USE_FUNC",
+            CommandType::CallDynamic => "CALL_DYNAMIC",
+            CommandType::HasFunc => "HAS_FUNC",
+            CommandType::MemoFunc => "MEMO_FUNC",
+            CommandType::Func => "FUNC",
+            CommandType::FuncEnd => "FUNC_END",
+            CommandType::Return => "RETURN",
+            CommandType::Break => "BREAK",
+            CommandType::Equals => "EQUALS",
+            CommandType::More => "MORE",
+            CommandType::Less => "LESS",
+            CommandType::And => "AND",
+            CommandType::Or => "OR",
+            CommandType::Not => "NOT",
+            CommandType::If => "IF",
+            CommandType::HasStr => "HAS_STR",
+            CommandType::HasItem => "HAS_ITEM",
+            CommandType::HasEntry => "HAS_ENTRY",
+            CommandType::HasKey => "HAS_KEY",
+            CommandType::HasValue => "HAS_VALUE",
+            CommandType::HasOptional => "HAS_OPTIONAL",
+            CommandType::IsDigit => "IS_DIGIT",
+            CommandType::IsAlpha => "IS_ALPHA",
+            CommandType::IsWhitespace => "IS_WHITESPACE",
+            CommandType::IsUpper => "IS_UPPER",
+            CommandType::IsLower => "IS_LOWER",
+            CommandType::UnpackOptional => "UNPACK_OPTIONAL",
+            CommandType::PackOptional => "PACK_OPTIONAL",
+            CommandType::NoneOptional => "NONE_OPTIONAL",
+            CommandType::UnpackOr => "UNPACK_OR",
+            CommandType::OptionalMap => "OPTIONAL_MAP",
+            CommandType::ImportText => "IMPORT_TEXT",
+            CommandType::Import => "IMPORT",
+            CommandType::Random => "RANDOM",
+            CommandType::RandomFloat => "RANDOM_FLOAT",
+            CommandType::RandomBytes => "RANDOM_BYTES",
+            CommandType::RandomChoice => "RANDOM_CHOICE",
+            CommandType::RandomSeed => "RANDOM_SEED",
+            CommandType::FileExists => "FILE_EXISTS",
+            CommandType::IsFolder => "IS_FOLDER",
+            CommandType::ListDir => "LIST_DIR",
+            CommandType::FileDelete => "FILE_DELETE",
+            CommandType::FileCopy => "FILE_COPY",
+            CommandType::FileRename => "FILE_RENAME",
+            CommandType::MakeDir => "MKDIR",
+        }
+    }
+
     pub fn from_name(name: &str) -> Result<CommandType, ScriptError> {
         match name {
             "INIT_VAR" => Ok(CommandType::InitVar),
@@ -413,44 +1279,149 @@ impl CommandType {
             "COPY_VAR" => Ok(CommandType::CopyVar),
             "DROP_VAR" => Ok(CommandType::DropVar),
             "HAS_VAR" => Ok(CommandType::HasVar),
+            "TYPE_ALIAS" => Ok(CommandType::TypeAlias),
+            "META" => Ok(CommandType::Meta),
+            "PRAGMA" => Ok(CommandType::Pragma),
             "TO_STRING" => Ok(CommandType::ToString),
             "TO_CHARS" => Ok(CommandType::ToChars),
             "TO_INTEGER" => Ok(CommandType::ToInteger),
+            "TO_INTEGER_RADIX" => Ok(CommandType::ToIntegerRadix),
+            "TO_STRING_RADIX" => Ok(CommandType::ToStringRadix),
+            "HASH" => Ok(CommandType::Hash),
+            "JSON_PARSE" => Ok(CommandType::JsonParse),
+            "JSON_STRINGIFY" => Ok(CommandType::JsonStringify),
             "TO_FLOAT" => Ok(CommandType::ToFloat),
+            "FORMAT_FLOAT" => Ok(CommandType::FormatFloat),
             "TO_CHAR" => Ok(CommandType::ToChar),
             "TO_BOOL" => Ok(CommandType::ToBool),
             "GET_SYMBOL" => Ok(CommandType::GetSymbol),
             "GET_ITEM" => Ok(CommandType::GetItem),
             "GET_VALUE" => Ok(CommandType::GetValue),
+            "GET_ITEM_OPT" => Ok(CommandType::GetItemOpt),
+            "GET_VALUE_OPT" => Ok(CommandType::GetValueOpt),
+            "GET_PATH_OPT" => Ok(CommandType::GetPathOpt),
             "ADD_INT" => Ok(CommandType::AddInt),
             "ADD_FLOAT" => Ok(CommandType::AddFloat),
             "ADD_STR" => Ok(CommandType::AddStr),
             "SUB_STR" => Ok(CommandType::SubStr),
             "SUB_LIST" => Ok(CommandType::SubList),
+            "BYTES_SLICE" => Ok(CommandType::BytesSlice),
+            "BYTES_CONCAT" => Ok(CommandType::BytesConcat),
+            "BYTES_LEN" => Ok(CommandType::BytesLen),
             "LIST_SIZE" => Ok(CommandType::ListSize),
             "MAP_SIZE" => Ok(CommandType::MapSize),
             "STRING_SIZE" => Ok(CommandType::StringSize),
+            "STRING_SIZE_BYTES" => Ok(CommandType::StringSizeBytes),
+            "STRING_SIZE_GRAPHEMES" => Ok(CommandType::StringSizeGraphemes),
+            "SET_ADD" => Ok(CommandType::SetAdd),
+            "SET_REMOVE" => Ok(CommandType::SetRemove),
+            "SET_HAS" => Ok(CommandType::SetHas),
+            "SET_UNION" => Ok(CommandType::SetUnion),
+            "SET_INTERSECT" => Ok(CommandType::SetIntersect),
+            "COUNT_IF" => Ok(CommandType::CountIf),
+            "SUM_LIST" => Ok(CommandType::SumList),
+            "AVG_LIST" => Ok(CommandType::AvgList),
             "WRITE" => Ok(CommandType::Write),
+            "WRITE_LINE" => Ok(CommandType::WriteLine),
+            "FLUSH" => Ok(CommandType::Flush),
+            "CLOSE" => Ok(CommandType::Close),
+            "PRINT_PRETTY" => Ok(CommandType::PrintPretty),
+            "DUMP_VARS" => Ok(CommandType::DumpVars),
             "READ" => Ok(CommandType::Read),
             "READ_ALL" => Ok(CommandType::ReadAll),
             "READ_LINE" => Ok(CommandType::ReadLine),
+            "PROMPT" => Ok(CommandType::Prompt),
+            "STYLE_TEXT" => Ok(CommandType::StyleText),
             "READ_CHAR" => Ok(CommandType::ReadChar),
             "READ_LENGTH" => Ok(CommandType::ReadLength),
+            "READ_SOME" => Ok(CommandType::ReadSome),
+            "PIPE" => Ok(CommandType::Pipe),
             "FOR" => Ok(CommandType::For),
             "FOR_MAP" => Ok(CommandType::ForMap),
             "FOR_LIST" => Ok(CommandType::ForList),
             "FOR_STRING" => Ok(CommandType::ForString),
             "WHILE" => Ok(CommandType::While),
+            "DO_WHILE" => Ok(CommandType::DoWhile),
+            "LOOP" => Ok(CommandType::Loop),
             "OPEN_FILE_IN" => Ok(CommandType::OpenFileIn),
             "OPEN_FILE_OUT" => Ok(CommandType::OpenFileOut),
+            "OPEN_FILE" => Ok(CommandType::OpenFile),
+            "SEEK" => Ok(CommandType::Seek),
+            "TELL" => Ok(CommandType::Tell),
             "OPEN_TCP_CONNECTION" => Ok(CommandType::OpenTcpConnection),
+            "SET_STREAM_TIMEOUT" => Ok(CommandType::SetStreamTimeout),
+            "OPEN_BUFFER" => Ok(CommandType::OpenBuffer),
+            #[cfg(feature = "gzip")]
+            "GZIP_WRAP_OUT" => Ok(CommandType::GzipWrapOut),
+            #[cfg(feature = "gzip")]
+            "GUNZIP_WRAP_IN" => Ok(CommandType::GunzipWrapIn),
+            #[cfg(feature = "serial")]
+            "OPEN_SERIAL" => Ok(CommandType::OpenSerial),
             "OPEN_TCP_LISTENER" => Ok(CommandType::OpenTcpListener),
+            "OPEN_HTTP_LISTENER" => Ok(CommandType::OpenHttpListener),
             "SLEEP" => Ok(CommandType::Sleep),
+            "SLEEP_UNTIL" => Ok(CommandType::SleepUntil),
+            "TIME_NOW" => Ok(CommandType::TimeNow),
+            "TIME_FORMAT" => Ok(CommandType::TimeFormat),
+            "TIME_DIFF" => Ok(CommandType::TimeDiff),
+            "DATE_PARSE" => Ok(CommandType::DateParse),
+            "DATE_FORMAT" => Ok(CommandType::DateFormat),
+            "DATE_ADD" => Ok(CommandType::DateAdd),
+            "DURATION_MS" => Ok(CommandType::DurationMs),
+            "DURATION_SEC" => Ok(CommandType::DurationSec),
+            "DURATION_MIN" => Ok(CommandType::DurationMin),
+            "DURATION_TO_MS" => Ok(CommandType::DurationToMs),
+            "MEASURE" => Ok(CommandType::Measure),
+            "TIMER_START" => Ok(CommandType::TimerStart),
+            "TIMER_STOP" => Ok(CommandType::TimerStop),
             "NEW_THREAD" => Ok(CommandType::NewThread),
+            "NEW_THREAD_HANDLE" => Ok(CommandType::NewThreadHandle),
+            "NEW_ISOLATED_THREAD" => Ok(CommandType::NewIsolatedThread),
+            "JOIN_THREAD" => Ok(CommandType::JoinThread),
+            "THREAD_IS_FINISHED" => Ok(CommandType::ThreadIsFinished),
+            "LOCK_NEW" => Ok(CommandType::LockNew),
+            "LOCK_ACQUIRE" => Ok(CommandType::LockAcquire),
+            "LOCK_RELEASE" => Ok(CommandType::LockRelease),
+            "ATOMIC_NEW" => Ok(CommandType::AtomicNew),
+            "ATOMIC_ADD" => Ok(CommandType::AtomicAdd),
+            "ATOMIC_GET" => Ok(CommandType::AtomicGet),
+            "RATE_LIMIT" => Ok(CommandType::RateLimit),
+            "ACQUIRE" => Ok(CommandType::Acquire),
+            "HTTP_FORM_ENCODE" => Ok(CommandType::HttpFormEncode),
+            "MULTIPART_BUILD" => Ok(CommandType::MultipartBuild),
+            "HTTP_SESSION_NEW" => Ok(CommandType::HttpSessionNew),
+            "HTTP_SESSION_SET_HEADER" => Ok(CommandType::HttpSessionSetHeader),
+            "HTTP_SESSION_STORE_COOKIE" => Ok(CommandType::HttpSessionStoreCookie),
+            "HTTP_SESSION_HEADERS" => Ok(CommandType::HttpSessionHeaders),
+            "GET_NETWORK_TIME" => Ok(CommandType::GetNetworkTime),
+            "DNS_RESOLVE" => Ok(CommandType::DnsResolve),
+            "HTTP_GET" => Ok(CommandType::HttpGet),
+            "HTTP_REQUEST" => Ok(CommandType::HttpRequest),
+            "ENV_GET" => Ok(CommandType::EnvGet),
+            "ENV_SET" => Ok(CommandType::EnvSet),
+            "SPAWN" => Ok(CommandType::Spawn),
+            #[cfg(feature = "mail")]
+            "SEND_MAIL" => Ok(CommandType::SendMail),
+            #[cfg(feature = "clipboard")]
+            "CLIPBOARD_GET" => Ok(CommandType::ClipboardGet),
+            #[cfg(feature = "clipboard")]
+            "CLIPBOARD_SET" => Ok(CommandType::ClipboardSet),
+            #[cfg(feature = "notify")]
+            "NOTIFY" => Ok(CommandType::Notify),
+            #[cfg(feature = "mqtt")]
+            "MQTT_CONNECT" => Ok(CommandType::MqttConnect),
+            #[cfg(feature = "mqtt")]
+            "MQTT_PUBLISH" => Ok(CommandType::MqttPublish),
+            #[cfg(feature = "mqtt")]
+            "MQTT_SUBSCRIBE" => Ok(CommandType::MqttSubscribe),
             "USE_FUNC" => Ok(CommandType::UseFunc),
+            "CALL_DYNAMIC" => Ok(CommandType::CallDynamic),
+            "HAS_FUNC" => Ok(CommandType::HasFunc),
+            "MEMO_FUNC" => Ok(CommandType::MemoFunc),
             "FUNC" => Ok(CommandType::Func),
             "FUNC_END" => Ok(CommandType::FuncEnd),
             "RETURN" => Ok(CommandType::Return),
+            "BREAK" => Ok(CommandType::Break),
             "EQUALS" => Ok(CommandType::Equals),
             "MORE" => Ok(CommandType::More),
             "LESS" => Ok(CommandType::Less),
@@ -464,16 +1435,31 @@ impl CommandType {
             "HAS_KEY" => Ok(CommandType::HasKey),
             "HAS_VALUE" => Ok(CommandType::HasValue),
             "HAS_OPTIONAL" => Ok(CommandType::HasOptional),
+            "IS_DIGIT" => Ok(CommandType::IsDigit),
+            "IS_ALPHA" => Ok(CommandType::IsAlpha),
+            "IS_WHITESPACE" => Ok(CommandType::IsWhitespace),
+            "IS_UPPER" => Ok(CommandType::IsUpper),
+            "IS_LOWER" => Ok(CommandType::IsLower),
             "UNPACK_OPTIONAL" => Ok(CommandType::UnpackOptional),
             "PACK_OPTIONAL" => Ok(CommandType::PackOptional),
             "NONE_OPTIONAL" => Ok(CommandType::NoneOptional),
+            "UNPACK_OR" => Ok(CommandType::UnpackOr),
+            "OPTIONAL_MAP" => Ok(CommandType::OptionalMap),
             "IMPORT_TEXT" => Ok(CommandType::ImportText),
             "IMPORT" => Ok(CommandType::Import),
             "RANDOM" => Ok(CommandType::Random),
+            "RANDOM_FLOAT" => Ok(CommandType::RandomFloat),
+            "RANDOM_BYTES" => Ok(CommandType::RandomBytes),
+            "RANDOM_CHOICE" => Ok(CommandType::RandomChoice),
+            "RANDOM_SEED" => Ok(CommandType::RandomSeed),
             "FILE_EXISTS" => Ok(CommandType::FileExists),
             "IS_FOLDER" => Ok(CommandType::IsFolder),
-            "FOLDER_LIST" => Ok(CommandType::FolderList),
-            _ => Err(ScriptError::CommandUnknownError),
+            "LIST_DIR" | "FOLDER_LIST" => Ok(CommandType::ListDir),
+            "FILE_DELETE" => Ok(CommandType::FileDelete),
+            "FILE_COPY" => Ok(CommandType::FileCopy),
+            "FILE_RENAME" => Ok(CommandType::FileRename),
+            "MKDIR" => Ok(CommandType::MakeDir),
+            _ => Err(ScriptError::CommandUnknownError(name.to_string())),
         }
     }
 }
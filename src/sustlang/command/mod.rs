@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod command;
 pub mod command_type;
 
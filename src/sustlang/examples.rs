@@ -0,0 +1,77 @@
+use super::script::{RunningScript, Script, ScriptError};
+
+use std::io::{self, Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+/// Example program from the `examples` folder of the crate: `name.sus` is run with `name.in`
+/// as `cin` and has to write exactly `name.out` to `cout`
+pub struct Example {
+    pub name: &'static str,
+    pub source: &'static str,
+    pub input: &'static str,
+    pub output: &'static str,
+}
+
+macro_rules! example {
+    ($name:literal) => {
+        Example {
+            name: $name,
+            source: include_str!(concat!("../../examples/", $name, ".sus")),
+            input: include_str!(concat!("../../examples/", $name, ".in")),
+            output: include_str!(concat!("../../examples/", $name, ".out")),
+        }
+    };
+}
+
+/// Examples bundled into the library, a new example has to be added here too
+pub const EXAMPLES: &[Example] = &[
+    example!("hello_world"),
+    example!("input"),
+    example!("hash"),
+    example!("random_seed"),
+];
+
+/// `cout` of the example, kept after the script is dropped
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Example {
+    pub fn find(name: &str) -> Option<&'static Example> {
+        EXAMPLES.iter().find(|example| example.name == name)
+    }
+
+    /// Run the example with its input in memory and return everything it wrote to `cout`
+    pub fn run(&self) -> Result<String, ScriptError> {
+        let script = Script::parse(self.source.to_string()).map_err(|(e, _)| e)?;
+        let output = SharedOutput::default();
+        RunningScript::builder(script)
+            .arg(format!("{}.sus", self.name))
+            .stdin(Cursor::new(self.input.as_bytes().to_vec()))
+            .stdout(output.clone())
+            .stderr(Vec::new())
+            .build()?
+            .run_with_result()
+            .map_err(|(e, _)| e)?;
+
+        let output = output.0.lock().unwrap().clone();
+        String::from_utf8(output).map_err(|_| ScriptError::StringUTF8Error)
+    }
+}
+
+/// Output of the bundled example `name`, see `Example::run`.
+/// An unknown example is `FileNotFoundError` with the path of its source.
+pub fn run_example(name: &str) -> Result<String, ScriptError> {
+    Example::find(name)
+        .ok_or_else(|| ScriptError::FileNotFoundError(format!("examples/{}.sus", name)))?
+        .run()
+}
@@ -0,0 +1,89 @@
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+
+/// Stream of a variable that is locked for every read, so other copies of the variable
+/// can still use it between reads
+struct SharedReader(Arc<Mutex<dyn Read + Send>>);
+
+impl Read for SharedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().read(buf)
+    }
+}
+
+/// Stream of a variable that is locked for every write
+struct SharedWriter(Arc<Mutex<dyn Write + Send>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
+
+/// Stream of `GUNZIP_WRAP_IN`: gzip data of the wrapped stream is read as plain data,
+/// several gzip members one after another are read as one stream
+pub struct GzipInStream {
+    decoder: MultiGzDecoder<SharedReader>,
+}
+
+impl GzipInStream {
+    pub fn new(stream: Arc<Mutex<dyn Read + Send>>) -> GzipInStream {
+        GzipInStream {
+            decoder: MultiGzDecoder::new(SharedReader(stream)),
+        }
+    }
+}
+
+impl Read for GzipInStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf)
+    }
+}
+
+/// Stream of `GZIP_WRAP_OUT`: written data is compressed into the wrapped stream.
+/// `FLUSH` writes everything compressed so far, the end of the gzip data is written
+/// and the wrapped stream is flushed when the stream is closed.
+pub struct GzipOutStream {
+    encoder: Option<GzEncoder<SharedWriter>>,
+}
+
+impl GzipOutStream {
+    pub fn new(stream: Arc<Mutex<dyn Write + Send>>) -> GzipOutStream {
+        GzipOutStream {
+            encoder: Some(GzEncoder::new(SharedWriter(stream), Compression::default())),
+        }
+    }
+}
+
+impl Write for GzipOutStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Some(encoder) => encoder.write(buf),
+            None => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Some(encoder) => encoder.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for GzipOutStream {
+    fn drop(&mut self) {
+        if let Some(encoder) = self.encoder.take() {
+            // errors can't be reported from drop, like with files closed by `CLOSE`
+            let _ = encoder.finish().and_then(|mut stream| stream.flush());
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use super::script::ScriptError;
+
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Answer of `HTTP_GET` and `HTTP_REQUEST`
+pub struct HttpResponse {
+    pub status: u16,
+    /// Header names are lowercase, values of repeated headers are joined with `, `
+    pub headers: HashMap<String, String>,
+    /// Values of every `Set-Cookie` header, they are not joined because cookies may contain `, `
+    pub set_cookies: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+/// Address (`host` or `host:port`) and path of `http://host[:port][/path]`
+pub fn parse_http_url(url: &str) -> Result<(String, String), ScriptError> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or(ScriptError::CommandArgsInvalidError)?;
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(index) if rest[index..].starts_with('/') => {
+            (&rest[..index], rest[index..].to_string())
+        }
+        Some(index) => (&rest[..index], format!("/{}", &rest[index..])),
+        None => (rest, String::from("/")),
+    };
+    if authority.is_empty() || authority.contains('@') {
+        return Err(ScriptError::CommandArgsInvalidError);
+    }
+    Ok((authority.to_string(), path))
+}
+
+fn find(data: &[u8], pattern: &[u8]) -> Option<usize> {
+    data.windows(pattern.len())
+        .position(|window| window == pattern)
+}
+
+/// Body of `Transfer-Encoding: chunked`, trailers are ignored
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, ScriptError> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = find(data, b"\r\n").ok_or(ScriptError::NetworkError)?;
+        let size = String::from_utf8_lossy(&data[..line_end]);
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| ScriptError::NetworkError)?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        body.extend_from_slice(data.get(..size).ok_or(ScriptError::NetworkError)?);
+        data = data.get(size + 2..).ok_or(ScriptError::NetworkError)?;
+    }
+}
+
+/// Answer of the server, `head_only` is for answers to `HEAD` that never have a body
+fn parse_response(data: &[u8], head_only: bool) -> Result<HttpResponse, ScriptError> {
+    let head_end = find(data, b"\r\n\r\n").ok_or(ScriptError::NetworkError)?;
+    let head = String::from_utf8_lossy(&data[..head_end]);
+    let mut lines = head.split("\r\n");
+
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or(ScriptError::NetworkError)?;
+
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut set_cookies = Vec::new();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim().to_lowercase(), value.trim());
+        if name == "set-cookie" {
+            set_cookies.push(value.to_string());
+        }
+        headers
+            .entry(name)
+            .and_modify(|old| {
+                old.push_str(", ");
+                old.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+
+    let body = &data[head_end + 4..];
+    let body = if head_only {
+        Vec::new()
+    } else if headers
+        .get("transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+    {
+        decode_chunked(body)?
+    } else if let Some(length) = headers.get("content-length") {
+        let length: usize = length.parse().map_err(|_| ScriptError::NetworkError)?;
+        body.get(..length)
+            .ok_or(ScriptError::NetworkError)?
+            .to_vec()
+    } else {
+        body.to_vec()
+    };
+
+    Ok(HttpResponse {
+        status,
+        headers,
+        set_cookies,
+        body,
+    })
+}
+
+/// Send an HTTP/1.1 request to an `http://` url and read the whole answer. `Host`,
+/// `Content-Length` and `Connection: close` are added unless they are in `headers`.
+pub fn http_request(
+    method: &str,
+    url: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+    timeout: Duration,
+) -> Result<HttpResponse, ScriptError> {
+    if method.is_empty() || method.contains(|c: char| !c.is_ascii_alphabetic()) {
+        return Err(ScriptError::CommandArgsInvalidError);
+    }
+    let (authority, path) = parse_http_url(url)?;
+    let has_header = |name: &str| headers.iter().any(|(n, _)| n.eq_ignore_ascii_case(name));
+
+    let mut request = format!("{} {} HTTP/1.1\r\n", method, path);
+    if !has_header("host") {
+        request.push_str(&format!("Host: {}\r\n", authority));
+    }
+    if !has_header("connection") {
+        request.push_str("Connection: close\r\n");
+    }
+    if !has_header("content-length") && (!body.is_empty() || !matches!(method, "GET" | "HEAD")) {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    for (name, value) in headers {
+        if name.contains([':', '\r', '\n']) || value.contains(['\r', '\n']) {
+            return Err(ScriptError::CommandArgsInvalidError);
+        }
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+
+    let address = if authority.contains(':') {
+        authority
+    } else {
+        format!("{}:80", authority)
+    };
+    let address = address
+        .to_socket_addrs()
+        .map_err(|_| ScriptError::NetworkError)?
+        .next()
+        .ok_or(ScriptError::NetworkError)?;
+    let mut stream =
+        TcpStream::connect_timeout(&address, timeout).map_err(|_| ScriptError::NetworkError)?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .map_err(|_| ScriptError::NetworkError)?;
+
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|_| stream.write_all(body))
+        .map_err(|_| ScriptError::NetworkError)?;
+
+    let mut data = Vec::new();
+    stream
+        .read_to_end(&mut data)
+        .map_err(|_| ScriptError::NetworkError)?;
+
+    parse_response(&data, method == "HEAD")
+}
+
+/// Longest request line with headers that `OPEN_HTTP_LISTENER` reads
+const MAX_REQUEST_HEAD: usize = 64 * 1024;
+
+/// Longest request body that `OPEN_HTTP_LISTENER` reads
+const MAX_REQUEST_BODY: usize = 16 * 1024 * 1024;
+
+/// Request got by `OPEN_HTTP_LISTENER`
+pub struct HttpRequest {
+    pub method: String,
+    /// Path with the query string, as it was sent
+    pub path: String,
+    /// Header names are lowercase, values of repeated headers are joined with `, `
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// Read one request, the body is read by `Content-Length`, chunked bodies are not supported
+pub fn read_http_request(stream: &mut impl BufRead) -> Result<HttpRequest, ScriptError> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") && !head.ends_with(b"\n\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return Err(ScriptError::NetworkError);
+        }
+        let read = stream
+            .take(MAX_REQUEST_HEAD as u64)
+            .read_until(b'\n', &mut head)
+            .map_err(|_| ScriptError::NetworkError)?;
+        if read == 0 {
+            return Err(ScriptError::NetworkError);
+        }
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Err(ScriptError::NetworkError);
+    };
+
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim().to_lowercase(), value.trim());
+        headers
+            .entry(name)
+            .and_modify(|old| {
+                old.push_str(", ");
+                old.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+
+    if headers.contains_key("transfer-encoding") {
+        return Err(ScriptError::NetworkError);
+    }
+    let length = match headers.get("content-length") {
+        Some(length) => length.parse().map_err(|_| ScriptError::NetworkError)?,
+        None => 0,
+    };
+    if length > MAX_REQUEST_BODY {
+        return Err(ScriptError::NetworkError);
+    }
+    let mut body = vec![0u8; length];
+    stream
+        .read_exact(&mut body)
+        .map_err(|_| ScriptError::NetworkError)?;
+
+    Ok(HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers,
+        body,
+    })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+/// Write the response and close the connection, `Content-Length` and `Connection`
+/// are always set by the server
+pub fn write_http_response(
+    stream: &mut impl Write,
+    status: u16,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Result<(), ScriptError> {
+    let mut response = format!("HTTP/1.1 {} {}\r\n", status, reason_phrase(status));
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case("content-length") || name.eq_ignore_ascii_case("connection") {
+            continue;
+        }
+        if name.contains([':', '\r', '\n']) || value.contains(['\r', '\n']) {
+            return Err(ScriptError::CommandArgsInvalidError);
+        }
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+
+    stream
+        .write_all(response.as_bytes())
+        .and_then(|_| stream.write_all(body))
+        .and_then(|_| stream.flush())
+        .map_err(|_| ScriptError::StreamWriteError)
+}
@@ -0,0 +1,52 @@
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use super::script::ScriptError;
+
+/// Port of SMTP over TLS, other ports use STARTTLS
+const SMTPS_PORT: u16 = 465;
+
+/// Server of `SEND_MAIL` with the login, the login is also the sender address
+pub struct MailServer {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub pass: String,
+}
+
+/// Send a plain text mail from `server.user` to `to`
+pub fn send_mail(
+    server: &MailServer,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), ScriptError> {
+    let message = Message::builder()
+        .from(
+            server
+                .user
+                .parse()
+                .map_err(|_| ScriptError::ParseVarError)?,
+        )
+        .to(to.parse().map_err(|_| ScriptError::ParseVarError)?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body.to_string())
+        .map_err(|_| ScriptError::ParseVarError)?;
+
+    let transport = if server.port == SMTPS_PORT {
+        SmtpTransport::relay(&server.host)
+    } else {
+        SmtpTransport::starttls_relay(&server.host)
+    }
+    .map_err(|_| ScriptError::NetworkError)?
+    .port(server.port)
+    .credentials(Credentials::new(server.user.clone(), server.pass.clone()))
+    .build();
+
+    transport
+        .send(&message)
+        .map(|_| ())
+        .map_err(|_| ScriptError::NetworkError)
+}
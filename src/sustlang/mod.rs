@@ -1,9 +1,37 @@
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
 pub mod command;
+pub mod examples;
+#[cfg(feature = "gzip")]
+pub mod gzip;
+pub mod http;
+#[cfg(feature = "mail")]
+pub mod mail;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "notify")]
+pub mod notify;
 pub mod other;
 pub mod script;
+#[cfg(feature = "serial")]
+pub mod serial;
 pub mod var;
 
+#[cfg(feature = "clipboard")]
+pub use clipboard::*;
 pub use command::*;
+pub use examples::*;
+#[cfg(feature = "gzip")]
+pub use gzip::*;
+pub use http::*;
+#[cfg(feature = "mail")]
+pub use mail::*;
+#[cfg(feature = "mqtt")]
+pub use mqtt::*;
+#[cfg(feature = "notify")]
+pub use notify::*;
 pub use other::*;
 pub use script::*;
+#[cfg(feature = "serial")]
+pub use serial::*;
 pub use var::*;
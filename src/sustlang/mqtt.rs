@@ -0,0 +1,202 @@
+use super::script::{Function, RunningScript, ScriptError};
+use super::var::Variable;
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Topic filters of `MQTT_SUBSCRIBE` with their handlers
+type Handlers = Arc<Mutex<Vec<(String, Function)>>>;
+
+/// Minimal MQTT 3.1.1 client of `mqtt` variables: QoS 0 publishing and subscriptions,
+/// keep alive is turned off. Messages are read in a separate thread, that calls
+/// the handlers one by one while the connection is open.
+pub struct MqttClient {
+    stream: Mutex<Box<dyn Write + Send>>,
+    handlers: Handlers,
+    next_packet_id: AtomicU16,
+}
+
+fn encode_length(mut length: usize, packet: &mut Vec<u8>) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+fn encode_string(text: &[u8], body: &mut Vec<u8>) -> Result<(), ScriptError> {
+    let length = u16::try_from(text.len()).map_err(|_| ScriptError::RangeError)?;
+    body.extend_from_slice(&length.to_be_bytes());
+    body.extend_from_slice(text);
+    Ok(())
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    encode_length(body.len(), &mut packet);
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Read one packet, returns the first byte of the fixed header and the rest of the packet
+fn read_packet(stream: &mut impl Read) -> Option<(u8, Vec<u8>)> {
+    let mut byte = [0u8; 1];
+    stream.read_exact(&mut byte).ok()?;
+    let header = byte[0];
+
+    let mut length = 0usize;
+    for shift in 0..4 {
+        stream.read_exact(&mut byte).ok()?;
+        length |= ((byte[0] & 0x7f) as usize) << (7 * shift);
+        if byte[0] & 0x80 == 0 {
+            let mut body = vec![0u8; length];
+            stream.read_exact(&mut body).ok()?;
+            return Some((header, body));
+        }
+    }
+    None
+}
+
+/// Topic filter with `+` (one level) and `#` (all levels left) wildcards
+fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut topic = topic.split('/');
+    for level in filter.split('/') {
+        match (level, topic.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (level, Some(name)) if level == name => {}
+            _ => return false,
+        }
+    }
+    topic.next().is_none()
+}
+
+impl MqttClient {
+    /// Connect to the broker at `host:port` and start reading messages
+    pub fn connect(
+        host: &str,
+        port: u16,
+        client_id: &str,
+        script: Arc<RunningScript>,
+    ) -> Result<MqttClient, ScriptError> {
+        let mut stream = TcpStream::connect((host, port)).map_err(|_| ScriptError::NetworkError)?;
+
+        let mut body = Vec::new();
+        encode_string(b"MQTT", &mut body)?;
+        // protocol level 4, clean session, keep alive off
+        body.extend_from_slice(&[4, 0x02, 0, 0]);
+        encode_string(client_id.as_bytes(), &mut body)?;
+        stream
+            .write_all(&packet(0x10, &body))
+            .map_err(|_| ScriptError::NetworkError)?;
+
+        match read_packet(&mut stream) {
+            Some((0x20, body)) if body.len() == 2 && body[1] == 0 => {}
+            _ => return Err(ScriptError::NetworkError),
+        }
+
+        let handlers: Handlers = Arc::new(Mutex::new(Vec::new()));
+        let reader = stream.try_clone().map_err(|_| ScriptError::NetworkError)?;
+        let reader_handlers = handlers.clone();
+        thread::spawn(move || MqttClient::read_messages(reader, reader_handlers, script));
+
+        Ok(MqttClient {
+            stream: Mutex::new(Box::new(stream)),
+            handlers,
+            next_packet_id: AtomicU16::new(1),
+        })
+    }
+
+    /// Client of a dry run: published messages go nowhere and handlers are never called
+    pub fn simulated() -> MqttClient {
+        MqttClient {
+            stream: Mutex::new(Box::new(io::sink())),
+            handlers: Arc::new(Mutex::new(Vec::new())),
+            next_packet_id: AtomicU16::new(1),
+        }
+    }
+
+    fn read_messages(mut stream: TcpStream, handlers: Handlers, script: Arc<RunningScript>) {
+        while let Some((header, body)) = read_packet(&mut stream) {
+            if header >> 4 != 3 {
+                continue;
+            }
+
+            let qos = (header >> 1) & 0x03;
+            let Some(topic_length) = body.get(..2) else {
+                break;
+            };
+            let topic_end = 2 + u16::from_be_bytes([topic_length[0], topic_length[1]]) as usize;
+            let payload_start = if qos > 0 { topic_end + 2 } else { topic_end };
+            let (Some(topic), Some(payload)) = (body.get(2..topic_end), body.get(payload_start..))
+            else {
+                break;
+            };
+
+            if qos == 1 {
+                let mut ack = vec![0x40, 0x02];
+                ack.extend_from_slice(&body[topic_end..payload_start]);
+                if stream.write_all(&ack).is_err() {
+                    break;
+                }
+            }
+
+            let topic = String::from_utf8_lossy(topic).to_string();
+            let payload = String::from_utf8_lossy(payload).to_string();
+            let funcs: Vec<Function> = handlers
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(filter, _)| topic_matches(filter, &topic))
+                .map(|(_, func)| func.clone())
+                .collect();
+
+            for func in funcs {
+                let args = vec![Variable::from_str(Some(payload.clone()))];
+                if let Err(error) = func.execute(script.clone(), "null".to_string(), args, false) {
+                    script.report_error(&error);
+                }
+            }
+        }
+    }
+
+    pub fn publish(&self, topic: &str, payload: &[u8]) -> Result<(), ScriptError> {
+        let mut body = Vec::new();
+        encode_string(topic.as_bytes(), &mut body)?;
+        body.extend_from_slice(payload);
+
+        self.stream
+            .lock()
+            .unwrap()
+            .write_all(&packet(0x30, &body))
+            .map_err(|_| ScriptError::NetworkError)
+    }
+
+    /// Call `func(string)` with the payload of every message on topics matching `filter`
+    pub fn subscribe(&self, filter: &str, func: Function) -> Result<(), ScriptError> {
+        let packet_id = self.next_packet_id.fetch_add(1, Ordering::SeqCst).max(1);
+
+        let mut body = packet_id.to_be_bytes().to_vec();
+        encode_string(filter.as_bytes(), &mut body)?;
+        body.push(0);
+
+        self.handlers
+            .lock()
+            .unwrap()
+            .push((filter.to_string(), func));
+        self.stream
+            .lock()
+            .unwrap()
+            .write_all(&packet(0x82, &body))
+            .map_err(|_| ScriptError::NetworkError)
+    }
+}
@@ -0,0 +1,13 @@
+use notify_rust::Notification;
+
+use super::script::ScriptError;
+
+/// Show a desktop notification through the notification service of the system
+pub fn notify(title: &str, body: &str) -> Result<(), ScriptError> {
+    Notification::new()
+        .summary(title)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|_| ScriptError::NotificationError)
+}
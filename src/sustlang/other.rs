@@ -1,4 +1,15 @@
-use std::{fs, io::Write};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use super::script::ScriptError;
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::net::UdpSocket;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub trait IgnoreResult<T, E> {
     fn ignore(&self) {}
@@ -6,29 +17,576 @@ pub trait IgnoreResult<T, E> {
 
 impl<T, E> IgnoreResult<T, E> for Result<T, E> {}
 
+/// How `OPEN_FILE_OUT` opens the file
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FileOutMode {
+    /// Write from the start, the old content is removed
+    Truncate,
+    /// Write after the old content
+    Append,
+    /// The file must not exist yet
+    CreateNew,
+}
+
+impl FileOutMode {
+    pub fn from_name(name: &str) -> Option<FileOutMode> {
+        Some(match name {
+            "truncate" => FileOutMode::Truncate,
+            "append" => FileOutMode::Append,
+            "create_new" => FileOutMode::CreateNew,
+            _ => return None,
+        })
+    }
+}
+
+/// Buffered file writer of `OPEN_FILE_OUT`, the data gets to the file on flush
+/// or when the last copy of the stream is gone
 pub struct FileOutStream {
-    bytes: Vec<u8>,
-    bytes_wrote: Vec<u8>,
-    file_path: String,
+    file: BufWriter<File>,
 }
 
 impl FileOutStream {
-    pub fn new(file_path: String, bytes: Vec<u8>) -> FileOutStream {
-        FileOutStream {
-            bytes,
-            file_path,
-            bytes_wrote: Vec::new(),
-        }
+    pub fn open(file_path: &str, mode: FileOutMode) -> io::Result<FileOutStream> {
+        let mut options = OpenOptions::new();
+        match mode {
+            FileOutMode::Truncate => options.write(true).create(true).truncate(true),
+            FileOutMode::Append => options.append(true).create(true),
+            FileOutMode::CreateNew => options.write(true).create_new(true),
+        };
+        Ok(FileOutStream {
+            file: BufWriter::new(options.open(file_path)?),
+        })
     }
 }
 
 impl Write for FileOutStream {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.bytes_wrote.write(buf)
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// How `OPEN_FILE` opens the file
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FileStreamMode {
+    /// Only reading, the file must exist
+    Read,
+    /// Reading and writing, the file is created if it does not exist
+    Write,
+    /// Reading and writing from the empty file, the old content is removed
+    Truncate,
+    /// Reading and writing, the file must not exist yet
+    CreateNew,
+}
+
+impl FileStreamMode {
+    pub fn from_name(name: &str) -> Option<FileStreamMode> {
+        Some(match name {
+            "read" => FileStreamMode::Read,
+            "write" => FileStreamMode::Write,
+            "truncate" => FileStreamMode::Truncate,
+            "create_new" => FileStreamMode::CreateNew,
+            _ => return None,
+        })
+    }
+}
+
+/// Data of `FileStream`, anything that can be read, written and seeked
+trait FileData: Read + Write + Seek + Send {}
+
+impl<T: Read + Write + Seek + Send> FileData for T {}
+
+/// File of `OPEN_FILE` that can be read, written and moved in with `SEEK`.
+/// It is not buffered, so every write gets to the file right away
+pub struct FileStream {
+    path: String,
+    file: Box<dyn FileData>,
+}
+
+impl FileStream {
+    pub fn open(file_path: &str, mode: FileStreamMode) -> Result<FileStream, ScriptError> {
+        let mut options = OpenOptions::new();
+        match mode {
+            FileStreamMode::Read => options.read(true),
+            FileStreamMode::Write => options.read(true).write(true).create(true),
+            FileStreamMode::Truncate => options.read(true).write(true).create(true).truncate(true),
+            FileStreamMode::CreateNew => options.read(true).write(true).create_new(true),
+        };
+        let file = options.open(file_path).map_err(|e| match mode {
+            FileStreamMode::Read => ScriptError::file_read(file_path, &e),
+            _ => ScriptError::file_write(file_path, &e),
+        })?;
+        Ok(FileStream {
+            path: file_path.to_string(),
+            file: Box::new(file),
+        })
+    }
+
+    /// Copy of the file in memory for dry runs: it starts with the content the file
+    /// would have after opening, writes don't get to the disk
+    pub fn simulated(file_path: &str, mode: FileStreamMode) -> Result<FileStream, ScriptError> {
+        let exists = Path::new(file_path).exists();
+        let content = match mode {
+            FileStreamMode::Read | FileStreamMode::Write if exists => {
+                fs::read(file_path).map_err(|e| ScriptError::file_read(file_path, &e))?
+            }
+            FileStreamMode::Read => {
+                return Err(ScriptError::FileNotFoundError(file_path.to_string()))
+            }
+            FileStreamMode::CreateNew if exists => {
+                return Err(ScriptError::FileWriteError(file_path.to_string()))
+            }
+            _ => Vec::new(),
+        };
+        Ok(FileStream {
+            path: file_path.to_string(),
+            file: Box::new(Cursor::new(content)),
+        })
+    }
+
+    /// Move to `offset` from `whence` (`start`, `current` or `end`), returns the new position
+    pub fn seek_to(&mut self, offset: i64, whence: &str) -> Result<u64, ScriptError> {
+        let from = match whence {
+            "start" => SeekFrom::Start(u64::try_from(offset).map_err(|_| ScriptError::RangeError)?),
+            "current" => SeekFrom::Current(offset),
+            "end" => SeekFrom::End(offset),
+            _ => return Err(ScriptError::CommandArgsInvalidError),
+        };
+        self.file
+            .seek(from)
+            .map_err(|e| ScriptError::file_read(&self.path, &e))
+    }
+
+    pub fn position(&mut self) -> Result<u64, ScriptError> {
+        self.file
+            .stream_position()
+            .map_err(|e| ScriptError::file_read(&self.path, &e))
+    }
+}
+
+impl Read for FileStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Write for FileStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// In-memory buffer of `OPEN_BUFFER`, all copies share the same bytes: what is
+/// written to one copy can be read from another. Reading an empty buffer returns
+/// the end of the stream instead of waiting
+#[derive(Clone, Default)]
+pub struct BufferStream {
+    data: Arc<Mutex<VecDeque<u8>>>,
+}
+
+impl BufferStream {
+    pub fn new() -> BufferStream {
+        BufferStream::default()
+    }
+}
+
+impl Read for BufferStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().read(buf)
+    }
+}
+
+impl Write for BufferStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().extend(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn ansi_code(style: &str) -> Option<&'static str> {
+    Some(match style {
+        "bold" => "1",
+        "dim" => "2",
+        "italic" => "3",
+        "underline" => "4",
+        "black" => "30",
+        "red" => "31",
+        "green" => "32",
+        "yellow" => "33",
+        "blue" => "34",
+        "magenta" => "35",
+        "cyan" => "36",
+        "white" => "37",
+        "bg_black" => "40",
+        "bg_red" => "41",
+        "bg_green" => "42",
+        "bg_yellow" => "43",
+        "bg_blue" => "44",
+        "bg_magenta" => "45",
+        "bg_cyan" => "46",
+        "bg_white" => "47",
+        _ => return None,
+    })
+}
+
+/// Wrap `text` into ANSI codes of comma separated `styles` like `bold,red`,
+/// `None` if some style is unknown
+pub fn style_text(text: &str, styles: &str) -> Option<String> {
+    let codes = styles
+        .split(',')
+        .map(|style| ansi_code(style.trim()))
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text))
+}
+
+/// Percent-encode `text` for `application/x-www-form-urlencoded`, spaces become `+`
+fn form_escape(text: &str) -> String {
+    let mut result = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                result.push(byte as char)
+            }
+            b' ' => result.push('+'),
+            byte => result.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    result
+}
+
+/// Body of `application/x-www-form-urlencoded` form, fields go in the given order
+pub fn form_urlencode(fields: &[(String, String)]) -> String {
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", form_escape(name), form_escape(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Body of `multipart/form-data` form with the `boundary`, `files` are field names
+/// with paths of the files to send
+pub fn multipart_body(
+    fields: &[(String, String)],
+    files: &[(String, String)],
+    boundary: &str,
+) -> Result<Vec<u8>, ScriptError> {
+    let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                boundary,
+                quote(name)
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(value.as_bytes());
+        body.extend_from_slice(b"\r\n");
+    }
+    for (name, path) in files {
+        let content = fs::read(path).map_err(|e| ScriptError::file_read(path.clone(), &e))?;
+        let file_name = Path::new(path)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                boundary,
+                quote(name),
+                quote(&file_name)
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    Ok(body)
+}
+
+/// Seconds from 1900 (start of NTP time) to 1970
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+/// Current unix time in milliseconds from the SNTP server `server` (`host` or `host:port`,
+/// port 123 by default), half of the round trip is added to the server time
+pub fn network_time(server: &str, timeout: Duration) -> Result<u128, ScriptError> {
+    let address = if server.contains(':') {
+        server.to_string()
+    } else {
+        format!("{}:123", server)
+    };
+
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|_| ScriptError::NetworkError)?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .map_err(|_| ScriptError::NetworkError)?;
+
+    // LI = 0, version 4, mode 3 (client)
+    let mut packet = [0u8; 48];
+    packet[0] = 0x23;
+
+    let sent = Instant::now();
+    socket
+        .send_to(&packet, address)
+        .map_err(|_| ScriptError::NetworkError)?;
+    let read = socket
+        .recv(&mut packet)
+        .map_err(|_| ScriptError::NetworkError)?;
+    let round_trip = sent.elapsed();
+    if read < 48 {
+        return Err(ScriptError::NetworkError);
+    }
+
+    // transmit timestamp: seconds and fraction of a second since 1900
+    let seconds = u32::from_be_bytes([packet[40], packet[41], packet[42], packet[43]]) as u64;
+    let fraction = u32::from_be_bytes([packet[44], packet[45], packet[46], packet[47]]) as u64;
+    let seconds = seconds
+        .checked_sub(NTP_UNIX_OFFSET)
+        .ok_or(ScriptError::NetworkError)?;
+
+    let millis = seconds as u128 * 1000 + ((fraction * 1000) >> 32) as u128;
+    Ok(millis + round_trip.as_millis() / 2)
+}
+
+/// Lowercase hex digest of `data` by the algorithm of `HASH`: `sha256`, `md5` or `crc32`,
+/// `None` if the algorithm is unknown
+pub fn hash_hex(algorithm: &str, data: &[u8]) -> Option<String> {
+    let digest = match algorithm {
+        "sha256" => Sha256::digest(data).to_vec(),
+        "md5" => Md5::digest(data).to_vec(),
+        "crc32" => crc32fast::hash(data).to_be_bytes().to_vec(),
+        _ => return None,
+    };
+    Some(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Year, month and day of the date `days` days after 1970-01-01
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days from 1970-01-01 to the date, the opposite of `civil_date`
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
     }
+}
+
+/// UTC time `millis` (milliseconds since the unix epoch) by the pattern of `TIME_FORMAT`:
+/// `%Y` year, `%m` month, `%d` day, `%H` hours, `%M` minutes, `%S` seconds, `%L` milliseconds,
+/// `%s` seconds since the epoch and `%%`. `None` if the pattern has another `%` code
+pub fn format_time(millis: i64, pattern: &str) -> Option<String> {
+    let (year, month, day) = civil_date(millis.div_euclid(86_400_000));
+    let day_millis = millis.rem_euclid(86_400_000);
+    let (hours, minutes) = (day_millis / 3_600_000, day_millis / 60_000 % 60);
+    let (seconds, rest_millis) = (day_millis / 1000 % 60, day_millis % 1000);
+
+    let mut text = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'Y' => text.push_str(&format!("{:04}", year)),
+            'm' => text.push_str(&format!("{:02}", month)),
+            'd' => text.push_str(&format!("{:02}", day)),
+            'H' => text.push_str(&format!("{:02}", hours)),
+            'M' => text.push_str(&format!("{:02}", minutes)),
+            'S' => text.push_str(&format!("{:02}", seconds)),
+            'L' => text.push_str(&format!("{:03}", rest_millis)),
+            's' => text.push_str(&millis.div_euclid(1000).to_string()),
+            '%' => text.push('%'),
+            _ => return None,
+        }
+    }
+    Some(text)
+}
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        self.bytes.write(&self.bytes_wrote)?;
-        fs::write(&self.file_path, &self.bytes)
+/// Number of exactly `width` digits (or any number of digits if `width` is 0) at the start of `text`,
+/// with a minus if `signed`
+fn take_number(text: &mut &str, width: usize, signed: bool) -> Option<i64> {
+    let sign_len = usize::from(signed && text.starts_with('-'));
+    let digits = text[sign_len..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len() - sign_len);
+    let len = if width == 0 { digits } else { width };
+    if len == 0 || digits < len {
+        return None;
+    }
+    let number = text[..sign_len + len].parse().ok()?;
+    *text = &text[sign_len + len..];
+    Some(number)
+}
+
+/// Milliseconds since the unix epoch of the UTC time written by `pattern` (the same codes as
+/// in `format_time`, `%Y` is 4 digits), `None` if the text doesn't match or the date doesn't exist
+pub fn parse_time(text: &str, pattern: &str) -> Option<i64> {
+    let (mut year, mut month, mut day) = (1970, 1, 1);
+    let (mut hours, mut minutes, mut seconds, mut millis) = (0, 0, 0, 0);
+    let mut epoch_seconds = None;
+
+    let mut text = text;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text = text.strip_prefix(c)?;
+            continue;
+        }
+        match chars.next()? {
+            'Y' => year = take_number(&mut text, 4, true)?,
+            'm' => month = take_number(&mut text, 2, false)?,
+            'd' => day = take_number(&mut text, 2, false)?,
+            'H' => hours = take_number(&mut text, 2, false)?,
+            'M' => minutes = take_number(&mut text, 2, false)?,
+            'S' => seconds = take_number(&mut text, 2, false)?,
+            'L' => millis = take_number(&mut text, 3, false)?,
+            's' => epoch_seconds = Some(take_number(&mut text, 0, true)?),
+            '%' => text = text.strip_prefix('%')?,
+            _ => return None,
+        }
+    }
+    if !text.is_empty()
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hours > 23
+        || minutes > 59
+        || seconds > 59
+    {
+        return None;
+    }
+
+    let seconds = match epoch_seconds {
+        Some(epoch_seconds) => epoch_seconds,
+        None => days_from_civil(year, month, day) * 86_400 + hours * 3600 + minutes * 60 + seconds,
+    };
+    seconds.checked_mul(1000)?.checked_add(millis)
+}
+
+/// How a `datetime` variable is written and read by `SET_VAR`
+const DATE_TIME_PATTERN: &str = "%Y-%m-%dT%H:%M:%S.%LZ";
+
+/// Text of a `datetime` variable like `2024-03-01T12:30:00.000Z`
+pub fn format_date_time(millis: i64) -> String {
+    format_time(millis, DATE_TIME_PATTERN).unwrap_or_default()
+}
+
+/// Value of a `datetime` variable from `2024-03-01T12:30:00.000Z`, the milliseconds,
+/// the time or `Z` can be left out
+pub fn parse_date_time(text: &str) -> Option<i64> {
+    [
+        DATE_TIME_PATTERN,
+        "%Y-%m-%dT%H:%M:%S.%L",
+        "%Y-%m-%dT%H:%M:%SZ",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d",
+    ]
+    .iter()
+    .find_map(|pattern| parse_time(text, pattern))
+}
+
+/// Units of a `duration` variable, from the largest
+const DURATION_UNITS: [(&str, i64); 4] = [("h", 3_600_000), ("m", 60_000), ("s", 1000), ("ms", 1)];
+
+/// Text of a `duration` variable like `1h30m` or `1s500ms`, `0ms` for zero
+pub fn format_duration(millis: i64) -> String {
+    if millis == 0 {
+        return String::from("0ms");
+    }
+    let mut text = String::from(if millis < 0 { "-" } else { "" });
+    let mut rest = millis.unsigned_abs();
+    for (unit, size) in DURATION_UNITS {
+        let count = rest / size as u64;
+        if count > 0 {
+            text.push_str(&format!("{}{}", count, unit));
+            rest %= size as u64;
+        }
+    }
+    text
+}
+
+/// Value of a `duration` variable from numbers with units `h`, `m`, `s` and `ms` like `1m30s`
+/// or a number of milliseconds without a unit, the text can start with a minus
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let (sign, mut text) = match text.strip_prefix('-') {
+        Some(text) => (-1, text),
+        None => (1, text),
+    };
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().all(|c| c.is_ascii_digit()) {
+        return text.parse::<i64>().ok().map(|millis| sign * millis);
+    }
+
+    let mut millis: i64 = 0;
+    while !text.is_empty() {
+        let number = take_number(&mut text, 0, false)?;
+        let size = if let Some(rest) = text.strip_prefix("ms") {
+            text = rest;
+            1
+        } else {
+            let (unit, size) = DURATION_UNITS
+                .iter()
+                .find(|(unit, _)| text.starts_with(unit))?;
+            text = &text[unit.len()..];
+            *size
+        };
+        millis = millis.checked_add(number.checked_mul(size)?)?;
+    }
+    Some(sign * millis)
+}
+
+/// Value of a `char` variable: the code of the symbol like `65` or the symbol itself like `ы`
+pub fn parse_char(text: &str) -> Option<char> {
+    if let Ok(code) = text.parse::<u32>() {
+        return char::from_u32(code);
+    }
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
     }
 }
@@ -0,0 +1,43 @@
+use super::super::command::{Command, CommandType};
+
+use std::sync::Arc;
+
+/// What kind of resource an audited command touched
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum AuditKind {
+    /// File, folder or device path
+    File,
+    /// Address the script connected to or listened on
+    Network,
+    /// Command line of a started process
+    Process,
+    /// Name of a read or changed environment variable
+    Environment,
+    /// `clipboard` or `notification`
+    Desktop,
+}
+
+/// One file, address, process or other resource touched by the script, passed to the audit hook
+#[derive(PartialEq, Clone, Debug)]
+pub struct AuditEvent {
+    pub kind: AuditKind,
+    /// Path, `host:port`, command line, environment variable name, `clipboard` or `notification`
+    pub target: String,
+    pub command: CommandType,
+    pub line: usize,
+}
+
+impl AuditEvent {
+    pub fn new(kind: AuditKind, target: impl Into<String>, command: &Command) -> AuditEvent {
+        AuditEvent {
+            kind,
+            target: target.into(),
+            command: command.command_type,
+            line: command.line,
+        }
+    }
+}
+
+/// Host callback of `RunningScript::set_audit_hook`, called before the resource is used
+#[derive(Clone)]
+pub struct AuditHook(pub Arc<dyn Fn(&AuditEvent) + Send + Sync>);
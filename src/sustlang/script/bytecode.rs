@@ -0,0 +1,178 @@
+use super::super::command::{Command, CommandType};
+use super::super::var::{VarType, Variable};
+use super::{constant_values, Function, Symbol, SymbolTable, VarPath};
+
+use std::fmt::{self, Debug};
+
+/// Value of `SET_VAR` or `TEMP_VAR` parsed before the run
+#[derive(PartialEq, Clone)]
+pub struct ConstValue(pub Variable);
+
+impl Debug for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.to_string() {
+            Ok(text) => write!(f, "ConstValue({})", text),
+            Err(_) => f.write_str("ConstValue"),
+        }
+    }
+}
+
+/// One instruction of a compiled function. Instructions go one to one with the commands
+/// of the function, so an error of the instruction at `pc` is reported with `commands[pc]`.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Instruction {
+    /// `INIT_VAR` with the type already parsed
+    InitVar { path: VarPath, var_type: VarType },
+    /// `SET_VAR`, the value is parsed with the type of the existing variable. `parsed` is
+    /// the value parsed before the run with the type the variable was created with, it's
+    /// used while the variable still has that type
+    SetVar {
+        path: VarPath,
+        value: String,
+        parsed: Option<ConstValue>,
+    },
+    /// `SET_VAR` with the value parsed once before the run instead of on every call,
+    /// see `constant_values`
+    SetConst { path: VarPath, value: ConstValue },
+    /// `USE_FUNC` with the function resolved to its symbol, `None` result means `null`.
+    /// If a `func` variable with the same name exists at runtime, the command is executed instead.
+    Call {
+        func: Symbol,
+        /// Variable id of the function name
+        func_var: Symbol,
+        result_var: Option<VarPath>,
+        args: Vec<VarPath>,
+    },
+    /// `COPY_VAR`
+    CopyVar { source: VarPath, target: VarPath },
+    /// `MOVE_VAR`
+    MoveVar { source: VarPath, target: VarPath },
+    /// `IF` with the function resolved to its symbol, like `Call`
+    If {
+        condition: VarPath,
+        func: Symbol,
+        /// Variable id of the function name
+        func_var: Symbol,
+    },
+    /// `TEMP_VAR` with the type and the value parsed before the run
+    TempVar {
+        path: VarPath,
+        name: String,
+        value: ConstValue,
+    },
+    /// `RETURN`
+    Return,
+    /// Any other command, executed by the tree-walking interpreter. Variable paths of the
+    /// command are still parsed once, when the command is created.
+    Command,
+}
+
+fn compile_command(command: &Command, functions: &SymbolTable) -> Instruction {
+    match command.command_type {
+        CommandType::InitVar => match (command.args.first(), command.args.get(1)) {
+            (Some(var_type), Some(name)) => match VarType::from_name(var_type) {
+                Ok(var_type) => Instruction::InitVar {
+                    path: VarPath::parse(name),
+                    var_type,
+                },
+                Err(_) => Instruction::Command,
+            },
+            _ => Instruction::Command,
+        },
+        CommandType::SetVar => match command.args.first() {
+            Some(name) => Instruction::SetVar {
+                path: VarPath::parse(name),
+                value: command.args[1..].join(" "),
+                parsed: None,
+            },
+            None => Instruction::Command,
+        },
+        CommandType::TempVar => match (command.args.first(), command.args.get(1)) {
+            (Some(var_type), Some(name)) => VarType::from_name(var_type)
+                .and_then(|var_type| Variable::parse_var(var_type, command.args[2..].join(" ")))
+                .map(|value| Instruction::TempVar {
+                    path: VarPath::parse(name),
+                    name: name.clone(),
+                    value: ConstValue(value),
+                })
+                .unwrap_or(Instruction::Command),
+            _ => Instruction::Command,
+        },
+        CommandType::UseFunc => match (command.args.first(), command.args.get(1)) {
+            (Some(func_name), Some(result_var)) => match functions.get(func_name) {
+                Some(func) => Instruction::Call {
+                    func,
+                    func_var: Symbol::variable(func_name),
+                    result_var: (result_var != "null").then(|| VarPath::parse(result_var)),
+                    args: command.args[2..]
+                        .iter()
+                        .map(|arg| VarPath::parse(arg))
+                        .collect(),
+                },
+                None => Instruction::Command,
+            },
+            _ => Instruction::Command,
+        },
+        CommandType::CopyVar | CommandType::MoveVar => {
+            match (command.args.first(), command.args.get(1)) {
+                (Some(source), Some(target)) => {
+                    let (source, target) = (VarPath::parse(source), VarPath::parse(target));
+                    if command.command_type == CommandType::CopyVar {
+                        Instruction::CopyVar { source, target }
+                    } else {
+                        Instruction::MoveVar { source, target }
+                    }
+                }
+                _ => Instruction::Command,
+            }
+        }
+        CommandType::If => match (command.args.first(), command.args.get(1)) {
+            (Some(condition), Some(func_name)) => match functions.get(func_name) {
+                Some(func) => Instruction::If {
+                    condition: VarPath::parse(condition),
+                    func,
+                    func_var: Symbol::variable(func_name),
+                },
+                None => Instruction::Command,
+            },
+            _ => Instruction::Command,
+        },
+        CommandType::Return => Instruction::Return,
+        _ => Instruction::Command,
+    }
+}
+
+/// Lower commands of the function into bytecode, `functions` are symbols of the script functions.
+/// Values of `SET_VAR` that are known before the run are parsed once, see `constant_values`.
+pub fn compile_function(func: &Function, functions: &SymbolTable) -> Vec<Instruction> {
+    compile_main(func, functions)
+        .into_iter()
+        .map(|instruction| match instruction {
+            Instruction::SetVar {
+                path,
+                parsed: Some(value),
+                ..
+            } => Instruction::SetConst { path, value },
+            instruction => instruction,
+        })
+        .collect()
+}
+
+/// Lower commands of the main function into bytecode. Its variables are global and other
+/// functions can change their types, so values of `SET_VAR` parsed before the run are only
+/// used while the variable keeps the type it was created with.
+pub fn compile_main(func: &Function, functions: &SymbolTable) -> Vec<Instruction> {
+    func.commands
+        .iter()
+        .map(|command| compile_command(command, functions))
+        .zip(constant_values(func))
+        .map(|(instruction, constant)| match instruction {
+            Instruction::SetVar { path, value, .. } => Instruction::SetVar {
+                path,
+                value,
+                parsed: constant.map(ConstValue),
+            },
+            instruction => instruction,
+        })
+        .collect()
+}
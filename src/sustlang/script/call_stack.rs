@@ -0,0 +1,106 @@
+use super::super::command::Command;
+use super::ScriptError;
+
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+/// Function in a traceback with the line of the command it was running
+#[derive(PartialEq, Clone, Debug)]
+pub struct StackFrame {
+    pub function: String,
+    pub line: usize,
+    /// File of the command if it was imported, see `Command::file`
+    pub file: Option<Arc<str>>,
+}
+
+impl StackFrame {
+    /// `file:line` for imported code, `line N` for the script itself
+    pub fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}:{}", file, self.line),
+            None => format!("line {}", self.line),
+        }
+    }
+}
+
+thread_local! {
+    static FRAMES: RefCell<Vec<StackFrame>> = const { RefCell::new(Vec::new()) };
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Functions running in the current thread. Frames of a failed call are kept until
+/// the next call at the same depth, so the error can be reported with the whole chain of calls.
+pub struct CallStack;
+
+impl CallStack {
+    /// Push a frame for the function, returns the depth to pass to `leave`
+    pub fn enter(function: &str) -> usize {
+        let depth = DEPTH.get();
+        FRAMES.with_borrow_mut(|frames| {
+            frames.truncate(depth);
+            frames.push(StackFrame {
+                function: function.to_string(),
+                line: 0,
+                file: None,
+            });
+        });
+        DEPTH.set(depth + 1);
+        depth
+    }
+
+    /// The function at `depth` returned, its frame is dropped unless the call failed
+    pub fn leave(depth: usize, failed: bool) {
+        if !failed {
+            FRAMES.with_borrow_mut(|frames| frames.truncate(depth));
+        }
+        DEPTH.set(depth);
+    }
+
+    /// `command` of the running function failed with `error`. If the error was raised by
+    /// the command itself and not by a function it called, frames above are left from
+    /// earlier calls and are dropped.
+    pub fn fail(command: &Command, error: &(ScriptError, Command)) {
+        let Some(depth) = DEPTH.get().checked_sub(1) else {
+            return;
+        };
+        FRAMES.with_borrow_mut(|frames| {
+            if error.1 == *command {
+                frames.truncate(depth + 1);
+            }
+            if let Some(frame) = frames.get_mut(depth) {
+                frame.line = command.line;
+                frame.file = command.file.clone();
+            }
+        });
+    }
+
+    /// Frames of the last error in the current thread, from the outermost call to the failed one
+    pub fn take() -> Vec<StackFrame> {
+        DEPTH.set(0);
+        FRAMES.take()
+    }
+}
+
+/// Runtime error with the calls that led to it, see `RunningScript::run_with_traceback`
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub error: ScriptError,
+    pub command: Command,
+    /// From the outermost call to the function where the error happened
+    pub traceback: Vec<StackFrame>,
+    /// Errors of other threads that nobody joined, they happened in the same run
+    pub thread_errors: Vec<RuntimeError>,
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        for frame in &self.traceback {
+            write!(f, "\n  in {} at {}", frame.function, frame.location())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RuntimeError {}
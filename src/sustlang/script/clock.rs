@@ -0,0 +1,65 @@
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of time for `SLEEP`, `TIME_NOW` and `MEASURE`
+pub trait Clock: Send + Sync {
+    /// Milliseconds since the unix epoch
+    fn now_millis(&self) -> u128;
+
+    /// Milliseconds from an unspecified start, never going backwards. The clock is used
+    /// to measure durations, by default it is the same as `now_millis`.
+    fn monotonic_millis(&self) -> u128 {
+        self.now_millis()
+    }
+
+    fn sleep(&self, duration: Duration);
+}
+
+/// Clock used by default, real time of the system
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    }
+
+    fn monotonic_millis(&self) -> u128 {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed().as_millis()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Clock that never waits, `sleep` just moves its time forward
+pub struct MockClock {
+    now: Mutex<Duration>,
+}
+
+impl MockClock {
+    pub fn new(start_millis: u64) -> MockClock {
+        MockClock {
+            now: Mutex::new(Duration::from_millis(start_millis)),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> u128 {
+        self.now.lock().unwrap().as_millis()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
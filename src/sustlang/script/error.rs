@@ -1,26 +1,227 @@
-use std::{error::Error, fmt::Display};
+use super::super::var::Variable;
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+    io,
+};
 
-#[derive(Debug)]
 pub enum ScriptError {
     ParseVarError,
-    TypeUnknownError,
-    CommandUnknownError,
+    /// Name of the type that could not be parsed
+    TypeUnknownError(String),
+    /// Name of the unknown command
+    CommandUnknownError(String),
     CommandArgsInvalidError,
-    UnknownVarError,
-    TypeMismatchError,
+    /// Name of the unknown variable
+    UnknownVarError(String),
+    TypeMismatchError {
+        expected: String,
+        actual: String,
+    },
     VarNotInitedError,
     StringUTF8Error,
     VarInitedError,
-    FunctionUnknownError,
-    FileReadError,
-    FileWriteError,
+    /// Name of the unknown function
+    FunctionUnknownError(String),
+    /// Path of the file
+    FileReadError(String),
+    /// Path of the file
+    FileWriteError(String),
+    /// Path of the file
+    FileNotFoundError(String),
     StreamReadError,
     StreamWriteError,
+    /// Reading or writing a network stream took longer than its timeout
+    StreamTimeoutError,
+    BreakOutsideLoopError,
+    StreamDirectionError,
+    ThreadPanicError,
+    RangeError,
+    NetworkError,
+    CompiledScriptError,
+    TypeAliasError,
+    MetaError,
+    VersionError,
+    PragmaError,
+    IndexOutOfBoundsError {
+        index: isize,
+        len: usize,
+    },
+    /// The missing key, as text
+    KeyNotFoundError(String),
+    ClipboardError,
+    NotificationError,
+    /// The program that can't be started
+    ProcessError(String),
+    /// The host name that can't be resolved
+    DnsError(String),
+    /// The file, address or command line that the sandbox of the script doesn't allow
+    SandboxError(String),
+    /// Bug in the interpreter: the command panicked, the panic was caught
+    InternalPanicError {
+        command: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl ScriptError {
+    /// `expected` is a type name or a description like `int or float`,
+    /// the actual type is taken from `var`
+    pub fn type_mismatch(expected: impl Into<String>, var: &Variable) -> ScriptError {
+        let actual = var.get_type().to_name();
+        ScriptError::TypeMismatchError {
+            expected: expected.into(),
+            actual: if var.is_initialized() {
+                actual
+            } else {
+                format!("uninitialized {}", actual)
+            },
+        }
+    }
+
+    /// Error of reading the file at `path`, a missing file is `FileNotFoundError`
+    pub fn file_read(path: impl Into<String>, error: &io::Error) -> ScriptError {
+        if error.kind() == io::ErrorKind::NotFound {
+            ScriptError::FileNotFoundError(path.into())
+        } else {
+            ScriptError::FileReadError(path.into())
+        }
+    }
+
+    /// Error of writing the file at `path`, a missing file is `FileNotFoundError`
+    pub fn file_write(path: impl Into<String>, error: &io::Error) -> ScriptError {
+        if error.kind() == io::ErrorKind::NotFound {
+            ScriptError::FileNotFoundError(path.into())
+        } else {
+            ScriptError::FileWriteError(path.into())
+        }
+    }
+
+    /// Error of reading a stream, a timeout of a network stream is `StreamTimeoutError`
+    pub fn stream_read(error: &io::Error) -> ScriptError {
+        match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => ScriptError::StreamTimeoutError,
+            _ => ScriptError::StreamReadError,
+        }
+    }
+
+    /// Error of writing a stream, a timeout of a network stream is `StreamTimeoutError`
+    pub fn stream_write(error: &io::Error) -> ScriptError {
+        match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => ScriptError::StreamTimeoutError,
+            _ => ScriptError::StreamWriteError,
+        }
+    }
+
+    /// Name of the variant, the same for any context it carries
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScriptError::ParseVarError => "ParseVarError",
+            ScriptError::TypeUnknownError(_) => "TypeUnknownError",
+            ScriptError::CommandUnknownError(_) => "CommandUnknownError",
+            ScriptError::CommandArgsInvalidError => "CommandArgsInvalidError",
+            ScriptError::UnknownVarError(_) => "UnknownVarError",
+            ScriptError::TypeMismatchError { .. } => "TypeMismatchError",
+            ScriptError::VarNotInitedError => "VarNotInitedError",
+            ScriptError::StringUTF8Error => "StringUTF8Error",
+            ScriptError::VarInitedError => "VarInitedError",
+            ScriptError::FunctionUnknownError(_) => "FunctionUnknownError",
+            ScriptError::FileReadError(_) => "FileReadError",
+            ScriptError::FileWriteError(_) => "FileWriteError",
+            ScriptError::FileNotFoundError(_) => "FileNotFoundError",
+            ScriptError::StreamReadError => "StreamReadError",
+            ScriptError::StreamWriteError => "StreamWriteError",
+            ScriptError::StreamTimeoutError => "StreamTimeoutError",
+            ScriptError::BreakOutsideLoopError => "BreakOutsideLoopError",
+            ScriptError::StreamDirectionError => "StreamDirectionError",
+            ScriptError::ThreadPanicError => "ThreadPanicError",
+            ScriptError::RangeError => "RangeError",
+            ScriptError::NetworkError => "NetworkError",
+            ScriptError::CompiledScriptError => "CompiledScriptError",
+            ScriptError::TypeAliasError => "TypeAliasError",
+            ScriptError::MetaError => "MetaError",
+            ScriptError::VersionError => "VersionError",
+            ScriptError::PragmaError => "PragmaError",
+            ScriptError::IndexOutOfBoundsError { .. } => "IndexOutOfBoundsError",
+            ScriptError::KeyNotFoundError(_) => "KeyNotFoundError",
+            ScriptError::ClipboardError => "ClipboardError",
+            ScriptError::NotificationError => "NotificationError",
+            ScriptError::ProcessError(_) => "ProcessError",
+            ScriptError::DnsError(_) => "DnsError",
+            ScriptError::SandboxError(_) => "SandboxError",
+            ScriptError::InternalPanicError { .. } => "InternalPanicError",
+        }
+    }
+}
+
+/// Only the name of the variant, so `{:?}` output doesn't change with the context
+impl Debug for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl Display for ScriptError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("some error ez")
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::ParseVarError => f.write_str("value can't be parsed as the variable type"),
+            ScriptError::TypeUnknownError(name) => write!(f, "unknown type `{}`", name),
+            ScriptError::CommandUnknownError(name) => write!(f, "unknown command `{}`", name),
+            ScriptError::CommandArgsInvalidError => f.write_str("invalid command arguments"),
+            ScriptError::UnknownVarError(name) => write!(f, "unknown variable `{}`", name),
+            ScriptError::TypeMismatchError { expected, actual } => {
+                write!(f, "type mismatch: expected {}, got {}", expected, actual)
+            }
+            ScriptError::VarNotInitedError => f.write_str("variable is not initialized"),
+            ScriptError::StringUTF8Error => f.write_str("string is not valid UTF-8"),
+            ScriptError::VarInitedError => f.write_str("variable is already initialized"),
+            ScriptError::FunctionUnknownError(name) => write!(f, "unknown function `{}`", name),
+            ScriptError::FileReadError(path) => write!(f, "can't read file `{}`", path),
+            ScriptError::FileWriteError(path) => write!(f, "can't write file `{}`", path),
+            ScriptError::FileNotFoundError(path) => write!(f, "file `{}` not found", path),
+            ScriptError::StreamReadError => f.write_str("can't read from the stream"),
+            ScriptError::StreamWriteError => f.write_str("can't write to the stream"),
+            ScriptError::StreamTimeoutError => f.write_str("stream timed out"),
+            ScriptError::BreakOutsideLoopError => f.write_str("BREAK outside of a loop"),
+            ScriptError::StreamDirectionError => {
+                f.write_str("stream can't be used in this direction")
+            }
+            ScriptError::ThreadPanicError => f.write_str("thread panicked"),
+            ScriptError::RangeError => f.write_str("value is out of range"),
+            ScriptError::NetworkError => f.write_str("network error"),
+            ScriptError::CompiledScriptError => f.write_str("invalid compiled script"),
+            ScriptError::TypeAliasError => f.write_str("invalid type alias"),
+            ScriptError::MetaError => f.write_str("invalid META header"),
+            ScriptError::VersionError => {
+                f.write_str("script requires a newer version of the language")
+            }
+            ScriptError::PragmaError => f.write_str("invalid PRAGMA"),
+            ScriptError::IndexOutOfBoundsError { index, len } => {
+                write!(f, "index {} is out of bounds for length {}", index, len)
+            }
+            ScriptError::KeyNotFoundError(key) => write!(f, "key `{}` not found", key),
+            ScriptError::ClipboardError => f.write_str("clipboard is not available"),
+            ScriptError::NotificationError => f.write_str("notification can't be shown"),
+            ScriptError::ProcessError(program) => {
+                write!(f, "process `{}` can't be started", program)
+            }
+            ScriptError::DnsError(host) => write!(f, "host `{}` can't be resolved", host),
+            ScriptError::SandboxError(target) => {
+                write!(f, "`{}` is not allowed by the sandbox", target)
+            }
+            ScriptError::InternalPanicError {
+                command,
+                line,
+                message,
+            } => write!(
+                f,
+                "internal error in {} at line {}: {}",
+                command, line, message
+            ),
+        }
     }
 }
+
 impl Error for ScriptError {}
@@ -1,60 +1,238 @@
 use super::super::command::{Command, CommandType};
 use super::super::other::IgnoreResult;
 use super::super::var::{VarType, Variable};
-use super::{RunningScript, ScriptError};
+use super::{CallStack, Instruction, RunningScript, ScriptError, Symbol};
 
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::fmt::{self, Debug};
+use std::sync::{Arc, LazyLock};
+
+/// Id of the `result` variable of every call
+static RESULT_VAR: LazyLock<Symbol> = LazyLock::new(|| Symbol::variable("result"));
+
+/// Rust function exposed to scripts, see `RunningScript::register_native_function`
+#[derive(Clone)]
+pub struct NativeFunction(
+    pub Arc<dyn Fn(Vec<Variable>) -> Result<Variable, ScriptError> + Send + Sync>,
+);
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NativeFunction")
+    }
+}
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct Function {
     pub name: String,
     pub result_type: VarType,
-    pub parameters: HashMap<String, VarType>,
+    pub parameters: Vec<(String, VarType)>,
     pub commands: Vec<Command>,
+    pub native: Option<NativeFunction>,
+    /// Compiled commands, set by `RunningScript` before the run if bytecode is enabled
+    pub bytecode: Option<Arc<Vec<Instruction>>>,
+    /// Variable ids of `parameters`, interned once instead of on every call
+    parameter_ids: Vec<Symbol>,
 }
 
 impl Function {
     pub fn new(
         name: String,
         result_type: VarType,
-        parameters: HashMap<String, VarType>,
+        parameters: Vec<(String, VarType)>,
         commands: Vec<Command>,
     ) -> Function {
         Function {
             name,
             result_type,
+            parameter_ids: parameter_ids(&parameters),
             parameters,
             commands,
+            native: None,
+            bytecode: None,
+        }
+    }
+
+    pub fn new_native(
+        name: String,
+        result_type: VarType,
+        arg_types: Vec<VarType>,
+        native: NativeFunction,
+    ) -> Function {
+        let parameters: Vec<(String, VarType)> = arg_types
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| (format!("arg{}", i), t))
+            .collect();
+
+        Function {
+            name,
+            result_type,
+            parameter_ids: parameter_ids(&parameters),
+            parameters,
+            commands: Vec::new(),
+            native: Some(native),
+            bytecode: None,
         }
     }
 
+    /// Command that errors of the call itself are reported with
+    fn call_command(&self) -> Command {
+        Command::new(CommandType::UseFunc, 0, vec![self.name.clone()])
+    }
+
+    fn execute_native(
+        &self,
+        native: &NativeFunction,
+        args: Vec<Variable>,
+    ) -> Result<Variable, (ScriptError, Command)> {
+        let command = self.call_command();
+
+        if args.len() != self.parameters.len() {
+            return Err((ScriptError::CommandArgsInvalidError, command));
+        }
+        for ((_, arg_type), arg) in self.parameters.iter().zip(&args) {
+            if arg.get_type() != *arg_type {
+                return Err((ScriptError::type_mismatch(arg_type.to_name(), arg), command));
+            }
+        }
+
+        let result = (native.0)(args).map_err(|f| (f, command.clone()))?;
+        if self.result_type != VarType::Null && result.get_type() != self.result_type {
+            return Err((
+                ScriptError::type_mismatch(self.result_type.to_name(), &result),
+                command,
+            ));
+        }
+
+        Ok(result)
+    }
+
+    /// Call the function and return its result. The result is also written to `result_var`
+    /// if it's not `null`, the caller's locals are not visible here, so it can only be a
+    /// global variable, commands write the returned result to their variables themselves.
     pub fn execute(
         &self,
-        script: Arc<Mutex<RunningScript>>,
+        script: Arc<RunningScript>,
         result_var: String,
         args: Vec<Variable>,
         is_global: bool,
-    ) -> Result<(), (ScriptError, Command)> {
-        let mut locals: HashMap<String, Variable> = HashMap::new();
-        let mut index = 0;
-        for (k, _) in self.parameters.clone() {
-            locals.insert(k, args[index].clone());
-            index += 1;
+    ) -> Result<Variable, (ScriptError, Command)> {
+        self.execute_raw(script, result_var, args, is_global)
+            .map_err(|(error, command)| match error {
+                // `BREAK` can't leave the loop through a call, the call itself fails
+                ScriptError::BreakOutsideLoopError
+                    if command.command_type == CommandType::Break =>
+                {
+                    (error, self.call_command())
+                }
+                error => (error, command),
+            })
+    }
+
+    /// Execute the function as the body of a loop or `IF`, a `BREAK` inside it is returned
+    /// as is, so the loop can stop
+    pub fn execute_block(
+        &self,
+        script: Arc<RunningScript>,
+        args: Vec<Variable>,
+    ) -> Result<Variable, (ScriptError, Command)> {
+        self.execute_raw(script, "null".to_string(), args, false)
+    }
+
+    /// `execute` without the check for `BREAK`, for loop bodies and the main function
+    pub(crate) fn execute_raw(
+        &self,
+        script: Arc<RunningScript>,
+        result_var: String,
+        args: Vec<Variable>,
+        is_global: bool,
+    ) -> Result<Variable, (ScriptError, Command)> {
+        let memo = script.get_memo_cache();
+        let result = if !memo.is_marked(&self.name) {
+            self.execute_uncached(script.clone(), args, is_global)?
+        } else if let Some(result) = memo.get(&self.name, &args) {
+            result
+        } else {
+            let result = self.execute_uncached(script.clone(), args.clone(), is_global)?;
+            memo.insert(&self.name, args, result.clone());
+            result
+        };
+
+        if result_var != "null" {
+            script
+                .set_var(
+                    result_var,
+                    result.clone(),
+                    is_global,
+                    false,
+                    &mut HashMap::new(),
+                )
+                .map_err(|f| (f, self.call_command()))?;
+        }
+
+        Ok(result)
+    }
+
+    fn execute_uncached(
+        &self,
+        script: Arc<RunningScript>,
+        args: Vec<Variable>,
+        is_global: bool,
+    ) -> Result<Variable, (ScriptError, Command)> {
+        if let Some(native) = &self.native {
+            return self.execute_native(native, args);
+        }
+
+        if args.len() != self.parameters.len() {
+            return Err((ScriptError::CommandArgsInvalidError, self.call_command()));
+        }
+        let mut locals: HashMap<Symbol, Variable> = HashMap::new();
+        for (id, arg) in self.parameter_ids.iter().zip(args) {
+            locals.insert(*id, arg);
         }
         locals.insert(
-            "result".to_string(),
-            Variable::empty_var(self.result_type.clone()).unwrap(),
+            *RESULT_VAR,
+            Variable::empty_var(self.result_type.clone()).map_err(|f| (f, self.call_command()))?,
         );
 
+        let depth = CallStack::enter(&self.name);
+        let executed = if let Some(code) = &self.bytecode {
+            RunningScript::execute_bytecode(script, self, code, is_global, &mut locals)
+        } else {
+            self.execute_commands(script, is_global, &mut locals)
+        };
+        CallStack::leave(depth, executed.is_err());
+        executed?;
+
+        Ok(locals
+            .get(&RESULT_VAR)
+            .cloned()
+            .unwrap_or(Variable::from_null()))
+    }
+
+    fn execute_commands(
+        &self,
+        script: Arc<RunningScript>,
+        is_global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
         let mut temp_vars: Vec<String> = Vec::new();
 
         for command in self.commands.clone() {
             if let CommandType::Return = command.command_type {
-                return Ok(());
+                break;
             }
 
-            command.execute(script.clone(), is_global, &mut locals, &mut temp_vars)?;
+            command
+                .execute(script.clone(), is_global, locals, &mut temp_vars)
+                .inspect_err(|e| CallStack::fail(&command, e))?;
 
             if let CommandType::TempVar = command.command_type {
                 continue;
@@ -62,30 +240,19 @@ impl Function {
 
             for ele in temp_vars.clone() {
                 script
-                    .clone()
-                    .lock()
-                    .unwrap()
-                    .drop_var(ele, &mut locals)
+                    .drop_var(ele, locals)
                     .map_err(|f| (f, command.clone()))
                     .ignore();
             }
         }
 
-        if result_var != "null" {
-            script
-                .clone()
-                .lock()
-                .unwrap()
-                .set_var(
-                    result_var,
-                    locals.get("result").unwrap().clone(),
-                    is_global,
-                    false,
-                    &mut locals,
-                )
-                .unwrap();
-        }
-
         Ok(())
     }
 }
+
+fn parameter_ids(parameters: &[(String, VarType)]) -> Vec<Symbol> {
+    parameters
+        .iter()
+        .map(|(name, _)| Symbol::variable(name))
+        .collect()
+}
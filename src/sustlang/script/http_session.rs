@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Cookies and default headers of `http_session`, shared by all copies of the variable.
+/// Cookies are kept by name only, domain and path of `Set-Cookie` are not checked.
+#[derive(Default)]
+pub struct HttpSession {
+    cookies: Mutex<HashMap<String, String>>,
+    headers: Mutex<HashMap<String, String>>,
+}
+
+impl HttpSession {
+    pub fn new() -> HttpSession {
+        HttpSession::default()
+    }
+
+    /// Header sent with every request of the session, empty `value` removes it
+    pub fn set_header(&self, name: &str, value: &str) {
+        let mut headers = self.headers.lock().unwrap();
+        if value.is_empty() {
+            headers.remove(name);
+        } else {
+            headers.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    /// Remember the cookie from the value of a `Set-Cookie` header, a cookie
+    /// with `Max-Age=0` or an empty value is removed
+    pub fn store_cookie(&self, set_cookie: &str) {
+        let mut parts = set_cookie.split(';');
+        let Some((name, value)) = parts.next().and_then(|pair| pair.split_once('=')) else {
+            return;
+        };
+        let (name, value) = (name.trim(), value.trim().trim_matches('"'));
+        if name.is_empty() {
+            return;
+        }
+
+        let expired = parts.any(|attr| {
+            attr.split_once('=').is_some_and(|(key, value)| {
+                key.trim().eq_ignore_ascii_case("max-age")
+                    && value.trim().parse::<i64>().is_ok_and(|age| age <= 0)
+            })
+        });
+
+        let mut cookies = self.cookies.lock().unwrap();
+        if expired || value.is_empty() {
+            cookies.remove(name);
+        } else {
+            cookies.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies.lock().unwrap().get(name).cloned()
+    }
+
+    /// Headers to send with a request: the default headers and `Cookie` with all cookies
+    pub fn request_headers(&self) -> HashMap<String, String> {
+        let mut headers = self.headers.lock().unwrap().clone();
+
+        let cookies = self.cookies.lock().unwrap();
+        if !cookies.is_empty() {
+            let mut pairs: Vec<String> = cookies
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            pairs.sort();
+            headers.insert("Cookie".to_string(), pairs.join("; "));
+        }
+
+        headers
+    }
+}
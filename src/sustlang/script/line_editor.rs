@@ -0,0 +1,51 @@
+use super::ScriptError;
+
+/// Line editing with history for `PROMPT`. It is only used when the script reads the terminal
+/// of the process and the `readline` feature is enabled, otherwise `PROMPT` reads `cin` as is.
+#[derive(Default)]
+pub struct LineEditor {
+    #[cfg(feature = "readline")]
+    editor: Option<rustyline::DefaultEditor>,
+}
+
+impl LineEditor {
+    /// Editor for a terminal, without the `readline` feature it does nothing
+    #[cfg(feature = "readline")]
+    pub fn terminal() -> LineEditor {
+        LineEditor {
+            editor: rustyline::DefaultEditor::new().ok(),
+        }
+    }
+
+    /// Editor for a terminal, without the `readline` feature it does nothing
+    #[cfg(not(feature = "readline"))]
+    pub fn terminal() -> LineEditor {
+        LineEditor::default()
+    }
+
+    /// Print the prompt and read a line with editing, `None` means that the line
+    /// has to be read from `cin`
+    #[cfg(feature = "readline")]
+    pub fn read_line(&mut self, prompt: &str) -> Option<Result<String, ScriptError>> {
+        use rustyline::error::ReadlineError;
+
+        let editor = self.editor.as_mut()?;
+        Some(match editor.readline(prompt) {
+            Ok(line) => {
+                if !line.is_empty() {
+                    let _ = editor.add_history_entry(line.as_str());
+                }
+                Ok(line)
+            }
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => Ok(String::new()),
+            Err(_) => Err(ScriptError::StreamReadError),
+        })
+    }
+
+    /// Print the prompt and read a line with editing, `None` means that the line
+    /// has to be read from `cin`
+    #[cfg(not(feature = "readline"))]
+    pub fn read_line(&mut self, _prompt: &str) -> Option<Result<String, ScriptError>> {
+        None
+    }
+}
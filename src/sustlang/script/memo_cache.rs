@@ -0,0 +1,40 @@
+use super::super::var::Variable;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Results of functions marked with `MEMO_FUNC`, by the function name and the arguments.
+/// The cache is never cleared, so it is meant for pure functions with a limited set of arguments.
+#[derive(Default)]
+pub struct MemoCache {
+    functions: RwLock<HashMap<String, HashMap<Vec<Variable>, Variable>>>,
+}
+
+impl MemoCache {
+    pub fn new() -> MemoCache {
+        MemoCache::default()
+    }
+
+    /// Start caching results of the function, results cached before are kept
+    pub fn mark(&self, name: &str) {
+        self.functions
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_default();
+    }
+
+    pub fn is_marked(&self, name: &str) -> bool {
+        self.functions.read().unwrap().contains_key(name)
+    }
+
+    pub fn get(&self, name: &str, args: &[Variable]) -> Option<Variable> {
+        self.functions.read().unwrap().get(name)?.get(args).cloned()
+    }
+
+    pub fn insert(&self, name: &str, args: Vec<Variable>, result: Variable) {
+        if let Some(results) = self.functions.write().unwrap().get_mut(name) {
+            results.insert(args, result);
+        }
+    }
+}
@@ -1,9 +1,42 @@
+pub mod audit;
+pub mod bytecode;
+pub mod call_stack;
+pub mod clock;
 pub mod error;
 pub mod function;
+pub mod http_session;
+pub mod line_editor;
+pub mod memo_cache;
+pub mod network_streams;
+pub mod optimizer;
+pub mod pragmas;
+pub mod rate_limiter;
 pub mod running_script;
+pub mod running_script_builder;
+pub mod sandbox;
+#[allow(clippy::module_inception)]
 pub mod script;
+pub mod script_meta;
+pub mod script_thread;
+pub mod symbol;
 
+pub use audit::*;
+pub use bytecode::*;
+pub use call_stack::*;
+pub use clock::*;
 pub use error::*;
 pub use function::*;
+pub use http_session::*;
+pub use line_editor::*;
+pub use memo_cache::*;
+pub use network_streams::*;
+pub use optimizer::*;
+pub use pragmas::*;
+pub use rate_limiter::*;
 pub use running_script::*;
+pub use running_script_builder::*;
+pub use sandbox::*;
 pub use script::*;
+pub use script_meta::*;
+pub use script_thread::*;
+pub use symbol::*;
@@ -0,0 +1,76 @@
+use super::super::var::Variable;
+use super::ScriptError;
+
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Sockets behind the stream variables of TCP connections, so `SET_STREAM_TIMEOUT` can find
+/// the socket of an `in_stream` or `out_stream`. Sockets are kept by weak references
+/// and are closed as usual when no variable uses them.
+#[derive(Default)]
+pub struct NetworkStreams {
+    sockets: Mutex<Vec<Weak<Mutex<TcpStream>>>>,
+}
+
+impl NetworkStreams {
+    pub fn new() -> NetworkStreams {
+        NetworkStreams::default()
+    }
+
+    /// Stream variables for reading and writing the socket
+    pub fn register(&self, stream: TcpStream) -> Result<(Variable, Variable), ScriptError> {
+        let reader = Arc::new(Mutex::new(
+            stream.try_clone().map_err(|_| ScriptError::NetworkError)?,
+        ));
+        let writer = Arc::new(Mutex::new(stream));
+
+        let mut sockets = self.sockets.lock().unwrap();
+        sockets.retain(|socket| socket.strong_count() > 0);
+        sockets.push(Arc::downgrade(&reader));
+        sockets.push(Arc::downgrade(&writer));
+
+        Ok((
+            Variable::from_in_stream(Some(reader)),
+            Variable::from_out_stream(Some(writer)),
+        ))
+    }
+
+    /// Set the read and write timeout of the socket behind the stream variable, `None` waits forever.
+    /// Both streams of a connection share the socket, so the timeout is set for both of them.
+    pub fn set_timeout(
+        &self,
+        stream: &Variable,
+        timeout: Option<Duration>,
+    ) -> Result<(), ScriptError> {
+        let address = match stream {
+            Variable::InStream(_, Some(stream)) => Arc::as_ptr(stream) as *const (),
+            Variable::OutStream(_, Some(stream)) => Arc::as_ptr(stream) as *const (),
+            Variable::InStream(_, None) | Variable::OutStream(_, None) => {
+                return Err(ScriptError::VarNotInitedError)
+            }
+            _ => {
+                return Err(ScriptError::type_mismatch(
+                    "in_stream or out_stream",
+                    stream,
+                ))
+            }
+        };
+
+        let socket = self
+            .sockets
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|socket| socket.as_ptr() as *const () == address)
+            .and_then(Weak::upgrade)
+            // not a stream of a TCP connection
+            .ok_or(ScriptError::CommandArgsInvalidError)?;
+
+        let socket = socket.lock().unwrap();
+        socket
+            .set_read_timeout(timeout)
+            .and_then(|_| socket.set_write_timeout(timeout))
+            .map_err(|_| ScriptError::NetworkError)
+    }
+}
@@ -0,0 +1,243 @@
+use super::super::command::{Command, CommandType};
+use super::super::var::{VarType, Variable};
+use super::Function;
+
+use std::collections::HashMap;
+
+/// The command uses the variable `name` or a path inside of it like `name.key`
+fn mentions(command: &Command, name: &str) -> bool {
+    command
+        .args
+        .iter()
+        .any(|arg| arg.split('.').next() == Some(name))
+}
+
+/// Commands after the first `RETURN` are never executed
+fn remove_unreachable(commands: &mut Vec<Command>) {
+    if let Some(index) = commands
+        .iter()
+        .position(|command| command.command_type == CommandType::Return)
+    {
+        commands.truncate(index + 1);
+    }
+}
+
+/// Sum of `SET_VAR x A`, `TEMP_VAR int t B`, `ADD_INT x t` (or the same with floats),
+/// `None` if the commands are not like that or the sum can't be known before the run
+fn fold_add(var_type: &VarType, set: &Command, temp: &Command, add: &Command) -> Option<Variable> {
+    let op_type = match add.command_type {
+        CommandType::AddInt => VarType::Integer,
+        CommandType::AddFloat => VarType::Float,
+        _ => return None,
+    };
+    let (name, temp_name) = (set.args.first()?, temp.args.get(1)?);
+    if *var_type != op_type
+        || temp.command_type != CommandType::TempVar
+        || VarType::from_name(temp.args.first()?).ok()? != op_type
+        || add.args.len() != 2
+        || add.args[0] != *name
+        || add.args[1] != *temp_name
+        || temp_name == name
+    {
+        return None;
+    }
+
+    let left = Variable::parse_var(op_type.clone(), set.args[1..].join(" ")).ok()?;
+    let right = Variable::parse_var(op_type, temp.args[2..].join(" ")).ok()?;
+    match (left, right) {
+        (Variable::Integer(_, Some(a)), Variable::Integer(_, Some(b))) => {
+            Some(Variable::from_int(Some(a.checked_add(b)?)))
+        }
+        (Variable::Float(_, Some(a)), Variable::Float(_, Some(b))) => {
+            let sum = a + b;
+            sum.is_finite().then(|| Variable::from_float(Some(sum)))
+        }
+        _ => None,
+    }
+}
+
+/// Types of `result` and of the variables created by `INIT_VAR`, while only `SET_VAR` changed them
+struct KnownTypes(HashMap<String, VarType>);
+
+impl KnownTypes {
+    fn new(result_type: &VarType) -> KnownTypes {
+        KnownTypes(HashMap::from([(
+            String::from("result"),
+            result_type.clone(),
+        )]))
+    }
+
+    fn get(&self, name: &str) -> Option<&VarType> {
+        self.0.get(name)
+    }
+
+    /// Update the types after a command that is not `SET_VAR`
+    fn apply(&mut self, command: &Command) {
+        self.0.retain(|name, _| !mentions(command, name));
+        if command.command_type == CommandType::InitVar {
+            if let (Some(var_type), Some(name)) = (command.args.first(), command.args.get(1)) {
+                if let Ok(var_type) = VarType::from_name(var_type) {
+                    self.0.insert(name.clone(), var_type);
+                }
+            }
+        }
+    }
+}
+
+/// Replace one addition of literals with `SET_VAR` of the sum, returns `false` if there is nothing to fold
+fn fold_constants(commands: &mut Vec<Command>, result_type: &VarType) -> bool {
+    let mut known = KnownTypes::new(result_type);
+
+    for index in 0..commands.len() {
+        let command = &commands[index];
+
+        if command.command_type == CommandType::SetVar && command.args.len() > 1 {
+            let name = &command.args[0];
+            let folded = match (
+                known.get(name),
+                commands.get(index + 1),
+                commands.get(index + 2),
+            ) {
+                (Some(var_type), Some(temp), Some(add)) => fold_add(var_type, command, temp, add)
+                    .filter(|_| {
+                        // the temporary variable is not used anywhere else
+                        commands.iter().enumerate().all(|(i, other)| {
+                            i == index + 1 || i == index + 2 || !mentions(other, &temp.args[1])
+                        })
+                    })
+                    .and_then(|sum| sum.to_string().ok()),
+                _ => None,
+            };
+
+            if let Some(sum) = folded {
+                let mut set =
+                    Command::new(CommandType::SetVar, command.line, vec![name.clone(), sum]);
+                set.file = command.file.clone();
+                commands.splice(index..index + 3, [set]);
+                return true;
+            }
+            continue;
+        }
+
+        known.apply(command);
+    }
+
+    false
+}
+
+/// Values of the `SET_VAR` commands of the function, parsed before the run. A value is known
+/// if the variable was created by `INIT_VAR` earlier in the function and only `SET_VAR` changed it
+pub fn constant_values(func: &Function) -> Vec<Option<Variable>> {
+    let mut known = KnownTypes::new(&func.result_type);
+    func.commands
+        .iter()
+        .map(
+            |command| match (command.command_type, command.args.first()) {
+                (CommandType::SetVar, Some(name)) => known.get(name).and_then(|var_type| {
+                    Variable::parse_var(var_type.clone(), command.args[1..].join(" ")).ok()
+                }),
+                _ => {
+                    known.apply(command);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// Indexes of all commands of the local variable `name` if its value is never read
+/// and none of them can fail
+fn dead_stores(commands: &[Command], name: &str) -> Option<Vec<usize>> {
+    let indexes: Vec<usize> = commands
+        .iter()
+        .enumerate()
+        .filter(|(_, command)| mentions(command, name))
+        .map(|(index, _)| index)
+        .collect();
+
+    // the first command has to create the variable, otherwise it is a parameter or a global
+    match commands[*indexes.first()?].command_type {
+        CommandType::InitVar => {}
+        CommandType::TempVar if indexes.len() == 1 => {}
+        _ => return None,
+    }
+
+    let mut var_type: Option<VarType> = None;
+    for &index in &indexes {
+        let command = &commands[index];
+        match command.command_type {
+            CommandType::InitVar if command.args.get(1).is_some_and(|arg| arg == name) => {
+                let parsed = VarType::from_name(&command.args[0]).ok()?;
+                Variable::empty_var(parsed.clone()).ok()?;
+                var_type = Some(parsed);
+            }
+            CommandType::TempVar if command.args.get(1).is_some_and(|arg| arg == name) => {
+                let parsed = VarType::from_name(&command.args[0]).ok()?;
+                Variable::parse_var(parsed, command.args[2..].join(" ")).ok()?;
+            }
+            CommandType::SetVar if command.args[0] == name => {
+                Variable::parse_var(var_type.clone()?, command.args[1..].join(" ")).ok()?;
+            }
+            _ => return None,
+        }
+    }
+
+    Some(indexes)
+}
+
+/// Remove writes to local variables that are never read
+fn remove_dead_stores(commands: &mut Vec<Command>, parameters: &[(String, VarType)]) {
+    // `DUMP_VARS` prints all local variables, so every variable is read by it
+    if commands
+        .iter()
+        .any(|command| command.command_type == CommandType::DumpVars)
+    {
+        return;
+    }
+
+    let mut names: Vec<&String> = commands
+        .iter()
+        .filter(|command| {
+            matches!(
+                command.command_type,
+                CommandType::InitVar | CommandType::TempVar
+            )
+        })
+        .filter_map(|command| command.args.get(1))
+        .filter(|name| *name != "result" && !parameters.iter().any(|(param, _)| param == *name))
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let mut dead: Vec<usize> = names
+        .iter()
+        .filter_map(|name| dead_stores(commands, name))
+        .flatten()
+        .collect();
+    dead.sort_unstable();
+    dead.dedup();
+
+    let mut index = 0;
+    commands.retain(|_| {
+        index += 1;
+        dead.binary_search(&(index - 1)).is_err()
+    });
+}
+
+/// Optimized commands of a function: commands after `RETURN` are dropped, additions of literals
+/// through `TEMP_VAR` are folded and writes to local variables that are never read are removed
+pub fn optimize_function(func: &Function) -> Vec<Command> {
+    let mut commands = func.commands.clone();
+    remove_unreachable(&mut commands);
+    while fold_constants(&mut commands, &func.result_type) {}
+    remove_dead_stores(&mut commands, &func.parameters);
+    commands
+}
+
+/// Optimized commands of the main function, only commands after `RETURN` are dropped.
+/// Its variables are global and can be read by threads and by the host after the run.
+pub fn optimize_main(commands: &[Command]) -> Vec<Command> {
+    let mut commands = commands.to_vec();
+    remove_unreachable(&mut commands);
+    commands
+}
@@ -0,0 +1,40 @@
+use super::ScriptError;
+
+/// Newest language level accepted by `PRAGMA lang`
+pub const LATEST_LANG: usize = 2;
+
+/// Switches of language semantics, declared with `PRAGMA key value` lines in the header
+/// of the script. Scripts without pragmas keep the old behavior.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Pragmas {
+    /// Language level, `1` by default. `2` only turns on checked integer arithmetic,
+    /// arguments are still variable names and scoping is the same as in `1`
+    pub lang: usize,
+}
+
+impl Default for Pragmas {
+    fn default() -> Self {
+        Pragmas { lang: 1 }
+    }
+}
+
+impl Pragmas {
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ScriptError> {
+        match key {
+            "lang" => {
+                let lang: usize = value.parse().map_err(|_| ScriptError::PragmaError)?;
+                if lang == 0 || lang > LATEST_LANG {
+                    return Err(ScriptError::PragmaError);
+                }
+                self.lang = lang;
+            }
+            _ => return Err(ScriptError::PragmaError),
+        }
+        Ok(())
+    }
+
+    /// Integer overflow is a `RangeError` instead of a panic, since `lang 2`
+    pub fn checked_arithmetic(&self) -> bool {
+        self.lang >= 2
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Token bucket of `RATE_LIMIT`: holds up to `permits_per_second` permits, taken permits
+/// come back at the same rate. Time is passed in by the caller, so the script clock is used.
+pub struct RateLimiter {
+    permits_per_second: f64,
+    /// Available permits (negative when permits are reserved ahead) and time of the last refill
+    state: Mutex<(f64, Option<u128>)>,
+}
+
+impl RateLimiter {
+    pub fn new(permits_per_second: u64) -> RateLimiter {
+        let permits_per_second = permits_per_second as f64;
+        RateLimiter {
+            permits_per_second,
+            state: Mutex::new((permits_per_second, None)),
+        }
+    }
+
+    /// Take `permits` at `now_millis`, returns how long to wait until they are available
+    pub fn reserve(&self, permits: u64, now_millis: u128) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let (available, last) = &mut *state;
+
+        if let Some(last) = last {
+            let elapsed = now_millis.saturating_sub(*last) as f64 / 1000.0;
+            *available =
+                (*available + elapsed * self.permits_per_second).min(self.permits_per_second);
+        }
+        *last = Some(now_millis);
+
+        *available -= permits as f64;
+        if *available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*available / self.permits_per_second)
+        }
+    }
+}
@@ -1,39 +1,338 @@
-use super::super::command::Command;
-use super::super::script::{Function, Script, ScriptError};
+use super::super::command::{Command, CommandType};
+use super::super::other::IgnoreResult;
+use super::super::script::{
+    compile_function, compile_main, optimize_function, optimize_main, Access, AuditEvent,
+    AuditHook, AuditKind, CallStack, Clock, Function, Instruction, LineEditor, MemoCache,
+    NativeFunction, NetworkStreams, Pragmas, RunningScriptBuilder, RuntimeError, Sandbox, Script,
+    ScriptError, ScriptMeta, ScriptThread, Symbol, SymbolTable, SystemClock, VarPath,
+};
 use super::super::var::{VarType, Variable};
 
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::sync::{Arc, Mutex};
+use std::env;
+#[cfg(feature = "serde")]
+use std::fs;
+use std::io::{stderr, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub struct RunningScript {
-    main_function: Function,
-    functions: Vec<Function>,
-    variables: HashMap<String, Variable>,
+fn parse_map_key(map_type: &VarType, key: &str) -> Result<Variable, ScriptError> {
+    match map_type {
+        VarType::Map(key_type, _) => {
+            Variable::parse_var(key_type.as_ref().clone(), key.to_string())
+        }
+        _ => Err(ScriptError::TypeMismatchError {
+            expected: String::from("map"),
+            actual: map_type.to_name(),
+        }),
+    }
+}
+
+/// Variable at the path inside `vars`, lists and maps along the path are made unique
+/// so the change does not leak into their other copies
+fn path_mut<'a>(
+    vars: &'a mut HashMap<Symbol, Variable>,
+    path: &VarPath,
+    segments: &[String],
+) -> Result<&'a mut Variable, ScriptError> {
+    let mut var = vars
+        .get_mut(&path.id)
+        .ok_or_else(|| ScriptError::UnknownVarError(path.to_string()))?;
+
+    for part in segments {
+        var = match var {
+            Variable::List(_, Some(list)) => {
+                let index: usize = part.parse().map_err(|_| ScriptError::ParseVarError)?;
+                Arc::make_mut(list)
+                    .get_mut(index)
+                    .ok_or_else(|| ScriptError::UnknownVarError(path.to_string()))?
+            }
+            Variable::Map(map_type, Some(map)) => {
+                let key_var = parse_map_key(map_type, part)?;
+                Arc::make_mut(map)
+                    .get_mut(&key_var)
+                    .ok_or_else(|| ScriptError::UnknownVarError(path.to_string()))?
+            }
+            Variable::List(_, None) | Variable::Map(_, None) => {
+                return Err(ScriptError::UnknownVarError(path.to_string()))
+            }
+            _ => return Err(ScriptError::type_mismatch("list or map", var)),
+        };
+    }
+
+    Ok(var)
 }
 
-unsafe impl Sync for RunningScript {}
-unsafe impl Send for RunningScript {}
+fn set_in(
+    vars: &mut HashMap<Symbol, Variable>,
+    path: &VarPath,
+    value: Variable,
+) -> Result<(), ScriptError> {
+    let Some((last, segments)) = path.segments.split_last() else {
+        vars.insert(path.id, value);
+        return Ok(());
+    };
+
+    match path_mut(vars, path, segments)? {
+        Variable::List(_, Some(list)) => {
+            let index: usize = last.parse().map_err(|_| ScriptError::ParseVarError)?;
+            match Arc::make_mut(list).get_mut(index) {
+                Some(item) => {
+                    *item = value;
+                    Ok(())
+                }
+                None => Err(ScriptError::UnknownVarError(path.to_string())),
+            }
+        }
+        Variable::Map(map_type, Some(map)) => {
+            let key_var = parse_map_key(map_type, last)?;
+            Arc::make_mut(map).insert(key_var, value);
+            Ok(())
+        }
+        Variable::List(_, None) | Variable::Map(_, None) => {
+            Err(ScriptError::UnknownVarError(path.to_string()))
+        }
+        var => Err(ScriptError::type_mismatch("list or map", var)),
+    }
+}
+
+fn drop_in(vars: &mut HashMap<Symbol, Variable>, path: &VarPath) -> Result<(), ScriptError> {
+    let Some((last, segments)) = path.segments.split_last() else {
+        return vars
+            .remove(&path.id)
+            .map(|_| ())
+            .ok_or_else(|| ScriptError::UnknownVarError(path.to_string()));
+    };
+
+    match path_mut(vars, path, segments)? {
+        Variable::List(_, Some(list)) => {
+            let index: usize = last.parse().map_err(|_| ScriptError::ParseVarError)?;
+            if index < list.len() {
+                Arc::make_mut(list).remove(index);
+                Ok(())
+            } else {
+                Err(ScriptError::UnknownVarError(path.to_string()))
+            }
+        }
+        Variable::Map(map_type, Some(map)) => {
+            let key_var = parse_map_key(map_type, last)?;
+            Arc::make_mut(map)
+                .remove(&key_var)
+                .map(|_| ())
+                .ok_or_else(|| ScriptError::UnknownVarError(path.to_string()))
+        }
+        Variable::List(_, None) | Variable::Map(_, None) => {
+            Err(ScriptError::UnknownVarError(path.to_string()))
+        }
+        var => Err(ScriptError::type_mismatch("list or map", var)),
+    }
+}
+
+/// What to do with threads started by `NEW_THREAD` when the main function finishes
+#[derive(PartialEq, Clone, Debug, Default)]
+pub enum ThreadPolicy {
+    /// Wait for every thread, including threads started while waiting
+    #[default]
+    JoinAll,
+    /// Return immediately, threads are killed when the process exits
+    Detach,
+    /// Wait for threads no longer than the given duration
+    Timeout(Duration),
+}
+
+/// State of a finished script, see `RunningScript::run_with_result`
+pub struct ScriptOutcome {
+    /// Global variables after the script and its threads finished
+    pub globals: HashMap<String, Variable>,
+    /// Value of `result` in the main function, `null` unless a result type was set
+    /// with `RunningScript::set_result_type`
+    pub result: Variable,
+}
+
+#[cfg(feature = "serde")]
+impl ScriptOutcome {
+    /// Global variables as a json object sorted by name, values are written like
+    /// `Variable::serialize` does, so streams, threads and locks become `null`
+    pub fn globals_json(&self) -> Result<String, ScriptError> {
+        let globals: BTreeMap<&String, &Variable> = self.globals.iter().collect();
+        serde_json::to_string_pretty(&globals).map_err(|_| ScriptError::ParseVarError)
+    }
+
+    /// Write `globals_json` to the file at `path`
+    pub fn export_globals(&self, path: &Path) -> Result<(), ScriptError> {
+        let json = self.globals_json()?;
+        fs::write(path, json)
+            .map_err(|e| ScriptError::file_write(path.to_string_lossy().to_string(), &e))
+    }
+}
+
+/// Script that is compiled to bytecode once and then run many times, for example once per
+/// request of a server. Every `RunningScriptBuilder::from_compiled` or `instantiate` gives a new
+/// `RunningScript` with its own globals, functions and their bytecode are shared by all of them.
+#[derive(Clone)]
+pub struct CompiledScript {
+    main_function: Arc<Function>,
+    functions: Arc<Vec<Function>>,
+    function_symbols: SymbolTable,
+    meta: ScriptMeta,
+    pragmas: Pragmas,
+}
+
+impl CompiledScript {
+    pub fn new(script: Script) -> CompiledScript {
+        let mut running_script = RunningScript::new(script);
+        running_script.compile();
+        CompiledScript {
+            main_function: running_script.main_function,
+            functions: running_script.functions,
+            function_symbols: running_script.function_symbols,
+            meta: running_script.meta,
+            pragmas: running_script.pragmas,
+        }
+    }
+
+    /// Parse and compile the text of the script, see `Script::parse`
+    pub fn parse(text: String) -> Result<CompiledScript, (ScriptError, usize)> {
+        Ok(CompiledScript::new(Script::parse(text)?))
+    }
+
+    pub fn builder(&self) -> RunningScriptBuilder {
+        RunningScriptBuilder::from_compiled(self)
+    }
+
+    /// New script without standard variables, like `RunningScript::new`
+    pub fn instantiate(&self) -> RunningScript {
+        let mut running_script = RunningScript::new(Script {
+            commands: Vec::new(),
+            functions: Vec::new(),
+            meta: self.meta.clone(),
+            pragmas: self.pragmas.clone(),
+            source: Vec::new(),
+        });
+        running_script.main_function = self.main_function.clone();
+        running_script.functions = self.functions.clone();
+        running_script.function_symbols = self.function_symbols.clone();
+        running_script
+    }
+
+    pub fn meta(&self) -> &ScriptMeta {
+        &self.meta
+    }
+}
+
+pub struct RunningScript {
+    main_function: Arc<Function>,
+    /// Functions by their symbol, a name is declared only once. Shared with isolated copies
+    /// and `CompiledScript`, changed copy-on-write by `register_native_function`
+    functions: Arc<Vec<Function>>,
+    function_symbols: SymbolTable,
+    /// Global variables, locals of a function are owned by its call and need no lock
+    variables: RwLock<HashMap<Symbol, Variable>>,
+    /// Shared with isolated copies of the script, so `run` waits for their threads too
+    threads: Arc<Mutex<Vec<Arc<Mutex<ScriptThread>>>>>,
+    thread_policy: ThreadPolicy,
+    clock: Arc<dyn Clock>,
+    bytecode: bool,
+    meta: ScriptMeta,
+    pragmas: Pragmas,
+    line_editor: Arc<Mutex<LineEditor>>,
+    styled_output: bool,
+    audit_hook: Option<AuditHook>,
+    dry_run: bool,
+    sandbox: Sandbox,
+    /// Generator of `RANDOM*` commands after `RANDOM_SEED`, shared with isolated copies
+    rng: Arc<Mutex<Option<StdRng>>>,
+    /// Start times of `TIMER_START` by name, shared with isolated copies
+    timers: Arc<Mutex<HashMap<String, u128>>>,
+    memo_cache: Arc<MemoCache>,
+    network_streams: Arc<NetworkStreams>,
+}
 
 impl RunningScript {
     pub fn new(script: Script) -> RunningScript {
+        let mut functions = Vec::new();
+        let mut function_symbols = SymbolTable::new();
+        for func in script.functions {
+            // the first declaration wins, as it always did with lookups by name
+            if function_symbols.get(&func.name).is_none() {
+                function_symbols.intern(&func.name);
+                functions.push(func);
+            }
+        }
+
         RunningScript {
-            functions: script.functions,
-            variables: HashMap::new(),
-            main_function: Function::new(
+            functions: Arc::new(functions),
+            function_symbols,
+            variables: RwLock::new(HashMap::new()),
+            threads: Arc::new(Mutex::new(Vec::new())),
+            thread_policy: ThreadPolicy::default(),
+            clock: Arc::new(SystemClock),
+            bytecode: true,
+            meta: script.meta,
+            pragmas: script.pragmas,
+            line_editor: Arc::new(Mutex::new(LineEditor::default())),
+            styled_output: false,
+            audit_hook: None,
+            dry_run: false,
+            sandbox: Sandbox::Full,
+            rng: Arc::new(Mutex::new(None)),
+            timers: Arc::new(Mutex::new(HashMap::new())),
+            memo_cache: Arc::new(MemoCache::new()),
+            network_streams: Arc::new(NetworkStreams::new()),
+            main_function: Arc::new(Function::new(
                 "main".to_string(),
                 VarType::Null,
-                HashMap::new(),
+                Vec::new(),
                 script.commands,
-            ),
+            )),
         }
     }
 
+    pub fn builder(script: Script) -> RunningScriptBuilder {
+        RunningScriptBuilder::new(script)
+    }
+
+    /// Copy of the script with a snapshot of the global variables, used by `NEW_ISOLATED_THREAD`.
+    /// Changes of globals in the copy are not seen by the original and the other way around,
+    /// functions, the clock and the list of threads are shared.
+    pub fn isolated(&self) -> RunningScript {
+        RunningScript {
+            main_function: self.main_function.clone(),
+            functions: self.functions.clone(),
+            function_symbols: self.function_symbols.clone(),
+            variables: RwLock::new(self.variables.read().unwrap().clone()),
+            threads: self.threads.clone(),
+            thread_policy: self.thread_policy.clone(),
+            clock: self.clock.clone(),
+            bytecode: self.bytecode,
+            meta: self.meta.clone(),
+            pragmas: self.pragmas.clone(),
+            line_editor: self.line_editor.clone(),
+            styled_output: self.styled_output,
+            audit_hook: self.audit_hook.clone(),
+            dry_run: self.dry_run,
+            sandbox: self.sandbox,
+            rng: self.rng.clone(),
+            timers: self.timers.clone(),
+            memo_cache: self.memo_cache.clone(),
+            network_streams: self.network_streams.clone(),
+        }
+    }
+
+    /// Set `args`, `cout`, `cin` and the other standard variables, `cerr` is the stderr of the
+    /// process until `set_stderr` is called
     pub fn set_standard_vars(
         &mut self,
         args: Vec<String>,
-        cout: Box<dyn Write>,
-        cin: Box<dyn Read>,
+        cout: Box<dyn Write + Send>,
+        cin: Box<dyn Read + Send>,
     ) -> Result<(), ScriptError> {
         self.set_var(
             String::from("args"),
@@ -63,226 +362,763 @@ impl RunningScript {
             true,
             &mut HashMap::new(),
         )?;
+        self.set_stderr(Box::new(stderr()))?;
+        self.set_var(
+            String::from("os_name"),
+            Variable::from_str(Some(env::consts::OS.to_string())),
+            true,
+            true,
+            &mut HashMap::new(),
+        )?;
+        self.set_var(
+            String::from("env"),
+            Variable::from_map(
+                Some(if self.sandbox.allows(Access::Environment) {
+                    env::vars_os()
+                        .map(|(key, value)| {
+                            (
+                                Variable::from_str(Some(key.to_string_lossy().to_string())),
+                                Variable::from_str(Some(value.to_string_lossy().to_string())),
+                            )
+                        })
+                        .collect()
+                } else {
+                    HashMap::new()
+                }),
+                VarType::String,
+                VarType::String,
+            ),
+            true,
+            true,
+            &mut HashMap::new(),
+        )?;
+        self.set_script_path(None)?;
 
         Ok(())
     }
 
-    pub fn get_var(
-        &mut self,
+    /// Set the stream of `cerr`, the errors that can't be returned are written there too
+    pub fn set_stderr(&mut self, cerr: Box<dyn Write + Send>) -> Result<(), ScriptError> {
+        self.set_var(
+            String::from("cerr"),
+            Variable::from_out_stream(Some(Arc::new(Mutex::new(cerr)))),
+            true,
+            true,
+            &mut HashMap::new(),
+        )
+    }
+
+    /// Set `script_path` and `script_dir` to the absolute path of the script file and its
+    /// folder, both are empty strings when the script was not loaded from a file
+    pub fn set_script_path(&mut self, path: Option<&Path>) -> Result<(), ScriptError> {
+        let path = path.map(|path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        let dir = path.as_ref().map(|path| match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => Path::new(".").to_path_buf(),
+        });
+
+        for (name, value) in [("script_path", path), ("script_dir", dir)] {
+            let value = value
+                .map(|value| value.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.set_var(
+                String::from(name),
+                Variable::from_str(Some(value)),
+                true,
+                true,
+                &mut HashMap::new(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolve a dotted path like `var.key.0`. Optionals along the path are unwrapped,
+    /// `Ok(None)` means that the path went through an empty optional, a missing map key
+    /// or a list index out of range.
+    pub fn get_var_opt(
+        &self,
         name: String,
-        locals: &mut HashMap<String, Variable>,
-    ) -> Result<Variable, ScriptError> {
-        let mut var: Option<Variable> = None;
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Option<Variable>, ScriptError> {
+        self.get_path_opt(&VarPath::parse(&name), locals)
+    }
+
+    /// Same as `get_var_opt` with the path already split
+    pub fn get_path_opt(
+        &self,
+        path: &VarPath,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Option<Variable>, ScriptError> {
+        self.find_path(path, true, locals)
+    }
+
+    /// Walk the path, `Ok(None)` if a map key or a list index is missing. Optionals along
+    /// the path are unwrapped only with `unwrap_optionals`, otherwise they are a type mismatch.
+    fn find_path(
+        &self,
+        path: &VarPath,
+        unwrap_optionals: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Option<Variable>, ScriptError> {
+        // locals are owned by the running function, only globals are behind the lock
+        let mut var = match locals.get(&path.id) {
+            Some(var) => var.clone(),
+            None => self
+                .variables
+                .read()
+                .unwrap()
+                .get(&path.id)
+                .cloned()
+                .ok_or_else(|| ScriptError::UnknownVarError(path.root.clone()))?,
+        };
+
+        for part in &path.segments {
+            if let (true, Variable::Optional(_, value)) = (unwrap_optionals, &var) {
+                var = match value {
+                    Some(Some(value)) => value.as_ref().clone(),
+                    _ => return Ok(None),
+                };
+            }
 
-        for part in name.split('.') {
             var = match &var {
-                Some(v) => match v {
-                    Variable::List(_, Some(list)) => {
-                        let index: usize = part.parse().map_err(|_| ScriptError::ParseVarError)?;
-                        Some(list.get(index).ok_or(ScriptError::UnknownVarError)?.clone())
+                Variable::List(_, Some(list)) => {
+                    let index: usize = part.parse().map_err(|_| ScriptError::ParseVarError)?;
+                    match list.get(index) {
+                        Some(item) => item.clone(),
+                        None => return Ok(None),
                     }
-                    Variable::Map(map_type, Some(map)) => {
-                        let key_var = Variable::parse_var(map_type.clone(), part.to_string())?;
-                        map.get(&key_var).cloned()
+                }
+                Variable::Map(map_type, Some(map)) => {
+                    let key_var = parse_map_key(map_type, part)?;
+                    match map.get(&key_var) {
+                        Some(value) => value.clone(),
+                        None => return Ok(None),
                     }
-                    _ => return Err(ScriptError::TypeMismatchError),
-                },
-                None => locals
-                    .get(part)
-                    .or_else(|| self.variables.get(part))
-                    .cloned(),
+                }
+                _ => return Err(ScriptError::type_mismatch("list or map", &var)),
             };
         }
 
-        var.ok_or(ScriptError::UnknownVarError)
+        Ok(Some(var))
+    }
+
+    pub fn get_var(
+        &self,
+        name: String,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Variable, ScriptError> {
+        self.get_path(&VarPath::parse(&name), locals)
+    }
+
+    pub fn get_path(
+        &self,
+        path: &VarPath,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<Variable, ScriptError> {
+        self.find_path(path, false, locals)?
+            .ok_or_else(|| ScriptError::UnknownVarError(path.to_string()))
     }
 
     pub fn drop_var(
-        &mut self,
+        &self,
         name: String,
-        locals: &mut HashMap<String, Variable>,
+        locals: &mut HashMap<Symbol, Variable>,
     ) -> Result<(), ScriptError> {
-        let mut var: Option<&mut Variable> = None;
-        let parts: Vec<&str> = name.split('.').collect();
-
-        if parts.len() == 1 {
-            if locals.remove(&name).is_some() || self.variables.remove(&name).is_some() {
-                return Ok(());
-            } else {
-                return Err(ScriptError::UnknownVarError);
-            }
-        }
+        self.drop_path(&VarPath::parse(&name), locals)
+    }
 
-        for (i, part) in parts.iter().enumerate() {
-            if i == parts.len() - 1 {
-                match &mut var {
-                    Some(v) => match v {
-                        Variable::List(_, list) => match list {
-                            Some(list) => {
-                                let index: usize =
-                                    part.parse().map_err(|_| ScriptError::ParseVarError)?;
-                                if index < list.len() {
-                                    list.remove(index);
-                                    return Ok(());
-                                } else {
-                                    return Err(ScriptError::UnknownVarError);
-                                }
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        Variable::Map(map_type, map) => match map {
-                            Some(map) => {
-                                let key_var =
-                                    Variable::parse_var(map_type.clone(), part.to_string())?;
-                                if map.remove(&key_var).is_some() {
-                                    return Ok(());
-                                } else {
-                                    return Err(ScriptError::UnknownVarError);
-                                }
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        _ => return Err(ScriptError::TypeMismatchError),
-                    },
-                    None => return Err(ScriptError::UnknownVarError),
-                }
-            } else {
-                var = match var {
-                    Some(v) => match v {
-                        Variable::List(_, list) => match list {
-                            Some(list) => {
-                                let index: usize =
-                                    part.parse().map_err(|_| ScriptError::ParseVarError)?;
-                                Some(list.get_mut(index).ok_or(ScriptError::UnknownVarError)?)
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        Variable::Map(map_type, map) => match map {
-                            Some(map) => {
-                                let key_var =
-                                    Variable::parse_var(map_type.clone(), part.to_string())?;
-                                map.get_mut(&key_var)
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        _ => return Err(ScriptError::TypeMismatchError),
-                    },
-                    None => locals
-                        .get_mut(*part)
-                        .or_else(|| self.variables.get_mut(*part)),
-                };
-            }
+    pub fn drop_path(
+        &self,
+        path: &VarPath,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), ScriptError> {
+        if locals.contains_key(&path.id) {
+            drop_in(locals, path)
+        } else {
+            drop_in(&mut self.variables.write().unwrap(), path)
         }
-
-        Err(ScriptError::UnknownVarError)
     }
 
     pub fn set_var(
-        &mut self,
+        &self,
         name: String,
         value: Variable,
         global: bool,
         init: bool,
-        locals: &mut HashMap<String, Variable>,
+        locals: &mut HashMap<Symbol, Variable>,
     ) -> Result<(), ScriptError> {
-        let var_type = value.get_type();
-        let mut var: Option<&mut Variable> = None;
-        let parts: Vec<&str> = (&name).split('.').collect();
+        self.set_path(&VarPath::parse(&name), value, global, init, locals)
+    }
 
-        let global = global
-            || (self.variables.contains_key(parts[0]) && !locals.contains_key(parts[0]) && !init);
+    pub fn set_path(
+        &self,
+        path: &VarPath,
+        value: Variable,
+        global: bool,
+        init: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), ScriptError> {
+        if !global && (init || locals.contains_key(&path.id)) {
+            return set_in(locals, path, value);
+        }
 
-        if parts.len() == 1 {
-            if global {
-                self.variables.insert(name, value);
-            } else {
-                locals.insert(name.clone(), value.clone());
+        let mut globals = self.variables.write().unwrap();
+        if global || globals.contains_key(&path.id) {
+            set_in(&mut globals, path, value)
+        } else {
+            drop(globals);
+            set_in(locals, path, value)
+        }
+    }
+
+    pub fn get_meta(&self) -> &ScriptMeta {
+        &self.meta
+    }
+
+    pub fn get_pragmas(&self) -> &Pragmas {
+        &self.pragmas
+    }
+
+    /// Write an error that has no caller to return to (like an error of a listener client
+    /// or a subscription handler) to `cerr`, nothing is written if `cerr` is not a stream
+    pub fn report_error(&self, error: &(ScriptError, Command)) {
+        let Ok(cerr) = self
+            .get_var(String::from("cerr"), &mut HashMap::new())
+            .and_then(|cerr| cerr.as_out_stream())
+        else {
+            return;
+        };
+        writeln!(
+            cerr.lock().unwrap(),
+            "error ({:?}): {} command: {:?}",
+            error.0,
+            error.0,
+            error.1
+        )
+        .ignore();
+    }
+
+    /// Copy of the global variables at the moment of the call
+    pub fn get_globals(&self) -> HashMap<String, Variable> {
+        self.variables
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, var)| (id.variable_name(), var.clone()))
+            .collect()
+    }
+
+    pub fn get_function(&self, name: String) -> Result<&Function, ScriptError> {
+        let symbol = self
+            .function_symbols
+            .get(&name)
+            .ok_or(ScriptError::FunctionUnknownError(name))?;
+        self.get_function_by_symbol(symbol)
+    }
+
+    pub fn get_function_by_symbol(&self, symbol: Symbol) -> Result<&Function, ScriptError> {
+        self.functions.get(symbol.0).ok_or_else(|| {
+            ScriptError::FunctionUnknownError(
+                self.function_symbols
+                    .name(symbol)
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        })
+    }
+
+    pub fn resolve_function(
+        &self,
+        name: String,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<&Function, ScriptError> {
+        match self.get_var(name.clone(), locals) {
+            Ok(Variable::Func(_, Some(func_name))) => self.get_function(func_name),
+            Ok(Variable::Func(_, None)) => Err(ScriptError::VarNotInitedError),
+            _ => self.get_function(name),
+        }
+    }
+
+    /// Expose a Rust function to the script, it can be called with `USE_FUNC` like any
+    /// function declared with `FUNC`. Arguments are checked against `arg_types` before the call.
+    pub fn register_native_function(
+        &mut self,
+        name: String,
+        arg_types: Vec<VarType>,
+        result_type: VarType,
+        func: impl Fn(Vec<Variable>) -> Result<Variable, ScriptError> + Send + Sync + 'static,
+    ) {
+        let symbol = self.function_symbols.intern(&name);
+        let func =
+            Function::new_native(name, result_type, arg_types, NativeFunction(Arc::new(func)));
+        let functions = Arc::make_mut(&mut self.functions);
+        if symbol.0 < functions.len() {
+            functions[symbol.0] = func;
+        } else {
+            functions.push(func);
+        }
+    }
+
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn get_clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Make `RANDOM*` commands give the same numbers on every run with the same `seed`,
+    /// like `RANDOM_SEED` does. Without a seed the numbers come from `rand::thread_rng`.
+    pub fn set_random_seed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+    }
+
+    /// Call `action` with the seeded generator if there is one, otherwise with `rand::thread_rng`
+    pub fn with_rng<T>(&self, action: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        match self.rng.lock().unwrap().as_mut() {
+            Some(rng) => action(rng),
+            None => action(&mut rand::thread_rng()),
+        }
+    }
+
+    /// Running timers of `TIMER_START`, monotonic milliseconds of the clock by name
+    pub fn get_timers(&self) -> &Mutex<HashMap<String, u128>> {
+        &self.timers
+    }
+
+    /// Editor used by `PROMPT` instead of plain reads from `cin`, see `LineEditor::terminal`
+    pub fn set_line_editor(&mut self, line_editor: LineEditor) {
+        self.line_editor = Arc::new(Mutex::new(line_editor));
+    }
+
+    pub fn get_line_editor(&self) -> Arc<Mutex<LineEditor>> {
+        self.line_editor.clone()
+    }
+
+    /// Whether `STYLE_TEXT` adds ANSI codes, disabled by default. The builder enables it
+    /// when the script writes to a terminal and `NO_COLOR` is not set.
+    pub fn set_styled_output(&mut self, styled_output: bool) {
+        self.styled_output = styled_output;
+    }
+
+    pub fn is_styled_output(&self) -> bool {
+        self.styled_output
+    }
+
+    /// Call `hook` with every file path, network address and process the script is about
+    /// to use, so the host can log or review what the script touched
+    pub fn set_audit_hook(&mut self, hook: impl Fn(&AuditEvent) + Send + Sync + 'static) {
+        self.audit_hook = Some(AuditHook(Arc::new(hook)));
+    }
+
+    /// Report the resource used by `command` to the audit hook, if there is one
+    pub fn audit(&self, kind: AuditKind, target: &str, command: &Command) {
+        if let Some(hook) = &self.audit_hook {
+            (hook.0)(&AuditEvent::new(kind, target, command));
+        }
+    }
+
+    /// Check that the sandbox allows `access` to `target` and report it to the audit hook,
+    /// denied accesses are reported too
+    pub fn access(
+        &self,
+        access: Access,
+        target: &str,
+        command: &Command,
+    ) -> Result<(), (ScriptError, Command)> {
+        self.audit(access.kind(), target, command);
+        if self.sandbox.allows(access) {
+            Ok(())
+        } else {
+            Err((
+                ScriptError::SandboxError(target.to_string()),
+                command.clone(),
+            ))
+        }
+    }
+
+    /// Limit the files, addresses, processes, environment variables and desktop that the script
+    /// may use, `Sandbox::Full` by default. If environment variables are denied, the standard
+    /// variable `env` is emptied.
+    pub fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.sandbox = sandbox;
+        if !sandbox.allows(Access::Environment) {
+            self.set_var(
+                String::from("env"),
+                Variable::from_map(Some(HashMap::new()), VarType::String, VarType::String),
+                true,
+                true,
+                &mut HashMap::new(),
+            )
+            .ignore();
+        }
+    }
+
+    pub fn get_sandbox(&self) -> Sandbox {
+        self.sandbox
+    }
+
+    /// In a dry run commands that write files, connect to the network or start processes
+    /// only report to the audit hook and succeed: written files and connections are replaced
+    /// with streams that lead nowhere. Reading files and computations work as usual.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Cached results of functions marked with `MEMO_FUNC`, shared with isolated copies
+    pub fn get_memo_cache(&self) -> &MemoCache {
+        &self.memo_cache
+    }
+
+    /// Sockets of the TCP stream variables, shared with isolated copies
+    pub fn get_network_streams(&self) -> &NetworkStreams {
+        &self.network_streams
+    }
+
+    pub fn set_thread_policy(&mut self, policy: ThreadPolicy) {
+        self.thread_policy = policy;
+    }
+
+    /// Register a thread started by the script so that `run` can apply the thread policy to it
+    /// and report its error if nobody took it with `JOIN_THREAD`
+    pub fn add_thread(&self, thread: Arc<Mutex<ScriptThread>>) {
+        let mut threads = self.threads.lock().unwrap();
+        threads.retain(|thread| {
+            let thread = thread.lock().unwrap();
+            !thread.is_finished() || thread.has_error()
+        });
+        threads.push(thread);
+    }
+
+    fn wait_threads(script: Arc<RunningScript>) -> Vec<RuntimeError> {
+        let policy = script.thread_policy.clone();
+        let deadline = match policy {
+            ThreadPolicy::JoinAll => None,
+            ThreadPolicy::Detach => Some(Instant::now()),
+            ThreadPolicy::Timeout(timeout) => Some(Instant::now() + timeout),
+        };
+
+        let mut errors = Vec::new();
+
+        loop {
+            let threads: Vec<_> = script.threads.lock().unwrap().drain(..).collect();
+            if threads.is_empty() {
+                break;
             }
-            return Ok(());
-        }
-
-        for (i, part) in parts.iter().enumerate() {
-            if i == parts.len() - 1 {
-                match &mut var {
-                    Some(v) => match v {
-                        Variable::List(_, list) => match list {
-                            Some(list) => {
-                                let index: usize =
-                                    part.parse().map_err(|_| ScriptError::ParseVarError)?;
-                                if index < list.len() {
-                                    list[index] = value;
-                                    return Ok(());
-                                } else {
-                                    return Err(ScriptError::UnknownVarError);
-                                }
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        Variable::Map(map_type, map) => match map {
-                            Some(map) => {
-                                let key_var =
-                                    Variable::parse_var(map_type.clone(), part.to_string())?;
-                                map.insert(key_var, value);
-                                return Ok(());
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        _ => return Err(ScriptError::TypeMismatchError),
-                    },
-                    None => return Err(ScriptError::UnknownVarError),
-                }
-            } else {
-                var = match var {
-                    Some(v) => match v {
-                        Variable::List(_, list) => match list {
-                            Some(list) => {
-                                let index: usize =
-                                    part.parse().map_err(|_| ScriptError::ParseVarError)?;
-                                Some(list.get_mut(index).ok_or(ScriptError::UnknownVarError)?)
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        Variable::Map(map_type, map) => match map {
-                            Some(map) => {
-                                let key_var =
-                                    Variable::parse_var(map_type.clone(), part.to_string())?;
-                                map.get_mut(&key_var)
-                            }
-                            None => return Err(ScriptError::UnknownVarError),
-                        },
-                        _ => return Err(ScriptError::TypeMismatchError),
-                    },
+
+            for thread in threads {
+                match deadline {
                     None => {
-                        if global {
-                            self.variables.get_mut(*part)
-                        } else {
-                            locals.get_mut(*part)
+                        let handle = thread.lock().unwrap().handle.take();
+                        if let Some(handle) = handle {
+                            let _ = handle.join();
+                        }
+                    }
+                    Some(deadline) => {
+                        while !thread.lock().unwrap().is_finished() && Instant::now() < deadline {
+                            thread::sleep(Duration::from_millis(10));
                         }
                     }
                 }
+
+                let mut thread = thread.lock().unwrap();
+                if let Some((error, command)) = thread.take_error() {
+                    errors.push(RuntimeError {
+                        error,
+                        command,
+                        traceback: std::mem::take(&mut thread.traceback),
+                        thread_errors: Vec::new(),
+                    });
+                }
+            }
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
             }
         }
 
-        Err(ScriptError::UnknownVarError)
+        errors
     }
 
-    pub fn get_function(&self, name: String) -> Result<Function, ScriptError> {
-        for func in &self.functions {
-            if func.name == name {
-                return Ok(func.clone());
+    /// Compile functions to bytecode before the run (enabled by default), when disabled
+    /// commands are executed by the tree-walking interpreter
+    pub fn set_bytecode(&mut self, bytecode: bool) {
+        self.bytecode = bytecode;
+    }
+
+    /// Optimize and compile functions that have no bytecode yet, functions of a `CompiledScript`
+    /// are already compiled and stay shared
+    fn compile(&mut self) {
+        let uncompiled = |func: &Function| func.native.is_none() && func.bytecode.is_none();
+        let symbols = &self.function_symbols;
+
+        if self.functions.iter().any(uncompiled) {
+            for func in Arc::make_mut(&mut self.functions).iter_mut() {
+                if uncompiled(func) {
+                    func.commands = optimize_function(func);
+                    func.bytecode = Some(Arc::new(compile_function(func, symbols)));
+                }
             }
         }
-        Err(ScriptError::FunctionUnknownError)
+        if uncompiled(&self.main_function) {
+            let main_function = Arc::make_mut(&mut self.main_function);
+            main_function.commands = optimize_main(&main_function.commands);
+            main_function.bytecode = Some(Arc::new(compile_main(main_function, symbols)));
+        }
     }
 
-    pub fn run(self) -> Result<(), (ScriptError, Command)> {
+    /// Remove bytecode of a `CompiledScript` when bytecode is disabled
+    fn decompile(&mut self) {
+        if self.functions.iter().any(|func| func.bytecode.is_some()) {
+            for func in Arc::make_mut(&mut self.functions).iter_mut() {
+                func.bytecode = None;
+            }
+        }
+        if self.main_function.bytecode.is_some() {
+            Arc::make_mut(&mut self.main_function).bytecode = None;
+        }
+    }
+
+    /// Execute compiled commands of the function, the same way as `Function::execute` does without bytecode
+    pub fn execute_bytecode(
+        script: Arc<RunningScript>,
+        func: &Function,
+        code: &[Instruction],
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+    ) -> Result<(), (ScriptError, Command)> {
+        let mut temp_vars: Vec<String> = Vec::new();
+
+        for (instruction, command) in code.iter().zip(&func.commands) {
+            if let Instruction::Return = instruction {
+                break;
+            }
+
+            command
+                .catch_panic(|| {
+                    RunningScript::execute_instruction(
+                        &script,
+                        instruction,
+                        command,
+                        global,
+                        locals,
+                        &mut temp_vars,
+                    )
+                })
+                .inspect_err(|e| CallStack::fail(command, e))?;
+
+            if let CommandType::TempVar = command.command_type {
+                continue;
+            }
+
+            for ele in temp_vars.clone() {
+                script
+                    .drop_var(ele, locals)
+                    .map_err(|f| (f, command.clone()))
+                    .ignore();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Function a compiled call resolved to, `None` if a variable with the function name
+    /// exists at runtime, then the command is executed instead
+    fn compiled_function(
+        &self,
+        func: Symbol,
+        func_var: Symbol,
+        locals: &HashMap<Symbol, Variable>,
+    ) -> Option<&Function> {
+        if locals.contains_key(&func_var) || self.variables.read().unwrap().contains_key(&func_var)
+        {
+            None
+        } else {
+            self.get_function_by_symbol(func).ok()
+        }
+    }
+
+    fn execute_instruction(
+        script: &Arc<RunningScript>,
+        instruction: &Instruction,
+        command: &Command,
+        global: bool,
+        locals: &mut HashMap<Symbol, Variable>,
+        temp_vars: &mut Vec<String>,
+    ) -> Result<(), (ScriptError, Command)> {
+        match instruction {
+            Instruction::InitVar { path, var_type } => {
+                let var =
+                    Variable::empty_var(var_type.clone()).map_err(|f| (f, command.clone()))?;
+                script
+                    .set_path(path, var, global, true, locals)
+                    .map_err(|f| (f, command.clone()))?;
+            }
+            Instruction::SetVar {
+                path,
+                value,
+                parsed,
+            } => {
+                let var_type = script
+                    .get_path(path, locals)
+                    .map_err(|f| (f, command.clone()))?
+                    .get_type();
+                let var = match parsed {
+                    Some(parsed) if parsed.0.get_type() == var_type => parsed.0.clone(),
+                    _ => Variable::parse_var(var_type, value.clone())
+                        .map_err(|f| (f, command.clone()))?,
+                };
+                script
+                    .set_path(path, var, global, false, locals)
+                    .map_err(|f| (f, command.clone()))?;
+            }
+            Instruction::SetConst { path, value } => {
+                script
+                    .set_path(path, value.0.clone(), global, false, locals)
+                    .map_err(|f| (f, command.clone()))?;
+            }
+            Instruction::Call {
+                func,
+                func_var,
+                result_var,
+                args,
+            } => match script.compiled_function(*func, *func_var, locals) {
+                Some(called) => {
+                    let mut values = Vec::new();
+                    for path in args {
+                        values.push(
+                            script
+                                .get_path(path, locals)
+                                .map_err(|f| (f, command.clone()))?,
+                        );
+                    }
+
+                    let result =
+                        called.execute(script.clone(), "null".to_string(), values, false)?;
+
+                    if let Some(result_var) = result_var {
+                        script
+                            .set_path(result_var, result, global, false, locals)
+                            .map_err(|f| (f, command.clone()))?;
+                    }
+                }
+                None => command.execute(script.clone(), global, locals, temp_vars)?,
+            },
+            Instruction::CopyVar { source, target } => {
+                let var = script
+                    .get_path(source, locals)
+                    .map_err(|f| (f, command.clone()))?;
+                script
+                    .set_path(target, var, global, false, locals)
+                    .map_err(|f| (f, command.clone()))?;
+            }
+            Instruction::MoveVar { source, target } => {
+                let var = script
+                    .get_path(source, locals)
+                    .map_err(|f| (f, command.clone()))?;
+                script
+                    .set_path(target, var, global, false, locals)
+                    .map_err(|f| (f, command.clone()))?;
+                script
+                    .drop_path(source, locals)
+                    .map_err(|f| (f, command.clone()))?;
+            }
+            Instruction::If {
+                condition,
+                func,
+                func_var,
+            } => match script.compiled_function(*func, *func_var, locals) {
+                Some(called) => {
+                    let condition = script
+                        .get_path(condition, locals)
+                        .map_err(|f| (f, command.clone()))?
+                        .as_bool()
+                        .map_err(|f| (f, command.clone()))?;
+                    if condition {
+                        called.execute_block(script.clone(), vec![])?;
+                    }
+                }
+                None => command.execute(script.clone(), global, locals, temp_vars)?,
+            },
+            Instruction::TempVar { path, name, value } => {
+                script
+                    .set_path(path, value.0.clone(), global, true, locals)
+                    .map_err(|f| (f, command.clone()))?;
+                temp_vars.push(name.clone());
+            }
+            Instruction::Return => {}
+            Instruction::Command => command.execute(script.clone(), global, locals, temp_vars)?,
+        }
+
+        Ok(())
+    }
+
+    /// Set the type of `result` in the main function, so the top level of the script can
+    /// return a value with `SET_VAR result value`
+    pub fn set_result_type(&mut self, result_type: VarType) {
+        Arc::make_mut(&mut self.main_function).result_type = result_type;
+    }
+
+    /// Run the main function and apply the thread policy. If the main function succeeded,
+    /// the first error of a thread that nobody joined is returned. Errors of other threads
+    /// are dropped, `run_with_traceback` returns them too.
+    pub fn run_with_result(self) -> Result<ScriptOutcome, (ScriptError, Command)> {
+        self.run_with_traceback()
+            .map_err(|error| (error.error, error.command))
+    }
+
+    /// Same as `run_with_result`, the error also has the calls that led to it and the
+    /// errors of the other threads in `thread_errors`
+    // the error is returned once per run, its size doesn't matter
+    #[allow(clippy::result_large_err)]
+    pub fn run_with_traceback(mut self) -> Result<ScriptOutcome, RuntimeError> {
+        if self.bytecode {
+            self.compile();
+        } else {
+            self.decompile();
+        }
+
         let main_function = self.main_function.clone();
+        let script = Arc::new(self);
 
-        main_function.execute(
-            Arc::new(Mutex::new(self)),
-            "null".to_string(),
-            Vec::new(),
-            true,
-        )
+        // frames left by an earlier run in this thread
+        CallStack::take();
+        let result = main_function
+            .execute_raw(script.clone(), "null".to_string(), Vec::new(), true)
+            .map_err(|(error, command)| RuntimeError {
+                error,
+                command,
+                traceback: CallStack::take(),
+                thread_errors: Vec::new(),
+            });
+
+        let mut errors = RunningScript::wait_threads(script.clone()).into_iter();
+        let result = result
+            .and_then(|result| errors.next().map_or(Ok(result), Err))
+            .map_err(|mut error| {
+                error.thread_errors.extend(errors);
+                error
+            });
+
+        let globals = script.get_globals();
+
+        // commands of the main function run in global scope, so `SET_VAR result` lands in globals
+        let mut result = result?;
+        if main_function.result_type != VarType::Null {
+            if let Some(global_result) = globals.get("result") {
+                result = global_result.clone();
+            }
+        }
+
+        Ok(ScriptOutcome { globals, result })
+    }
+
+    pub fn run(self) -> Result<(), (ScriptError, Command)> {
+        self.run_with_result().map(|_| ())
     }
 }
@@ -0,0 +1,256 @@
+use super::super::script::{
+    AuditEvent, AuditHook, Clock, CompiledScript, LineEditor, NativeFunction, RunningScript,
+    Sandbox, Script, ScriptError, ThreadPolicy,
+};
+use super::super::var::{VarType, Variable};
+
+use std::collections::HashMap;
+use std::env;
+use std::io::{stdin, stdout, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Name and value of `name=value` or `name:type=value`
+fn parse_assignment(assignment: &str) -> Result<(String, Variable), ScriptError> {
+    let (name, value) = assignment
+        .split_once('=')
+        .ok_or(ScriptError::ParseVarError)?;
+    let (name, var_type) = match name.split_once(':') {
+        Some((name, type_name)) => (name, VarType::from_name(type_name)?),
+        None => (name, VarType::String),
+    };
+    if name.is_empty() {
+        return Err(ScriptError::ParseVarError);
+    }
+    Ok((
+        name.to_string(),
+        Variable::parse_var(var_type, value.to_string())?,
+    ))
+}
+
+/// Builder for `RunningScript`, streams that are not set default to the process stdio
+pub struct RunningScriptBuilder {
+    script: RunningScript,
+    args: Vec<String>,
+    script_path: Option<PathBuf>,
+    stdout: Option<Box<dyn Write + Send>>,
+    stdin: Option<Box<dyn Read + Send>>,
+    stderr: Option<Box<dyn Write + Send>>,
+    globals: Vec<(String, Variable)>,
+    globals_json: Vec<String>,
+    assignments: Vec<String>,
+    native_fns: Vec<(String, Vec<VarType>, VarType, NativeFunction)>,
+    thread_policy: ThreadPolicy,
+    clock: Option<Arc<dyn Clock>>,
+    bytecode: bool,
+    audit_hook: Option<AuditHook>,
+    dry_run: bool,
+    sandbox: Sandbox,
+    random_seed: Option<u64>,
+}
+
+impl RunningScriptBuilder {
+    pub fn new(script: Script) -> RunningScriptBuilder {
+        RunningScriptBuilder::from_running_script(RunningScript::new(script))
+    }
+
+    /// Builder for a new instance of the compiled script, see `CompiledScript`
+    pub fn from_compiled(script: &CompiledScript) -> RunningScriptBuilder {
+        RunningScriptBuilder::from_running_script(script.instantiate())
+    }
+
+    fn from_running_script(script: RunningScript) -> RunningScriptBuilder {
+        RunningScriptBuilder {
+            script,
+            args: Vec::new(),
+            script_path: None,
+            stdout: None,
+            stdin: None,
+            stderr: None,
+            globals: Vec::new(),
+            globals_json: Vec::new(),
+            assignments: Vec::new(),
+            native_fns: Vec::new(),
+            thread_policy: ThreadPolicy::default(),
+            clock: None,
+            bytecode: true,
+            audit_hook: None,
+            dry_run: false,
+            sandbox: Sandbox::Full,
+            random_seed: None,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// File the script was loaded from, see `RunningScript::set_script_path`
+    pub fn script_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.script_path = Some(path.into());
+        self
+    }
+
+    pub fn stdout(mut self, stdout: impl Write + Send + 'static) -> Self {
+        self.stdout = Some(Box::new(stdout));
+        self
+    }
+
+    pub fn stdin(mut self, stdin: impl Read + Send + 'static) -> Self {
+        self.stdin = Some(Box::new(stdin));
+        self
+    }
+
+    pub fn stderr(mut self, stderr: impl Write + Send + 'static) -> Self {
+        self.stderr = Some(Box::new(stderr));
+        self
+    }
+
+    pub fn global_var(mut self, name: impl Into<String>, value: impl Into<Variable>) -> Self {
+        self.globals.push((name.into(), value.into()));
+        self
+    }
+
+    /// Global variables from a json object, the types are inferred from the values like
+    /// in `JSON_PARSE`. The json is parsed in `build`, it gives `ParseVarError` if it is
+    /// not an object.
+    pub fn globals_json(mut self, json: impl Into<String>) -> Self {
+        self.globals_json.push(json.into());
+        self
+    }
+
+    /// Global variable from `name=value` or `name:type=value`, the value is parsed like in
+    /// `SET_VAR`, the type is `string` by default. Errors are returned by `build`.
+    /// Variables from `globals_json` are set first, then assignments, then `global_var`.
+    pub fn global_assignment(mut self, assignment: impl Into<String>) -> Self {
+        self.assignments.push(assignment.into());
+        self
+    }
+
+    pub fn native_fn(
+        mut self,
+        name: impl Into<String>,
+        arg_types: Vec<VarType>,
+        result_type: VarType,
+        func: impl Fn(Vec<Variable>) -> Result<Variable, ScriptError> + Send + Sync + 'static,
+    ) -> Self {
+        self.native_fns.push((
+            name.into(),
+            arg_types,
+            result_type,
+            NativeFunction(Arc::new(func)),
+        ));
+        self
+    }
+
+    pub fn thread_policy(mut self, policy: ThreadPolicy) -> Self {
+        self.thread_policy = policy;
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// See `RunningScript::set_bytecode`
+    pub fn bytecode(mut self, bytecode: bool) -> Self {
+        self.bytecode = bytecode;
+        self
+    }
+
+    /// See `RunningScript::set_audit_hook`
+    pub fn audit_hook(mut self, hook: impl Fn(&AuditEvent) + Send + Sync + 'static) -> Self {
+        self.audit_hook = Some(AuditHook(Arc::new(hook)));
+        self
+    }
+
+    /// See `RunningScript::set_dry_run`
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// See `RunningScript::set_sandbox`
+    pub fn sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// See `RunningScript::set_random_seed`
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<RunningScript, ScriptError> {
+        let mut running_script = self.script;
+
+        // line editing only makes sense when the script talks to the terminal directly
+        if self.stdin.is_none() && self.stdout.is_none() && stdin().is_terminal() {
+            running_script.set_line_editor(LineEditor::terminal());
+        }
+        if self.stdout.is_none() && stdout().is_terminal() && env::var_os("NO_COLOR").is_none() {
+            running_script.set_styled_output(true);
+        }
+
+        running_script.set_standard_vars(
+            self.args,
+            self.stdout.unwrap_or_else(|| Box::new(stdout())),
+            self.stdin.unwrap_or_else(|| Box::new(stdin())),
+        )?;
+        if let Some(stderr) = self.stderr {
+            running_script.set_stderr(stderr)?;
+        }
+        running_script.set_script_path(self.script_path.as_deref())?;
+
+        for json in self.globals_json {
+            let value: serde_json::Value =
+                serde_json::from_str(&json).map_err(|_| ScriptError::ParseVarError)?;
+            let serde_json::Value::Object(fields) = value else {
+                return Err(ScriptError::ParseVarError);
+            };
+            for (name, value) in fields {
+                let value = Variable::from_json(&value.to_string(), None)?;
+                running_script.set_var(name, value, true, true, &mut HashMap::new())?;
+            }
+        }
+
+        for assignment in self.assignments {
+            let (name, value) = parse_assignment(&assignment)?;
+            running_script.set_var(name, value, true, true, &mut HashMap::new())?;
+        }
+
+        for (name, value) in self.globals {
+            running_script.set_var(name, value, true, true, &mut HashMap::new())?;
+        }
+
+        for (name, arg_types, result_type, native) in self.native_fns {
+            running_script.register_native_function(name, arg_types, result_type, move |args| {
+                (native.0)(args)
+            });
+        }
+
+        running_script.set_thread_policy(self.thread_policy);
+        running_script.set_bytecode(self.bytecode);
+        running_script.set_dry_run(self.dry_run);
+        running_script.set_sandbox(self.sandbox);
+        if let Some(seed) = self.random_seed {
+            running_script.set_random_seed(seed);
+        }
+        if let Some(clock) = self.clock {
+            running_script.set_clock(clock);
+        }
+        if let Some(hook) = self.audit_hook {
+            running_script.set_audit_hook(move |event| (hook.0)(event));
+        }
+
+        Ok(running_script)
+    }
+}
@@ -0,0 +1,79 @@
+use super::AuditKind;
+
+/// How a command uses a file, an address or a process
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Access {
+    /// Reading a file or a folder, or checking that it exists
+    FileRead,
+    /// Creating, writing, moving or deleting a file or a folder, opening a device
+    FileWrite,
+    /// Connecting to a server or resolving its address
+    NetworkClient,
+    /// Listening for connections
+    NetworkServer,
+    /// Starting a process
+    Process,
+    /// Reading or changing an environment variable
+    Environment,
+    /// Using the clipboard or showing a notification
+    Desktop,
+}
+
+impl Access {
+    /// Kind of the audit event of the access
+    pub fn kind(&self) -> AuditKind {
+        match self {
+            Access::FileRead | Access::FileWrite => AuditKind::File,
+            Access::NetworkClient | Access::NetworkServer => AuditKind::Network,
+            Access::Process => AuditKind::Process,
+            Access::Environment => AuditKind::Environment,
+            Access::Desktop => AuditKind::Desktop,
+        }
+    }
+}
+
+/// Ready-made set of what a script may touch, see `RunningScript::set_sandbox`.
+/// A command that needs something else fails with `SandboxError` before the resource is used.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Sandbox {
+    /// No files, no network, no processes, no environment variables and no clipboard or
+    /// notifications, only computations and the standard streams
+    Pure,
+    /// Files and folders can only be read
+    ReadOnlyFs,
+    /// Only connections to servers, no files and no listeners
+    NetworkClientOnly,
+    /// Everything is allowed
+    #[default]
+    Full,
+}
+
+impl Sandbox {
+    pub fn from_name(name: &str) -> Option<Sandbox> {
+        match name {
+            "pure" => Some(Sandbox::Pure),
+            "read-only-fs" => Some(Sandbox::ReadOnlyFs),
+            "network-client-only" => Some(Sandbox::NetworkClientOnly),
+            "full" => Some(Sandbox::Full),
+            _ => None,
+        }
+    }
+
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Sandbox::Pure => "pure",
+            Sandbox::ReadOnlyFs => "read-only-fs",
+            Sandbox::NetworkClientOnly => "network-client-only",
+            Sandbox::Full => "full",
+        }
+    }
+
+    pub fn allows(&self, access: Access) -> bool {
+        match self {
+            Sandbox::Pure => false,
+            Sandbox::ReadOnlyFs => access == Access::FileRead,
+            Sandbox::NetworkClientOnly => access == Access::NetworkClient,
+            Sandbox::Full => true,
+        }
+    }
+}
@@ -1,121 +1,868 @@
 use super::super::command::{Command, CommandType};
-use super::super::script::{Function, ScriptError};
+use super::super::script::{
+    optimize_function, optimize_main, Function, Pragmas, ScriptError, ScriptMeta,
+};
 use super::super::var::VarType;
 
-use std::collections::HashMap;
+use bytebuffer::ByteBuffer;
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::thread;
+
+/// Header of a compiled script, the last byte is the format version
+const COMPILED_MAGIC: &[u8] = b"SUSTC\x04";
+
+/// Line without the comment and the indentation
+fn prepare_line(line: &str) -> String {
+    let line = match line.split_once("#") {
+        Some(s) => s.0,
+        None => line,
+    };
+    line.trim_end_matches(" ")
+        .trim_end_matches("\t")
+        .trim_start_matches(" ")
+        .trim_start_matches("\t")
+        .to_string()
+}
 
 fn prepare_script(text: String) -> Vec<String> {
-    text.lines()
-        .map(|s| match s.split_once("#") {
-            Some(s) => s.0,
-            None => s,
-        })
-        .map(|s| {
-            s.trim_end_matches(" ")
-                .trim_end_matches("\t")
-                .trim_start_matches(" ")
-                .trim_start_matches("\t")
-                .to_string()
+    text.lines().map(prepare_line).collect()
+}
+
+/// Split a line by spaces, but keep brackets of a type like `map[string, int]` in one part
+fn split_line(line: &str) -> Vec<String> {
+    let mut params: Vec<String> = Vec::new();
+    let mut parts = line.split(' ');
+
+    while let Some(part) = parts.next() {
+        let mut param = part.to_string();
+        let mut depth = bracket_depth(&param);
+
+        if depth > 0 {
+            let mut joined = param.clone();
+            let mut rest = parts.clone();
+            while depth > 0 {
+                let Some(next) = rest.next() else {
+                    break;
+                };
+                joined.push(' ');
+                joined.push_str(next);
+                depth += bracket_depth(next);
+            }
+            // join only if the brackets are closed on this line
+            if depth == 0 {
+                param = joined;
+                parts = rest;
+            }
+        }
+
+        params.push(param);
+    }
+
+    params
+}
+
+fn bracket_depth(text: &str) -> isize {
+    text.chars()
+        .map(|c| match c {
+            '[' => 1,
+            ']' => -1,
+            _ => 0,
         })
+        .sum()
+}
+
+/// Scripts with fewer lines are parsed in one thread, starting threads would take longer
+const PARALLEL_PARSE_LINES: usize = 20_000;
+
+/// Parse prepared lines, large scripts are split into chunks that are parsed in parallel
+fn parse_commands(lines: Vec<String>, diagnostics: &mut Vec<(ScriptError, usize)>) -> Vec<Command> {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    if lines.len() < PARALLEL_PARSE_LINES || threads < 2 {
+        return parse_lines(&lines, 0, diagnostics);
+    }
+
+    let chunks = thread::scope(|scope| {
+        let handles: Vec<_> = chunk_bounds(&lines, threads)
+            .into_iter()
+            .map(|(start, end)| {
+                let chunk = &lines[start..end];
+                scope.spawn(move || {
+                    let mut diagnostics = Vec::new();
+                    let commands = parse_lines(chunk, start, &mut diagnostics);
+                    (commands, diagnostics)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let mut commands = Vec::with_capacity(lines.len());
+    for (chunk_commands, chunk_diagnostics) in chunks {
+        commands.extend(chunk_commands);
+        diagnostics.extend(chunk_diagnostics);
+    }
+    commands
+}
+
+/// Bounds of `count` chunks of about the same size, a chunk starts at the next `FUNC` line
+/// after the split point if there is one before the next split point
+fn chunk_bounds(lines: &[String], count: usize) -> Vec<(usize, usize)> {
+    let size = lines.len().div_ceil(count);
+    let mut starts = vec![0];
+    for split in (size..lines.len()).step_by(size) {
+        let start = (split..(split + size).min(lines.len()))
+            .find(|&i| line_command(&lines[i]) == "FUNC")
+            .unwrap_or(split);
+        if start > *starts.last().unwrap() {
+            starts.push(start);
+        }
+    }
+
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain([&lines.len()]))
+        .map(|(&start, &end)| (start, end))
         .collect()
 }
 
-fn parse_commands(lines: Vec<String>) -> Result<Vec<Command>, (ScriptError, usize)> {
+/// Parse lines that start after `skipped` lines of the script, so line numbers stay the same
+fn parse_lines(
+    lines: &[String],
+    skipped: usize,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) -> Vec<Command> {
     let mut commands = Vec::new();
-    let mut line_num = 0;
+    for (index, line) in lines.iter().enumerate() {
+        parse_line(line, skipped + index + 1, &mut commands, diagnostics);
+    }
+    commands
+}
 
-    for line in lines {
-        line_num += 1;
+/// Parse one prepared line with the number `line_num`, empty lines give no command
+fn parse_line(
+    line: &str,
+    line_num: usize,
+    commands: &mut Vec<Command>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) {
+    if line.trim().is_empty() {
+        return;
+    }
 
-        if line.trim().is_empty() {
-            continue;
+    let params = split_line(line);
+
+    let command_type = match CommandType::from_name(&params[0]) {
+        Ok(command_type) => command_type,
+        Err(e) => {
+            diagnostics.push((e, line_num));
+            return;
         }
+    };
+
+    let args = if params.is_empty() {
+        Vec::new()
+    } else {
+        params[1..].to_vec()
+    };
 
-        let params: Vec<String> = line.split(" ").map(|v| v.to_string()).collect();
+    commands.push(Command::new(command_type, line_num, args))
+}
 
-        let command_type = CommandType::from_name(&params[0]).map_err(|f| (f, line_num))?;
+/// Remove `META` and `PRAGMA` lines from the header of the script, they can't go after any other command
+fn cut_header(
+    commands: &mut Vec<Command>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) -> (ScriptMeta, Pragmas) {
+    let mut meta = ScriptMeta::default();
+    let mut pragmas = Pragmas::default();
+    let mut in_header = true;
 
-        let args = if params.is_empty() {
-            Vec::new()
+    commands.retain(|command| {
+        let result = match (command.command_type, in_header, command.args.first()) {
+            (CommandType::Meta, false, _) => Err(ScriptError::MetaError),
+            (CommandType::Pragma, false, _) => Err(ScriptError::PragmaError),
+            (CommandType::Meta | CommandType::Pragma, true, None) => {
+                Err(ScriptError::CommandArgsInvalidError)
+            }
+            (CommandType::Meta, true, Some(key)) => {
+                meta.set(key.clone(), command.args[1..].join(" "))
+            }
+            (CommandType::Pragma, true, Some(key)) => {
+                pragmas.set(key, &command.args[1..].join(" "))
+            }
+            _ => {
+                in_header = false;
+                return true;
+            }
+        };
+        if let Err(e) = result {
+            diagnostics.push((e, command.line));
+        }
+        false
+    });
+
+    (meta, pragmas)
+}
+
+/// Replace type aliases inside of a type name, including nested ones like `list[headers]`
+fn expand_type_aliases(type_name: &str, aliases: &HashMap<String, String>) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+    for c in type_name.chars().chain(std::iter::once(' ')) {
+        if c == '[' || c == ']' || c == ',' || c == ' ' {
+            match aliases.get(&word) {
+                Some(alias) => result.push_str(alias),
+                None => result.push_str(&word),
+            }
+            word.clear();
+            result.push(c);
         } else {
-            params[1..].to_vec()
+            word.push(c);
+        }
+    }
+    result.pop();
+    result
+}
+
+/// Remove `TYPE_ALIAS` declarations and expand aliases in the type parameters of commands after them
+fn resolve_type_aliases(commands: &mut Vec<Command>, diagnostics: &mut Vec<(ScriptError, usize)>) {
+    let mut aliases: HashMap<String, String> = HashMap::new();
+
+    commands.retain_mut(|command| {
+        let type_indexes: Vec<usize> = match command.command_type {
+            CommandType::InitVar | CommandType::TempVar => vec![0],
+            CommandType::Func => std::iter::once(0)
+                .chain((3..command.args.len()).step_by(2))
+                .collect(),
+            CommandType::TypeAlias => {
+                let (Some(name), Some(_)) = (command.args.first(), command.args.get(1)) else {
+                    diagnostics.push((ScriptError::CommandArgsInvalidError, command.line));
+                    return false;
+                };
+                let valid_name = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !matches!(name.as_str(), "list" | "optional" | "map")
+                    && VarType::from_name(name).is_err();
+                if !valid_name || aliases.contains_key(name) {
+                    diagnostics.push((ScriptError::TypeAliasError, command.line));
+                    return false;
+                }
+                let type_name = expand_type_aliases(&command.args[1..].join(" "), &aliases);
+                match VarType::from_name(&type_name) {
+                    Ok(_) => {
+                        aliases.insert(name.clone(), type_name);
+                    }
+                    Err(e) => diagnostics.push((e, command.line)),
+                }
+                return false;
+            }
+            _ => return true,
         };
 
-        commands.push(Command::new(command_type, line_num, args))
+        for index in type_indexes {
+            if let Some(arg) = command.args.get_mut(index) {
+                *arg = expand_type_aliases(arg, &aliases);
+            }
+        }
+        true
+    });
+}
+
+fn parse_func_header(command: &Command) -> Result<Function, ScriptError> {
+    let (result_type, name) = match (command.args.first(), command.args.get(1)) {
+        (Some(result_type), Some(name)) => (result_type, name),
+        _ => return Err(ScriptError::CommandArgsInvalidError),
+    };
+    let result_type = VarType::from_name(result_type)?;
+
+    let mut parameters = Vec::new();
+
+    let mut param_key: Option<String> = None;
+    for i in &command.args[2..] {
+        match &param_key {
+            Some(key) => {
+                parameters.push((key.to_string(), VarType::from_name(i)?));
+                param_key = None;
+            }
+            None => {
+                param_key = Some(i.to_string());
+            }
+        }
     }
 
-    Ok(commands)
+    Ok(Function::new(
+        name.clone(),
+        result_type,
+        parameters,
+        Vec::new(),
+    ))
 }
 
-fn cut_funcs(commands: &mut Vec<Command>) -> Result<Vec<Function>, (ScriptError, usize)> {
+fn cut_funcs(
+    commands: &mut Vec<Command>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) -> Vec<Function> {
     let mut functions: Vec<Function> = Vec::new();
 
-    let mut now_func: Option<Function> = None;
-
-    let mut index = 0;
-    for command in commands.clone() {
-        index += 1;
+    // `Some(None)` - inside of a function with an invalid header, its body is skipped
+    let mut now_func: Option<Option<Function>> = None;
 
-        match now_func.clone() {
+    let mut main_commands = Vec::new();
+    for command in commands.drain(..) {
+        match now_func.as_mut() {
             Some(func) => {
-                index -= 1;
-                commands.remove(index);
-
                 if let CommandType::FuncEnd = command.command_type {
-                    functions.push(func.clone());
-                    now_func = None;
-                } else {
-                    now_func.as_mut().unwrap().commands.push(command);
+                    if let Some(func) = now_func.take().flatten() {
+                        functions.push(func);
+                    }
+                } else if let Some(func) = func {
+                    func.commands.push(command);
                 }
             }
             None => {
                 if let CommandType::Func = command.command_type {
-                    index -= 1;
-                    commands.remove(index);
-
-                    let name = command.args[1].clone();
-                    let result_type =
-                        VarType::from_name(&command.args[0]).map_err(|f| (f, command.line))?;
-                    let mut parameters = HashMap::new();
-
-                    let mut param_key: Option<String> = None;
-                    for i in &command.args[2..] {
-                        match &param_key {
-                            Some(key) => {
-                                parameters.insert(
-                                    key.to_string(),
-                                    VarType::from_name(i).map_err(|f| (f, command.line))?,
-                                );
-                                param_key = None;
-                            }
-                            None => {
-                                param_key = Some(i.to_string());
-                            }
+                    now_func = Some(match parse_func_header(&command) {
+                        Ok(func) => Some(func),
+                        Err(e) => {
+                            diagnostics.push((e, command.line));
+                            None
                         }
-                    }
+                    });
+                } else {
+                    main_commands.push(command);
+                }
+            }
+        }
+    }
+    *commands = main_commands;
 
-                    now_func = Some(Function::new(name, result_type, parameters, Vec::new()));
+    functions
+}
+
+fn check_stream_commands(
+    commands: &[Command],
+    vars: &mut HashMap<String, VarType>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) {
+    for command in commands {
+        let (stream_var, expected_type) = match command.command_type {
+            CommandType::InitVar => {
+                if let (Some(type_var), Some(name_var)) =
+                    (command.args.first(), command.args.get(1))
+                {
+                    if let Ok(var_type) = VarType::from_name(type_var) {
+                        vars.insert(name_var.clone(), var_type);
+                    }
+                }
+                continue;
+            }
+            CommandType::OpenFileIn => {
+                if let Some(stream_var) = command.args.get(1) {
+                    vars.insert(stream_var.clone(), VarType::InStream);
+                }
+                continue;
+            }
+            CommandType::OpenFileOut => {
+                if let Some(stream_var) = command.args.get(1) {
+                    vars.insert(stream_var.clone(), VarType::OutStream);
+                }
+                continue;
+            }
+            CommandType::OpenFile => {
+                if let Some(stream_var) = command.args.get(1) {
+                    vars.insert(stream_var.clone(), VarType::FileStream);
+                }
+                continue;
+            }
+            CommandType::OpenTcpConnection => {
+                if let Some(in_stream) = command.args.get(2) {
+                    vars.insert(in_stream.clone(), VarType::InStream);
+                }
+                if let Some(out_stream) = command.args.get(3) {
+                    vars.insert(out_stream.clone(), VarType::OutStream);
+                }
+                continue;
+            }
+            CommandType::OpenBuffer => {
+                if let Some(in_stream) = command.args.first() {
+                    vars.insert(in_stream.clone(), VarType::InStream);
+                }
+                if let Some(out_stream) = command.args.get(1) {
+                    vars.insert(out_stream.clone(), VarType::OutStream);
+                }
+                continue;
+            }
+            #[cfg(feature = "serial")]
+            CommandType::OpenSerial => {
+                if let Some(in_stream) = command.args.get(2) {
+                    vars.insert(in_stream.clone(), VarType::InStream);
+                }
+                if let Some(out_stream) = command.args.get(3) {
+                    vars.insert(out_stream.clone(), VarType::OutStream);
+                }
+                continue;
+            }
+            #[cfg(feature = "gzip")]
+            CommandType::GzipWrapOut => {
+                check_stream_var(
+                    command.args.first(),
+                    VarType::OutStream,
+                    command,
+                    vars,
+                    diagnostics,
+                );
+                if let Some(gzip_stream) = command.args.get(1) {
+                    vars.insert(gzip_stream.clone(), VarType::OutStream);
+                }
+                continue;
+            }
+            #[cfg(feature = "gzip")]
+            CommandType::GunzipWrapIn => {
+                check_stream_var(
+                    command.args.first(),
+                    VarType::InStream,
+                    command,
+                    vars,
+                    diagnostics,
+                );
+                if let Some(plain_stream) = command.args.get(1) {
+                    vars.insert(plain_stream.clone(), VarType::InStream);
+                }
+                continue;
+            }
+            CommandType::Spawn => {
+                if let Some(stdout_stream) = command.args.get(3) {
+                    vars.insert(stdout_stream.clone(), VarType::InStream);
+                }
+                if let Some(stdin_stream) = command.args.get(4) {
+                    vars.insert(stdin_stream.clone(), VarType::OutStream);
                 }
+                continue;
             }
+            CommandType::Read
+            | CommandType::ReadAll
+            | CommandType::ReadLine
+            | CommandType::ReadChar => (command.args.get(1), VarType::InStream),
+            CommandType::ReadLength | CommandType::ReadSome => {
+                (command.args.get(2), VarType::InStream)
+            }
+            CommandType::Pipe => {
+                check_stream_var(
+                    command.args.first(),
+                    VarType::InStream,
+                    command,
+                    vars,
+                    diagnostics,
+                );
+                (command.args.get(1), VarType::OutStream)
+            }
+            CommandType::DumpVars | CommandType::Flush => {
+                (command.args.first(), VarType::OutStream)
+            }
+            CommandType::Write | CommandType::WriteLine | CommandType::PrintPretty => {
+                (command.args.get(1), VarType::OutStream)
+            }
+            _ => continue,
+        };
+
+        check_stream_var(stream_var, expected_type, command, vars, diagnostics);
+    }
+}
+
+fn check_stream_var(
+    stream_var: Option<&String>,
+    expected_type: VarType,
+    command: &Command,
+    vars: &HashMap<String, VarType>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) {
+    if let Some(var_type) = stream_var.and_then(|name| vars.get(name)) {
+        if (*var_type == VarType::InStream || *var_type == VarType::OutStream)
+            && *var_type != expected_type
+        {
+            diagnostics.push((ScriptError::StreamDirectionError, command.line));
+        }
+    }
+}
+
+fn check_streams(
+    commands: &[Command],
+    functions: &[Function],
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) {
+    let mut globals: HashMap<String, VarType> = HashMap::new();
+    globals.insert("cout".to_string(), VarType::OutStream);
+    globals.insert("cin".to_string(), VarType::InStream);
+    globals.insert("cerr".to_string(), VarType::OutStream);
+
+    check_stream_commands(commands, &mut globals, diagnostics);
+
+    for func in functions {
+        let mut vars = globals.clone();
+        for (name, var_type) in &func.parameters {
+            vars.insert(name.clone(), var_type.clone());
+        }
+        check_stream_commands(&func.commands, &mut vars, diagnostics);
+    }
+}
+
+fn write_commands(buffer: &mut ByteBuffer, commands: &[Command]) {
+    buffer.write_u32(commands.len() as u32);
+    for command in commands {
+        buffer.write_string(command.command_type.to_name());
+        buffer.write_u32(command.line as u32);
+        buffer.write_string(command.file.as_deref().unwrap_or_default());
+        buffer.write_u32(command.args.len() as u32);
+        for arg in &command.args {
+            buffer.write_string(arg);
+        }
+    }
+}
+
+fn read_commands(buffer: &mut ByteBuffer) -> Result<Vec<Command>, ScriptError> {
+    let error = |_| ScriptError::CompiledScriptError;
+
+    let count = buffer.read_u32().map_err(error)?;
+    let mut commands = Vec::new();
+    // commands of one file go one after another, they share the name
+    let mut last_file: Option<Arc<str>> = None;
+    for _ in 0..count {
+        let command_type = CommandType::from_name(&buffer.read_string().map_err(error)?)?;
+        let line = buffer.read_u32().map_err(error)? as usize;
+        let file = buffer.read_string().map_err(error)?;
+        let file = match last_file {
+            _ if file.is_empty() => None,
+            Some(last) if *last == *file => Some(last),
+            _ => Some(Arc::from(file)),
+        };
+        last_file = file.clone();
+        let args_count = buffer.read_u32().map_err(error)?;
+        let mut args = Vec::new();
+        for _ in 0..args_count {
+            args.push(buffer.read_string().map_err(error)?);
         }
+        let mut command = Command::new(command_type, line, args);
+        command.file = file;
+        commands.push(command);
     }
 
-    Ok(functions)
+    Ok(commands)
+}
+
+/// Change of the script text for `Script::parse_incremental`: lines from `start_line` to `end_line`
+/// (counting from zero, `end_line` not included) are replaced with `text`
+#[derive(PartialEq, Clone, Debug)]
+pub struct TextEdit {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+}
+
+/// First word of a prepared line, that is the command name
+fn line_command(line: &str) -> &str {
+    line.split(' ').next().unwrap_or_default()
+}
+
+/// `FUNC` ... `FUNC_END` blocks of prepared lines as `(func_line, func_end_line)`, the same way `cut_funcs` finds them
+fn func_blocks(lines: &[String]) -> Vec<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+    for (i, line) in lines.iter().enumerate() {
+        match (start, line_command(line)) {
+            (None, "FUNC") => start = Some(i),
+            (Some(func_line), "FUNC_END") => {
+                blocks.push((func_line, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Run every parse step except for stream checks, the lines are already prepared
+fn parse_prepared(
+    lines: Vec<String>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) -> (Vec<Command>, Vec<Function>, ScriptMeta, Pragmas) {
+    let commands = parse_commands(lines, diagnostics);
+    parse_parsed_commands(commands, diagnostics)
+}
+
+/// Parse steps after the lines became commands, except for stream checks
+fn parse_parsed_commands(
+    mut commands: Vec<Command>,
+    diagnostics: &mut Vec<(ScriptError, usize)>,
+) -> (Vec<Command>, Vec<Function>, ScriptMeta, Pragmas) {
+    let (meta, pragmas) = cut_header(&mut commands, diagnostics);
+    resolve_type_aliases(&mut commands, diagnostics);
+    let functions = cut_funcs(&mut commands, diagnostics);
+    (commands, functions, meta, pragmas)
 }
 
 pub struct Script {
     pub commands: Vec<Command>,
     pub functions: Vec<Function>,
+    pub meta: ScriptMeta,
+    pub pragmas: Pragmas,
+    /// Lines of the text the script was parsed from, empty for compiled scripts
+    pub source: Vec<String>,
 }
 
 impl Script {
     pub fn parse(text: String) -> Result<Script, (ScriptError, usize)> {
-        let lines = prepare_script(text);
-        let mut commands = parse_commands(lines)?;
-        let functions = cut_funcs(&mut commands)?;
+        let (script, diagnostics) = Script::parse_lenient(text);
+        match diagnostics.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(script),
+        }
+    }
+
+    /// Encode parsed commands and functions, the result can be loaded with `Script::from_bytes`
+    /// without parsing the source again
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = ByteBuffer::new();
+        buffer.write_bytes(COMPILED_MAGIC);
+
+        let meta = self.meta.entries();
+        buffer.write_u32(meta.len() as u32);
+        for (key, value) in meta {
+            buffer.write_string(&key);
+            buffer.write_string(&value);
+        }
+        buffer.write_u32(self.pragmas.lang as u32);
+
+        write_commands(&mut buffer, &self.commands);
+
+        buffer.write_u32(self.functions.len() as u32);
+        for func in &self.functions {
+            buffer.write_string(&func.name);
+            buffer.write_string(&func.result_type.to_name());
+            buffer.write_u32(func.parameters.len() as u32);
+            for (name, var_type) in &func.parameters {
+                buffer.write_string(name);
+                buffer.write_string(&var_type.to_name());
+            }
+            write_commands(&mut buffer, &func.commands);
+        }
+
+        buffer.into_vec()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Script, ScriptError> {
+        let error = |_| ScriptError::CompiledScriptError;
+
+        let mut buffer = ByteBuffer::from_bytes(bytes);
+        if buffer.read_bytes(COMPILED_MAGIC.len()).map_err(error)? != COMPILED_MAGIC {
+            return Err(ScriptError::CompiledScriptError);
+        }
+
+        let mut meta = ScriptMeta::default();
+        let count = buffer.read_u32().map_err(error)?;
+        for _ in 0..count {
+            let key = buffer.read_string().map_err(error)?;
+            meta.set(key, buffer.read_string().map_err(error)?)?;
+        }
+        let mut pragmas = Pragmas::default();
+        pragmas.set("lang", &buffer.read_u32().map_err(error)?.to_string())?;
+
+        let commands = read_commands(&mut buffer)?;
+
+        let count = buffer.read_u32().map_err(error)?;
+        let mut functions = Vec::new();
+        for _ in 0..count {
+            let name = buffer.read_string().map_err(error)?;
+            let result_type = VarType::from_name(&buffer.read_string().map_err(error)?)?;
+            let params_count = buffer.read_u32().map_err(error)?;
+            let mut parameters = Vec::new();
+            for _ in 0..params_count {
+                let param_name = buffer.read_string().map_err(error)?;
+                let param_type = VarType::from_name(&buffer.read_string().map_err(error)?)?;
+                parameters.push((param_name, param_type));
+            }
+            let func_commands = read_commands(&mut buffer)?;
+            functions.push(Function::new(name, result_type, parameters, func_commands));
+        }
+
         Ok(Script {
             commands,
             functions,
+            meta,
+            pragmas,
+            source: Vec::new(),
         })
     }
+
+    /// Parse as much of the script as possible and never panic: lines with unknown commands
+    /// and functions with invalid headers are skipped, every problem is returned with its line number
+    pub fn parse_lenient(text: String) -> (Script, Vec<(ScriptError, usize)>) {
+        let mut diagnostics = Vec::new();
+
+        let source = text.lines().map(str::to_string).collect();
+        let (commands, functions, meta, pragmas) =
+            parse_prepared(prepare_script(text), &mut diagnostics);
+        check_streams(&commands, &functions, &mut diagnostics);
+
+        (
+            Script {
+                commands,
+                functions,
+                meta,
+                pragmas,
+                source,
+            },
+            diagnostics,
+        )
+    }
+
+    /// Parse the script line by line from `reader`, like `parse` does, without keeping the whole
+    /// text in memory, so scripts can come from a pipe or a network stream. `source` of the result
+    /// is empty, like of compiled scripts. A failed read is `StreamReadError` (or `StringUTF8Error`
+    /// for text that is not UTF-8) with the number of the line that couldn't be read.
+    pub fn parse_reader(reader: impl BufRead) -> Result<Script, (ScriptError, usize)> {
+        let mut diagnostics = Vec::new();
+
+        let mut commands = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidData => (ScriptError::StringUTF8Error, index + 1),
+                _ => (ScriptError::stream_read(&e), index + 1),
+            })?;
+            parse_line(
+                &prepare_line(&line),
+                index + 1,
+                &mut commands,
+                &mut diagnostics,
+            );
+        }
+
+        let (commands, functions, meta, pragmas) =
+            parse_parsed_commands(commands, &mut diagnostics);
+        check_streams(&commands, &functions, &mut diagnostics);
+
+        match diagnostics.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(Script {
+                commands,
+                functions,
+                meta,
+                pragmas,
+                source: Vec::new(),
+            }),
+        }
+    }
+
+    /// Mark every command as written in `file`, like code taken from another file, so errors
+    /// and tracebacks report `file:line` instead of a line of the script it was merged into
+    pub fn set_source_file(&mut self, file: &str) {
+        let file: Arc<str> = Arc::from(file);
+        let functions = self
+            .functions
+            .iter_mut()
+            .flat_map(|func| &mut func.commands);
+        for command in self.commands.iter_mut().chain(functions) {
+            command.file = Some(file.clone());
+        }
+    }
+
+    /// Optimize commands of the main function and of every function, see `optimize_function`.
+    /// The bytecode compiler of `RunningScript` does the same, so scripts don't need to call it.
+    pub fn optimize(&mut self) {
+        self.commands = optimize_main(&self.commands);
+        for func in &mut self.functions {
+            func.commands = optimize_function(func);
+        }
+    }
+
+    /// Apply `edits` to the source of `old` and parse the result like `parse_lenient` does.
+    /// Functions with unchanged text are taken from `old` instead of being parsed again,
+    /// scripts with `TYPE_ALIAS` or with several functions of the same name are parsed fully.
+    pub fn parse_incremental(
+        old: &Script,
+        edits: &[TextEdit],
+    ) -> (Script, Vec<(ScriptError, usize)>) {
+        let mut source = old.source.clone();
+        for edit in edits {
+            let end = edit.end_line.min(source.len());
+            let start = edit.start_line.min(end);
+            source.splice(start..end, edit.text.lines().map(str::to_string));
+        }
+        let text = source.join("\n");
+
+        let old_lines = prepare_script(old.source.join("\n"));
+        let lines = prepare_script(text.clone());
+
+        let has_aliases = old_lines
+            .iter()
+            .chain(&lines)
+            .any(|line| line_command(line) == "TYPE_ALIAS");
+        if has_aliases {
+            return Script::parse_lenient(text);
+        }
+
+        // functions of the old script that parsed without errors, by the text of their block
+        let mut old_functions: HashMap<String, (usize, &Function)> = HashMap::new();
+        let mut old_iter = old.functions.iter().peekable();
+        for (start, end) in func_blocks(&old_lines) {
+            // blocks with an invalid header have no function
+            let name = split_line(&old_lines[start]).get(2).cloned();
+            let Some(func) = old_iter.next_if(|func| Some(&func.name) == name.as_ref()) else {
+                continue;
+            };
+            let body = &old_lines[start + 1..end];
+            if body.iter().filter(|line| !line.is_empty()).count() == func.commands.len() {
+                old_functions.insert(old_lines[start..=end].join("\n"), (start, func));
+            }
+        }
+
+        // bodies of reused functions are blanked, so only their headers are parsed again
+        let mut reused: Vec<Function> = Vec::new();
+        let mut parse_lines = lines.clone();
+        for (start, end) in func_blocks(&lines) {
+            if let Some((old_start, func)) = old_functions.get(&lines[start..=end].join("\n")) {
+                let mut func = (*func).clone();
+                for command in func.commands.iter_mut() {
+                    command.line = command.line + start - old_start;
+                }
+                reused.push(func);
+                for line in &mut parse_lines[start + 1..end] {
+                    line.clear();
+                }
+            }
+        }
+
+        let mut diagnostics = Vec::new();
+        let (commands, mut functions, meta, pragmas) =
+            parse_prepared(parse_lines, &mut diagnostics);
+
+        let mut names = HashSet::new();
+        if !functions.iter().all(|func| names.insert(func.name.clone())) {
+            return Script::parse_lenient(text);
+        }
+
+        let mut reused = reused.into_iter().peekable();
+        for func in functions.iter_mut() {
+            if reused.peek().is_some_and(|next| next.name == func.name) {
+                func.commands = reused.next().unwrap().commands;
+            }
+        }
+
+        check_streams(&commands, &functions, &mut diagnostics);
+
+        (
+            Script {
+                commands,
+                functions,
+                meta,
+                pragmas,
+                source,
+            },
+            diagnostics,
+        )
+    }
 }
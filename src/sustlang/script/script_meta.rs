@@ -0,0 +1,87 @@
+use super::ScriptError;
+
+use std::collections::HashMap;
+
+/// Version of the language, compared with `META require`
+pub const LANGUAGE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn parse_version(version: &str) -> Result<Vec<usize>, ScriptError> {
+    version
+        .split('.')
+        .map(|part| part.parse().map_err(|_| ScriptError::MetaError))
+        .collect()
+}
+
+/// Compare two versions like `0.2` and `1.0.0`, missing parts are zeros
+pub fn compare_versions(version: &str, other: &str) -> Result<std::cmp::Ordering, ScriptError> {
+    let mut version = parse_version(version)?;
+    let mut other = parse_version(other)?;
+    let len = version.len().max(other.len());
+    version.resize(len, 0);
+    other.resize(len, 0);
+    Ok(version.cmp(&other))
+}
+
+/// Header of the script, declared with `META key value` lines before any other command
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ScriptMeta {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// Minimal version of the language the script needs
+    pub require: Option<String>,
+    /// Keys other than `name`, `version` and `require`
+    pub other: HashMap<String, String>,
+}
+
+impl ScriptMeta {
+    pub fn get(&self, key: &str) -> Option<&String> {
+        match key {
+            "name" => self.name.as_ref(),
+            "version" => self.version.as_ref(),
+            "require" => self.require.as_ref(),
+            _ => self.other.get(key),
+        }
+    }
+
+    /// Set a key, the same key can't be declared twice
+    pub fn set(&mut self, key: String, value: String) -> Result<(), ScriptError> {
+        if self.get(&key).is_some() {
+            return Err(ScriptError::MetaError);
+        }
+
+        match key.as_str() {
+            "name" => self.name = Some(value),
+            "version" => self.version = Some(value),
+            "require" => {
+                if compare_versions(&value, LANGUAGE_VERSION)?.is_gt() {
+                    return Err(ScriptError::VersionError);
+                }
+                self.require = Some(value);
+            }
+            _ => {
+                self.other.insert(key, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// All keys with values, in no particular order
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = self
+            .other
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        for (key, value) in [
+            ("name", &self.name),
+            ("version", &self.version),
+            ("require", &self.require),
+        ] {
+            if let Some(value) = value {
+                entries.push((key.to_string(), value.clone()));
+            }
+        }
+        entries
+    }
+}
@@ -0,0 +1,30 @@
+use super::super::command::Command;
+use super::{ScriptError, StackFrame};
+
+use std::thread::JoinHandle;
+
+/// Thread started by `NEW_THREAD`, the error of its function is kept here until someone takes it
+#[derive(Default)]
+pub struct ScriptThread {
+    pub handle: Option<JoinHandle<()>>,
+    pub error: Option<(ScriptError, Command)>,
+    /// Calls that led to `error`, see `CallStack::take`
+    pub traceback: Vec<StackFrame>,
+}
+
+impl ScriptThread {
+    pub fn is_finished(&self) -> bool {
+        match &self.handle {
+            Some(handle) => handle.is_finished(),
+            None => true,
+        }
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    pub fn take_error(&mut self) -> Option<(ScriptError, Command)> {
+        self.error.take()
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::{LazyLock, RwLock};
+
+/// Id of an interned name, see `SymbolTable`
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Symbol(pub usize);
+
+/// Names of variables, one table for the whole process, so commands get the ids of their
+/// variables when they are parsed, before the script they run in exists. Names are never removed.
+static VARIABLE_NAMES: LazyLock<RwLock<SymbolTable>> = LazyLock::new(Default::default);
+
+impl Symbol {
+    /// Id of the variable name, the same in every script. Function symbols come from the
+    /// `SymbolTable` of their script instead.
+    pub fn variable(name: &str) -> Symbol {
+        if let Some(symbol) = VARIABLE_NAMES.read().unwrap().get(name) {
+            return symbol;
+        }
+        VARIABLE_NAMES.write().unwrap().intern(name)
+    }
+
+    /// Name of the id from `Symbol::variable`
+    pub fn variable_name(self) -> String {
+        VARIABLE_NAMES
+            .read()
+            .unwrap()
+            .name(self)
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// Names interned to integer ids, ids go from zero in the order the names were added
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    names: Vec<String>,
+    ids: HashMap<String, Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable::default()
+    }
+
+    /// Id of the name, the name is added if it is not in the table yet
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.ids.get(name) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(self.names.len());
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), symbol);
+        symbol
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, symbol: Symbol) -> Option<&str> {
+        self.names.get(symbol.0).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Variable name split by dots once: `users.0.name` is the variable `users`
+/// with the segments `0` and `name`
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+pub struct VarPath {
+    pub root: String,
+    /// Id of `root`, variables are looked up by it
+    pub id: Symbol,
+    pub segments: Vec<String>,
+}
+
+impl VarPath {
+    pub fn parse(name: &str) -> VarPath {
+        let mut parts = name.split('.');
+        let root = parts.next().unwrap_or_default();
+        VarPath {
+            root: root.to_string(),
+            id: Symbol::variable(root),
+            segments: parts.map(str::to_string).collect(),
+        }
+    }
+
+    /// All parts of the path, starting with the variable name
+    pub fn parts(&self) -> Vec<&str> {
+        std::iter::once(self.root.as_str())
+            .chain(self.segments.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+impl Display for VarPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.parts().join("."))
+    }
+}
@@ -0,0 +1,57 @@
+use serialport::SerialPort;
+
+use super::script::ScriptError;
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+/// How long one read of the port waits before trying again
+const SERIAL_READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reading half of `OPEN_SERIAL`, waits for data like other streams
+/// instead of returning the timeout error of the port
+pub struct SerialInStream {
+    port: Box<dyn SerialPort>,
+}
+
+impl Read for SerialInStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.port.read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => continue,
+                read => return read,
+            }
+        }
+    }
+}
+
+/// Writing half of `OPEN_SERIAL`
+pub struct SerialOutStream {
+    port: Box<dyn SerialPort>,
+}
+
+impl Write for SerialOutStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+/// Open the serial port at `path` with `baud_rate` and 8N1 settings
+pub fn open_serial(
+    path: &str,
+    baud_rate: u32,
+) -> Result<(SerialInStream, SerialOutStream), ScriptError> {
+    let port = serialport::new(path, baud_rate)
+        .timeout(SERIAL_READ_TIMEOUT)
+        .open()
+        .map_err(|e| ScriptError::file_read(path, &io::Error::from(e)))?;
+    let reader = port
+        .try_clone()
+        .map_err(|e| ScriptError::file_read(path, &io::Error::from(e)))?;
+
+    Ok((SerialInStream { port: reader }, SerialOutStream { port }))
+}
@@ -0,0 +1,173 @@
+use super::super::script::ScriptError;
+use super::var_type::VarType;
+use super::variable::Variable;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Rust value that can be passed to a script as a `Variable`
+pub trait IntoVariable {
+    /// Script type of the value, needed to type empty lists and maps
+    fn var_type() -> VarType;
+
+    fn into_variable(self) -> Variable;
+}
+
+/// Rust value that can be read back from a script `Variable`
+pub trait FromVariable: Sized {
+    fn from_variable(var: Variable) -> Result<Self, ScriptError>;
+}
+
+macro_rules! impl_scalar {
+    ($rust_type:ty, $var_type:expr, $from:ident, $as:ident) => {
+        impl IntoVariable for $rust_type {
+            fn var_type() -> VarType {
+                $var_type
+            }
+
+            fn into_variable(self) -> Variable {
+                Variable::$from(Some(self))
+            }
+        }
+
+        impl FromVariable for $rust_type {
+            fn from_variable(var: Variable) -> Result<Self, ScriptError> {
+                var.$as()
+            }
+        }
+
+        impl From<$rust_type> for Variable {
+            fn from(value: $rust_type) -> Variable {
+                value.into_variable()
+            }
+        }
+
+        impl TryFrom<Variable> for $rust_type {
+            type Error = ScriptError;
+
+            fn try_from(var: Variable) -> Result<Self, ScriptError> {
+                <$rust_type>::from_variable(var)
+            }
+        }
+    };
+}
+
+impl_scalar!(bool, VarType::Bool, from_bool, as_bool);
+impl_scalar!(String, VarType::String, from_str, as_str);
+impl_scalar!(isize, VarType::Integer, from_int, as_int);
+impl_scalar!(f64, VarType::Float, from_float, as_float);
+impl_scalar!(char, VarType::Char, from_char, as_char);
+
+impl From<&str> for Variable {
+    fn from(value: &str) -> Variable {
+        Variable::from_str(Some(value.to_string()))
+    }
+}
+
+impl<T: IntoVariable> IntoVariable for Vec<T> {
+    fn var_type() -> VarType {
+        VarType::List(Box::new(T::var_type()))
+    }
+
+    fn into_variable(self) -> Variable {
+        Variable::from_list(
+            Some(self.into_iter().map(T::into_variable).collect()),
+            T::var_type(),
+        )
+    }
+}
+
+impl<T: FromVariable> FromVariable for Vec<T> {
+    fn from_variable(var: Variable) -> Result<Self, ScriptError> {
+        Arc::unwrap_or_clone(var.as_list()?)
+            .into_iter()
+            .map(T::from_variable)
+            .collect()
+    }
+}
+
+impl<K: IntoVariable, V: IntoVariable> IntoVariable for HashMap<K, V> {
+    fn var_type() -> VarType {
+        VarType::Map(Box::new(K::var_type()), Box::new(V::var_type()))
+    }
+
+    fn into_variable(self) -> Variable {
+        Variable::from_map(
+            Some(
+                self.into_iter()
+                    .map(|(k, v)| (k.into_variable(), v.into_variable()))
+                    .collect(),
+            ),
+            K::var_type(),
+            V::var_type(),
+        )
+    }
+}
+
+impl<K: FromVariable + Eq + Hash, V: FromVariable> FromVariable for HashMap<K, V> {
+    fn from_variable(var: Variable) -> Result<Self, ScriptError> {
+        Arc::unwrap_or_clone(var.as_map()?)
+            .into_iter()
+            .map(|(k, v)| Ok((K::from_variable(k)?, V::from_variable(v)?)))
+            .collect()
+    }
+}
+
+impl<T: IntoVariable> IntoVariable for Option<T> {
+    fn var_type() -> VarType {
+        VarType::Optional(Box::new(T::var_type()))
+    }
+
+    fn into_variable(self) -> Variable {
+        Variable::from_optional(Some(self.map(T::into_variable)), T::var_type())
+    }
+}
+
+impl<T: FromVariable> FromVariable for Option<T> {
+    fn from_variable(var: Variable) -> Result<Self, ScriptError> {
+        var.as_option()?.map(|v| T::from_variable(*v)).transpose()
+    }
+}
+
+impl<T: IntoVariable> From<Vec<T>> for Variable {
+    fn from(value: Vec<T>) -> Variable {
+        value.into_variable()
+    }
+}
+
+impl<K: IntoVariable, V: IntoVariable> From<HashMap<K, V>> for Variable {
+    fn from(value: HashMap<K, V>) -> Variable {
+        value.into_variable()
+    }
+}
+
+impl<T: IntoVariable> From<Option<T>> for Variable {
+    fn from(value: Option<T>) -> Variable {
+        value.into_variable()
+    }
+}
+
+impl<T: FromVariable> TryFrom<Variable> for Vec<T> {
+    type Error = ScriptError;
+
+    fn try_from(var: Variable) -> Result<Self, ScriptError> {
+        Vec::from_variable(var)
+    }
+}
+
+impl<K: FromVariable + Eq + Hash, V: FromVariable> TryFrom<Variable> for HashMap<K, V> {
+    type Error = ScriptError;
+
+    fn try_from(var: Variable) -> Result<Self, ScriptError> {
+        HashMap::from_variable(var)
+    }
+}
+
+impl<T: FromVariable> TryFrom<Variable> for Option<T> {
+    type Error = ScriptError;
+
+    fn try_from(var: Variable) -> Result<Self, ScriptError> {
+        Option::from_variable(var)
+    }
+}
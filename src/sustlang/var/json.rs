@@ -0,0 +1,210 @@
+use super::super::other::{format_date_time, parse_date_time};
+use super::super::script::ScriptError;
+use super::var_type::VarType;
+use super::variable::Variable;
+
+use serde_json::{Map, Number, Value};
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+/// Merge types of two json values. `null` type means "unknown" (items of an empty array),
+/// json `null` is `optional[null]` and makes the other type optional
+fn unify(a: VarType, b: VarType) -> Result<VarType, ScriptError> {
+    let mismatch = ScriptError::TypeMismatchError {
+        expected: a.to_name(),
+        actual: b.to_name(),
+    };
+    Ok(match (a, b) {
+        (VarType::Null, t) | (t, VarType::Null) => t,
+        (VarType::Optional(a), VarType::Optional(b)) => VarType::Optional(Box::new(unify(*a, *b)?)),
+        (VarType::Optional(a), b) | (b, VarType::Optional(a)) => {
+            VarType::Optional(Box::new(unify(*a, b)?))
+        }
+        (VarType::List(a), VarType::List(b)) => VarType::List(Box::new(unify(*a, *b)?)),
+        (VarType::Map(ak, av), VarType::Map(bk, bv)) => {
+            VarType::Map(Box::new(unify(*ak, *bk)?), Box::new(unify(*av, *bv)?))
+        }
+        (VarType::Integer, VarType::Float) | (VarType::Float, VarType::Integer) => VarType::Float,
+        (a, b) if a == b => a,
+        _ => return Err(mismatch),
+    })
+}
+
+fn infer_type(value: &Value) -> Result<VarType, ScriptError> {
+    Ok(match value {
+        Value::Null => VarType::Optional(Box::new(VarType::Null)),
+        Value::Bool(_) => VarType::Bool,
+        Value::Number(n) if n.is_i64() => VarType::Integer,
+        Value::Number(_) => VarType::Float,
+        Value::String(_) => VarType::String,
+        Value::Array(items) => {
+            let mut item_type = VarType::Null;
+            for item in items {
+                item_type = unify(item_type, infer_type(item)?)?;
+            }
+            VarType::List(Box::new(item_type))
+        }
+        Value::Object(map) => {
+            let mut value_type = VarType::Null;
+            for item in map.values() {
+                value_type = unify(value_type, infer_type(item)?)?;
+            }
+            VarType::Map(Box::new(VarType::String), Box::new(value_type))
+        }
+    })
+}
+
+/// Kind of the json value for error messages
+fn json_kind(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("json null"),
+        Value::Bool(_) => String::from("json bool"),
+        Value::Number(n) => format!("json number {}", n),
+        Value::String(_) => String::from("json string"),
+        Value::Array(_) => String::from("json array"),
+        Value::Object(_) => String::from("json object"),
+    }
+}
+
+fn json_to_var(value: &Value, var_type: &VarType) -> Result<Variable, ScriptError> {
+    let mismatch = || ScriptError::TypeMismatchError {
+        expected: var_type.to_name(),
+        actual: json_kind(value),
+    };
+    Ok(match (var_type, value) {
+        (VarType::Optional(inner), Value::Null) => {
+            Variable::from_optional(Some(None), inner.as_ref().clone())
+        }
+        (VarType::Optional(inner), value) => Variable::from_optional(
+            Some(Some(json_to_var(value, inner)?)),
+            inner.as_ref().clone(),
+        ),
+        (VarType::Null, Value::Null) => Variable::from_null(),
+        (VarType::Bool, Value::Bool(v)) => Variable::from_bool(Some(*v)),
+        (VarType::Integer, Value::Number(v)) => {
+            Variable::from_int(Some(v.as_i64().ok_or_else(mismatch)? as isize))
+        }
+        (VarType::Float, Value::Number(v)) => {
+            Variable::from_float(Some(v.as_f64().ok_or_else(mismatch)?))
+        }
+        (VarType::Char, Value::Number(v)) => Variable::from_char(Some(
+            v.as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .and_then(char::from_u32)
+                .ok_or_else(mismatch)?,
+        )),
+        (VarType::String, Value::String(v)) => Variable::from_str(Some(v.clone())),
+        (VarType::Func, Value::String(v)) => Variable::from_func(Some(v.clone())),
+        (VarType::Bytes, Value::Array(items)) => Variable::from_bytes(Some(
+            items
+                .iter()
+                .map(|item| item.as_u64().and_then(|v| u8::try_from(v).ok()))
+                .collect::<Option<_>>()
+                .ok_or_else(mismatch)?,
+        )),
+        (VarType::Duration, Value::Number(v)) => {
+            Variable::from_duration(Some(v.as_i64().ok_or_else(mismatch)?))
+        }
+        (VarType::DateTime, Value::String(v)) => {
+            Variable::from_date_time(Some(parse_date_time(v).ok_or_else(mismatch)?))
+        }
+        (VarType::List(inner), Value::Array(items)) => Variable::from_list(
+            Some(
+                items
+                    .iter()
+                    .map(|item| json_to_var(item, inner))
+                    .collect::<Result<_, _>>()?,
+            ),
+            inner.as_ref().clone(),
+        ),
+        (VarType::Set(inner), Value::Array(items)) => Variable::from_set(
+            Some(
+                items
+                    .iter()
+                    .map(|item| json_to_var(item, inner))
+                    .collect::<Result<_, _>>()?,
+            ),
+            inner.as_ref().clone(),
+        ),
+        (VarType::Map(key_type, value_type), Value::Object(map)) => {
+            let mut result = HashMap::new();
+            for (k, v) in map {
+                result.insert(
+                    Variable::parse_var(key_type.as_ref().clone(), k.clone())?,
+                    json_to_var(v, value_type)?,
+                );
+            }
+            Variable::from_map(
+                Some(result),
+                key_type.as_ref().clone(),
+                value_type.as_ref().clone(),
+            )
+        }
+        _ => return Err(mismatch()),
+    })
+}
+
+fn var_to_json(var: &Variable) -> Result<Value, ScriptError> {
+    Ok(match var {
+        Variable::Bool(_, Some(v)) => Value::Bool(*v),
+        Variable::String(_, Some(v)) => Value::String(v.as_ref().clone()),
+        Variable::Integer(_, Some(v)) => Value::Number(Number::from(*v as i64)),
+        Variable::Float(_, Some(v)) => Number::from_f64(*v).map_or(Value::Null, Value::Number),
+        Variable::Char(_, Some(v)) => Value::Number(Number::from(*v as u32)),
+        Variable::DateTime(_, Some(v)) => Value::String(format_date_time(*v)),
+        Variable::Duration(_, Some(v)) => Value::Number(Number::from(*v)),
+        Variable::Bytes(_, Some(v)) => Value::Array(v.iter().map(|b| Value::from(*b)).collect()),
+        Variable::List(_, Some(v)) => {
+            Value::Array(v.iter().map(var_to_json).collect::<Result<_, _>>()?)
+        }
+        Variable::Set(_, Some(v)) => {
+            let mut items = v.iter().map(var_to_json).collect::<Result<Vec<_>, _>>()?;
+            // sorted, so the text doesn't depend on the order of the set
+            items.sort_by_key(|item| item.to_string());
+            Value::Array(items)
+        }
+        Variable::Map(_, Some(v)) => {
+            let mut map = Map::new();
+            for (k, v) in v.iter() {
+                map.insert(k.to_string()?, var_to_json(v)?);
+            }
+            Value::Object(map)
+        }
+        Variable::Optional(_, Some(Some(v))) => var_to_json(v)?,
+        Variable::Optional(_, Some(None)) => Value::Null,
+        Variable::Func(_, Some(v)) => Value::String(v.clone()),
+        Variable::Atomic(_, Some(v)) => {
+            Value::Number(Number::from(v.load(Ordering::SeqCst) as i64))
+        }
+        Variable::InStream(_, Some(_))
+        | Variable::OutStream(_, Some(_))
+        | Variable::FileStream(_, Some(_))
+        | Variable::Thread(_, Some(_))
+        | Variable::Lock(_, Some(_))
+        | Variable::Limiter(_, Some(_))
+        | Variable::HttpSession(_, Some(_))
+        | Variable::Null(_) => Value::Null,
+        #[cfg(feature = "mqtt")]
+        Variable::Mqtt(_, Some(_)) => Value::Null,
+        _ => return Err(ScriptError::VarNotInitedError),
+    })
+}
+
+impl Variable {
+    /// Parse json text, objects become `map[string,...]`, arrays become lists and `null`
+    /// becomes an empty optional. Without `var_type` the type is inferred from the data.
+    pub fn from_json(text: &str, var_type: Option<VarType>) -> Result<Variable, ScriptError> {
+        let value: Value = serde_json::from_str(text).map_err(|_| ScriptError::ParseVarError)?;
+        let var_type = match var_type {
+            Some(var_type) => var_type,
+            None => infer_type(&value)?,
+        };
+        json_to_var(&value, &var_type)
+    }
+
+    /// Streams, threads and locks are written as `null`
+    pub fn to_json(&self) -> Result<String, ScriptError> {
+        Ok(var_to_json(self)?.to_string())
+    }
+}
@@ -1,5 +1,12 @@
+pub mod convert;
+pub mod json;
+#[cfg(feature = "serde")]
+pub mod serde_impl;
 pub mod var_type;
 pub mod variable;
 
+pub use convert::*;
+#[cfg(feature = "serde")]
+pub use serde_impl::*;
 pub use var_type::*;
 pub use variable::*;
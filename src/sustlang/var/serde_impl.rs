@@ -0,0 +1,289 @@
+use super::super::other::{format_date_time, parse_date_time};
+use super::var_type::VarType;
+use super::variable::Variable;
+
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::Arc;
+
+impl Serialize for VarType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for VarType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        VarType::from_name(&name).map_err(|_| de::Error::custom(format!("unknown type {}", name)))
+    }
+}
+
+/// Streams, threads and locks can't be serialized and are written as `null`
+impl Serialize for Variable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Variable::Bool(_, Some(v)) => serializer.serialize_bool(*v),
+            Variable::String(_, Some(v)) => serializer.serialize_str(v),
+            Variable::Integer(_, Some(v)) => serializer.serialize_i64(*v as i64),
+            Variable::Float(_, Some(v)) => serializer.serialize_f64(*v),
+            Variable::Char(_, Some(v)) => serializer.serialize_u32(*v as u32),
+            Variable::DateTime(_, Some(v)) => serializer.serialize_str(&format_date_time(*v)),
+            Variable::Duration(_, Some(v)) => serializer.serialize_i64(*v),
+            Variable::Bytes(_, Some(v)) => serializer.serialize_bytes(v),
+            Variable::List(_, Some(v)) => serializer.collect_seq(v.iter()),
+            Variable::Map(_, Some(v)) => serializer.collect_map(v.iter()),
+            Variable::Set(_, Some(v)) => serializer.collect_seq(v.iter()),
+            Variable::Optional(_, Some(Some(v))) => serializer.serialize_some(v.as_ref()),
+            Variable::Optional(_, Some(None)) => serializer.serialize_none(),
+            Variable::Func(_, Some(v)) => serializer.serialize_str(v),
+            Variable::Atomic(_, Some(v)) => {
+                serializer.serialize_i64(v.load(Ordering::SeqCst) as i64)
+            }
+            _ => serializer.serialize_unit(),
+        }
+    }
+}
+
+/// Merge types of two values, `null` parts (empty collections, `null` values) match anything
+fn unify(a: VarType, b: VarType) -> Option<VarType> {
+    match (a, b) {
+        (VarType::Null, t) | (t, VarType::Null) => Some(t),
+        (VarType::List(a), VarType::List(b)) => Some(VarType::List(Box::new(unify(*a, *b)?))),
+        (VarType::Optional(a), VarType::Optional(b)) => {
+            Some(VarType::Optional(Box::new(unify(*a, *b)?)))
+        }
+        (VarType::Map(ak, av), VarType::Map(bk, bv)) => Some(VarType::Map(
+            Box::new(unify(*ak, *bk)?),
+            Box::new(unify(*av, *bv)?),
+        )),
+        (a, b) if a == b => Some(a),
+        _ => None,
+    }
+}
+
+/// Untyped reading of data. In strict mode all items of a collection must have the same type,
+/// otherwise the collection is typed by its first item and `TypedVariable` fixes it afterwards.
+#[derive(Clone, Copy)]
+struct Untyped {
+    strict: bool,
+}
+
+impl Untyped {
+    fn common_type<'a, E: de::Error>(
+        self,
+        mut items: impl Iterator<Item = &'a Variable>,
+    ) -> Result<VarType, E> {
+        let Some(first) = items.next() else {
+            return Ok(VarType::Null);
+        };
+        let mut var_type = first.get_type();
+        for item in items {
+            match unify(var_type.clone(), item.get_type()) {
+                Some(t) => var_type = t,
+                None if self.strict => {
+                    return Err(E::custom("collection items have different types"))
+                }
+                None => {}
+            }
+        }
+        Ok(var_type)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Untyped {
+    type Value = Variable;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Variable, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Untyped {
+    type Value = Variable;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a script value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Variable, E> {
+        Ok(Variable::from_bool(Some(v)))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Variable, E> {
+        Ok(Variable::from_int(Some(v as isize)))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Variable, E> {
+        let v = isize::try_from(v).map_err(|_| E::custom("integer is too big"))?;
+        Ok(Variable::from_int(Some(v)))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Variable, E> {
+        Ok(Variable::from_float(Some(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Variable, E> {
+        Ok(Variable::from_str(Some(v.to_string())))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Variable, E> {
+        Ok(Variable::from_bytes(Some(v.to_vec())))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Variable, E> {
+        Ok(Variable::from_null())
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Variable, E> {
+        Ok(Variable::from_null())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Variable, D::Error> {
+        let value = self.deserialize(deserializer)?;
+        let value_type = value.get_type();
+        Ok(Variable::from_optional(Some(Some(value)), value_type))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Variable, A::Error> {
+        let mut list = Vec::new();
+        while let Some(item) = seq.next_element_seed(self)? {
+            list.push(item);
+        }
+        let value_type = self.common_type(list.iter())?;
+        Ok(Variable::from_list(Some(list), value_type))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Variable, A::Error> {
+        let mut map = HashMap::new();
+        while let Some(key) = access.next_key_seed(self)? {
+            let value = access.next_value_seed(self)?;
+            map.insert(key, value);
+        }
+        let key_type = self.common_type(map.keys())?;
+        let value_type = self.common_type(map.values())?;
+        Ok(Variable::from_map(Some(map), key_type, value_type))
+    }
+}
+
+/// Types are inferred from the data: numbers without a fraction become `int`, objects become
+/// maps and so on, items of a list or values of a map must have the same type.
+/// Use `TypedVariable` to read data into a known type.
+impl<'de> Deserialize<'de> for Variable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Untyped { strict: true }.deserialize(deserializer)
+    }
+}
+
+/// Deserialize a `Variable` of the given type, for example to restore saved globals or to read
+/// JSON config (where map keys are always strings) into `map[int,float]`
+pub struct TypedVariable(pub VarType);
+
+impl<'de> DeserializeSeed<'de> for TypedVariable {
+    type Value = Variable;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Variable, D::Error> {
+        let value = Untyped { strict: false }.deserialize(deserializer)?;
+        coerce(value, &self.0).map_err(de::Error::custom)
+    }
+}
+
+fn coerce(value: Variable, var_type: &VarType) -> Result<Variable, String> {
+    let mismatch = || format!("expected {}", var_type.to_name());
+
+    Ok(match (var_type, value) {
+        (VarType::Optional(inner), Variable::Null(_)) => {
+            Variable::from_optional(Some(None), inner.as_ref().clone())
+        }
+        (VarType::Optional(inner), Variable::Optional(_, Some(Some(v)))) => {
+            Variable::from_optional(Some(Some(coerce(*v, inner)?)), inner.as_ref().clone())
+        }
+        (VarType::Optional(inner), v) => {
+            Variable::from_optional(Some(Some(coerce(v, inner)?)), inner.as_ref().clone())
+        }
+        (VarType::Float, Variable::Integer(_, Some(v))) => Variable::from_float(Some(v as f64)),
+        (VarType::Char, Variable::Integer(_, Some(v))) => Variable::from_char(Some(
+            u32::try_from(v)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(mismatch)?,
+        )),
+        (VarType::Func, Variable::String(_, Some(v))) => {
+            Variable::from_func(Some(v.as_ref().clone()))
+        }
+        (VarType::Bytes, Variable::List(_, Some(list))) => Variable::from_bytes(Some(
+            list.iter()
+                .map(|v| match v {
+                    Variable::Integer(_, Some(v)) => u8::try_from(*v).ok(),
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .ok_or_else(mismatch)?,
+        )),
+        (VarType::Duration, Variable::Integer(_, Some(v))) => {
+            Variable::from_duration(Some(v as i64))
+        }
+        (VarType::DateTime, Variable::String(_, Some(v))) => {
+            Variable::from_date_time(Some(parse_date_time(&v).ok_or_else(mismatch)?))
+        }
+        (VarType::Atomic, Variable::Integer(_, Some(v))) => {
+            Variable::from_atomic(Some(Arc::new(AtomicIsize::new(v))))
+        }
+        (VarType::List(inner), Variable::List(_, Some(list))) => Variable::from_list(
+            Some(
+                Arc::unwrap_or_clone(list)
+                    .into_iter()
+                    .map(|v| coerce(v, inner))
+                    .collect::<Result<_, _>>()?,
+            ),
+            inner.as_ref().clone(),
+        ),
+        (VarType::Set(inner), Variable::List(_, Some(list))) => Variable::from_set(
+            Some(
+                Arc::unwrap_or_clone(list)
+                    .into_iter()
+                    .map(|v| coerce(v, inner))
+                    .collect::<Result<_, _>>()?,
+            ),
+            inner.as_ref().clone(),
+        ),
+        (VarType::Map(key_type, value_type), Variable::Map(_, Some(map))) => {
+            let mut result = HashMap::new();
+            for (k, v) in Arc::unwrap_or_clone(map) {
+                let k = match k {
+                    Variable::String(_, Some(k)) if **key_type != VarType::String => {
+                        Variable::parse_var(key_type.as_ref().clone(), Arc::unwrap_or_clone(k))
+                            .map_err(|_| mismatch())?
+                    }
+                    k => coerce(k, key_type)?,
+                };
+                result.insert(k, coerce(v, value_type)?);
+            }
+            Variable::from_map(
+                Some(result),
+                key_type.as_ref().clone(),
+                value_type.as_ref().clone(),
+            )
+        }
+        (
+            VarType::InStream
+            | VarType::OutStream
+            | VarType::FileStream
+            | VarType::Thread
+            | VarType::Lock
+            | VarType::Limiter
+            | VarType::HttpSession,
+            Variable::Null(_),
+        ) => Variable::not_inited_var(var_type.clone()).map_err(|_| mismatch())?,
+        #[cfg(feature = "mqtt")]
+        (VarType::Mqtt, Variable::Null(_)) => {
+            Variable::not_inited_var(var_type.clone()).map_err(|_| mismatch())?
+        }
+        (_, v) if v.get_type() == *var_type => v,
+        _ => return Err(mismatch()),
+    })
+}
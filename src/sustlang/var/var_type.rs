@@ -7,57 +7,76 @@ pub enum VarType {
     Integer,
     Float,
     Char,
+    DateTime,
+    Duration,
+    Bytes,
     List(Box<VarType>),
     Map(Box<VarType>, Box<VarType>),
+    Set(Box<VarType>),
     Optional(Box<VarType>),
     InStream,
     OutStream,
+    FileStream,
+    Func,
+    Thread,
+    Lock,
+    Atomic,
+    Limiter,
+    HttpSession,
+    #[cfg(feature = "mqtt")]
+    Mqtt,
     Null,
 }
 
 impl VarType {
-    pub fn from_name(name: &str) -> Result<VarType, ScriptError> {
-        if name.starts_with("map[") {
-            let value_type = name[9..name.len() - 1].to_string();
-
-            let mut key_type = String::new();
-            let mut val_type = String::new();
-
-            let mut val_tree = 0;
-            let mut val_stat = 0;
-            for char in value_type.chars() {
-                if val_stat == 0 {
-                    key_type.push(char);
-                } else if val_stat == 1 {
-                    val_type.push(char);
-                }
-                if char == ',' && val_tree == 0 {
-                    val_stat += 1;
-                }
-                if char == '[' {
-                    val_tree += 1;
-                }
-                if char == ']' {
-                    val_tree -= 1;
-                }
+    pub fn to_name(&self) -> String {
+        match self {
+            VarType::Bool => String::from("bool"),
+            VarType::String => String::from("string"),
+            VarType::Integer => String::from("integer"),
+            VarType::Float => String::from("float"),
+            VarType::Char => String::from("char"),
+            VarType::DateTime => String::from("datetime"),
+            VarType::Duration => String::from("duration"),
+            VarType::Bytes => String::from("bytes"),
+            VarType::List(value_type) => format!("list[{}]", value_type.to_name()),
+            VarType::Map(key_type, value_type) => {
+                format!("map[{},{}]", key_type.to_name(), value_type.to_name())
             }
+            VarType::Set(value_type) => format!("set[{}]", value_type.to_name()),
+            VarType::Optional(value_type) => format!("optional[{}]", value_type.to_name()),
+            VarType::InStream => String::from("in_stream"),
+            VarType::OutStream => String::from("out_stream"),
+            VarType::FileStream => String::from("file_stream"),
+            VarType::Func => String::from("func"),
+            VarType::Thread => String::from("thread"),
+            VarType::Lock => String::from("lock"),
+            VarType::Atomic => String::from("atomic"),
+            VarType::Limiter => String::from("limiter"),
+            VarType::HttpSession => String::from("http_session"),
+            #[cfg(feature = "mqtt")]
+            VarType::Mqtt => String::from("mqtt"),
+            VarType::Null => String::from("null"),
+        }
+    }
 
-            let key_type = Box::new(VarType::from_name(&key_type)?);
-            let val_type = Box::new(VarType::from_name(&val_type)?);
+    pub fn from_name(name: &str) -> Result<VarType, ScriptError> {
+        VarType::parse(name).map_err(|(e, _)| e)
+    }
 
-            return Ok(VarType::Map(key_type, val_type));
-        }
-        if name.starts_with("list[") {
-            let value_type = name[5..name.len() - 1].to_string();
-            let value_type = Box::new(VarType::from_name(&value_type)?);
-            return Ok(VarType::List(value_type));
-        }
-        if name.starts_with("optional[") {
-            let value_type = name[9..name.len() - 1].to_string();
-            let value_type = Box::new(VarType::from_name(&value_type)?);
-            return Ok(VarType::Optional(value_type));
+    /// Parse a type name like `map[string, list[int]]`, on error also returns the byte
+    /// position in `name` where parsing failed
+    pub fn parse(name: &str) -> Result<VarType, (ScriptError, usize)> {
+        let mut parser = TypeParser { text: name, pos: 0 };
+        let var_type = parser.parse_type(0)?;
+        parser.skip_spaces();
+        if parser.pos != name.len() {
+            return Err((ScriptError::TypeUnknownError(name.to_string()), parser.pos));
         }
+        Ok(var_type)
+    }
 
+    fn from_simple_name(name: &str) -> Result<VarType, ScriptError> {
         match name {
             "bool" => Ok(VarType::Bool),
             "b" => Ok(VarType::Bool),
@@ -71,12 +90,95 @@ impl VarType {
             "f" => Ok(VarType::Float),
             "char" => Ok(VarType::Char),
             "c" => Ok(VarType::Char),
+            "datetime" => Ok(VarType::DateTime),
+            "date" => Ok(VarType::DateTime),
+            "duration" => Ok(VarType::Duration),
+            "bytes" => Ok(VarType::Bytes),
             "in_stream" => Ok(VarType::InStream),
             "in" => Ok(VarType::InStream),
             "out_stream" => Ok(VarType::OutStream),
             "out" => Ok(VarType::OutStream),
+            "file_stream" => Ok(VarType::FileStream),
+            "func" => Ok(VarType::Func),
+            "function" => Ok(VarType::Func),
+            "thread" => Ok(VarType::Thread),
+            "lock" => Ok(VarType::Lock),
+            "atomic" => Ok(VarType::Atomic),
+            "limiter" => Ok(VarType::Limiter),
+            "http_session" => Ok(VarType::HttpSession),
+            #[cfg(feature = "mqtt")]
+            "mqtt" => Ok(VarType::Mqtt),
             "null" => Ok(VarType::Null),
-            _ => Err(ScriptError::TypeUnknownError),
+            _ => Err(ScriptError::TypeUnknownError(name.to_string())),
+        }
+    }
+}
+
+/// Nesting deeper than this is rejected so untrusted type names can't overflow the stack
+const MAX_TYPE_DEPTH: usize = 64;
+
+struct TypeParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl TypeParser<'_> {
+    fn skip_spaces(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), (ScriptError, usize)> {
+        self.skip_spaces();
+        if self.text[self.pos..].starts_with(symbol) {
+            self.pos += symbol.len_utf8();
+            Ok(())
+        } else {
+            Err((
+                ScriptError::TypeUnknownError(self.text.to_string()),
+                self.pos,
+            ))
         }
     }
+
+    fn parse_type(&mut self, depth: usize) -> Result<VarType, (ScriptError, usize)> {
+        self.skip_spaces();
+        let start = self.pos;
+        if depth > MAX_TYPE_DEPTH {
+            return Err((ScriptError::TypeUnknownError(self.text.to_string()), start));
+        }
+
+        let name_len = self.text[start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(self.text.len() - start);
+        let name = &self.text[start..start + name_len];
+        self.pos += name_len;
+
+        let generic = match name {
+            "list" | "optional" | "map" | "set" => {
+                self.expect('[')?;
+                true
+            }
+            _ => false,
+        };
+
+        if !generic {
+            return VarType::from_simple_name(name).map_err(|e| (e, start));
+        }
+
+        let var_type = match name {
+            "list" => VarType::List(Box::new(self.parse_type(depth + 1)?)),
+            "optional" => VarType::Optional(Box::new(self.parse_type(depth + 1)?)),
+            "set" => VarType::Set(Box::new(self.parse_type(depth + 1)?)),
+            _ => {
+                let key_type = self.parse_type(depth + 1)?;
+                self.expect(',')?;
+                let value_type = self.parse_type(depth + 1)?;
+                VarType::Map(Box::new(key_type), Box::new(value_type))
+            }
+        };
+        self.expect(']')?;
+
+        Ok(var_type)
+    }
 }
@@ -1,24 +1,47 @@
-use super::super::script::ScriptError;
+#[cfg(feature = "mqtt")]
+use super::super::mqtt::MqttClient;
+use super::super::other::{
+    format_date_time, format_duration, parse_char, parse_date_time, parse_duration, FileStream,
+};
+use super::super::script::{HttpSession, RateLimiter, ScriptError, ScriptThread};
 use super::var_type::VarType;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::io::{Read, Write};
 use std::ptr::hash;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 
 #[derive(Clone)]
 pub enum Variable {
     Bool(VarType, Option<bool>),
-    String(VarType, Option<String>),
+    String(VarType, Option<Arc<String>>),
     Integer(VarType, Option<isize>),
     Float(VarType, Option<f64>),
-    Char(VarType, Option<u8>),
-    List(VarType, Option<Vec<Variable>>),
-    Map(VarType, Option<HashMap<Variable, Variable>>),
+    /// Unicode scalar value, in streams it is written as UTF-8
+    Char(VarType, Option<char>),
+    /// Milliseconds since the unix epoch, UTC
+    DateTime(VarType, Option<i64>),
+    /// Milliseconds, can be negative
+    Duration(VarType, Option<i64>),
+    /// Binary data, one variable for all bytes unlike `list[char]`
+    Bytes(VarType, Option<Arc<Vec<u8>>>),
+    List(VarType, Option<Arc<Vec<Variable>>>),
+    Map(VarType, Option<Arc<HashMap<Variable, Variable>>>),
+    Set(VarType, Option<Arc<HashSet<Variable>>>),
     Optional(VarType, Option<Option<Box<Variable>>>),
-    InStream(VarType, Option<Arc<Mutex<dyn Read>>>),
-    OutStream(VarType, Option<Arc<Mutex<dyn Write>>>),
+    InStream(VarType, Option<Arc<Mutex<dyn Read + Send>>>),
+    OutStream(VarType, Option<Arc<Mutex<dyn Write + Send>>>),
+    FileStream(VarType, Option<Arc<Mutex<FileStream>>>),
+    Func(VarType, Option<String>),
+    Thread(VarType, Option<Arc<Mutex<ScriptThread>>>),
+    Lock(VarType, Option<Arc<(Mutex<bool>, Condvar)>>),
+    Atomic(VarType, Option<Arc<AtomicIsize>>),
+    Limiter(VarType, Option<Arc<RateLimiter>>),
+    HttpSession(VarType, Option<Arc<HttpSession>>),
+    #[cfg(feature = "mqtt")]
+    Mqtt(VarType, Option<Arc<MqttClient>>),
     Null(VarType),
 }
 
@@ -30,11 +53,24 @@ impl Variable {
             Variable::Integer(t, _) => t.clone(),
             Variable::Float(t, _) => t.clone(),
             Variable::Char(t, _) => t.clone(),
+            Variable::DateTime(t, _) => t.clone(),
+            Variable::Duration(t, _) => t.clone(),
+            Variable::Bytes(t, _) => t.clone(),
             Variable::List(t, _) => t.clone(),
             Variable::Map(t, _) => t.clone(),
+            Variable::Set(t, _) => t.clone(),
             Variable::Optional(t, _) => t.clone(),
             Variable::InStream(t, _) => t.clone(),
             Variable::OutStream(t, _) => t.clone(),
+            Variable::FileStream(t, _) => t.clone(),
+            Variable::Func(t, _) => t.clone(),
+            Variable::Thread(t, _) => t.clone(),
+            Variable::Lock(t, _) => t.clone(),
+            Variable::Atomic(t, _) => t.clone(),
+            Variable::Limiter(t, _) => t.clone(),
+            Variable::HttpSession(t, _) => t.clone(),
+            #[cfg(feature = "mqtt")]
+            Variable::Mqtt(t, _) => t.clone(),
             Variable::Null(t) => t.clone(),
         }
     }
@@ -42,18 +78,21 @@ impl Variable {
     pub fn to_string(&self) -> Result<String, ScriptError> {
         Ok(match self.clone() {
             Variable::Bool(_, Some(v)) => if v { "true" } else { "false" }.to_string(),
-            Variable::String(_, Some(v)) => v,
+            Variable::String(_, Some(v)) => v.as_ref().clone(),
             Variable::Integer(_, Some(v)) => v.to_string(),
             Variable::Float(_, Some(v)) => v.to_string(),
-            Variable::Char(_, Some(v)) => {
-                String::from_utf8(vec![v]).or(Err(ScriptError::StringUTF8Error))?
+            Variable::Char(_, Some(v)) => v.to_string(),
+            Variable::DateTime(_, Some(v)) => format_date_time(v),
+            Variable::Duration(_, Some(v)) => format_duration(v),
+            Variable::Bytes(_, Some(v)) => {
+                String::from_utf8(v.as_ref().clone()).or(Err(ScriptError::StringUTF8Error))?
             }
             Variable::List(VarType::Char, Some(v)) => {
-                let mut bytes = Vec::new();
-                for ele in v {
-                    bytes.push(ele.as_char()?);
+                let mut text = String::new();
+                for ele in v.iter() {
+                    text.push(ele.as_char()?);
                 }
-                String::from_utf8(bytes).or(Err(ScriptError::StringUTF8Error))?
+                text
             }
             Variable::List(_, Some(v)) => {
                 let mut text = String::from("[");
@@ -69,36 +108,96 @@ impl Variable {
             }
             Variable::Map(_, Some(v)) => {
                 let mut text = String::from("{");
-                let mut i = 0;
-                for (key, value) in &v {
+                for (i, (key, value)) in v.iter().enumerate() {
                     text.push_str(&key.to_string()?);
                     text.push_str(": ");
                     text.push_str(&value.to_string()?);
                     if i != v.len() - 1 {
                         text.push_str(", ");
                     }
-                    i += 1;
                 }
                 text.push('}');
                 text
             }
+            Variable::Set(_, Some(v)) => {
+                // sorted, so the text doesn't depend on the order of the set
+                let mut items = v
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Result<Vec<String>, ScriptError>>()?;
+                items.sort();
+                format!("{{{}}}", items.join(", "))
+            }
             Variable::Optional(_, Some(v)) => match v {
                 Some(v) => format!("({})", v.to_string()?),
                 None => String::from("none"),
             },
             Variable::InStream(_, Some(_)) => String::from("IN_STREAM"),
             Variable::OutStream(_, Some(_)) => String::from("OUT_STREAM"),
+            Variable::FileStream(_, Some(_)) => String::from("FILE_STREAM"),
+            Variable::Func(_, Some(v)) => v,
+            Variable::Thread(_, Some(_)) => String::from("THREAD"),
+            Variable::Lock(_, Some(_)) => String::from("LOCK"),
+            Variable::Limiter(_, Some(_)) => String::from("LIMITER"),
+            Variable::HttpSession(_, Some(_)) => String::from("HTTP_SESSION"),
+            #[cfg(feature = "mqtt")]
+            Variable::Mqtt(_, Some(_)) => String::from("MQTT"),
+            Variable::Atomic(_, Some(v)) => v.load(Ordering::SeqCst).to_string(),
             Variable::Null(_) => String::from("null"),
             _ => return Err(ScriptError::VarNotInitedError),
         })
     }
 
+    /// Like `to_string`, but nested lists and maps are printed on separate lines with
+    /// `indent` spaces per level, map entries are sorted by key
+    pub fn to_pretty_string(&self, indent: usize) -> Result<String, ScriptError> {
+        self.to_pretty_string_at(indent, 0)
+    }
+
+    fn to_pretty_string_at(&self, indent: usize, depth: usize) -> Result<String, ScriptError> {
+        let pad = " ".repeat(indent * (depth + 1));
+        let end_pad = " ".repeat(indent * depth);
+
+        Ok(match self {
+            Variable::List(VarType::List(list_type), Some(v))
+                if list_type.as_ref() != &VarType::Char =>
+            {
+                if v.is_empty() {
+                    return Ok(String::from("[]"));
+                }
+                let mut items = Vec::new();
+                for item in v.iter() {
+                    items.push(pad.clone() + &item.to_pretty_string_at(indent, depth + 1)?);
+                }
+                format!("[\n{}\n{}]", items.join(",\n"), end_pad)
+            }
+            Variable::Map(_, Some(v)) => {
+                if v.is_empty() {
+                    return Ok(String::from("{}"));
+                }
+                let mut entries = Vec::new();
+                for (key, value) in v.iter() {
+                    entries.push((
+                        key.to_string()?,
+                        value.to_pretty_string_at(indent, depth + 1)?,
+                    ));
+                }
+                entries.sort();
+                let entries: Vec<String> = entries
+                    .into_iter()
+                    .map(|(key, value)| format!("{}{}: {}", pad, key, value))
+                    .collect();
+                format!("{{\n{}\n{}}}", entries.join(",\n"), end_pad)
+            }
+            Variable::Optional(_, Some(Some(v))) => {
+                format!("({})", v.to_pretty_string_at(indent, depth)?)
+            }
+            _ => self.to_string()?,
+        })
+    }
+
     pub fn is_null(&self) -> bool {
-        if let Variable::Null(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Variable::Null(_))
     }
 
     pub fn is_initialized(&self) -> bool {
@@ -108,11 +207,24 @@ impl Variable {
             Variable::Integer(_, b) => b.is_some(),
             Variable::Float(_, b) => b.is_some(),
             Variable::Char(_, b) => b.is_some(),
+            Variable::DateTime(_, b) => b.is_some(),
+            Variable::Duration(_, b) => b.is_some(),
+            Variable::Bytes(_, b) => b.is_some(),
             Variable::List(_, b) => b.is_some(),
             Variable::Map(_, b) => b.is_some(),
+            Variable::Set(_, b) => b.is_some(),
             Variable::Optional(_, b) => b.is_some(),
             Variable::InStream(_, b) => b.is_some(),
             Variable::OutStream(_, b) => b.is_some(),
+            Variable::FileStream(_, b) => b.is_some(),
+            Variable::Func(_, b) => b.is_some(),
+            Variable::Thread(_, b) => b.is_some(),
+            Variable::Lock(_, b) => b.is_some(),
+            Variable::Atomic(_, b) => b.is_some(),
+            Variable::Limiter(_, b) => b.is_some(),
+            Variable::HttpSession(_, b) => b.is_some(),
+            #[cfg(feature = "mqtt")]
+            Variable::Mqtt(_, b) => b.is_some(),
             Variable::Null(_) => true,
         }
     }
@@ -121,8 +233,9 @@ impl Variable {
         Variable::Bool(VarType::Bool, value)
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(value: Option<String>) -> Variable {
-        Variable::String(VarType::String, value)
+        Variable::String(VarType::String, value.map(Arc::new))
     }
 
     pub fn from_int(value: Option<isize>) -> Variable {
@@ -133,12 +246,24 @@ impl Variable {
         Variable::Float(VarType::Float, value)
     }
 
-    pub fn from_char(value: Option<u8>) -> Variable {
+    pub fn from_char(value: Option<char>) -> Variable {
         Variable::Char(VarType::Char, value)
     }
 
+    pub fn from_date_time(value: Option<i64>) -> Variable {
+        Variable::DateTime(VarType::DateTime, value)
+    }
+
+    pub fn from_duration(value: Option<i64>) -> Variable {
+        Variable::Duration(VarType::Duration, value)
+    }
+
+    pub fn from_bytes(value: Option<Vec<u8>>) -> Variable {
+        Variable::Bytes(VarType::Bytes, value.map(Arc::new))
+    }
+
     pub fn from_list(value: Option<Vec<Variable>>, value_type: VarType) -> Variable {
-        Variable::List(VarType::List(Box::new(value_type)), value)
+        Variable::List(VarType::List(Box::new(value_type)), value.map(Arc::new))
     }
 
     pub fn from_map(
@@ -148,10 +273,14 @@ impl Variable {
     ) -> Variable {
         Variable::Map(
             VarType::Map(Box::new(key_type), Box::new(value_type)),
-            value,
+            value.map(Arc::new),
         )
     }
 
+    pub fn from_set(value: Option<HashSet<Variable>>, value_type: VarType) -> Variable {
+        Variable::Set(VarType::Set(Box::new(value_type)), value.map(Arc::new))
+    }
+
     pub fn from_optional(value: Option<Option<Variable>>, var_type: VarType) -> Variable {
         Variable::Optional(
             VarType::Optional(Box::new(var_type)),
@@ -169,27 +298,119 @@ impl Variable {
         Variable::Null(VarType::Null)
     }
 
-    pub fn from_out_stream(value: Option<Arc<Mutex<dyn Write>>>) -> Variable {
+    pub fn from_out_stream(value: Option<Arc<Mutex<dyn Write + Send>>>) -> Variable {
         Variable::OutStream(VarType::OutStream, value)
     }
 
-    pub fn from_in_stream(value: Option<Arc<Mutex<dyn Read>>>) -> Variable {
+    pub fn from_in_stream(value: Option<Arc<Mutex<dyn Read + Send>>>) -> Variable {
         Variable::InStream(VarType::InStream, value)
     }
 
-    pub fn as_out_stream(&self) -> Result<Arc<Mutex<dyn Write>>, ScriptError> {
-        if let Variable::OutStream(_, Some(b)) = self {
+    pub fn from_file_stream(value: Option<Arc<Mutex<FileStream>>>) -> Variable {
+        Variable::FileStream(VarType::FileStream, value)
+    }
+
+    pub fn from_func(value: Option<String>) -> Variable {
+        Variable::Func(VarType::Func, value)
+    }
+
+    pub fn from_thread(value: Option<Arc<Mutex<ScriptThread>>>) -> Variable {
+        Variable::Thread(VarType::Thread, value)
+    }
+
+    pub fn as_thread(&self) -> Result<Arc<Mutex<ScriptThread>>, ScriptError> {
+        if let Variable::Thread(_, Some(b)) = self {
             Ok(b.clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("thread", self))
         }
     }
 
-    pub fn as_in_stream(&self) -> Result<Arc<Mutex<dyn Read>>, ScriptError> {
-        if let Variable::InStream(_, Some(b)) = self {
+    pub fn from_lock(value: Option<Arc<(Mutex<bool>, Condvar)>>) -> Variable {
+        Variable::Lock(VarType::Lock, value)
+    }
+
+    pub fn as_lock(&self) -> Result<Arc<(Mutex<bool>, Condvar)>, ScriptError> {
+        if let Variable::Lock(_, Some(b)) = self {
             Ok(b.clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("lock", self))
+        }
+    }
+
+    pub fn from_atomic(value: Option<Arc<AtomicIsize>>) -> Variable {
+        Variable::Atomic(VarType::Atomic, value)
+    }
+
+    pub fn as_atomic(&self) -> Result<Arc<AtomicIsize>, ScriptError> {
+        if let Variable::Atomic(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("atomic", self))
+        }
+    }
+
+    pub fn from_limiter(value: Option<Arc<RateLimiter>>) -> Variable {
+        Variable::Limiter(VarType::Limiter, value)
+    }
+
+    pub fn as_limiter(&self) -> Result<Arc<RateLimiter>, ScriptError> {
+        if let Variable::Limiter(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("limiter", self))
+        }
+    }
+
+    pub fn from_http_session(value: Option<Arc<HttpSession>>) -> Variable {
+        Variable::HttpSession(VarType::HttpSession, value)
+    }
+
+    pub fn as_http_session(&self) -> Result<Arc<HttpSession>, ScriptError> {
+        if let Variable::HttpSession(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("http_session", self))
+        }
+    }
+
+    #[cfg(feature = "mqtt")]
+    pub fn from_mqtt(value: Option<Arc<MqttClient>>) -> Variable {
+        Variable::Mqtt(VarType::Mqtt, value)
+    }
+
+    #[cfg(feature = "mqtt")]
+    pub fn as_mqtt(&self) -> Result<Arc<MqttClient>, ScriptError> {
+        if let Variable::Mqtt(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("mqtt", self))
+        }
+    }
+
+    /// Stream for writing, `file_stream` can be written too
+    pub fn as_out_stream(&self) -> Result<Arc<Mutex<dyn Write + Send>>, ScriptError> {
+        match self {
+            Variable::OutStream(_, Some(b)) => Ok(b.clone()),
+            Variable::FileStream(_, Some(b)) => Ok(b.clone()),
+            _ => Err(ScriptError::type_mismatch("out_stream", self)),
+        }
+    }
+
+    /// Stream for reading, `file_stream` can be read too
+    pub fn as_in_stream(&self) -> Result<Arc<Mutex<dyn Read + Send>>, ScriptError> {
+        match self {
+            Variable::InStream(_, Some(b)) => Ok(b.clone()),
+            Variable::FileStream(_, Some(b)) => Ok(b.clone()),
+            _ => Err(ScriptError::type_mismatch("in_stream", self)),
+        }
+    }
+
+    pub fn as_file_stream(&self) -> Result<Arc<Mutex<FileStream>>, ScriptError> {
+        if let Variable::FileStream(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("file_stream", self))
         }
     }
 
@@ -197,7 +418,7 @@ impl Variable {
         if let Variable::Optional(VarType::Optional(v), _) = self {
             Ok(v.as_ref().clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("optional", self))
         }
     }
 
@@ -205,7 +426,7 @@ impl Variable {
         if let Variable::Optional(_, Some(b)) = self {
             Ok(b.clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("optional", self))
         }
     }
 
@@ -213,15 +434,33 @@ impl Variable {
         if let Variable::Map(VarType::Map(k, v), _) = self {
             Ok((k.as_ref().clone(), v.as_ref().clone()))
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("map", self))
         }
     }
 
-    pub fn as_map(&self) -> Result<HashMap<Variable, Variable>, ScriptError> {
+    /// Shared map, cloning it is cheap, use `Arc::make_mut` or `Arc::unwrap_or_clone` to change it
+    pub fn as_map(&self) -> Result<Arc<HashMap<Variable, Variable>>, ScriptError> {
         if let Variable::Map(_, Some(b)) = self {
             Ok(b.clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("map", self))
+        }
+    }
+
+    pub fn get_set_type(&self) -> Result<VarType, ScriptError> {
+        if let Variable::Set(VarType::Set(v), _) = self {
+            Ok(v.as_ref().clone())
+        } else {
+            Err(ScriptError::type_mismatch("set", self))
+        }
+    }
+
+    /// Shared set, cloning it is cheap, like `as_map`
+    pub fn as_set(&self) -> Result<Arc<HashSet<Variable>>, ScriptError> {
+        if let Variable::Set(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("set", self))
         }
     }
 
@@ -229,23 +468,24 @@ impl Variable {
         if let Variable::List(VarType::List(v), _) = self {
             Ok(v.as_ref().clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("list", self))
         }
     }
 
-    pub fn as_list(&self) -> Result<Vec<Variable>, ScriptError> {
+    /// Shared list, cloning it is cheap, use `Arc::make_mut` or `Arc::unwrap_or_clone` to change it
+    pub fn as_list(&self) -> Result<Arc<Vec<Variable>>, ScriptError> {
         if let Variable::List(_, Some(b)) = self {
             Ok(b.clone())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("list", self))
         }
     }
 
-    pub fn as_char(&self) -> Result<u8, ScriptError> {
+    pub fn as_char(&self) -> Result<char, ScriptError> {
         if let Variable::Char(_, Some(b)) = self {
             Ok(*b)
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("char", self))
         }
     }
 
@@ -253,7 +493,31 @@ impl Variable {
         if let Variable::Float(_, Some(b)) = self {
             Ok(*b)
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("float", self))
+        }
+    }
+
+    pub fn as_date_time(&self) -> Result<i64, ScriptError> {
+        if let Variable::DateTime(_, Some(b)) = self {
+            Ok(*b)
+        } else {
+            Err(ScriptError::type_mismatch("datetime", self))
+        }
+    }
+
+    pub fn as_duration(&self) -> Result<i64, ScriptError> {
+        if let Variable::Duration(_, Some(b)) = self {
+            Ok(*b)
+        } else {
+            Err(ScriptError::type_mismatch("duration", self))
+        }
+    }
+
+    pub fn as_bytes(&self) -> Result<Arc<Vec<u8>>, ScriptError> {
+        if let Variable::Bytes(_, Some(b)) = self {
+            Ok(b.clone())
+        } else {
+            Err(ScriptError::type_mismatch("bytes", self))
         }
     }
 
@@ -261,7 +525,7 @@ impl Variable {
         if let Variable::Integer(_, Some(b)) = self {
             Ok(*b)
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("integer", self))
         }
     }
 
@@ -269,7 +533,15 @@ impl Variable {
         if let Variable::String(_, Some(b)) = self {
             Ok(b.to_string())
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("string", self))
+        }
+    }
+
+    pub fn as_func(&self) -> Result<String, ScriptError> {
+        if let Variable::Func(_, Some(b)) = self {
+            Ok(b.to_string())
+        } else {
+            Err(ScriptError::type_mismatch("func", self))
         }
     }
 
@@ -277,7 +549,7 @@ impl Variable {
         if let Variable::Bool(_, Some(b)) = self {
             Ok(*b)
         } else {
-            Err(ScriptError::TypeMismatchError)
+            Err(ScriptError::type_mismatch("bool", self))
         }
     }
 
@@ -288,6 +560,9 @@ impl Variable {
             VarType::Integer => Ok(Variable::Integer(VarType::Integer, None)),
             VarType::Float => Ok(Variable::Float(VarType::Float, None)),
             VarType::Char => Ok(Variable::Char(VarType::Char, None)),
+            VarType::DateTime => Ok(Variable::DateTime(VarType::DateTime, None)),
+            VarType::Duration => Ok(Variable::Duration(VarType::Duration, None)),
+            VarType::Bytes => Ok(Variable::Bytes(VarType::Bytes, None)),
             VarType::Optional(optional_type) => {
                 Ok(Variable::Optional(VarType::Optional(optional_type), None))
             }
@@ -295,8 +570,18 @@ impl Variable {
             VarType::Map(key_type, value_type) => {
                 Ok(Variable::Map(VarType::Map(key_type, value_type), None))
             }
+            VarType::Set(value_type) => Ok(Variable::Set(VarType::Set(value_type), None)),
             VarType::InStream => Ok(Variable::InStream(VarType::InStream, None)),
             VarType::OutStream => Ok(Variable::OutStream(VarType::OutStream, None)),
+            VarType::FileStream => Ok(Variable::FileStream(VarType::FileStream, None)),
+            VarType::Func => Ok(Variable::Func(VarType::Func, None)),
+            VarType::Thread => Ok(Variable::Thread(VarType::Thread, None)),
+            VarType::Lock => Ok(Variable::Lock(VarType::Lock, None)),
+            VarType::Atomic => Ok(Variable::Atomic(VarType::Atomic, None)),
+            VarType::Limiter => Ok(Variable::Limiter(VarType::Limiter, None)),
+            VarType::HttpSession => Ok(Variable::HttpSession(VarType::HttpSession, None)),
+            #[cfg(feature = "mqtt")]
+            VarType::Mqtt => Ok(Variable::Mqtt(VarType::Mqtt, None)),
             VarType::Null => Ok(Variable::Null(VarType::Null)),
         }
     }
@@ -308,19 +593,36 @@ impl Variable {
             VarType::Integer => Ok(Variable::Integer(VarType::Integer, None)),
             VarType::Float => Ok(Variable::Float(VarType::Float, None)),
             VarType::Char => Ok(Variable::Char(VarType::Char, None)),
+            VarType::DateTime => Ok(Variable::DateTime(VarType::DateTime, None)),
+            VarType::Duration => Ok(Variable::Duration(VarType::Duration, None)),
+            VarType::Bytes => Ok(Variable::Bytes(VarType::Bytes, Some(Arc::default()))),
             VarType::Optional(optional_type) => Ok(Variable::Optional(
                 VarType::Optional(optional_type),
                 Some(None),
             )),
-            VarType::List(value_type) => {
-                Ok(Variable::List(VarType::List(value_type), Some(Vec::new())))
-            }
+            VarType::List(value_type) => Ok(Variable::List(
+                VarType::List(value_type),
+                Some(Arc::default()),
+            )),
             VarType::Map(key_type, value_type) => Ok(Variable::Map(
                 VarType::Map(key_type, value_type),
-                Some(HashMap::new()),
+                Some(Arc::default()),
+            )),
+            VarType::Set(value_type) => Ok(Variable::Set(
+                VarType::Set(value_type),
+                Some(Arc::default()),
             )),
             VarType::InStream => Ok(Variable::InStream(VarType::InStream, None)),
             VarType::OutStream => Ok(Variable::OutStream(VarType::OutStream, None)),
+            VarType::FileStream => Ok(Variable::FileStream(VarType::FileStream, None)),
+            VarType::Func => Ok(Variable::Func(VarType::Func, None)),
+            VarType::Thread => Ok(Variable::Thread(VarType::Thread, None)),
+            VarType::Lock => Ok(Variable::Lock(VarType::Lock, None)),
+            VarType::Atomic => Ok(Variable::Atomic(VarType::Atomic, None)),
+            VarType::Limiter => Ok(Variable::Limiter(VarType::Limiter, None)),
+            VarType::HttpSession => Ok(Variable::HttpSession(VarType::HttpSession, None)),
+            #[cfg(feature = "mqtt")]
+            VarType::Mqtt => Ok(Variable::Mqtt(VarType::Mqtt, None)),
             VarType::Null => Ok(Variable::Null(VarType::Null)),
         }
     }
@@ -340,7 +642,8 @@ impl Variable {
                 }),
             )),
             VarType::Null => Ok(Variable::Null(VarType::Null)),
-            VarType::String => Ok(Variable::String(VarType::String, Some(text))),
+            VarType::String => Ok(Variable::String(VarType::String, Some(Arc::new(text)))),
+            VarType::Func => Ok(Variable::Func(VarType::Func, Some(text))),
             VarType::Integer => Ok(Variable::Integer(
                 VarType::Integer,
                 Some(match text.parse() {
@@ -361,13 +664,17 @@ impl Variable {
             )),
             VarType::Char => Ok(Variable::Char(
                 VarType::Char,
-                Some(match text.parse() {
-                    Ok(i) => i,
-                    Err(_) => {
-                        return Err(ScriptError::ParseVarError);
-                    }
-                }),
+                Some(parse_char(&text).ok_or(ScriptError::ParseVarError)?),
             )),
+            VarType::DateTime => Ok(Variable::DateTime(
+                VarType::DateTime,
+                Some(parse_date_time(&text).ok_or(ScriptError::ParseVarError)?),
+            )),
+            VarType::Duration => Ok(Variable::Duration(
+                VarType::Duration,
+                Some(parse_duration(&text).ok_or(ScriptError::ParseVarError)?),
+            )),
+            VarType::Bytes => Ok(Variable::from_bytes(Some(text.into_bytes()))),
             VarType::Optional(optional_type) => {
                 if text.starts_with("[") && text.ends_with("]") {
                     let text = text[1..text.len() - 1].to_string();
@@ -411,12 +718,25 @@ impl Hash for Variable {
             Variable::Char(_, value) => {
                 value.hash(state);
             }
+            Variable::DateTime(_, value) => {
+                value.hash(state);
+            }
+            Variable::Duration(_, value) => {
+                value.hash(state);
+            }
+            Variable::Bytes(_, value) => {
+                value.hash(state);
+            }
             Variable::List(_, value) => {
                 value.hash(state);
             }
             Variable::Map(_, value) => {
                 hash(value, state);
             }
+            Variable::Set(_, value) => {
+                // equal sets can have different order, so only the size is hashed
+                value.as_ref().map(|v| v.len()).hash(state);
+            }
             Variable::Optional(_, value) => {
                 value.hash(state);
             }
@@ -426,6 +746,31 @@ impl Hash for Variable {
             Variable::OutStream(_, value) => {
                 hash(value, state);
             }
+            Variable::FileStream(_, value) => {
+                hash(value, state);
+            }
+            Variable::Func(_, value) => {
+                value.hash(state);
+            }
+            Variable::Thread(_, value) => {
+                hash(value, state);
+            }
+            Variable::Lock(_, value) => {
+                hash(value, state);
+            }
+            Variable::Atomic(_, value) => {
+                hash(value, state);
+            }
+            Variable::Limiter(_, value) => {
+                hash(value, state);
+            }
+            Variable::HttpSession(_, value) => {
+                hash(value, state);
+            }
+            #[cfg(feature = "mqtt")]
+            Variable::Mqtt(_, value) => {
+                hash(value, state);
+            }
             Variable::Null(t) => {
                 hash(t, state);
             }
@@ -447,6 +792,10 @@ impl PartialEq for Variable {
                 Variable::String(_, other_value) => value == other_value,
                 _ => false,
             },
+            Variable::Func(_, value) => match other {
+                Variable::Func(_, other_value) => value == other_value,
+                _ => false,
+            },
             Variable::Integer(_, value) => match other {
                 Variable::Integer(_, other_value) => value == other_value,
                 _ => false,
@@ -459,40 +808,43 @@ impl PartialEq for Variable {
                 Variable::Char(_, other_value) => value == other_value,
                 _ => false,
             },
+            Variable::DateTime(_, value) => match other {
+                Variable::DateTime(_, other_value) => value == other_value,
+                _ => false,
+            },
+            Variable::Duration(_, value) => match other {
+                Variable::Duration(_, other_value) => value == other_value,
+                _ => false,
+            },
+            Variable::Bytes(_, value) => match other {
+                Variable::Bytes(_, other_value) => value == other_value,
+                _ => false,
+            },
             Variable::List(_, value) => match other {
                 Variable::List(_, other_value) => value == other_value,
                 _ => false,
             },
+            Variable::Set(_, value) => match other {
+                Variable::Set(_, other_value) => value == other_value,
+                _ => false,
+            },
             Variable::Map(_, value) => match other {
                 Variable::Map(_, other_value) => match value {
                     Some(value) => match other_value {
-                        Some(other_value) => {
-                            if other_value.len() == value.len() {
-                                let mut ovi = other_value.iter();
-                                let mut vi = value.iter();
-
-                                loop {
-                                    let Some((ok, ov)) = ovi.next() else {
-                                        break;
-                                    };
-                                    let Some((k, v)) = vi.next() else {
-                                        break;
-                                    };
-                                    if k != ok || v != ov {
-                                        return false;
-                                    }
+                        Some(other_value) if other_value.len() == value.len() => {
+                            let mut ovi = other_value.iter();
+                            let mut vi = value.iter();
+
+                            while let (Some((ok, ov)), Some((k, v))) = (ovi.next(), vi.next()) {
+                                if k != ok || v != ov {
+                                    return false;
                                 }
-                                true
-                            } else {
-                                false
                             }
+                            true
                         }
-                        None => false,
-                    },
-                    None => match other_value {
-                        Some(_) => false,
-                        None => true,
+                        _ => false,
                     },
+                    None => other_value.is_none(),
                 },
                 _ => false,
             },
@@ -506,10 +858,7 @@ impl PartialEq for Variable {
                         Some(other_value) => Arc::ptr_eq(value, other_value),
                         None => false,
                     },
-                    None => match other_value {
-                        Some(_) => false,
-                        None => true,
-                    },
+                    None => other_value.is_none(),
                 },
                 _ => false,
             },
@@ -519,10 +868,78 @@ impl PartialEq for Variable {
                         Some(other_value) => Arc::ptr_eq(value, other_value),
                         None => false,
                     },
-                    None => match other_value {
-                        Some(_) => false,
-                        None => true,
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            Variable::FileStream(_, value) => match other {
+                Variable::FileStream(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
+                    },
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            Variable::Thread(_, value) => match other {
+                Variable::Thread(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
+                    },
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            Variable::Lock(_, value) => match other {
+                Variable::Lock(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
+                    },
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            Variable::Atomic(_, value) => match other {
+                Variable::Atomic(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
+                    },
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            Variable::Limiter(_, value) => match other {
+                Variable::Limiter(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
+                    },
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            Variable::HttpSession(_, value) => match other {
+                Variable::HttpSession(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
+                    },
+                    None => other_value.is_none(),
+                },
+                _ => false,
+            },
+            #[cfg(feature = "mqtt")]
+            Variable::Mqtt(_, value) => match other {
+                Variable::Mqtt(_, other_value) => match value {
+                    Some(value) => match other_value {
+                        Some(other_value) => Arc::ptr_eq(value, other_value),
+                        None => false,
                     },
+                    None => other_value.is_none(),
                 },
                 _ => false,
             },
@@ -0,0 +1,86 @@
+mod common;
+
+use common::run;
+
+use sustlang::ScriptError;
+
+const PREDICATE: &str = "FUNC bool big n int\n    INIT_VAR int limit\n    SET_VAR limit 2\n    MORE n limit result\nFUNC_END\n";
+
+fn list(json: &str, item_type: &str) -> String {
+    format!("INIT_VAR string json\nSET_VAR json {json}\nINIT_VAR list[{item_type}] items\nJSON_PARSE json items\n")
+}
+
+#[test]
+fn count_if_counts_matching_items() {
+    let globals = run(format!(
+        "{PREDICATE}{}INIT_VAR int count\nCOUNT_IF items big count\n",
+        list("[1, 5, 2, 3, 10]", "int")
+    ))
+    .unwrap();
+    assert_eq!(globals["count"].as_int().unwrap(), 3);
+
+    let globals = run(format!(
+        "{PREDICATE}{}INIT_VAR int count\nCOUNT_IF items big count\n",
+        list("[]", "int")
+    ))
+    .unwrap();
+    assert_eq!(globals["count"].as_int().unwrap(), 0);
+}
+
+#[test]
+fn sum_list_keeps_item_type() {
+    let globals = run(format!(
+        "{}INIT_VAR int sum\nSUM_LIST items sum\n",
+        list("[1, 2, 3]", "int")
+    ))
+    .unwrap();
+    assert_eq!(globals["sum"].as_int().unwrap(), 6);
+
+    let globals = run(format!(
+        "{}INIT_VAR float sum\nSUM_LIST items sum\n",
+        list("[0.5, 1.25]", "float")
+    ))
+    .unwrap();
+    assert_eq!(globals["sum"].as_float().unwrap(), 1.75);
+
+    let globals = run(format!(
+        "{}INIT_VAR int sum\nSUM_LIST items sum\n",
+        list("[]", "int")
+    ))
+    .unwrap();
+    assert_eq!(globals["sum"].as_int().unwrap(), 0);
+
+    let error = run(format!(
+        "{}INIT_VAR int sum\nSUM_LIST items sum\n",
+        list("[\"a\"]", "string")
+    ))
+    .err();
+    assert!(matches!(error, Some(ScriptError::TypeMismatchError { .. })));
+}
+
+#[test]
+fn avg_list_gives_float() {
+    let globals = run(format!(
+        "{}INIT_VAR float avg\nAVG_LIST items avg\n",
+        list("[1, 2]", "int")
+    ))
+    .unwrap();
+    assert_eq!(globals["avg"].as_float().unwrap(), 1.5);
+
+    let globals = run(format!(
+        "{}INIT_VAR float avg\nAVG_LIST items avg\n",
+        list("[0.5, 1.5, 4.0]", "float")
+    ))
+    .unwrap();
+    assert_eq!(globals["avg"].as_float().unwrap(), 2.0);
+}
+
+#[test]
+fn avg_list_of_empty_list_is_nan() {
+    let globals = run(format!(
+        "{}INIT_VAR float avg\nAVG_LIST items avg\n",
+        list("[]", "float")
+    ))
+    .unwrap();
+    assert!(globals["avg"].as_float().unwrap().is_nan());
+}
@@ -0,0 +1,94 @@
+use sustlang::{AuditEvent, AuditKind, CommandType, RunningScript, Script};
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn audit_hook_sees_touched_files() {
+    let dir = std::env::temp_dir().join(format!("sustlang-{}-audit", std::process::id()));
+    let file = dir.join("log.txt");
+    let script = Script::parse(format!(
+        "INIT_VAR string dir\nSET_VAR dir {}\nMKDIR dir\n\
+         INIT_VAR string path\nSET_VAR path {}\nINIT_VAR out_stream log\nOPEN_FILE_OUT path log\nCLOSE log\n\
+         INIT_VAR bool exists\nFILE_EXISTS path exists\n",
+        dir.display(),
+        file.display()
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let events: Arc<Mutex<Vec<AuditEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let log = events.clone();
+    RunningScript::builder(script)
+        .stdout(Vec::new())
+        .audit_hook(move |event| log.lock().unwrap().push(event.clone()))
+        .build()
+        .unwrap()
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    let events = events.lock().unwrap();
+    let touched: Vec<(AuditKind, String, CommandType, usize)> = events
+        .iter()
+        .map(|e| (e.kind, e.target.clone(), e.command, e.line))
+        .collect();
+    let dir_name = dir.display().to_string();
+    let file_name = file.display().to_string();
+    assert_eq!(
+        touched,
+        vec![
+            (AuditKind::File, dir_name, CommandType::MakeDir, 3),
+            (
+                AuditKind::File,
+                file_name.clone(),
+                CommandType::OpenFileOut,
+                7
+            ),
+            (AuditKind::File, file_name, CommandType::FileExists, 10),
+        ]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn dry_run_leaves_files_alone() {
+    let dir = std::env::temp_dir().join(format!("sustlang-{}-dry-run", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("data.txt");
+    fs::write(&file, b"keep").unwrap();
+    let script = Script::parse(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR string mode\nSET_VAR mode write\n\
+         INIT_VAR file_stream data\nOPEN_FILE path data mode\nINIT_VAR string text\nSET_VAR text gone\nWRITE text data\n\
+         INIT_VAR int offset\nSET_VAR offset 0\nINIT_VAR string whence\nSET_VAR whence start\nSEEK data offset whence\n\
+         INIT_VAR string copy\nREAD_ALL copy data\nINIT_VAR out_stream log\nOPEN_FILE_OUT path log\nWRITE text log\nCLOSE log\n\
+         FILE_DELETE path\nINIT_VAR string sub\nSET_VAR sub {}\nMKDIR sub\n",
+        file.display(),
+        dir.join("sub").display()
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let events: Arc<Mutex<Vec<AuditEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let log = events.clone();
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .dry_run(true)
+        .audit_hook(move |event| log.lock().unwrap().push(event.clone()))
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["copy"].as_str().unwrap(), "gone");
+    assert_eq!(fs::read(&file).unwrap(), b"keep");
+    assert!(!dir.join("sub").exists());
+    let commands: Vec<CommandType> = events.lock().unwrap().iter().map(|e| e.command).collect();
+    assert_eq!(
+        commands,
+        vec![
+            CommandType::OpenFile,
+            CommandType::OpenFileOut,
+            CommandType::FileDelete,
+            CommandType::MakeDir
+        ]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{builder, outcome};
+
+use sustlang::{compile_function, Instruction, Script, SymbolTable, Variable};
+
+use std::collections::HashMap;
+
+const SCRIPT: &str = "INIT_VAR int total
+SET_VAR total 0
+FUNC int twice n int
+    COPY_VAR n result
+    ADD_INT result n
+FUNC_END
+FUNC null step i int
+    INIT_VAR int value
+    USE_FUNC twice value i
+    INIT_VAR int moved
+    MOVE_VAR value moved
+    ADD_INT total moved
+    RETURN
+    SET_VAR total -1000
+FUNC_END
+FUNC null other i int
+    SET_VAR total -1
+FUNC_END
+FUNC null check
+    SET_VAR checked 1
+FUNC_END
+INIT_VAR int from
+SET_VAR from 1
+INIT_VAR int to
+SET_VAR to 10
+FOR step from to
+INIT_VAR int checked
+INIT_VAR bool done
+SET_VAR done true
+IF done check
+INIT_VAR func twice
+SET_VAR twice other
+TEMP_VAR int shadowed 5
+USE_FUNC twice null shadowed
+";
+
+fn run(bytecode: bool) -> HashMap<String, Variable> {
+    outcome(builder(SCRIPT).bytecode(bytecode))
+        .unwrap_or_else(|_| panic!("run failed"))
+        .globals
+}
+
+#[test]
+fn bytecode_matches_tree_walker() {
+    let compiled = run(true);
+    let interpreted = run(false);
+
+    // `twice` variable shadows the function of the same name, so the last call runs `other`
+    assert_eq!(compiled["total"].as_int().unwrap(), -1);
+    assert!(!compiled.contains_key("shadowed"));
+    assert_eq!(compiled["checked"].as_int().unwrap(), 1);
+    assert_eq!(interpreted["checked"].as_int().unwrap(), 1);
+    assert_eq!(
+        compiled["total"].as_int().unwrap(),
+        interpreted["total"].as_int().unwrap()
+    );
+
+    let mut compiled_names: Vec<_> = compiled.keys().collect();
+    let mut interpreted_names: Vec<_> = interpreted.keys().collect();
+    compiled_names.sort();
+    interpreted_names.sort();
+    assert_eq!(compiled_names, interpreted_names);
+}
+
+#[test]
+fn variable_commands_are_lowered() {
+    let script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let mut symbols = SymbolTable::new();
+    symbols.intern("twice");
+
+    let code = compile_function(&script.functions[0], &symbols);
+    assert!(matches!(&code[0], Instruction::CopyVar { source, target }
+        if source.root == "n" && target.root == "result"));
+
+    let code = compile_function(&script.functions[1], &symbols);
+    assert!(matches!(&code[1], Instruction::Call { .. }));
+    assert!(matches!(&code[3], Instruction::MoveVar { .. }));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{builder, outcome, SharedOutput};
+
+use sustlang::{ScriptError, ScriptOutcome, VarType, Variable};
+
+use std::io::Cursor;
+
+fn run_with_input(text: &str, input: &[u8]) -> Result<(ScriptOutcome, Vec<u8>), ScriptError> {
+    let output = SharedOutput::default();
+    let outcome = outcome(
+        builder(text)
+            .stdin(Cursor::new(input.to_vec()))
+            .stdout(output.clone()),
+    )?;
+    Ok((outcome, output.bytes()))
+}
+
+#[test]
+fn bytes_are_read_sliced_and_written() {
+    let input = [0u8, 159, 146, 150, 255, 10];
+    let (outcome, output) = run_with_input(
+        "INIT_VAR bytes data\nREAD_ALL data cin\nINIT_VAR int len\nBYTES_LEN data len\n\
+         INIT_VAR bytes middle\nCOPY_VAR data middle\nINIT_VAR int start\nSET_VAR start 1\n\
+         INIT_VAR int end\nSET_VAR end 5\nBYTES_SLICE middle start end\n\
+         INIT_VAR bytes out\nSET_VAR out >\nBYTES_CONCAT out middle\nBYTES_CONCAT out data\n\
+         INIT_VAR int out_len\nBYTES_LEN out out_len\nWRITE out cout\n",
+        &input,
+    )
+    .unwrap();
+
+    assert_eq!(outcome.globals["len"].as_int().unwrap(), 6);
+    assert_eq!(
+        outcome.globals["middle"].as_bytes().unwrap().as_slice(),
+        &[159, 146, 150, 255]
+    );
+    assert_eq!(outcome.globals["out_len"].as_int().unwrap(), 11);
+    assert_eq!(output, [b">", &input[1..5], &input[..]].concat());
+}
+
+#[test]
+fn bytes_slice_out_of_range() {
+    let error = run_with_input(
+        "INIT_VAR bytes data\nSET_VAR data abc\nINIT_VAR int start\nSET_VAR start 2\n\
+         INIT_VAR int end\nSET_VAR end 4\nBYTES_SLICE data start end\n",
+        b"",
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, ScriptError::RangeError));
+}
+
+#[test]
+fn bytes_are_a_json_array() {
+    let (outcome, _) = run_with_input(
+        "INIT_VAR bytes data\nSET_VAR data AB\nINIT_VAR string json\nJSON_STRINGIFY data json\n\
+         INIT_VAR bytes parsed\nJSON_PARSE json parsed\n",
+        b"",
+    )
+    .unwrap();
+    assert_eq!(outcome.globals["json"].as_str().unwrap(), "[65,66]");
+    assert!(outcome.globals["parsed"] == outcome.globals["data"]);
+    assert!(outcome.globals["data"].get_type() == VarType::Bytes);
+    assert!(
+        Variable::parse_var(VarType::from_name("bytes").unwrap(), String::from("hi"))
+            .unwrap()
+            .as_bytes()
+            .unwrap()
+            .as_slice()
+            == b"hi"
+    );
+}
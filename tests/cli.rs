@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn sustlang(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sustlang"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn script_file(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sustlang-{}-{}.sus", std::process::id(), name));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn missing_arguments_print_usage() {
+    let output = sustlang(&[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage:"));
+
+    let output = sustlang(&["compile"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage:"));
+}
+
+#[test]
+fn option_without_value_fails() {
+    let output = sustlang(&["--set"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`--set` requires a value"));
+}
+
+#[test]
+fn missing_script_file_fails() {
+    let output = sustlang(&["/nonexistent/script.sus"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can't read"));
+}
+
+#[test]
+fn missing_globals_file_fails() {
+    let script = script_file("globals", "INIT_VAR int x\n");
+    let output = sustlang(&[
+        "--globals",
+        "/nonexistent/globals.json",
+        script.to_str().unwrap(),
+    ]);
+    fs::remove_file(&script).unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("/nonexistent/globals.json"));
+}
+
+#[test]
+fn runtime_error_exits_with_failure() {
+    let script = script_file("runtime", "WRITE missing cout\n");
+    let output = sustlang(&[script.to_str().unwrap()]);
+    fs::remove_file(&script).unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error"));
+}
+
+#[test]
+fn dry_run_flag_leaves_files_alone() {
+    let target = std::env::temp_dir().join(format!("sustlang-{}-cli-dry-run", std::process::id()));
+    let script = script_file(
+        "dry-run",
+        &format!(
+            "INIT_VAR string dir\nSET_VAR dir {}\nMKDIR dir\n",
+            target.display()
+        ),
+    );
+    let output = sustlang(&["--dry-run", script.to_str().unwrap()]);
+    fs::remove_file(&script).unwrap();
+    assert!(output.status.success());
+    assert!(!target.exists());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("dry run: MKDIR"));
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "clipboard")]
+
+use sustlang::{RunningScript, Sandbox, Script, ScriptError};
+
+#[test]
+fn clipboard_round_trip() {
+    let script = Script::parse(
+        "INIT_VAR string text\nSET_VAR text copied from sustlang\nCLIPBOARD_SET text\n\
+         INIT_VAR string pasted\nCLIPBOARD_GET pasted\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result();
+
+    // without a desktop session there is no clipboard to talk to
+    match result {
+        Ok(outcome) => assert_eq!(
+            outcome.globals["pasted"].as_str().unwrap(),
+            "copied from sustlang"
+        ),
+        Err((error, _)) => assert!(matches!(error, ScriptError::ClipboardError)),
+    }
+}
+
+#[test]
+fn clipboard_is_denied_by_sandbox() {
+    let script = Script::parse("INIT_VAR string pasted\nCLIPBOARD_GET pasted\n".to_string())
+        .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .sandbox(Sandbox::Pure)
+        .build()
+        .unwrap()
+        .run();
+
+    assert!(matches!(result, Err((ScriptError::SandboxError(_), _))));
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use sustlang::{format_time, MockClock, RunningScript, Script, ScriptError};
+
+#[test]
+fn sleep_uses_mock_clock() {
+    let script = Script::parse(
+        "INIT_VAR int wait\nSET_VAR wait 60000\nSLEEP wait\nINIT_VAR int now\nTIME_NOW now\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let clock = Arc::new(MockClock::new(1000));
+    let started = Instant::now();
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .clock(clock.clone())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert!(started.elapsed().as_secs() < 1);
+    assert_eq!(outcome.globals["now"].as_int().unwrap(), 61000);
+}
+
+#[test]
+fn measure_times_function_with_clock() {
+    let script = Script::parse(
+        "FUNC null work\n    INIT_VAR int wait\n    SET_VAR wait 250\n    SLEEP wait\nFUNC_END\nINIT_VAR int took\nMEASURE work took\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .clock(Arc::new(MockClock::new(1000)))
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["took"].as_int().unwrap(), 250);
+}
+
+#[test]
+fn acquire_waits_for_rate_limiter() {
+    let script = Script::parse(
+        "INIT_VAR int rate\nSET_VAR rate 2\nINIT_VAR limiter limiter\nRATE_LIMIT limiter rate\n\
+         ACQUIRE limiter\nACQUIRE limiter\nINIT_VAR int burst\nTIME_NOW burst\n\
+         ACQUIRE limiter\nACQUIRE limiter\nINIT_VAR int three\nSET_VAR three 3\nACQUIRE limiter three\nINIT_VAR int now\nTIME_NOW now\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .clock(Arc::new(MockClock::new(1000)))
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["burst"].as_int().unwrap(), 1000);
+    assert_eq!(outcome.globals["now"].as_int().unwrap(), 3500);
+}
+
+#[test]
+fn time_diff_and_format_use_clock() {
+    let script = Script::parse(
+        "INIT_VAR int start\nTIME_NOW start\nINIT_VAR int wait\nSET_VAR wait 1500\nSLEEP wait\n\
+         INIT_VAR int end\nTIME_NOW end\nINIT_VAR int elapsed\nTIME_DIFF end start elapsed\n\
+         INIT_VAR string pattern\nSET_VAR pattern %Y-%m-%d %H:%M:%S.%L\nINIT_VAR string text\nTIME_FORMAT start pattern text\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .clock(Arc::new(MockClock::new(1_700_000_000_123)))
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["elapsed"].as_int().unwrap(), 1500);
+    assert_eq!(
+        outcome.globals["text"].as_str().unwrap(),
+        "2023-11-14 22:13:20.123"
+    );
+}
+
+#[test]
+fn format_time_handles_dates() {
+    assert_eq!(
+        format_time(951_782_400_000, "%Y-%m-%d").as_deref(),
+        Some("2000-02-29")
+    );
+    assert_eq!(
+        format_time(-1, "%Y-%m-%dT%H:%M:%S.%L %s").as_deref(),
+        Some("1969-12-31T23:59:59.999 -1")
+    );
+    assert_eq!(format_time(0, "100%%").as_deref(), Some("100%"));
+    assert_eq!(format_time(0, "%q"), None);
+    assert_eq!(format_time(0, "%"), None);
+}
+
+#[test]
+fn timer_stop_removes_the_timer() {
+    let script = Script::parse(
+        "INIT_VAR string name\nSET_VAR name parse\nTIMER_START name\n\
+         INIT_VAR int wait\nSET_VAR wait 400\nSLEEP wait\nINIT_VAR int took\nTIMER_STOP name took\n\
+         INIT_VAR int again\nTIMER_STOP name again\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .clock(Arc::new(MockClock::new(1000)))
+        .build()
+        .unwrap()
+        .run_with_result();
+
+    // the timer was removed by the first stop
+    match result {
+        Err((ScriptError::KeyNotFoundError(name), command)) => {
+            assert_eq!(name, "parse");
+            assert_eq!(command.line, 10);
+        }
+        _ => panic!("second stop succeeded"),
+    }
+}
+
+#[test]
+fn timer_stop_gives_elapsed_millis() {
+    let script = Script::parse(
+        "INIT_VAR string name\nSET_VAR name section\nTIMER_START name\n\
+         INIT_VAR int wait\nSET_VAR wait 400\nSLEEP wait\nINIT_VAR int took\nTIMER_STOP name took\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .clock(Arc::new(MockClock::new(1000)))
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["took"].as_int().unwrap(), 400);
+}
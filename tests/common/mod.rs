@@ -0,0 +1,80 @@
+#![allow(dead_code)]
+
+use sustlang::{RunningScript, RunningScriptBuilder, Script, ScriptError, ScriptOutcome, Variable};
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Output that can be read after the script took ownership of its clone
+#[derive(Clone, Default)]
+pub struct SharedOutput(pub Arc<Mutex<Vec<u8>>>);
+
+impl SharedOutput {
+    pub fn bytes(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Builder of the script `text` with `cout` written to memory
+pub fn builder(text: impl Into<String>) -> RunningScriptBuilder {
+    let script = Script::parse(text.into()).unwrap_or_else(|_| panic!("parse failed"));
+    RunningScript::builder(script).stdout(Vec::new())
+}
+
+pub fn outcome(builder: RunningScriptBuilder) -> Result<ScriptOutcome, ScriptError> {
+    builder
+        .build()
+        .unwrap()
+        .run_with_result()
+        .map_err(|(error, _)| error)
+}
+
+/// Runs the script `text` and returns its globals
+pub fn run(text: impl Into<String>) -> Result<HashMap<String, Variable>, ScriptError> {
+    outcome(builder(text)).map(|outcome| outcome.globals)
+}
+
+/// Server for one request, returns the request it got
+pub fn serve(answer: &'static str) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
+        loop {
+            let read = stream.read(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            let Some(head_end) = text.find("\r\n\r\n") else {
+                continue;
+            };
+            let length = text[..head_end]
+                .lines()
+                .find_map(|line| line.strip_prefix("Content-Length: "))
+                .map_or(0, |length| length.parse().unwrap());
+            if request.len() >= head_end + 4 + length {
+                break;
+            }
+        }
+        stream.write_all(answer.as_bytes()).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+    (url, handle)
+}
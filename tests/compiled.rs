@@ -0,0 +1,34 @@
+use sustlang::{Script, ScriptError};
+
+#[test]
+fn compiled_script_round_trip() {
+    let source = "FUNC map[string,list[int]] build name string count int\n\
+                  \x20   TEMP_VAR string text hello world \n\
+                  FUNC_END\n\
+                  INIT_VAR string text\n\
+                  SET_VAR text Hello World!\n\
+                  WRITE text cout\n";
+    let script = Script::parse(source.to_string()).unwrap();
+    let bytes = script.to_bytes();
+    let loaded = Script::from_bytes(&bytes).unwrap();
+
+    assert_eq!(loaded.commands, script.commands);
+    assert_eq!(loaded.functions, script.functions);
+    assert_eq!(loaded.to_bytes(), bytes);
+}
+
+#[test]
+fn compiled_script_rejects_garbage() {
+    assert!(matches!(
+        Script::from_bytes(b"not a script"),
+        Err(ScriptError::CompiledScriptError)
+    ));
+
+    let bytes = Script::parse("INIT_VAR int a\n".to_string())
+        .unwrap()
+        .to_bytes();
+    assert!(matches!(
+        Script::from_bytes(&bytes[..bytes.len() - 2]),
+        Err(ScriptError::CompiledScriptError)
+    ));
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use sustlang::{FromVariable, IntoVariable, VarType, Variable};
+
+fn round_trip<T: IntoVariable + FromVariable>(value: T) -> T {
+    T::from_variable(value.into_variable()).unwrap()
+}
+
+#[test]
+fn scalars_round_trip() {
+    assert!(round_trip(true));
+    assert_eq!(round_trip(String::from("text")), "text");
+    assert_eq!(round_trip(-42isize), -42);
+    assert_eq!(round_trip(1.5f64), 1.5);
+    assert_eq!(round_trip('ы'), 'ы');
+}
+
+#[test]
+fn containers_round_trip() {
+    assert_eq!(round_trip(vec![1isize, 2, 3]), vec![1, 2, 3]);
+    assert_eq!(round_trip(Vec::<String>::new()), Vec::<String>::new());
+    assert_eq!(round_trip(Some(5isize)), Some(5));
+    assert_eq!(round_trip(None::<isize>), None);
+
+    let mut map = HashMap::new();
+    map.insert(String::from("a"), vec![Some(1isize), None]);
+    map.insert(String::from("b"), Vec::new());
+    assert_eq!(round_trip(map.clone()), map);
+}
+
+#[test]
+fn types_are_set() {
+    assert_eq!(
+        Vec::<isize>::new().into_variable().get_type(),
+        VarType::List(Box::new(VarType::Integer))
+    );
+    assert_eq!(
+        HashMap::<String, bool>::new().into_variable().get_type(),
+        VarType::Map(Box::new(VarType::String), Box::new(VarType::Bool))
+    );
+    assert_eq!(
+        Variable::from(None::<f64>).get_type(),
+        VarType::Optional(Box::new(VarType::Float))
+    );
+}
+
+#[test]
+fn from_and_try_from() {
+    let var = Variable::from("text");
+    assert_eq!(String::try_from(var.clone()).unwrap(), "text");
+    assert!(isize::try_from(var).is_err());
+
+    let var = Variable::from(vec![true, false]);
+    assert_eq!(Vec::<bool>::try_from(var).unwrap(), vec![true, false]);
+}
@@ -0,0 +1,41 @@
+use sustlang::{RunningScript, Script, Variable};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[test]
+fn copies_share_data_until_changed() {
+    let script = Script::parse(
+        "COPY_VAR ints copy\nCOPY_VAR ints changed\nSET_VAR changed.0 10\n".to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let ints: Vec<isize> = vec![1, 2, 3];
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .global_var("ints", ints)
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    let list = |name: &str| outcome.globals[name].as_list().unwrap();
+    assert!(Arc::ptr_eq(&list("ints"), &list("copy")));
+    assert!(!Arc::ptr_eq(&list("ints"), &list("changed")));
+    assert_eq!(list("ints")[0].as_int().unwrap(), 1);
+    assert_eq!(list("changed")[0].as_int().unwrap(), 10);
+}
+
+#[test]
+fn shared_map_is_cloned_on_write() {
+    let map: HashMap<String, isize> = HashMap::from([("a".to_string(), 1)]);
+    let var = Variable::from(map);
+    let mut copy = var.clone();
+
+    if let Variable::Map(_, Some(entries)) = &mut copy {
+        Arc::make_mut(entries).clear();
+    }
+
+    assert_eq!(var.as_map().unwrap().len(), 1);
+    assert!(copy.as_map().unwrap().is_empty());
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{builder, outcome};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sustlang::{MockClock, ScriptError, VarType, Variable};
+
+fn run(text: &str) -> Result<HashMap<String, Variable>, ScriptError> {
+    let clock = Arc::new(MockClock::new(1_700_000_000_123));
+    outcome(builder(text).clock(clock)).map(|o| o.globals)
+}
+
+#[test]
+fn datetime_literals_round_trip() {
+    let date = Variable::parse_var(VarType::DateTime, "2024-02-29T23:59:59.500Z".to_string())
+        .unwrap_or_else(|_| panic!("parse failed"));
+    assert_eq!(date.to_string().unwrap(), "2024-02-29T23:59:59.500Z");
+
+    let date = Variable::parse_var(VarType::DateTime, "2024-03-01".to_string())
+        .unwrap_or_else(|_| panic!("parse failed"));
+    assert_eq!(date.to_string().unwrap(), "2024-03-01T00:00:00.000Z");
+
+    assert!(Variable::parse_var(VarType::DateTime, "2023-02-29".to_string()).is_err());
+    assert!(Variable::parse_var(VarType::DateTime, "tomorrow".to_string()).is_err());
+}
+
+#[test]
+fn date_commands_parse_add_format_and_compare() {
+    let globals = run(
+        "INIT_VAR string text\nSET_VAR text 01.03.2024 12:30\nINIT_VAR string pattern\nSET_VAR pattern %d.%m.%Y %H:%M\n\
+         INIT_VAR datetime start\nDATE_PARSE text pattern start\n\
+         INIT_VAR datetime end\nCOPY_VAR start end\nINIT_VAR int day\nSET_VAR day 86400000\nDATE_ADD end day\n\
+         INIT_VAR string formatted\nSET_VAR pattern %Y/%m/%d %H:%M\nDATE_FORMAT end pattern formatted\n\
+         INIT_VAR bool later\nMORE end start later\nINIT_VAR bool earlier\nLESS end start earlier\n\
+         INIT_VAR datetime now\nTIME_NOW now\n",
+    )
+    .unwrap();
+
+    assert_eq!(globals["formatted"].as_str().unwrap(), "2024/03/02 12:30");
+    assert!(globals["later"].as_bool().unwrap());
+    assert!(!globals["earlier"].as_bool().unwrap());
+    assert_eq!(
+        globals["now"].to_string().unwrap(),
+        "2023-11-14T22:13:20.123Z"
+    );
+
+    let error = run(
+        "INIT_VAR string text\nSET_VAR text 31.02.2024\nINIT_VAR string pattern\nSET_VAR pattern %d.%m.%Y\n\
+         INIT_VAR datetime date\nDATE_PARSE text pattern date\n",
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, ScriptError::ParseVarError));
+
+    let error = run(
+        "INIT_VAR datetime date\nSET_VAR date 2024-01-01\nINIT_VAR int number\nSET_VAR number 5\n\
+         INIT_VAR bool later\nMORE date number later\n",
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, ScriptError::TypeMismatchError { .. }));
+}
+
+#[test]
+fn datetime_is_a_json_string() {
+    let globals = run(
+        "INIT_VAR datetime date\nSET_VAR date 2024-01-01T08:00:00Z\nINIT_VAR string json\nJSON_STRINGIFY date json\n\
+         INIT_VAR datetime parsed\nJSON_PARSE json parsed\n",
+    )
+    .unwrap();
+    assert_eq!(
+        globals["json"].as_str().unwrap(),
+        "\"2024-01-01T08:00:00.000Z\""
+    );
+    assert!(globals["parsed"] == globals["date"]);
+}
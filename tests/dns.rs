@@ -0,0 +1,37 @@
+use sustlang::{RunningScript, Script, ScriptError, Variable};
+
+use std::collections::HashMap;
+
+fn resolve(host: &str) -> Result<HashMap<String, Variable>, ScriptError> {
+    let script = Script::parse(format!(
+        "INIT_VAR string host\nSET_VAR host {}\nINIT_VAR list[string] ips\nDNS_RESOLVE host ips\n",
+        host
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .map(|outcome| outcome.globals)
+        .map_err(|(error, _)| error)
+}
+
+#[test]
+fn dns_resolve_returns_ip_list() {
+    let vars = resolve("127.0.0.1").unwrap();
+    let ips = vars["ips"].as_list().unwrap();
+
+    assert_eq!(ips.len(), 1);
+    assert_eq!(ips[0].as_str().unwrap(), "127.0.0.1");
+}
+
+#[test]
+fn dns_resolve_reports_unknown_host() {
+    let result = resolve("sustlang-missing-host.invalid");
+
+    assert!(
+        matches!(result, Err(ScriptError::DnsError(host)) if host == "sustlang-missing-host.invalid")
+    );
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{builder, outcome};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sustlang::{format_duration, parse_duration, Clock, MockClock, ScriptError, Variable};
+
+fn run(text: &str, clock: Arc<MockClock>) -> Result<HashMap<String, Variable>, ScriptError> {
+    outcome(builder(text).clock(clock)).map(|o| o.globals)
+}
+
+#[test]
+fn duration_text() {
+    assert_eq!(format_duration(90_000), "1m30s");
+    assert_eq!(format_duration(3_601_500), "1h1s500ms");
+    assert_eq!(format_duration(-250), "-250ms");
+    assert_eq!(format_duration(0), "0ms");
+
+    assert_eq!(parse_duration("1m30s"), Some(90_000));
+    assert_eq!(parse_duration("2h15ms"), Some(7_200_015));
+    assert_eq!(parse_duration("-1s"), Some(-1000));
+    assert_eq!(parse_duration("1500"), Some(1500));
+    assert_eq!(parse_duration(""), None);
+    assert_eq!(parse_duration("5 days"), None);
+}
+
+#[test]
+fn duration_helpers_and_sleep() {
+    let clock = Arc::new(MockClock::new(1000));
+    let globals = run(
+        "INIT_VAR int ms\nSET_VAR ms 250\nINIT_VAR duration short\nDURATION_MS ms short\n\
+         INIT_VAR float sec\nSET_VAR sec 1.5\nINIT_VAR duration medium\nDURATION_SEC sec medium\n\
+         INIT_VAR int min\nSET_VAR min 2\nINIT_VAR duration long\nDURATION_MIN min long\n\
+         INIT_VAR duration literal\nSET_VAR literal 2m\nINIT_VAR bool same\nEQUALS long literal same\n\
+         INIT_VAR bool shorter\nLESS short medium shorter\n\
+         INIT_VAR int total\nDURATION_TO_MS medium total\n\
+         SLEEP short\nSLEEP medium\n",
+        clock.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(globals["long"].to_string().unwrap(), "2m");
+    assert!(globals["same"].as_bool().unwrap());
+    assert!(globals["shorter"].as_bool().unwrap());
+    assert_eq!(globals["total"].as_int().unwrap(), 1500);
+    assert_eq!(clock.now_millis(), 2750);
+
+    let error = run(
+        "INIT_VAR duration back\nSET_VAR back -1s\nSLEEP back\n",
+        Arc::new(MockClock::new(0)),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, ScriptError::RangeError));
+}
+
+#[test]
+fn sleep_until_does_not_drift() {
+    let clock = Arc::new(MockClock::new(1_000_000));
+    let period =
+        "INIT_VAR duration period\nSET_VAR period 1s\nINIT_VAR duration work\nSET_VAR work 300ms\n\
+                  INIT_VAR datetime next\nTIME_NOW next\n";
+    let tick = "SLEEP work\nDATE_ADD next period\nSLEEP_UNTIL next\n";
+    let globals = run(
+        &format!("{}{}{}{}", period, tick, tick, tick),
+        clock.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(clock.now_millis(), 1_003_000);
+    assert_eq!(globals["next"].as_date_time().unwrap(), 1_003_000);
+
+    // a deadline in the past doesn't wait
+    let clock = Arc::new(MockClock::new(5000));
+    run(
+        "INIT_VAR int deadline\nSET_VAR deadline 4000\nSLEEP_UNTIL deadline\n",
+        clock.clone(),
+    )
+    .unwrap();
+    assert_eq!(clock.now_millis(), 5000);
+}
@@ -0,0 +1,42 @@
+use sustlang::{RunningScript, Script, ScriptError};
+
+fn run_error(text: &str) -> ScriptError {
+    let script = Script::parse(text.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    match RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run()
+    {
+        Ok(_) => panic!("run succeeded"),
+        Err((error, _)) => error,
+    }
+}
+
+#[test]
+fn errors_render_context() {
+    let error = run_error("WRITE missing cout\n");
+    assert_eq!(error.to_string(), "unknown variable `missing`");
+    assert_eq!(format!("{:?}", error), "UnknownVarError");
+
+    let error = run_error("INIT_VAR int number\nSET_VAR number 5\nWRITE number cout\n");
+    assert_eq!(
+        error.to_string(),
+        "type mismatch: expected string, char, bytes or list[char], got integer"
+    );
+    assert_eq!(format!("{:?}", error), "TypeMismatchError");
+
+    let error = run_error("INIT_VAR list[nope] items\n");
+    assert_eq!(error.to_string(), "unknown type `nope`");
+
+    let error = run_error("USE_FUNC nothing null\n");
+    assert_eq!(error.to_string(), "unknown function `nothing`");
+}
+
+#[test]
+fn unknown_command_is_named() {
+    match Script::parse("NOT_A_COMMAND\n".to_string()) {
+        Err((error, _)) => assert_eq!(error.to_string(), "unknown command `NOT_A_COMMAND`"),
+        Ok(_) => panic!("parse succeeded"),
+    }
+}
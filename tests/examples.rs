@@ -0,0 +1,21 @@
+use sustlang::{run_example, Example, ScriptError, EXAMPLES};
+
+#[test]
+fn examples_give_expected_output() {
+    for example in EXAMPLES {
+        let output = example
+            .run()
+            .unwrap_or_else(|e| panic!("example {} failed: {}", example.name, e));
+        assert_eq!(output, example.output, "output of example {}", example.name);
+    }
+}
+
+#[test]
+fn examples_are_found_by_name() {
+    assert_eq!(run_example("hello_world").unwrap(), "Hello World!\n");
+    assert!(Example::find("input").is_some());
+    assert!(matches!(
+        run_example("missing"),
+        Err(ScriptError::FileNotFoundError(_))
+    ));
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "serde")]
+
+use sustlang::{RunningScript, Script};
+
+use std::fs;
+
+#[test]
+fn export_globals_writes_json() {
+    let script = Script::parse(
+        "INIT_VAR int count\nSET_VAR count 3\nINIT_VAR string json\nSET_VAR json [\"a\"]\n\
+         INIT_VAR list[string] names\nJSON_PARSE json names\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    let path = std::env::temp_dir().join(format!("sustlang-{}-globals.json", std::process::id()));
+    outcome.export_globals(&path).unwrap();
+
+    let value: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(value["count"], 3);
+    assert_eq!(value["names"], serde_json::json!(["a"]));
+    assert!(value["cout"].is_null());
+    fs::remove_file(path).unwrap();
+}
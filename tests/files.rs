@@ -0,0 +1,53 @@
+mod common;
+
+use common::run;
+
+use sustlang::ScriptError;
+
+use std::fs;
+
+#[test]
+fn manages_files_and_folders() {
+    let dir = std::env::temp_dir().join(format!("sustlang-{}-files", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let dir = dir.display();
+
+    let globals = run(format!(
+        "INIT_VAR string dir\nSET_VAR dir {dir}/nested\nMKDIR dir\n\
+         INIT_VAR string a\nSET_VAR a {dir}/nested/a.txt\nINIT_VAR out_stream file\nOPEN_FILE_OUT a file\n\
+         INIT_VAR string text\nSET_VAR text hello\nWRITE text file\nCLOSE file\n\
+         INIT_VAR string b\nSET_VAR b {dir}/nested/b.txt\nFILE_COPY a b\n\
+         INIT_VAR string c\nSET_VAR c {dir}/nested/c.txt\nFILE_RENAME a c\n\
+         INIT_VAR bool a_exists\nFILE_EXISTS a a_exists\nINIT_VAR bool is_folder\nIS_FOLDER dir is_folder\n\
+         INIT_VAR list[string] files\nLIST_DIR dir files\nFILE_DELETE b\nINIT_VAR list[string] after\nFOLDER_LIST dir after\n"
+    ))
+    .unwrap_or_else(|_| panic!("run failed"));
+
+    let names = |name: &str| -> Vec<String> {
+        globals[name]
+            .as_list()
+            .unwrap()
+            .iter()
+            .map(|path| {
+                path.as_str()
+                    .unwrap()
+                    .rsplit('/')
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+    assert!(!globals["a_exists"].as_bool().unwrap());
+    assert!(globals["is_folder"].as_bool().unwrap());
+    assert_eq!(names("files"), ["b.txt", "c.txt"]);
+    assert_eq!(names("after"), ["c.txt"]);
+    assert_eq!(fs::read(format!("{dir}/nested/c.txt")).unwrap(), b"hello");
+
+    let missing = run(format!(
+        "INIT_VAR string path\nSET_VAR path {dir}/missing.txt\nFILE_DELETE path\n"
+    ));
+    assert!(matches!(missing, Err(ScriptError::FileNotFoundError(_))));
+
+    fs::remove_dir_all(format!("{dir}")).unwrap();
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{builder, outcome};
+
+use std::collections::HashMap;
+
+use sustlang::{ScriptError, Variable};
+
+fn run(text: &str, bytecode: bool) -> Result<HashMap<String, Variable>, ScriptError> {
+    outcome(builder(text).bytecode(bytecode)).map(|outcome| outcome.globals)
+}
+
+#[test]
+fn return_writes_result() {
+    let text =
+        "FUNC int pick n int\n    COPY_VAR n result\n    RETURN\n    SET_VAR result -1\nFUNC_END\n\
+        INIT_VAR int n\nSET_VAR n 7\nINIT_VAR int picked\nUSE_FUNC pick picked n\n";
+
+    for bytecode in [true, false] {
+        let globals = run(text, bytecode).unwrap();
+        assert_eq!(globals["picked"].as_int().unwrap(), 7);
+    }
+}
+
+#[test]
+fn call_with_wrong_number_of_args_fails() {
+    let text = "FUNC int pick n int\n    COPY_VAR n result\nFUNC_END\n\
+        INIT_VAR int n\nSET_VAR n 7\nINIT_VAR int picked\n";
+
+    for bytecode in [true, false] {
+        let error = run(&format!("{text}USE_FUNC pick picked n n\n"), bytecode).err();
+        assert!(matches!(error, Some(ScriptError::CommandArgsInvalidError)));
+
+        let error = run(&format!("{text}USE_FUNC pick picked\n"), bytecode).err();
+        assert!(matches!(error, Some(ScriptError::CommandArgsInvalidError)));
+    }
+}
+
+#[test]
+fn memo_hit_writes_local_result() {
+    let text = "FUNC int twice n int\n    COPY_VAR n result\n    ADD_INT result n\nFUNC_END\nMEMO_FUNC twice\n\
+        FUNC int both n int\n    INIT_VAR int first\n    USE_FUNC twice first n\n\
+        INIT_VAR int second\n    USE_FUNC twice second n\n    COPY_VAR first result\n    ADD_INT result second\nFUNC_END\n\
+        INIT_VAR int n\nSET_VAR n 5\nINIT_VAR int sum\nUSE_FUNC both sum n\n";
+
+    for bytecode in [true, false] {
+        let globals = run(text, bytecode).unwrap();
+        assert_eq!(globals["sum"].as_int().unwrap(), 20);
+    }
+}
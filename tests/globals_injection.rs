@@ -0,0 +1,61 @@
+mod common;
+
+use sustlang::{RunningScript, RunningScriptBuilder, ScriptError, Variable};
+
+use std::collections::HashMap;
+
+const SCRIPT: &str =
+    "INIT_VAR int one\nSET_VAR one 1\nINIT_VAR int next\nCOPY_VAR port next\nADD_INT next one\n";
+
+fn build(
+    builder: impl FnOnce(RunningScriptBuilder) -> RunningScriptBuilder,
+) -> Result<RunningScript, ScriptError> {
+    builder(common::builder(SCRIPT)).build()
+}
+
+fn run(running_script: RunningScript) -> HashMap<String, Variable> {
+    running_script
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"))
+        .globals
+}
+
+#[test]
+fn global_assignment_parses_typed_values() {
+    let vars = run(build(|b| {
+        b.global_assignment("port:int=8080")
+            .global_assignment("host=a=b")
+    })
+    .unwrap());
+
+    assert_eq!(vars["next"].as_int().unwrap(), 8081);
+    assert_eq!(vars["host"].as_str().unwrap(), "a=b");
+}
+
+#[test]
+fn globals_json_is_overridden_by_assignments() {
+    let vars = run(build(|b| {
+        b.global_assignment("port:int=1")
+            .globals_json(r#"{"port": 8080, "tags": ["a", "b"]}"#)
+    })
+    .unwrap());
+
+    assert_eq!(vars["next"].as_int().unwrap(), 2);
+    assert_eq!(vars["tags"].as_list().unwrap().len(), 2);
+}
+
+#[test]
+fn invalid_globals_are_build_errors() {
+    assert!(matches!(
+        build(|b| b.global_assignment("port")),
+        Err(ScriptError::ParseVarError)
+    ));
+    assert!(matches!(
+        build(|b| b.global_assignment("port:number=1")),
+        Err(ScriptError::TypeUnknownError(_))
+    ));
+    assert!(matches!(
+        build(|b| b.globals_json("[1]")),
+        Err(ScriptError::ParseVarError)
+    ));
+}
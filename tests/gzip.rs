@@ -0,0 +1,91 @@
+#![cfg(feature = "gzip")]
+
+mod common;
+
+use common::run;
+
+use sustlang::{Script, ScriptError};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use std::io::Write;
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn compressed_data_is_read_back() {
+    let vars = run("INIT_VAR in_stream input
+INIT_VAR out_stream output
+OPEN_BUFFER input output
+INIT_VAR out_stream packed
+GZIP_WRAP_OUT output packed
+INIT_VAR string text
+SET_VAR text hello hello hello
+WRITE text packed
+CLOSE packed
+INIT_VAR in_stream plain
+GUNZIP_WRAP_IN input plain
+INIT_VAR string unpacked
+READ_ALL unpacked plain
+")
+    .unwrap();
+
+    assert_eq!(vars["unpacked"].as_str().unwrap(), "hello hello hello");
+}
+
+#[test]
+fn gzip_file_is_read() {
+    let path = std::env::temp_dir().join(format!("sustlang-gzip-{}.gz", std::process::id()));
+    std::fs::write(&path, gzip(b"line one\nline two\n")).unwrap();
+
+    let vars = run(format!(
+        "INIT_VAR string path
+SET_VAR path {}
+INIT_VAR in_stream file
+OPEN_FILE_IN path file
+INIT_VAR in_stream plain
+GUNZIP_WRAP_IN file plain
+INIT_VAR string first
+READ_LINE first plain
+",
+        path.display()
+    ));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(vars.unwrap()["first"].as_str().unwrap(), "line one");
+}
+
+#[test]
+fn broken_data_is_read_error() {
+    let result = run("INIT_VAR in_stream input
+INIT_VAR out_stream output
+OPEN_BUFFER input output
+INIT_VAR string text
+SET_VAR text not gzip at all
+WRITE text output
+INIT_VAR in_stream plain
+GUNZIP_WRAP_IN input plain
+INIT_VAR string unpacked
+READ_ALL unpacked plain
+");
+
+    assert!(matches!(result, Err(ScriptError::StreamReadError)));
+}
+
+#[test]
+fn wrapping_checks_direction() {
+    let script = Script::parse(
+        "INIT_VAR in_stream input\nINIT_VAR out_stream output\nOPEN_BUFFER input output\nINIT_VAR out_stream packed\nGZIP_WRAP_OUT input packed\n"
+            .to_string(),
+    );
+
+    assert!(matches!(
+        script,
+        Err((ScriptError::StreamDirectionError, 5))
+    ));
+}
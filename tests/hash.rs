@@ -0,0 +1,41 @@
+use sustlang::{RunningScript, Script, ScriptError, Variable};
+
+use std::collections::HashMap;
+
+fn hash(algo: &str) -> Result<HashMap<String, Variable>, ScriptError> {
+    let text = format!(
+        "INIT_VAR string algo\nSET_VAR algo {}\nINIT_VAR string input\nSET_VAR input abc\nINIT_VAR string digest\nHASH algo input digest\n",
+        algo
+    );
+    let script = Script::parse(text).unwrap_or_else(|_| panic!("parse failed"));
+    RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .map(|outcome| outcome.globals)
+        .map_err(|(error, _)| error)
+}
+
+#[test]
+fn hash_gives_hex_digests() {
+    for (algo, digest) in [
+        (
+            "sha256",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        ("md5", "900150983cd24fb0d6963f7d28e17f72"),
+        ("crc32", "352441c2"),
+    ] {
+        let vars = hash(algo).unwrap();
+        assert_eq!(vars["digest"].as_str().unwrap(), digest);
+    }
+}
+
+#[test]
+fn unknown_algorithm_is_invalid() {
+    assert!(matches!(
+        hash("sha3"),
+        Err(ScriptError::CommandArgsInvalidError)
+    ));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{run, serve};
+
+use sustlang::{http_request, ScriptError, Variable};
+
+use std::time::Duration;
+
+#[test]
+fn http_get_reads_status_and_body() {
+    let (url, server) = serve("HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+
+    let vars = run(format!(
+        "INIT_VAR string url\nSET_VAR url {}/path?q=1\nINIT_VAR int status\nINIT_VAR string body\nHTTP_GET url status body\n",
+        url
+    ))
+    .unwrap();
+    let request = server.join().unwrap();
+
+    assert!(request.starts_with("GET /path?q=1 HTTP/1.1\r\n"));
+    assert_eq!(vars["status"].as_int().unwrap(), 200);
+    assert_eq!(vars["body"].as_str().unwrap(), "hello");
+}
+
+#[test]
+fn http_request_sends_method_headers_and_body() {
+    let (url, server) = serve(
+        "HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\nX-Id: 7\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n",
+    );
+
+    let vars = run(format!(
+        "INIT_VAR string url\nSET_VAR url {}\nINIT_VAR string method\nSET_VAR method POST\n\
+         INIT_VAR string json\nSET_VAR json {{\"X-Token\":\"secret\"}}\nINIT_VAR map[string,string] headers\nJSON_PARSE json headers\n\
+         INIT_VAR string payload\nSET_VAR payload payload\nINIT_VAR int status\nINIT_VAR string body\n\
+         INIT_VAR map[string,string] response_headers\n\
+         HTTP_REQUEST method url headers payload status body response_headers\n",
+        url
+    ))
+    .unwrap();
+    let request = server.join().unwrap();
+
+    assert!(request.starts_with("POST / HTTP/1.1\r\n"));
+    assert!(request.contains("X-Token: secret\r\n"));
+    assert!(request.contains("Content-Length: 7\r\n"));
+    assert!(request.ends_with("\r\n\r\npayload"));
+    assert_eq!(vars["status"].as_int().unwrap(), 201);
+    assert_eq!(vars["body"].as_str().unwrap(), "abcde");
+    let response_headers = vars["response_headers"].as_map().unwrap();
+    assert_eq!(
+        response_headers[&Variable::from_str(Some("x-id".to_string()))]
+            .as_str()
+            .unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn http_get_rejects_other_schemes() {
+    let result = run(
+        "INIT_VAR string url\nSET_VAR url https://example.com\nINIT_VAR int status\nINIT_VAR string body\nHTTP_GET url status body\n"
+            .to_string(),
+    );
+
+    assert!(matches!(result, Err(ScriptError::CommandArgsInvalidError)));
+}
+
+#[test]
+fn set_cookie_headers_are_kept_apart() {
+    let (url, server) = serve(
+        "HTTP/1.1 200 OK\r\nSet-Cookie: sid=abc123; Expires=Wed, 21 Oct 2037 07:28:00 GMT\r\n\
+         Set-Cookie: theme=dark\r\nContent-Length: 0\r\n\r\n",
+    );
+
+    let response = http_request("GET", &url, &[], &[], Duration::from_secs(5))
+        .unwrap_or_else(|_| panic!("request failed"));
+    server.join().unwrap();
+
+    assert_eq!(
+        response.set_cookies,
+        [
+            "sid=abc123; Expires=Wed, 21 Oct 2037 07:28:00 GMT",
+            "theme=dark"
+        ]
+    );
+}
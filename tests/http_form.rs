@@ -0,0 +1,72 @@
+use sustlang::{RunningScript, Script};
+
+use std::collections::HashMap;
+use std::fs;
+
+#[test]
+fn form_fields_are_url_encoded() {
+    let script = Script::parse(
+        "INIT_VAR string body\nINIT_VAR string content_type\nHTTP_FORM_ENCODE fields body content_type\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let fields = HashMap::from([
+        ("q".to_string(), "rust & sust".to_string()),
+        ("lang".to_string(), "ру".to_string()),
+    ]);
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .global_var("fields", fields)
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(
+        outcome.globals["body"].as_str().unwrap(),
+        "lang=%D1%80%D1%83&q=rust+%26+sust"
+    );
+    assert_eq!(
+        outcome.globals["content_type"].as_str().unwrap(),
+        "application/x-www-form-urlencoded"
+    );
+}
+
+#[test]
+fn multipart_body_contains_fields_and_files() {
+    let path = std::env::temp_dir().join(format!("sustlang-{}-upload.txt", std::process::id()));
+    fs::write(&path, b"file data").unwrap();
+
+    let script = Script::parse(
+        "INIT_VAR string body\nINIT_VAR string content_type\nMULTIPART_BUILD fields files body content_type\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let fields = HashMap::from([("name".to_string(), "Bob".to_string())]);
+    let files = HashMap::from([("upload".to_string(), path.display().to_string())]);
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .global_var("fields", fields)
+        .global_var("files", files)
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+    fs::remove_file(&path).unwrap();
+
+    let content_type = outcome.globals["content_type"].as_str().unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    assert_eq!(
+        outcome.globals["body"].as_str().unwrap(),
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nBob\r\n\
+             --{boundary}\r\nContent-Disposition: form-data; name=\"upload\"; filename=\"{file_name}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\nfile data\r\n--{boundary}--\r\n"
+        )
+    );
+}
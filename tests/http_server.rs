@@ -0,0 +1,136 @@
+mod common;
+
+use common::SharedOutput;
+
+use sustlang::{RunningScript, Script, Variable};
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+const SCRIPT: &str = "FUNC map[string,string] handle method string path string headers map[string,string] body string
+    INIT_VAR string reply
+    COPY_VAR method reply
+    ADD_STR reply path
+    ADD_STR reply body
+    COPY_VAR reply result.body
+    INIT_VAR string status
+    SET_VAR status 201
+    COPY_VAR status result.status
+    INIT_VAR string agent
+    SET_VAR agent test
+    COPY_VAR agent result.X-Agent
+FUNC_END
+INIT_VAR string addr
+SET_VAR addr 127.0.0.1
+OPEN_HTTP_LISTENER addr port handle
+";
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn request(port: u16, text: &str) -> String {
+    for _ in 0..100 {
+        if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) {
+            stream.write_all(text.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            return response;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("server did not start");
+}
+
+#[test]
+fn http_listener_calls_handler() {
+    let port = free_port();
+    let script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let running_script = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .global_var("port", Variable::from_int(Some(port as isize)))
+        .build()
+        .unwrap();
+    thread::spawn(move || running_script.run());
+
+    let response = request(
+        port,
+        "POST /items?id=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\n\r\ndata",
+    );
+
+    assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+    assert!(response.contains("X-Agent: test\r\n"));
+    assert!(response.ends_with("\r\n\r\nPOST/items?id=1data"));
+}
+
+#[test]
+fn handler_error_is_written_to_cerr() {
+    let port = free_port();
+    let script = Script::parse(
+        "FUNC map[string,string] handle method string path string headers map[string,string] body string
+    INIT_VAR int number
+    COPY_VAR number result.body
+FUNC_END
+INIT_VAR string addr
+SET_VAR addr 127.0.0.1
+OPEN_HTTP_LISTENER addr port handle
+"
+        .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+    let errors = SharedOutput::default();
+    let running_script = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .stderr(errors.clone())
+        .global_var("port", Variable::from_int(Some(port as isize)))
+        .build()
+        .unwrap();
+    thread::spawn(move || running_script.run());
+
+    let response = request(port, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 500 "));
+
+    for _ in 0..100 {
+        if !errors.bytes().is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    let errors = String::from_utf8(errors.bytes()).unwrap();
+    assert!(errors.starts_with("error (TypeMismatchError): "));
+}
+
+#[test]
+fn oversized_request_body_is_rejected() {
+    let port = free_port();
+    let script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let errors = SharedOutput::default();
+    let running_script = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .stderr(errors.clone())
+        .global_var("port", Variable::from_int(Some(port as isize)))
+        .build()
+        .unwrap();
+    thread::spawn(move || running_script.run());
+
+    let response = request(
+        port,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1000000000000\r\n\r\ndata",
+    );
+    assert!(response.is_empty());
+
+    for _ in 0..100 {
+        if !errors.bytes().is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    let errors = String::from_utf8(errors.bytes()).unwrap();
+    assert!(errors.starts_with("error (NetworkError): "));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{run, serve};
+
+use sustlang::{HttpSession, RunningScript, Script};
+
+#[test]
+fn session_keeps_cookies_and_headers() {
+    let script = Script::parse(
+        "INIT_VAR http_session session\nHTTP_SESSION_NEW session\n\
+         INIT_VAR string name\nSET_VAR name User-Agent\nINIT_VAR string value\nSET_VAR value sust\nHTTP_SESSION_SET_HEADER session name value\n\
+         INIT_VAR string cookie\nSET_VAR cookie sid=abc123; Path=/; HttpOnly\nHTTP_SESSION_STORE_COOKIE session cookie\n\
+         SET_VAR cookie theme=dark\nHTTP_SESSION_STORE_COOKIE session cookie\n\
+         INIT_VAR map[string,string] headers\nHTTP_SESSION_HEADERS session headers\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    let headers = outcome.globals["headers"].as_map().unwrap();
+    let header = |name: &str| headers[&name.into()].as_str().unwrap();
+    assert_eq!(header("User-Agent"), "sust");
+    assert_eq!(header("Cookie"), "sid=abc123; theme=dark");
+}
+
+#[test]
+fn expired_cookie_is_removed() {
+    let session = HttpSession::new();
+    session.store_cookie("sid=abc123");
+    assert_eq!(session.cookie("sid").as_deref(), Some("abc123"));
+
+    session.store_cookie("sid=gone; Max-Age=0");
+    assert_eq!(session.cookie("sid"), None);
+    assert!(!session.request_headers().contains_key("Cookie"));
+}
+
+#[test]
+fn http_commands_use_session() {
+    let (login_url, login) = serve(
+        "HTTP/1.1 200 OK\r\nSet-Cookie: sid=abc123; Expires=Wed, 21 Oct 2037 07:28:00 GMT\r\n\
+         Set-Cookie: theme=dark\r\nContent-Length: 0\r\n\r\n",
+    );
+    let (api_url, api) = serve("HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n");
+
+    let vars = run(format!(
+        "INIT_VAR http_session session\nHTTP_SESSION_NEW session\n\
+         INIT_VAR string name\nSET_VAR name User-Agent\nINIT_VAR string value\nSET_VAR value sust\nHTTP_SESSION_SET_HEADER session name value\n\
+         SET_VAR name Accept\nSET_VAR value text/plain\nHTTP_SESSION_SET_HEADER session name value\n\
+         INIT_VAR string url\nSET_VAR url {}\nINIT_VAR int status\nINIT_VAR string body\nHTTP_GET url status body session\n\
+         SET_VAR url {}\nINIT_VAR string method\nSET_VAR method POST\n\
+         INIT_VAR string json\nSET_VAR json {{\"Accept\":\"application/json\"}}\nINIT_VAR map[string,string] headers\nJSON_PARSE json headers\n\
+         HTTP_REQUEST method url headers body status body null session\n",
+        login_url, api_url
+    ))
+    .unwrap();
+    let login = login.join().unwrap();
+    let api = api.join().unwrap();
+
+    assert!(login.contains("User-Agent: sust\r\n"));
+    assert!(!login.contains("Cookie:"));
+    assert!(api.contains("User-Agent: sust\r\n"));
+    assert!(api.contains("Accept: application/json\r\n"));
+    assert!(!api.contains("Accept: text/plain"));
+    assert!(api.contains("Cookie: sid=abc123; theme=dark\r\n"));
+    assert_eq!(vars["status"].as_int().unwrap(), 204);
+}
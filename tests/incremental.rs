@@ -0,0 +1,74 @@
+use sustlang::{Script, TextEdit};
+
+const SOURCE: &str = "META name demo
+FUNC null greet name string
+    TEMP_VAR string hello Hello, # comment
+    WRITE hello cout
+    WRITE name cout
+FUNC_END
+FUNC int broken
+    UNKNOWN_COMMAND
+FUNC_END
+FUNC
+FUNC_END
+FUNC null reader input in_stream
+    READ_LINE line input
+FUNC_END
+INIT_VAR string who
+SET_VAR who world
+USE_FUNC greet null who";
+
+fn edit(start_line: usize, end_line: usize, text: &str) -> TextEdit {
+    TextEdit {
+        start_line,
+        end_line,
+        text: text.to_string(),
+    }
+}
+
+fn assert_same_as_full_parse(edits: &[TextEdit]) {
+    let (old, _) = Script::parse_lenient(SOURCE.to_string());
+    let (incremental, diagnostics) = Script::parse_incremental(&old, edits);
+    let (full, full_diagnostics) = Script::parse_lenient(incremental.source.join("\n"));
+
+    assert_eq!(incremental.commands, full.commands, "{:?}", edits);
+    assert_eq!(incremental.functions, full.functions, "{:?}", edits);
+    assert_eq!(incremental.meta, full.meta, "{:?}", edits);
+    assert_eq!(
+        format!("{:?}", diagnostics),
+        format!("{:?}", full_diagnostics),
+        "{:?}",
+        edits
+    );
+}
+
+#[test]
+fn incremental_parse_matches_full_parse() {
+    for edits in [
+        vec![],
+        vec![edit(15, 16, "SET_VAR who everyone")],
+        vec![edit(0, 0, "\n\n# new comment")],
+        vec![edit(3, 4, "    WRITE hello cerr")],
+        vec![edit(2, 2, "    TEMP_VAR int x 1")],
+        vec![edit(7, 8, "    RETURN")],
+        vec![edit(5, 6, "")],
+        vec![edit(12, 13, "    READ_LINE line cout")],
+        vec![edit(1, 6, "FUNC null reader x int\nFUNC_END")],
+        vec![
+            edit(0, 0, "TYPE_ALIAS text string"),
+            edit(2, 2, "INIT_VAR text a"),
+        ],
+        vec![edit(100, 200, "INIT_VAR int a"), edit(0, 1, "")],
+    ] {
+        assert_same_as_full_parse(&edits);
+    }
+}
+
+#[test]
+fn unchanged_functions_are_shifted() {
+    let (old, _) = Script::parse_lenient(SOURCE.to_string());
+    let (script, _) = Script::parse_incremental(&old, &[edit(0, 0, "# one more line")]);
+
+    let greet = script.functions.iter().find(|f| f.name == "greet").unwrap();
+    assert_eq!(greet.commands[0].line, 4);
+}
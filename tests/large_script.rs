@@ -0,0 +1,56 @@
+use sustlang::{CommandType, Script, ScriptError};
+
+/// Script with `count` functions of 4 lines and a call of each of them,
+/// it is long enough to be parsed in parallel
+fn generated(count: usize) -> String {
+    let mut text = String::new();
+    for i in 0..count {
+        text.push_str(&format!(
+            "FUNC int f{}\n    SET_VAR result {}\n\n    RETURN\nFUNC_END\n",
+            i, i
+        ));
+    }
+    for i in 0..count {
+        text.push_str(&format!("USE_FUNC f{} null\n", i));
+    }
+    text
+}
+
+#[test]
+fn large_script_keeps_order_and_lines() {
+    let count = 10_000;
+    let script = Script::parse(generated(count)).unwrap_or_else(|_| panic!("parse failed"));
+
+    assert_eq!(script.functions.len(), count);
+    for (i, func) in script.functions.iter().enumerate() {
+        assert_eq!(func.name, format!("f{}", i));
+        assert_eq!(func.commands[0].line, i * 5 + 2);
+        assert_eq!(func.commands[1].line, i * 5 + 4);
+    }
+
+    assert_eq!(script.commands.len(), count);
+    for (i, command) in script.commands.iter().enumerate() {
+        assert_eq!(command.command_type, CommandType::UseFunc);
+        assert_eq!(command.args[0], format!("f{}", i));
+        assert_eq!(command.line, count * 5 + i + 1);
+    }
+}
+
+#[test]
+fn large_script_reports_errors_in_order() {
+    let mut lines: Vec<String> = generated(10_000).lines().map(str::to_string).collect();
+    for line in [3, 25_003, 49_998] {
+        lines[line - 1] = String::from("UNKNOWN_COMMAND");
+    }
+
+    let (_, diagnostics) = Script::parse_lenient(lines.join("\n"));
+    let lines: Vec<usize> = diagnostics
+        .iter()
+        .map(|(error, line)| {
+            assert!(matches!(error, ScriptError::CommandUnknownError(_)));
+            *line
+        })
+        .collect();
+
+    assert_eq!(lines, vec![3, 25_003, 49_998]);
+}
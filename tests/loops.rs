@@ -0,0 +1,72 @@
+mod common;
+
+use common::{builder, outcome};
+
+use std::collections::HashMap;
+
+use sustlang::{ScriptError, Variable};
+
+const COUNTER: &str = "INIT_VAR int count\nSET_VAR count 0\nINIT_VAR int one\nSET_VAR one 1\n\
+INIT_VAR int limit\nSET_VAR limit 3\nFUNC null stop\n    BREAK\nFUNC_END\n";
+
+fn run(text: &str) -> Vec<Result<HashMap<String, Variable>, ScriptError>> {
+    [true, false]
+        .into_iter()
+        .map(|bytecode| {
+            outcome(builder(format!("{COUNTER}{text}")).bytecode(bytecode))
+                .map(|outcome| outcome.globals)
+        })
+        .collect()
+}
+
+fn count(text: &str) -> Vec<isize> {
+    run(text)
+        .into_iter()
+        .map(|globals| globals.unwrap()["count"].as_int().unwrap())
+        .collect()
+}
+
+fn break_error(text: &str) -> bool {
+    run(text)
+        .into_iter()
+        .all(|result| matches!(result, Err(ScriptError::BreakOutsideLoopError)))
+}
+
+#[test]
+fn do_while_runs_once_with_false_condition() {
+    let text = "INIT_VAR bool again\nSET_VAR again false\n\
+        FUNC null body\n    ADD_INT count one\nFUNC_END\nDO_WHILE body again\n";
+
+    assert_eq!(count(text), [1, 1]);
+}
+
+#[test]
+fn loop_ends_with_break_in_if() {
+    let text = "FUNC null body\n    ADD_INT count one\n    INIT_VAR bool done\n    EQUALS count limit done\n\
+        IF done stop\n    RETURN\n    SET_VAR count -100\nFUNC_END\nLOOP body\n";
+
+    // RETURN ends only the current call of the body, BREAK in IF ends the loop
+    assert_eq!(count(text), [3, 3]);
+}
+
+#[test]
+fn break_in_for_list_stops_iteration() {
+    let text = "INIT_VAR string json\nSET_VAR json [1, 2, 3, 4, 5]\nINIT_VAR list[int] items\nJSON_PARSE json items\n\
+        FUNC null body item int\n    ADD_INT count one\n    INIT_VAR bool done\n    EQUALS item limit done\n\
+        IF done stop\nFUNC_END\nFOR_LIST body items\n";
+
+    assert_eq!(count(text), [3, 3]);
+}
+
+#[test]
+fn break_outside_loop_is_an_error() {
+    assert!(break_error("BREAK\n"));
+    assert!(break_error("USE_FUNC stop null\n"));
+}
+
+#[test]
+fn break_does_not_leave_called_function() {
+    let text = "FUNC null body\n    USE_FUNC stop null\nFUNC_END\nLOOP body\n";
+
+    assert!(break_error(text));
+}
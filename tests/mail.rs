@@ -0,0 +1,23 @@
+#![cfg(feature = "mail")]
+
+use sustlang::{RunningScript, Script, ScriptError};
+
+#[test]
+fn send_mail_rejects_bad_address() {
+    let script = Script::parse(
+        "INIT_VAR string server\nSET_VAR server localhost\nINIT_VAR int port\nSET_VAR port 587\n\
+         INIT_VAR string user\nSET_VAR user bot@example.com\nINIT_VAR string pass\nSET_VAR pass secret\n\
+         INIT_VAR string to\nSET_VAR to not an address\nINIT_VAR string subject\nSET_VAR subject done\n\
+         INIT_VAR string body\nSET_VAR body the job finished\nSEND_MAIL server port user pass to subject body\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run();
+
+    assert!(matches!(result, Err((ScriptError::ParseVarError, _))));
+}
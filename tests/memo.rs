@@ -0,0 +1,71 @@
+mod common;
+
+use common::{builder, outcome};
+
+use sustlang::{VarType, Variable};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const SCRIPT: &str = "INIT_VAR int calls
+SET_VAR calls 0
+INIT_VAR int one
+SET_VAR one 1
+FUNC int twice n int
+    ADD_INT calls one
+    COPY_VAR n result
+    ADD_INT result n
+FUNC_END
+INIT_VAR int a
+SET_VAR a 20
+INIT_VAR int b
+SET_VAR b 21
+INIT_VAR int result_a
+INIT_VAR int result_b
+INIT_VAR int squared
+USE_FUNC twice result_a a
+USE_FUNC twice result_a a
+USE_FUNC twice result_b b
+USE_FUNC square squared a
+USE_FUNC square squared a
+";
+
+/// (calls of `twice`, calls of `square`, result of `twice b`)
+fn run(memo: bool, bytecode: bool) -> (isize, usize, isize) {
+    let square_calls = Arc::new(AtomicUsize::new(0));
+    let counter = square_calls.clone();
+    let text = if memo {
+        format!("MEMO_FUNC twice\nMEMO_FUNC square\n{}", SCRIPT)
+    } else {
+        SCRIPT.to_string()
+    };
+
+    let outcome = outcome(
+        builder(text)
+            .native_fn(
+                "square",
+                vec![VarType::Integer],
+                VarType::Integer,
+                move |args| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let n = args[0].as_int()?;
+                    Ok(Variable::from_int(Some(n * n)))
+                },
+            )
+            .bytecode(bytecode),
+    )
+    .unwrap_or_else(|_| panic!("run failed"));
+
+    (
+        outcome.globals["calls"].as_int().unwrap(),
+        square_calls.load(Ordering::SeqCst),
+        outcome.globals["result_b"].as_int().unwrap(),
+    )
+}
+
+#[test]
+fn memo_func_caches_results_by_arguments() {
+    assert_eq!(run(false, true), (3, 2, 42));
+    assert_eq!(run(true, true), (2, 1, 42));
+    assert_eq!(run(true, false), (2, 1, 42));
+}
@@ -0,0 +1,48 @@
+use sustlang::{Script, ScriptError};
+
+#[test]
+fn meta_header_is_parsed() {
+    let script = Script::parse(
+        "META name http server\nMETA version 1.2.0\nMETA require 0.2\nMETA author someone\n\
+         INIT_VAR int a\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    assert_eq!(script.meta.name.as_deref(), Some("http server"));
+    assert_eq!(script.meta.version.as_deref(), Some("1.2.0"));
+    assert_eq!(script.meta.require.as_deref(), Some("0.2"));
+    assert_eq!(
+        script.meta.get("author").map(String::as_str),
+        Some("someone")
+    );
+    assert_eq!(script.commands.len(), 1);
+
+    let loaded = Script::from_bytes(&script.to_bytes()).unwrap();
+    assert_eq!(loaded.meta, script.meta);
+}
+
+#[test]
+fn invalid_meta_is_reported() {
+    for (text, line) in [
+        ("META require 999.0", 1),
+        ("META name a\nMETA name b", 2),
+        ("INIT_VAR int a\nMETA name a", 2),
+        ("META require one", 1),
+    ] {
+        let (_, diagnostics) = Script::parse_lenient(text.to_string());
+        assert!(
+            matches!(
+                diagnostics[..],
+                [(ScriptError::MetaError | ScriptError::VersionError, l)] if l == line
+            ),
+            "{}",
+            text
+        );
+    }
+
+    assert!(matches!(
+        Script::parse("META require 999".to_string()),
+        Err((ScriptError::VersionError, 1))
+    ));
+}
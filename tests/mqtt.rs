@@ -0,0 +1,101 @@
+#![cfg(feature = "mqtt")]
+
+use sustlang::{RunningScript, Script};
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header).unwrap();
+    let mut body = vec![0u8; header[1] as usize];
+    stream.read_exact(&mut body).unwrap();
+    (header[0], body)
+}
+
+/// Subscribe with `handler`, publish one message and return the output and the errors of the script
+fn subscribe(handler: &str) -> (SharedOutput, SharedOutput) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let broker = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(header, 0x10);
+        assert_eq!(&body[2..6], b"MQTT");
+        stream.write_all(&[0x20, 2, 0, 0]).unwrap();
+
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(header, 0x82);
+        assert_eq!(&body[4..10], b"home/+");
+
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(header, 0x30);
+        assert_eq!(&body[2..11], b"home/lamp");
+        assert_eq!(&body[11..], b"on");
+
+        let mut message = vec![0x30, body.len() as u8];
+        message.extend_from_slice(&body);
+        stream.write_all(&message).unwrap();
+        stream
+    });
+
+    let script = Script::parse(format!(
+        "FUNC null on_message payload string\n    {}\nFUNC_END\n\
+         INIT_VAR string host\nSET_VAR host 127.0.0.1\nINIT_VAR int port\nSET_VAR port {}\n\
+         INIT_VAR string id\nSET_VAR id sensor\nINIT_VAR mqtt client\nMQTT_CONNECT host port id client\n\
+         INIT_VAR string filter\nSET_VAR filter home/+\nMQTT_SUBSCRIBE client filter on_message\n\
+         INIT_VAR string topic\nSET_VAR topic home/lamp\nINIT_VAR string payload\nSET_VAR payload on\n\
+         MQTT_PUBLISH client topic payload\n",
+        handler, port
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let output = SharedOutput::default();
+    let errors = SharedOutput::default();
+    RunningScript::builder(script)
+        .stdout(output.clone())
+        .stderr(errors.clone())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+    let _stream = broker.join().unwrap();
+
+    let start = Instant::now();
+    while output.0.lock().unwrap().is_empty()
+        && errors.0.lock().unwrap().is_empty()
+        && start.elapsed() < Duration::from_secs(5)
+    {
+        thread::sleep(Duration::from_millis(10));
+    }
+    (output, errors)
+}
+
+#[test]
+fn mqtt_subscribe_calls_handler() {
+    let (output, _) = subscribe("WRITE payload cout");
+    assert_eq!(output.0.lock().unwrap().as_slice(), b"on");
+}
+
+#[test]
+fn mqtt_handler_error_is_written_to_cerr() {
+    let (_, errors) = subscribe("INIT_VAR bool flag\n    WRITE flag cout");
+    let errors = String::from_utf8(errors.0.lock().unwrap().clone()).unwrap();
+    assert!(errors.starts_with("error (TypeMismatchError): "));
+}
@@ -0,0 +1,37 @@
+use sustlang::{RunningScript, Script};
+
+use std::net::UdpSocket;
+use std::thread;
+
+#[test]
+fn network_time_reads_sntp_answer() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+    let answer = thread::spawn(move || {
+        let mut packet = [0u8; 48];
+        let (_, client) = server.recv_from(&mut packet).unwrap();
+        assert_eq!(packet[0] & 0x07, 3);
+
+        packet[0] = 0x24;
+        let seconds: u32 = 1_700_000_000 + 2_208_988_800;
+        packet[40..44].copy_from_slice(&seconds.to_be_bytes());
+        packet[44..48].copy_from_slice(&(1u32 << 31).to_be_bytes());
+        server.send_to(&packet, client).unwrap();
+    });
+
+    let script = Script::parse(format!(
+        "INIT_VAR string server\nSET_VAR server {}\nINIT_VAR int now\nGET_NETWORK_TIME server now\n",
+        address
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+    answer.join().unwrap();
+
+    let now = outcome.globals["now"].as_int().unwrap();
+    assert!((1_700_000_000_500..1_700_000_001_500).contains(&now));
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "notify")]
+
+use sustlang::{RunningScript, Sandbox, Script, ScriptError};
+
+#[test]
+fn notify_shows_or_reports_error() {
+    let script = Script::parse(
+        "INIT_VAR string title\nSET_VAR title sustlang\nINIT_VAR string body\nSET_VAR body job finished\nNOTIFY title body\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run();
+
+    // without a notification service the command fails instead of panicking
+    if let Err((error, _)) = result {
+        assert!(matches!(error, ScriptError::NotificationError));
+    }
+}
+
+#[test]
+fn notify_is_denied_by_sandbox() {
+    let script = Script::parse(
+        "INIT_VAR string title\nSET_VAR title sustlang\nINIT_VAR string body\nSET_VAR body job finished\nNOTIFY title body\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .sandbox(Sandbox::ReadOnlyFs)
+        .build()
+        .unwrap()
+        .run();
+
+    assert!(matches!(result, Err((ScriptError::SandboxError(_), _))));
+}
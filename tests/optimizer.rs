@@ -0,0 +1,202 @@
+use sustlang::{
+    compile_function, compile_main, CommandType, ConstValue, Function, Instruction, RunningScript,
+    Script, SymbolTable, VarPath, VarType, Variable,
+};
+
+use std::sync::Arc;
+
+const SCRIPT: &str = "FUNC int sum
+    INIT_VAR int unused
+    SET_VAR unused 5
+    SET_VAR result 2
+    TEMP_VAR int step 3
+    ADD_INT result step
+    RETURN
+    SET_VAR result 100
+FUNC_END
+INIT_VAR int total
+USE_FUNC sum total
+RETURN
+SET_VAR total 0
+";
+
+fn function_commands(script: &Script) -> Vec<(CommandType, Vec<String>)> {
+    script.functions[0]
+        .commands
+        .iter()
+        .map(|command| (command.command_type, command.args.clone()))
+        .collect()
+}
+
+#[test]
+fn optimize_folds_and_removes_dead_code() {
+    let mut script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    script.optimize();
+
+    assert_eq!(
+        function_commands(&script),
+        vec![
+            (
+                CommandType::SetVar,
+                vec!["result".to_string(), "5".to_string()]
+            ),
+            (CommandType::Return, Vec::new()),
+        ]
+    );
+    assert_eq!(script.commands.len(), 3);
+    assert_eq!(script.commands[2].command_type, CommandType::Return);
+}
+
+#[test]
+fn used_variables_are_kept() {
+    let mut script = Script::parse(
+        "FUNC int get
+    INIT_VAR int value
+    SET_VAR value 1
+    TEMP_VAR int step 2
+    ADD_INT value step
+    COPY_VAR value result
+FUNC_END
+"
+        .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+    script.optimize();
+
+    assert_eq!(
+        function_commands(&script),
+        vec![
+            (
+                CommandType::InitVar,
+                vec!["int".to_string(), "value".to_string()]
+            ),
+            (
+                CommandType::SetVar,
+                vec!["value".to_string(), "3".to_string()]
+            ),
+            (
+                CommandType::CopyVar,
+                vec!["value".to_string(), "result".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn overflow_is_not_folded() {
+    let mut script = Script::parse(format!(
+        "FUNC int get\n    SET_VAR result {}\n    TEMP_VAR int step 1\n    ADD_INT result step\nFUNC_END\n",
+        isize::MAX
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+    script.optimize();
+
+    assert_eq!(script.functions[0].commands.len(), 3);
+}
+
+#[test]
+fn optimized_script_gives_same_result() {
+    for bytecode in [true, false] {
+        let script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+        let globals = RunningScript::builder(script)
+            .stdout(Vec::new())
+            .bytecode(bytecode)
+            .build()
+            .unwrap()
+            .run_with_result()
+            .map(|outcome| outcome.globals)
+            .unwrap_or_else(|_| panic!("run failed"));
+
+        assert_eq!(globals["total"].as_int().unwrap(), 5);
+    }
+}
+
+const LOOP_SCRIPT: &str = "FUNC null step i int
+    INIT_VAR int add
+    SET_VAR add 2
+    ADD_INT total add
+    SET_VAR add.0 1
+FUNC_END
+INIT_VAR int total
+SET_VAR total 0
+INIT_VAR int from
+SET_VAR from 1
+INIT_VAR int to
+SET_VAR to 10
+FOR step from to
+";
+
+#[test]
+fn function_values_are_parsed_once() {
+    let script = Script::parse(LOOP_SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let mut symbols = SymbolTable::new();
+    symbols.intern("step");
+
+    let code = compile_function(&script.functions[0], &symbols);
+    assert!(matches!(&code[1], Instruction::SetConst { path, value }
+        if path.root == "add" && value.0.as_int().unwrap() == 2));
+    assert!(matches!(&code[3], Instruction::SetVar { .. }));
+
+    // globals of the main function can be changed by the functions it calls
+    let main = Function::new(
+        "main".to_string(),
+        VarType::Null,
+        Vec::new(),
+        script.commands.clone(),
+    );
+    let code = compile_main(&main, &symbols);
+    assert!(
+        matches!(&code[1], Instruction::SetVar { parsed: Some(value), .. }
+        if value.0.as_int().unwrap() == 0)
+    );
+}
+
+#[test]
+fn temp_var_values_are_parsed_once() {
+    let script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let code = compile_function(&script.functions[0], &SymbolTable::new());
+    assert!(matches!(&code[3], Instruction::TempVar { name, value, .. }
+        if name == "step" && value.0.as_int().unwrap() == 3));
+}
+
+#[test]
+fn parsed_value_of_other_type_is_not_used() {
+    let mut script = Script::parse(
+        "FUNC string text\n    SET_VAR result 7\nFUNC_END\nINIT_VAR string out\nUSE_FUNC text out\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+    script.functions[0].bytecode = Some(Arc::new(vec![Instruction::SetVar {
+        path: VarPath::parse("result"),
+        value: "7".to_string(),
+        parsed: Some(ConstValue(Variable::from_int(Some(5)))),
+    }]));
+
+    let globals = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .map(|outcome| outcome.globals)
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(globals["out"].as_str().unwrap(), "7");
+}
+
+#[test]
+fn loop_body_gives_same_result() {
+    for bytecode in [true, false] {
+        let script = Script::parse(LOOP_SCRIPT.replace("    SET_VAR add.0 1\n", ""))
+            .unwrap_or_else(|_| panic!("parse failed"));
+        let globals = RunningScript::builder(script)
+            .stdout(Vec::new())
+            .bytecode(bytecode)
+            .build()
+            .unwrap()
+            .run_with_result()
+            .map(|outcome| outcome.globals)
+            .unwrap_or_else(|_| panic!("run failed"));
+
+        assert_eq!(globals["total"].as_int().unwrap(), 20);
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::run;
+
+use sustlang::ScriptError;
+
+const CONFIG: &str = "INIT_VAR string json\nSET_VAR json {\"port\": 8080}\nINIT_VAR map[string,int] config\nJSON_PARSE json config\n\
+INIT_VAR optional[map[string,int]] some\nPACK_OPTIONAL config some\n\
+INIT_VAR optional[map[string,int]] empty\nNONE_OPTIONAL empty\n";
+
+#[test]
+fn get_path_opt_unwraps_optionals() {
+    let globals = run(format!(
+        "{CONFIG}INIT_VAR optional[int] port\nGET_PATH_OPT some.port port\n\
+         INIT_VAR optional[int] missing\nGET_PATH_OPT some.host missing\n\
+         INIT_VAR optional[int] none\nGET_PATH_OPT empty.port none\n"
+    ))
+    .unwrap();
+
+    let port = globals["port"].as_option().unwrap().unwrap();
+    assert_eq!(port.as_int().unwrap(), 8080);
+    assert!(globals["missing"].as_option().unwrap().is_none());
+    assert!(globals["none"].as_option().unwrap().is_none());
+}
+
+#[test]
+fn other_commands_do_not_unwrap_optionals() {
+    let error = run(format!(
+        "{CONFIG}INIT_VAR int port\nCOPY_VAR some.port port\n"
+    ))
+    .err();
+    assert!(matches!(error, Some(ScriptError::TypeMismatchError { .. })));
+
+    let error = run(format!(
+        "{CONFIG}INIT_VAR int port\nCOPY_VAR config.host port\n"
+    ))
+    .err();
+    assert!(matches!(error, Some(ScriptError::UnknownVarError(_))));
+}
@@ -0,0 +1,21 @@
+use sustlang::{RunningScript, Script, VarType};
+
+#[test]
+fn globals_and_result_are_returned() {
+    let script =
+        Script::parse("INIT_VAR int total\nSET_VAR total 7\nSET_VAR result done\n".to_string())
+            .unwrap_or_else(|_| panic!("parse failed"));
+
+    let mut running_script = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap();
+    running_script.set_result_type(VarType::String);
+
+    let outcome = running_script
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["total"].as_int().unwrap(), 7);
+    assert_eq!(outcome.result.as_str().unwrap(), "done");
+}
@@ -0,0 +1,130 @@
+use sustlang::{RunningScript, Script, ScriptError, VarType};
+
+use std::collections::HashMap;
+use std::io::Write;
+
+struct BrokenOutput;
+
+impl Write for BrokenOutput {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn run_error(text: &str) -> ScriptError {
+    let script = Script::parse(text.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+
+    let ints: Vec<isize> = vec![1, 2, 3];
+    let mut ages: HashMap<String, isize> = HashMap::new();
+    ages.insert("alice".to_string(), 30);
+
+    match RunningScript::builder(script)
+        .stdout(BrokenOutput)
+        .global_var("ints", ints)
+        .global_var("ages", ages)
+        .build()
+        .unwrap()
+        .run()
+    {
+        Ok(_) => panic!("run succeeded"),
+        Err((error, _)) => error,
+    }
+}
+
+#[test]
+fn bad_index_is_an_error() {
+    let error = run_error(
+        "INIT_VAR int index\nSET_VAR index 3\nINIT_VAR int item\nGET_ITEM ints index item\n",
+    );
+    assert!(matches!(
+        error,
+        ScriptError::IndexOutOfBoundsError { index: 3, len: 3 }
+    ));
+
+    let error = run_error(
+        "INIT_VAR int index\nSET_VAR index -1\nINIT_VAR int item\nGET_ITEM ints index item\n",
+    );
+    assert!(matches!(
+        error,
+        ScriptError::IndexOutOfBoundsError { index: -1, len: 3 }
+    ));
+}
+
+#[test]
+fn missing_key_is_an_error() {
+    let error = run_error(
+        "INIT_VAR string key\nSET_VAR key bob\nINIT_VAR int age\nGET_VALUE ages key age\n",
+    );
+    assert_eq!(error.to_string(), "key `bob` not found");
+}
+
+#[test]
+fn failed_write_is_an_error() {
+    let error = run_error("INIT_VAR string text\nSET_VAR text hi\nWRITE text cout\n");
+    assert!(matches!(error, ScriptError::StreamWriteError));
+}
+
+#[test]
+fn opt_commands_return_none_when_missing() {
+    let script = Script::parse(
+        "INIT_VAR int index\nSET_VAR index 5\nINIT_VAR optional[int] item\nGET_ITEM_OPT ints index item\nINIT_VAR bool has_item\nHAS_OPTIONAL item has_item\n\
+         SET_VAR index 1\nINIT_VAR optional[int] second\nGET_ITEM_OPT ints index second\n\
+         INIT_VAR string key\nSET_VAR key bob\nINIT_VAR optional[int] age\nGET_VALUE_OPT ages key age\nINIT_VAR bool has_age\nHAS_OPTIONAL age has_age\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let ints: Vec<isize> = vec![1, 2, 3];
+    let ages: HashMap<String, isize> = HashMap::new();
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .global_var("ints", ints)
+        .global_var("ages", ages)
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert!(!outcome.globals["has_item"].as_bool().unwrap());
+    assert!(!outcome.globals["has_age"].as_bool().unwrap());
+    let second = outcome.globals["second"].as_option().unwrap().unwrap();
+    assert_eq!(second.as_int().unwrap(), 2);
+}
+
+#[test]
+fn panic_in_command_becomes_error() {
+    for bytecode in [true, false] {
+        let script = Script::parse("INIT_VAR int result\nUSE_FUNC explode result\n".to_string())
+            .unwrap_or_else(|_| panic!("parse failed"));
+
+        let result = RunningScript::builder(script)
+            .stdout(Vec::new())
+            .native_fn("explode", Vec::new(), VarType::Integer, |_| {
+                panic!("host bug")
+            })
+            .bytecode(bytecode)
+            .build()
+            .unwrap()
+            .run();
+
+        match result {
+            Err((
+                ScriptError::InternalPanicError {
+                    command,
+                    line,
+                    message,
+                },
+                _,
+            )) => {
+                assert_eq!(command, "USE_FUNC");
+                assert_eq!(line, 2);
+                assert_eq!(message, "host bug");
+            }
+            _ => panic!("expected InternalPanicError"),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use sustlang::{Script, ScriptError};
+
+const PATHOLOGICAL: &[&str] = &[
+    "",
+    "FUNC",
+    "FUNC int",
+    "FUNC map[ f",
+    "FUNC null f a map[",
+    "INIT_VAR map[ x",
+    "INIT_VAR list[ x",
+    "INIT_VAR optional[] x",
+    "INIT_VAR map[] x",
+    "FUNC null f\nFUNC null g\nFUNC_END",
+    "FUNC_END\nFUNC_END",
+    "UNKNOWN_COMMAND a b",
+    "   \t  # only comment",
+    "INIT_VAR мапа[ x",
+    "WRITE cin cout\nREAD x cout",
+];
+
+const TOKENS: &[&str] = &[
+    "FUNC",
+    "FUNC_END",
+    "INIT_VAR",
+    "SET_VAR",
+    "WRITE",
+    "READ",
+    "USE_FUNC",
+    "map[",
+    "list[",
+    "optional[",
+    "]",
+    ",",
+    "int",
+    "string",
+    "cout",
+    "cin",
+    "x",
+    "#",
+    " ",
+    "\t",
+    "\n",
+    "ы",
+];
+
+#[test]
+fn pathological_inputs_do_not_panic() {
+    for text in PATHOLOGICAL {
+        let _ = Script::parse_lenient(text.to_string());
+        let _ = Script::parse(text.to_string());
+    }
+}
+
+#[test]
+fn random_inputs_do_not_panic() {
+    let mut state: u64 = 0x2545F4914F6CDD1D;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..2000 {
+        let len = next() % 40;
+        let text: String = (0..len)
+            .map(|_| TOKENS[(next() % TOKENS.len() as u64) as usize])
+            .collect();
+        let _ = Script::parse_lenient(text);
+    }
+}
+
+#[test]
+fn lenient_parse_reports_all_problems() {
+    let (script, diagnostics) =
+        Script::parse_lenient("BAD_ONE\nINIT_VAR int x\nBAD_TWO\nFUNC\nFUNC_END\n".to_string());
+
+    let lines: Vec<usize> = diagnostics.iter().map(|(_, line)| *line).collect();
+    assert_eq!(lines, vec![1, 3, 4]);
+    assert!(matches!(
+        diagnostics[0].0,
+        ScriptError::CommandUnknownError(_)
+    ));
+    assert!(matches!(
+        diagnostics[2].0,
+        ScriptError::CommandArgsInvalidError
+    ));
+    assert_eq!(script.commands.len(), 1);
+    assert!(script.functions.is_empty());
+}
@@ -0,0 +1,79 @@
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+use sustlang::{RunningScript, Script, ScriptError};
+
+const SCRIPT: &str = "META name test
+# comment
+
+FUNC int add_one x int
+    COPY_VAR x result
+    TEMP_VAR int one 1
+    ADD_INT result one   # trailing comment
+    RETURN
+FUNC_END
+
+INIT_VAR int value
+SET_VAR value 41
+USE_FUNC add_one value value
+";
+
+#[test]
+fn parse_reader_matches_parse() {
+    let parsed = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let read = Script::parse_reader(BufReader::with_capacity(4, SCRIPT.as_bytes()))
+        .unwrap_or_else(|_| panic!("parse failed"));
+
+    assert_eq!(read.to_bytes(), parsed.to_bytes());
+    assert_eq!(read.functions[0].commands[2].line, 7);
+    assert!(read.source.is_empty());
+
+    let outcome = RunningScript::builder(read)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+    assert_eq!(outcome.globals["value"].as_int().unwrap(), 42);
+}
+
+#[test]
+fn parse_reader_reports_line_of_unknown_command() {
+    let text = "INIT_VAR int a\n\nNOT_A_COMMAND a\n";
+    let error = Script::parse_reader(Cursor::new(text)).err().unwrap();
+    assert_eq!(error.1, 3);
+}
+
+/// Gives the first line and then fails
+struct BrokenReader(Cursor<&'static str>);
+
+impl Read for BrokenReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for BrokenReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.0.position() as usize == self.0.get_ref().len() {
+            return Err(io::ErrorKind::ConnectionReset.into());
+        }
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+#[test]
+fn parse_reader_reports_read_errors() {
+    let error = Script::parse_reader(BrokenReader(Cursor::new("INIT_VAR int a\n")))
+        .err()
+        .unwrap();
+    assert!(matches!(error, (ScriptError::StreamReadError, 2)));
+
+    let error = Script::parse_reader(Cursor::new(b"INIT_VAR int a\nWRITE \xff\n".to_vec()))
+        .err()
+        .unwrap();
+    assert!(matches!(error, (ScriptError::StringUTF8Error, 2)));
+}
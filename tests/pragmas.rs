@@ -0,0 +1,43 @@
+use sustlang::{RunningScript, Script, ScriptError};
+
+const OVERFLOW: &str =
+    "INIT_VAR int a\nSET_VAR a 9223372036854775807\nINIT_VAR int b\nSET_VAR b 1\nADD_INT a b\n";
+
+#[test]
+fn lang_2_checks_integer_overflow() {
+    let script = Script::parse(format!("META name overflow\nPRAGMA lang 2\n{}", OVERFLOW))
+        .unwrap_or_else(|_| panic!("parse failed"));
+    assert_eq!(script.pragmas.lang, 2);
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run();
+    assert!(matches!(result, Err((ScriptError::RangeError, _))));
+}
+
+#[test]
+fn invalid_pragmas_are_reported() {
+    assert_eq!(
+        Script::parse(OVERFLOW.to_string())
+            .unwrap_or_else(|_| panic!("parse failed"))
+            .pragmas
+            .lang,
+        1
+    );
+
+    for text in [
+        "PRAGMA lang 99",
+        "PRAGMA lang two",
+        "PRAGMA unknown 1",
+        "INIT_VAR int a\nPRAGMA lang 2",
+    ] {
+        let (_, diagnostics) = Script::parse_lenient(text.to_string());
+        assert!(
+            matches!(diagnostics[..], [(ScriptError::PragmaError, _)]),
+            "{}",
+            text
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use sustlang::{RunningScript, Script};
+
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn prompt_reads_line_from_cin() {
+    let script = Script::parse(
+        "INIT_VAR string question\nSET_VAR question name:\nINIT_VAR string name\nPROMPT question name\nINIT_VAR string greeting\nSET_VAR greeting hello,\nADD_STR greeting name\nWRITE greeting cout\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let output = SharedOutput::default();
+    RunningScript::builder(script)
+        .stdin(Cursor::new(b"Bob\r\nAlice\n".to_vec()))
+        .stdout(output.clone())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(output.0.lock().unwrap().as_slice(), b"name:hello,Bob");
+}
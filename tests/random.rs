@@ -0,0 +1,84 @@
+mod common;
+
+use common::run;
+
+use std::collections::HashMap;
+
+use sustlang::{RunningScript, Script, ScriptError, Variable};
+
+#[test]
+fn random_float_is_in_unit_range() {
+    let globals = run("INIT_VAR float x\nRANDOM_FLOAT x\n").unwrap();
+    let x = globals["x"].as_float().unwrap();
+    assert!((0.0..1.0).contains(&x));
+}
+
+#[test]
+fn random_with_min_above_max_fails() {
+    let error = run(
+        "INIT_VAR int min\nSET_VAR min 5\nINIT_VAR int max\nSET_VAR max 1\nINIT_VAR int a\nRANDOM min max a\n",
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, ScriptError::RangeError));
+}
+
+#[test]
+fn random_bytes_gives_count_bytes() {
+    let globals =
+        run("INIT_VAR int n\nSET_VAR n 16\nINIT_VAR bytes bytes\nRANDOM_BYTES n bytes\n").unwrap();
+    assert_eq!(globals["bytes"].as_bytes().unwrap().len(), 16);
+
+    let error = run("INIT_VAR int n\nSET_VAR n -1\nINIT_VAR bytes bytes\nRANDOM_BYTES n bytes\n")
+        .err()
+        .unwrap();
+    assert!(matches!(error, ScriptError::RangeError));
+}
+
+#[test]
+fn random_choice_picks_an_item() {
+    let globals = run(
+        "INIT_VAR string json\nSET_VAR json [3, 5, 7]\nINIT_VAR list[int] items\nJSON_PARSE json items\nINIT_VAR int picked\nRANDOM_CHOICE items picked\n",
+    )
+    .unwrap();
+    assert!([3, 5, 7].contains(&globals["picked"].as_int().unwrap()));
+
+    let error = run("INIT_VAR string json\nSET_VAR json []\nINIT_VAR list[int] items\nJSON_PARSE json items\nINIT_VAR int picked\nRANDOM_CHOICE items picked\n")
+        .err()
+        .unwrap();
+    assert!(matches!(error, ScriptError::IndexOutOfBoundsError { .. }));
+}
+
+const SEEDED: &str = "INIT_VAR int min\nSET_VAR min 0\nINIT_VAR int max\nSET_VAR max 1000000\n\
+INIT_VAR int a\nRANDOM min max a\nINIT_VAR float f\nRANDOM_FLOAT f\n\
+INIT_VAR int n\nSET_VAR n 8\nINIT_VAR bytes bytes\nRANDOM_BYTES n bytes\n";
+
+fn seeded_values(globals: &HashMap<String, Variable>) -> (isize, f64, Vec<u8>) {
+    (
+        globals["a"].as_int().unwrap(),
+        globals["f"].as_float().unwrap(),
+        globals["bytes"].as_bytes().unwrap().as_ref().clone(),
+    )
+}
+
+#[test]
+fn random_seed_repeats_values() {
+    let text = format!(
+        "INIT_VAR int seed\nSET_VAR seed 42\nRANDOM_SEED seed\n{}",
+        SEEDED
+    );
+    let first = seeded_values(&run(&text).unwrap());
+    let second = seeded_values(&run(&text).unwrap());
+    assert_eq!(first, second);
+
+    let script = Script::parse(SEEDED.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    let globals = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .random_seed(42)
+        .build()
+        .unwrap()
+        .run_with_result()
+        .map(|o| o.globals)
+        .unwrap_or_else(|_| panic!("run failed"));
+    assert_eq!(seeded_values(&globals), first);
+}
@@ -0,0 +1,101 @@
+use sustlang::{RunningScript, ScriptOutcome, Script};
+
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn run_with_input(text: &str, input: &[u8]) -> ScriptOutcome {
+    let script = Script::parse(text.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    RunningScript::builder(script)
+        .stdin(Cursor::new(input.to_vec()))
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"))
+}
+
+#[test]
+fn read_fills_the_buffer() {
+    let outcome = run_with_input(
+        "INIT_VAR string head\nINIT_VAR int size\nSET_VAR size 3\nREAD_LENGTH head size cin\nINIT_VAR string rest\nREAD rest cin\n",
+        b"abcdef",
+    );
+
+    assert_eq!(outcome.globals["head"].as_str().unwrap(), "abc");
+    assert_eq!(outcome.globals["rest"].as_str().unwrap(), "def");
+}
+
+#[test]
+fn read_some_returns_count() {
+    let outcome = run_with_input(
+        "INIT_VAR list[char] chars\nINIT_VAR int size\nSET_VAR size 10\nINIT_VAR int read\nREAD_SOME chars size cin read\n\
+         INIT_VAR string tail\nINIT_VAR int tail_read\nREAD_SOME tail size cin tail_read\n",
+        b"hey",
+    );
+
+    assert_eq!(outcome.globals["read"].as_int().unwrap(), 3);
+    let chars = outcome.globals["chars"].as_list().unwrap();
+    assert_eq!(chars.len(), 3);
+    assert_eq!(chars[0].as_char().unwrap(), 'h');
+    assert_eq!(outcome.globals["tail_read"].as_int().unwrap(), 0);
+    assert_eq!(outcome.globals["tail"].as_str().unwrap(), "");
+}
+
+#[test]
+fn read_line_signals_end_of_stream() {
+    let outcome = run_with_input(
+        "INIT_VAR optional[string] first\nREAD_LINE first cin\nINIT_VAR optional[string] second\nREAD_LINE second cin\n\
+         INIT_VAR optional[string] third\nREAD_LINE third cin\n",
+        b"one\r\ntwo",
+    );
+
+    let line = |name: &str| {
+        outcome.globals[name]
+            .as_option()
+            .unwrap()
+            .map(|line| line.as_str().unwrap())
+    };
+    assert_eq!(line("first").as_deref(), Some("one"));
+    assert_eq!(line("second").as_deref(), Some("two"));
+    assert_eq!(line("third"), None);
+}
+
+#[test]
+fn write_line_appends_newline() {
+    let script = Script::parse(
+        "INIT_VAR string text\nSET_VAR text hi\nWRITE_LINE text cout\nINIT_VAR list[char] chars\nREAD_LINE chars cin\nWRITE_LINE chars cout\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let output = SharedOutput::default();
+    RunningScript::builder(script)
+        .stdin(Cursor::new(b"there\n".to_vec()))
+        .stdout(output.clone())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(output.0.lock().unwrap().as_slice(), b"hi\nthere\n");
+}
+
+#[test]
+fn read_keeps_only_read_bytes() {
+    let outcome = run_with_input("INIT_VAR bytes data\nREAD data cin\n", b"abc");
+
+    assert_eq!(outcome.globals["data"].as_bytes().unwrap().as_slice(), b"abc");
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{builder, outcome};
+
+use sustlang::{Sandbox, ScriptError, ScriptOutcome};
+
+use std::fs;
+
+fn run(text: String, sandbox: Sandbox) -> Result<ScriptOutcome, ScriptError> {
+    outcome(builder(text).sandbox(sandbox))
+}
+
+fn denied(result: Result<ScriptOutcome, ScriptError>) -> bool {
+    matches!(result, Err(ScriptError::SandboxError(_)))
+}
+
+#[test]
+fn presets_limit_files_network_and_processes() {
+    let dir = std::env::temp_dir().join(format!("sustlang-{}-sandbox", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("data.txt");
+    fs::write(&path, "hello").unwrap();
+    let path = path.display();
+
+    let read = format!(
+        "INIT_VAR string path\nSET_VAR path {path}\nINIT_VAR bool exists\nFILE_EXISTS path exists\n"
+    );
+    let write = format!(
+        "INIT_VAR string path\nSET_VAR path {path}\nINIT_VAR out_stream file\nOPEN_FILE_OUT path file\n"
+    );
+    let connect = String::from(
+        "INIT_VAR string host\nSET_VAR host 127.0.0.1\nINIT_VAR int port\nSET_VAR port 9\n\
+         INIT_VAR in_stream in\nINIT_VAR out_stream out\nOPEN_TCP_CONNECTION host port in out\n",
+    );
+
+    assert!(denied(run(read.clone(), Sandbox::Pure)));
+    assert!(denied(run(connect.clone(), Sandbox::Pure)));
+
+    let outcome = run(read.clone(), Sandbox::ReadOnlyFs).unwrap_or_else(|_| panic!("run failed"));
+    assert!(outcome.globals["exists"].as_bool().unwrap());
+    assert!(denied(run(write.clone(), Sandbox::ReadOnlyFs)));
+    assert!(denied(run(connect.clone(), Sandbox::ReadOnlyFs)));
+
+    assert!(denied(run(read.clone(), Sandbox::NetworkClientOnly)));
+    assert!(!denied(run(connect, Sandbox::NetworkClientOnly)));
+
+    run(write, Sandbox::Full).unwrap_or_else(|_| panic!("run failed"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sandbox_names_round_trip() {
+    for sandbox in [
+        Sandbox::Pure,
+        Sandbox::ReadOnlyFs,
+        Sandbox::NetworkClientOnly,
+        Sandbox::Full,
+    ] {
+        assert_eq!(Sandbox::from_name(sandbox.to_name()), Some(sandbox));
+    }
+    assert_eq!(Sandbox::from_name("none"), None);
+}
+
+#[test]
+fn presets_limit_environment_variables() {
+    let get = String::from(
+        "INIT_VAR string name\nSET_VAR name PATH\nINIT_VAR optional[string] value\nENV_GET name value\n",
+    );
+    let set = String::from(
+        "INIT_VAR string name\nSET_VAR name SUSTLANG_SANDBOX_TEST\nINIT_VAR string value\nSET_VAR value 1\nENV_SET name value\n",
+    );
+
+    for sandbox in [
+        Sandbox::Pure,
+        Sandbox::ReadOnlyFs,
+        Sandbox::NetworkClientOnly,
+    ] {
+        assert!(denied(run(get.clone(), sandbox)));
+        assert!(denied(run(set.clone(), sandbox)));
+
+        let outcome = run(String::new(), sandbox).unwrap_or_else(|_| panic!("run failed"));
+        assert!(outcome.globals["env"].as_map().unwrap().is_empty());
+    }
+    assert!(std::env::var_os("SUSTLANG_SANDBOX_TEST").is_none());
+
+    run(get, Sandbox::Full).unwrap_or_else(|_| panic!("run failed"));
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+
+use serde::de::DeserializeSeed;
+use sustlang::{FromVariable, IntoVariable, TypedVariable, VarType, Variable};
+
+#[test]
+fn var_type_as_name() {
+    let var_type = VarType::List(Box::new(VarType::Optional(Box::new(VarType::Integer))));
+    let json = serde_json::to_string(&var_type).unwrap();
+    assert_eq!(json, "\"list[optional[integer]]\"");
+    assert_eq!(serde_json::from_str::<VarType>(&json).unwrap(), var_type);
+}
+
+#[test]
+fn variable_to_json() {
+    let value = vec![Some(1isize), None].into_variable();
+    assert_eq!(serde_json::to_string(&value).unwrap(), "[1,null]");
+}
+
+#[test]
+fn json_config_with_inferred_types() {
+    let config: HashMap<String, Variable> =
+        serde_json::from_str(r#"{"name": "server", "port": 8080, "tags": [["a"], []]}"#).unwrap();
+
+    assert_eq!(config["name"].as_str().unwrap(), "server");
+    assert_eq!(config["port"].as_int().unwrap(), 8080);
+    assert_eq!(
+        config["tags"].get_type(),
+        VarType::List(Box::new(VarType::List(Box::new(VarType::String))))
+    );
+
+    assert!(serde_json::from_str::<Variable>(r#"[1, "a"]"#).is_err());
+}
+
+#[test]
+fn typed_round_trip() {
+    let mut scores = HashMap::new();
+    scores.insert(1isize, vec![1.5f64, 2.0]);
+    scores.insert(2isize, Vec::new());
+
+    let var = scores.clone().into_variable();
+    let json = serde_json::to_string(&var).unwrap();
+
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    let restored = TypedVariable(var.get_type())
+        .deserialize(&mut deserializer)
+        .unwrap();
+
+    assert_eq!(restored.get_type(), var.get_type());
+    assert_eq!(
+        HashMap::<isize, Vec<f64>>::from_variable(restored).unwrap(),
+        scores
+    );
+}
+
+#[test]
+fn streams_are_null() {
+    let stream = Variable::not_inited_var(VarType::OutStream).unwrap();
+    assert_eq!(serde_json::to_string(&stream).unwrap(), "null");
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "serial")]
+
+use sustlang::{RunningScript, Script, ScriptError};
+
+#[test]
+fn open_serial_reports_missing_port() {
+    let script = Script::parse(
+        "INIT_VAR string path\nSET_VAR path /dev/sustlang-missing-port\nINIT_VAR int baud\nSET_VAR baud 9600\n\
+         INIT_VAR in_stream port_in\nINIT_VAR out_stream port_out\nOPEN_SERIAL path baud port_in port_out\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let result = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run();
+
+    assert!(matches!(result, Err((ScriptError::FileNotFoundError(_), _))));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::run;
+
+use sustlang::{ScriptError, VarType};
+
+const SETS: &str = "INIT_VAR set[int] a\nINIT_VAR set[int] b\nINIT_VAR int x\n\
+SET_VAR x 1\nSET_ADD a x\nSET_VAR x 2\nSET_ADD a x\nSET_ADD b x\nSET_ADD a x\n\
+SET_VAR x 3\nSET_ADD a x\nSET_ADD b x\nSET_VAR x 4\nSET_ADD b x\n";
+
+#[test]
+fn set_commands() {
+    let globals = run(format!(
+        "{}INIT_VAR set[int] union\nSET_UNION a b union\nINIT_VAR set[int] common\nSET_INTERSECT a b common\n\
+         INIT_VAR bool has\nSET_VAR x 1\nSET_HAS a x has\nSET_REMOVE a x\nINIT_VAR bool removed\nSET_HAS a x removed\n\
+         SET_REMOVE a x\nINIT_VAR string text\nTO_STRING union text\nINIT_VAR string json\nJSON_STRINGIFY common json\n",
+        SETS
+    ))
+    .unwrap();
+
+    assert_eq!(globals["text"].as_str().unwrap(), "{1, 2, 3, 4}");
+    assert_eq!(globals["json"].as_str().unwrap(), "[2,3]");
+    assert!(globals["has"].as_bool().unwrap());
+    assert!(!globals["removed"].as_bool().unwrap());
+    assert_eq!(globals["a"].as_set().unwrap().len(), 2);
+    assert!(globals["union"].get_type() == VarType::from_name("set[int]").unwrap());
+}
+
+#[test]
+fn set_types_are_checked() {
+    let error = run("INIT_VAR set[int] a\nINIT_VAR string x\nSET_VAR x 1\nSET_ADD a x\n")
+        .err()
+        .unwrap();
+    assert!(matches!(error, ScriptError::TypeMismatchError { .. }));
+
+    let error =
+        run("INIT_VAR set[int] a\nINIT_VAR set[string] b\nINIT_VAR set[int] c\nSET_UNION a b c\n")
+            .err()
+            .unwrap();
+    assert!(matches!(error, ScriptError::TypeMismatchError { .. }));
+}
+
+#[test]
+fn json_array_becomes_a_set() {
+    let globals = run(
+        "INIT_VAR string json\nSET_VAR json [\"b\", \"a\", \"b\"]\nINIT_VAR set[string] names\nJSON_PARSE json names\n\
+         INIT_VAR string name\nSET_VAR name a\nINIT_VAR bool has\nSET_HAS names name has\n",
+    )
+    .unwrap();
+    assert_eq!(globals["names"].as_set().unwrap().len(), 2);
+    assert!(globals["has"].as_bool().unwrap());
+}
+
+#[test]
+fn set_add_does_not_change_copies() {
+    let globals = run(format!(
+        "{}INIT_VAR set[int] copy\nCOPY_VAR a copy\nSET_VAR x 5\nSET_ADD a x\nSET_REMOVE copy x\nSET_VAR x 1\nSET_REMOVE copy x\n",
+        SETS
+    ))
+    .unwrap();
+
+    assert_eq!(globals["a"].as_set().unwrap().len(), 4);
+    assert_eq!(globals["copy"].as_set().unwrap().len(), 2);
+}
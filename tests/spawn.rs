@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+mod common;
+
+use sustlang::{ScriptError, Variable};
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+fn run(cmd: &str, args: &str, rest: &str) -> Result<HashMap<String, Variable>, ScriptError> {
+    common::run(format!(
+        "INIT_VAR string cmd\nSET_VAR cmd {}\nINIT_VAR string json\nSET_VAR json {}\n\
+         INIT_VAR list[string] cmd_args\nJSON_PARSE json cmd_args\nINIT_VAR atomic code\n\
+         INIT_VAR in_stream child_out\nINIT_VAR out_stream child_in\nSPAWN cmd cmd_args code child_out child_in\n{}",
+        cmd, args, rest
+    ))
+}
+
+fn exit_code(vars: &HashMap<String, Variable>) -> isize {
+    let code = vars["code"].as_atomic().unwrap();
+    for _ in 0..500 {
+        if code.load(Ordering::SeqCst) != -1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    code.load(Ordering::SeqCst)
+}
+
+#[test]
+fn spawn_pipes_stdio() {
+    let vars = run(
+        "cat",
+        "[]",
+        "INIT_VAR string text\nSET_VAR text hello\nWRITE text child_in\nCLOSE child_in\n\
+         INIT_VAR string output\nREAD_ALL output child_out\n",
+    )
+    .unwrap();
+
+    assert_eq!(vars["output"].as_str().unwrap(), "hello");
+    assert_eq!(exit_code(&vars), 0);
+}
+
+#[test]
+fn spawn_sets_exit_code() {
+    let vars = run("sh", r#"["-c", "exit 3"]"#, "").unwrap();
+
+    assert_eq!(exit_code(&vars), 3);
+}
+
+#[test]
+fn spawn_reports_missing_program() {
+    let result = run("sustlang-missing-program", "[]", "");
+
+    assert!(matches!(result, Err(ScriptError::FileNotFoundError(_))));
+}
@@ -0,0 +1,77 @@
+use sustlang::{RunningScript, Script, Variable};
+
+use std::env;
+use std::fs;
+
+#[test]
+fn script_location_and_platform_are_set() {
+    let path = env::temp_dir().join(format!("sustlang-{}-location.sus", std::process::id()));
+    fs::write(&path, b"").unwrap();
+    let script = Script::parse(String::new()).unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .script_path(&path)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    let path = path.canonicalize().unwrap();
+    assert_eq!(
+        outcome.globals["script_path"].as_str().unwrap(),
+        path.to_string_lossy()
+    );
+    assert_eq!(
+        outcome.globals["script_dir"].as_str().unwrap(),
+        path.parent().unwrap().to_string_lossy()
+    );
+    assert_eq!(
+        outcome.globals["os_name"].as_str().unwrap(),
+        env::consts::OS
+    );
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn env_holds_environment_variables() {
+    let script = Script::parse(String::new()).unwrap_or_else(|_| panic!("parse failed"));
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["script_path"].as_str().unwrap(), "");
+    let vars = outcome.globals["env"].as_map().unwrap();
+    for (key, value) in env::vars() {
+        assert_eq!(
+            vars[&Variable::from_str(Some(key))].as_str().unwrap(),
+            value
+        );
+    }
+}
+
+#[test]
+fn env_get_and_set() {
+    let name = format!("SUSTLANG_TEST_{}", std::process::id());
+    let script = Script::parse(format!(
+        "INIT_VAR string name\nSET_VAR name {}\nINIT_VAR optional[string] before\nENV_GET name before\n\
+         INIT_VAR string value\nSET_VAR value on\nENV_SET name value\nINIT_VAR optional[string] after\nENV_GET name after\n",
+        name
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert!(outcome.globals["before"].as_option().unwrap().is_none());
+    let after = outcome.globals["after"].as_option().unwrap().unwrap();
+    assert_eq!(after.as_str().unwrap(), "on");
+    assert_eq!(env::var(name).unwrap(), "on");
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::run;
+
+use sustlang::ScriptError;
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+/// Server that answers `hello` and keeps the connection open without sending anything else
+fn serve() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.write_all(b"hello").unwrap();
+        thread::sleep(Duration::from_secs(5));
+    });
+    port
+}
+
+fn connect(port: u16, timeout: &str) -> String {
+    format!(
+        "INIT_VAR string addr\nSET_VAR addr 127.0.0.1\nINIT_VAR int port\nSET_VAR port {}\nINIT_VAR int timeout\nSET_VAR timeout 100\nINIT_VAR in_stream input\nINIT_VAR out_stream output\nOPEN_TCP_CONNECTION addr port input output{}\nINIT_VAR int five\nSET_VAR five 5\nINIT_VAR string first\nREAD_LENGTH first five input\n",
+        port, timeout
+    )
+}
+
+#[test]
+fn connection_reads_with_timeout() {
+    let vars = run(connect(serve(), " timeout")).unwrap();
+
+    assert_eq!(vars["first"].as_str().unwrap(), "hello");
+}
+
+#[test]
+fn connection_reads_until_timeout() {
+    let result = run(connect(serve(), " timeout") + "INIT_VAR string rest\nREAD_ALL rest input\n");
+
+    assert!(matches!(result, Err(ScriptError::StreamTimeoutError)));
+}
+
+#[test]
+fn set_stream_timeout_applies_to_connection() {
+    let script = connect(serve(), "")
+        + "SET_STREAM_TIMEOUT output timeout\nINIT_VAR string rest\nREAD rest input\n";
+    let result = run(script);
+
+    assert!(matches!(result, Err(ScriptError::StreamTimeoutError)));
+}
+
+#[test]
+fn set_stream_timeout_needs_network_stream() {
+    let result = run(
+        "INIT_VAR in_stream input\nINIT_VAR out_stream output\nOPEN_BUFFER input output\nINIT_VAR int timeout\nSET_VAR timeout 100\nSET_STREAM_TIMEOUT input timeout\n"
+            .to_string(),
+    );
+
+    assert!(matches!(result, Err(ScriptError::CommandArgsInvalidError)));
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use common::{builder, run, SharedOutput};
+
+use sustlang::{RunningScript, Script, ScriptError};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sustlang-{}-{}", std::process::id(), name));
+    fs::write(&path, b"").unwrap();
+    path
+}
+
+#[test]
+fn flush_writes_file_once() {
+    let path = temp_file("flush");
+    let result = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR out_stream file\nOPEN_FILE_OUT path file\n\
+         INIT_VAR string text\nSET_VAR text a\nWRITE text file\nFLUSH file\nSET_VAR text b\nWRITE text file\nFLUSH file\n",
+        path.display()
+    ));
+
+    assert!(result.is_ok());
+    assert_eq!(fs::read(&path).unwrap(), b"ab");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn closed_stream_can_not_be_written() {
+    let path = temp_file("close");
+    let result = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR out_stream file\nOPEN_FILE_OUT path file\n\
+         INIT_VAR string text\nSET_VAR text a\nWRITE text file\nCLOSE file\nWRITE text file\n",
+        path.display()
+    ));
+
+    assert!(matches!(result, Err(ScriptError::TypeMismatchError { .. })));
+    assert_eq!(fs::read(&path).unwrap(), b"a");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn open_file_out_modes() {
+    let path = temp_file("modes");
+    fs::write(&path, b"old").unwrap();
+    let open = |mode: &str| {
+        run(format!(
+            "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR string mode\nSET_VAR mode {}\nINIT_VAR out_stream file\nOPEN_FILE_OUT path file mode\n\
+             INIT_VAR string text\nSET_VAR text new\nWRITE text file\n",
+            path.display(),
+            mode
+        ))
+    };
+
+    assert!(open("append").is_ok());
+    assert_eq!(fs::read(&path).unwrap(), b"oldnew");
+    assert!(open("truncate").is_ok());
+    assert_eq!(fs::read(&path).unwrap(), b"new");
+    assert!(matches!(
+        open("create_new"),
+        Err(ScriptError::FileWriteError(_))
+    ));
+    assert!(matches!(
+        open("sideways"),
+        Err(ScriptError::CommandArgsInvalidError)
+    ));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn open_file_in_reads_from_disk() {
+    let path = temp_file("in");
+    fs::write(&path, b"first\nsecond\n").unwrap();
+    let globals = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR in_stream file\nOPEN_FILE_IN path file\n\
+         INIT_VAR string line\nREAD_LINE line file\nINIT_VAR string rest\nREAD_ALL rest file\n",
+        path.display()
+    ))
+    .unwrap_or_else(|_| panic!("run failed"));
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(globals["line"].as_str().unwrap(), "first");
+    assert_eq!(globals["rest"].as_str().unwrap(), "second\n");
+
+    let missing = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR in_stream file\nOPEN_FILE_IN path file\n",
+        path.display()
+    ));
+    assert!(matches!(missing, Err(ScriptError::FileNotFoundError(_))));
+}
+
+#[test]
+fn seek_moves_in_file_stream() {
+    let path = temp_file("seek");
+    fs::write(&path, b"header:0000").unwrap();
+    let globals = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR string mode\nSET_VAR mode write\n\
+         INIT_VAR file_stream file\nOPEN_FILE path file mode\n\
+         INIT_VAR int offset\nSET_VAR offset -4\nINIT_VAR string whence\nSET_VAR whence end\nSEEK file offset whence\n\
+         INIT_VAR int position\nTELL file position\nINIT_VAR string text\nSET_VAR text 1234\nWRITE text file\n\
+         SET_VAR offset 0\nSET_VAR whence start\nSEEK file offset whence\n\
+         INIT_VAR string head\nINIT_VAR int size\nSET_VAR size 6\nREAD_LENGTH head size file\n",
+        path.display()
+    ))
+    .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(globals["position"].as_int().unwrap(), 7);
+    assert_eq!(globals["head"].as_str().unwrap(), "header");
+    assert_eq!(fs::read(&path).unwrap(), b"header:1234");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn seek_before_start_is_an_error() {
+    let path = temp_file("seek-start");
+    let result = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR file_stream file\nOPEN_FILE path file\n\
+         INIT_VAR int offset\nSET_VAR offset -1\nINIT_VAR string whence\nSET_VAR whence start\nSEEK file offset whence\n",
+        path.display()
+    ));
+
+    assert!(matches!(result, Err(ScriptError::RangeError)));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn pipe_copies_file_with_limit() {
+    let source = temp_file("pipe-source");
+    let whole = temp_file("pipe-whole");
+    let head = temp_file("pipe-head");
+    let data: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+    fs::write(&source, &data).unwrap();
+
+    let result = run(format!(
+        "INIT_VAR string path\nSET_VAR path {}\nINIT_VAR in_stream source\nOPEN_FILE_IN path source\n\
+         SET_VAR path {}\nINIT_VAR out_stream whole\nOPEN_FILE_OUT path whole\nPIPE source whole\nCLOSE whole\n\
+         SET_VAR path {}\nOPEN_FILE_IN path source\nSET_VAR path {}\nINIT_VAR out_stream head\nOPEN_FILE_OUT path head\n\
+         INIT_VAR int limit\nSET_VAR limit 5000\nPIPE source head limit\nCLOSE head\n",
+        source.display(),
+        whole.display(),
+        source.display(),
+        head.display()
+    ));
+
+    assert!(result.is_ok());
+    assert_eq!(fs::read(&whole).unwrap(), data);
+    assert_eq!(fs::read(&head).unwrap(), &data[..5000]);
+    for path in [source, whole, head] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn buffer_captures_function_output() {
+    let globals = run(
+        "FUNC null greet target out_stream\n    INIT_VAR string text\n    SET_VAR text hello\n    WRITE text target\nFUNC_END\n\
+         INIT_VAR in_stream reader\nINIT_VAR out_stream writer\nOPEN_BUFFER reader writer\n\
+         USE_FUNC greet null writer\nUSE_FUNC greet null writer\n\
+         INIT_VAR string captured\nREAD_ALL captured reader\nINIT_VAR string empty\nREAD_ALL empty reader\n",
+    )
+    .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(globals["captured"].as_str().unwrap(), "hellohello");
+    assert_eq!(globals["empty"].as_str().unwrap(), "");
+}
+
+#[test]
+fn cerr_is_separate_from_cout() {
+    let stdout = SharedOutput::default();
+    let stderr = SharedOutput::default();
+    builder(
+        "INIT_VAR string data\nSET_VAR data 42\nWRITE data cout\n\
+         INIT_VAR string warning\nSET_VAR warning skipped a line\nWRITE_LINE warning cerr\n",
+    )
+    .stdout(stdout.clone())
+    .stderr(stderr.clone())
+    .build()
+    .unwrap()
+    .run()
+    .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(stdout.bytes(), b"42");
+    assert_eq!(stderr.bytes(), b"skipped a line\n");
+}
+
+#[test]
+fn set_standard_vars_with_separate_stderr() {
+    let script = Script::parse(
+        "INIT_VAR string data\nSET_VAR data 42\nWRITE data cout\nWRITE data cerr\n".to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+    let stdout = SharedOutput::default();
+    let stderr = SharedOutput::default();
+
+    let mut running_script = RunningScript::new(script);
+    running_script
+        .set_standard_vars(
+            Vec::new(),
+            Box::new(stdout.clone()),
+            Box::new(std::io::empty()),
+        )
+        .unwrap();
+    running_script.set_stderr(Box::new(stderr.clone())).unwrap();
+    running_script
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(stdout.bytes(), b"42");
+    assert_eq!(stderr.bytes(), b"42");
+}
@@ -0,0 +1,36 @@
+use sustlang::{RunningScript, Script, ScriptError};
+
+fn styled(style: &str, styled_output: bool) -> Result<String, ScriptError> {
+    let script = Script::parse(format!(
+        "INIT_VAR string text\nSET_VAR text done\nINIT_VAR string result\nSTYLE_TEXT text {} result\n",
+        style
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let mut running_script = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap();
+    running_script.set_styled_output(styled_output);
+
+    let outcome = running_script.run_with_result().map_err(|(e, _)| e)?;
+    Ok(outcome.globals["result"].as_str().unwrap())
+}
+
+#[test]
+fn style_text_wraps_in_ansi_codes() {
+    assert_eq!(styled("bold,green", true).unwrap(), "\x1b[1;32mdone\x1b[0m");
+}
+
+#[test]
+fn style_text_is_plain_without_terminal() {
+    assert_eq!(styled("red", false).unwrap(), "done");
+}
+
+#[test]
+fn unknown_style_is_an_error() {
+    assert!(matches!(
+        styled("sparkly", false),
+        Err(ScriptError::CommandArgsInvalidError)
+    ));
+}
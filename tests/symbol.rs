@@ -0,0 +1,31 @@
+use sustlang::{Symbol, SymbolTable, VarPath};
+
+#[test]
+fn symbols_are_interned_in_order() {
+    let mut symbols = SymbolTable::new();
+    assert_eq!(symbols.intern("main"), Symbol(0));
+    assert_eq!(symbols.intern("print"), Symbol(1));
+    assert_eq!(symbols.intern("main"), Symbol(0));
+    assert_eq!(symbols.get("print"), Some(Symbol(1)));
+    assert_eq!(symbols.get("missing"), None);
+    assert_eq!(symbols.name(Symbol(1)), Some("print"));
+    assert_eq!(symbols.len(), 2);
+}
+
+#[test]
+fn var_paths_are_split_once() {
+    let path = VarPath::parse("users.0.name");
+    assert_eq!(path.root, "users");
+    assert_eq!(path.segments, vec!["0", "name"]);
+    assert_eq!(path.to_string(), "users.0.name");
+    assert!(VarPath::parse("users").segments.is_empty());
+}
+
+#[test]
+fn variable_names_share_ids() {
+    let id = Symbol::variable("users");
+    assert_eq!(Symbol::variable("users"), id);
+    assert_ne!(Symbol::variable("items"), id);
+    assert_eq!(id.variable_name(), "users");
+    assert_eq!(VarPath::parse("users.0.name").id, id);
+}
@@ -0,0 +1,133 @@
+use sustlang::{RunningScript, Script, ScriptError, Variable};
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn script_state_is_send_and_sync() {
+    assert_send_sync::<RunningScript>();
+    assert_send_sync::<Variable>();
+}
+
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn thread_writes_to_shared_stream() {
+    let script = Script::parse(
+        "FUNC null greet text string\n    WRITE text cout\nFUNC_END\nINIT_VAR string text\nSET_VAR text hi\nINIT_VAR thread worker\nNEW_THREAD_HANDLE greet worker text\nJOIN_THREAD worker\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let output = SharedOutput::default();
+    RunningScript::builder(script)
+        .stdout(output.clone())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(output.0.lock().unwrap().as_slice(), b"hi");
+}
+
+fn counter_after(spawn: &str) -> isize {
+    let script = Script::parse(format!(
+        "FUNC null bump\n    SET_VAR counter 5\nFUNC_END\nINIT_VAR int counter\nSET_VAR counter 1\nINIT_VAR thread worker\n{spawn} bump worker\nJOIN_THREAD worker\n"
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    outcome.globals["counter"].as_int().unwrap()
+}
+
+#[test]
+fn isolated_thread_works_on_a_copy_of_globals() {
+    assert_eq!(counter_after("NEW_THREAD_HANDLE"), 5);
+    assert_eq!(counter_after("NEW_ISOLATED_THREAD"), 1);
+}
+
+#[test]
+fn new_thread_passes_arguments_without_handle() {
+    let script = Script::parse(
+        "FUNC null greet first string second string\n    WRITE first cout\n    WRITE second cout\nFUNC_END\nINIT_VAR string a\nSET_VAR a hi\nINIT_VAR string b\nSET_VAR b !\nNEW_THREAD greet a b\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let output = SharedOutput::default();
+    RunningScript::builder(script)
+        .stdout(output.clone())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(output.0.lock().unwrap().as_slice(), b"hi!");
+}
+
+#[test]
+fn atomic_add_wraps_around() {
+    let script = Script::parse(format!(
+        "INIT_VAR int start\nSET_VAR start {}\nINIT_VAR atomic counter\nATOMIC_NEW counter start\n\
+         INIT_VAR int one\nSET_VAR one 1\nINIT_VAR int added\nATOMIC_ADD counter one added\n\
+         INIT_VAR int current\nATOMIC_GET counter current\n",
+        isize::MAX
+    ))
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let outcome = RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"));
+
+    assert_eq!(outcome.globals["added"].as_int().unwrap(), isize::MIN);
+    assert_eq!(outcome.globals["current"].as_int().unwrap(), isize::MIN);
+}
+
+#[test]
+fn errors_of_other_threads_are_returned() {
+    let script = Script::parse(
+        "FUNC null fail\n    INIT_VAR bool flag\n    WRITE flag cout\nFUNC_END\n\
+         NEW_THREAD fail\nNEW_THREAD fail\n"
+            .to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+
+    let error = match RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_traceback()
+    {
+        Ok(_) => panic!("run succeeded"),
+        Err(error) => error,
+    };
+
+    assert!(matches!(error.error, ScriptError::TypeMismatchError { .. }));
+    assert_eq!(error.thread_errors.len(), 1);
+    assert!(matches!(
+        error.thread_errors[0].error,
+        ScriptError::TypeMismatchError { .. }
+    ));
+}
@@ -0,0 +1,91 @@
+use sustlang::{RunningScript, Script, ScriptError, StackFrame};
+
+const SCRIPT: &str = "FUNC null inner
+    WRITE missing cout
+FUNC_END
+FUNC null outer
+    USE_FUNC inner null
+FUNC_END
+USE_FUNC outer null
+";
+
+fn frame(function: &str, line: usize) -> StackFrame {
+    StackFrame {
+        function: function.to_string(),
+        line,
+        file: None,
+    }
+}
+
+#[test]
+fn runtime_error_has_call_stack() {
+    for bytecode in [true, false] {
+        let script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+        let error = match RunningScript::builder(script)
+            .stdout(Vec::new())
+            .bytecode(bytecode)
+            .build()
+            .unwrap()
+            .run_with_traceback()
+        {
+            Ok(_) => panic!("run succeeded"),
+            Err(error) => error,
+        };
+
+        assert!(matches!(error.error, ScriptError::UnknownVarError(_)));
+        assert_eq!(
+            error.traceback,
+            vec![frame("main", 7), frame("outer", 5), frame("inner", 2)]
+        );
+        assert_eq!(
+            error.to_string(),
+            "unknown variable `missing`\n  in main at line 7\n  in outer at line 5\n  in inner at line 2"
+        );
+    }
+}
+
+#[test]
+fn finished_calls_are_not_in_call_stack() {
+    let script = Script::parse(
+        "FUNC null fine\nFUNC_END\nUSE_FUNC fine null\nWRITE missing cout\n".to_string(),
+    )
+    .unwrap_or_else(|_| panic!("parse failed"));
+    let error = match RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_traceback()
+    {
+        Ok(_) => panic!("run succeeded"),
+        Err(error) => error,
+    };
+
+    assert_eq!(error.traceback, vec![frame("main", 4)]);
+}
+
+#[test]
+fn imported_commands_report_their_file() {
+    let mut script = Script::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+    script.set_source_file("lib/strings.sust");
+    let script = Script::from_bytes(&script.to_bytes()).unwrap();
+    assert_eq!(
+        script.functions[0].commands[0].location(),
+        "lib/strings.sust:2"
+    );
+
+    let error = match RunningScript::builder(script)
+        .stdout(Vec::new())
+        .build()
+        .unwrap()
+        .run_with_traceback()
+    {
+        Ok(_) => panic!("run succeeded"),
+        Err(error) => error,
+    };
+
+    assert_eq!(error.command.location(), "lib/strings.sust:2");
+    assert_eq!(
+        error.to_string(),
+        "unknown variable `missing`\n  in main at lib/strings.sust:7\n  in outer at lib/strings.sust:5\n  in inner at lib/strings.sust:2"
+    );
+}
@@ -0,0 +1,53 @@
+use sustlang::{CommandType, Script, ScriptError, VarType};
+
+#[test]
+fn type_aliases_are_expanded() {
+    let script = Script::parse(
+        "TYPE_ALIAS headers map[string,string]\n\
+         TYPE_ALIAS batch list[headers]\n\
+         FUNC batch send url string data headers\n\
+         FUNC_END\n\
+         INIT_VAR batch all\n\
+         TEMP_VAR optional[headers] maybe none\n"
+            .to_string(),
+    )
+    .unwrap();
+
+    let headers = VarType::Map(Box::new(VarType::String), Box::new(VarType::String));
+    let func = &script.functions[0];
+    assert_eq!(func.result_type, VarType::List(Box::new(headers.clone())));
+    assert_eq!(func.parameters[1], ("data".to_string(), headers.clone()));
+
+    assert_eq!(script.commands.len(), 2);
+    assert_eq!(script.commands[0].command_type, CommandType::InitVar);
+    assert_eq!(
+        VarType::from_name(&script.commands[0].args[0]).unwrap(),
+        VarType::List(Box::new(headers.clone()))
+    );
+    assert_eq!(
+        VarType::from_name(&script.commands[1].args[0]).unwrap(),
+        VarType::Optional(Box::new(headers))
+    );
+}
+
+#[test]
+fn invalid_type_aliases_are_reported() {
+    for (text, line) in [
+        ("TYPE_ALIAS int string", 1),
+        ("TYPE_ALIAS list string", 1),
+        ("TYPE_ALIAS a string\nTYPE_ALIAS a int", 2),
+    ] {
+        let (_, diagnostics) = Script::parse_lenient(text.to_string());
+        assert!(
+            matches!(diagnostics[..], [(ScriptError::TypeAliasError, l)] if l == line),
+            "{}",
+            text
+        );
+    }
+
+    let (_, diagnostics) = Script::parse_lenient("TYPE_ALIAS a unknown\nINIT_VAR a x".to_string());
+    assert!(matches!(
+        diagnostics[0],
+        (ScriptError::TypeUnknownError(_), 1)
+    ));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{builder, outcome};
+
+use sustlang::{ScriptError, ScriptOutcome};
+
+use std::io::Cursor;
+
+fn run_with_input(text: &str, input: &str) -> Result<ScriptOutcome, ScriptError> {
+    outcome(builder(text).stdin(Cursor::new(input.as_bytes().to_vec())))
+}
+
+#[test]
+fn strings_are_indexed_by_symbols() {
+    let outcome = run_with_input(
+        "INIT_VAR string text\nSET_VAR text привет, мир\nINIT_VAR int size\nSTRING_SIZE text size\n\
+         INIT_VAR int index\nSET_VAR index 1\nINIT_VAR char second\nGET_SYMBOL text index second\n\
+         INIT_VAR list[char] chars\nTO_CHARS text chars\nINIT_VAR int chars_size\nLIST_SIZE chars chars_size\n\
+         INIT_VAR string word\nCOPY_VAR text word\nINIT_VAR int start\nSET_VAR start 8\nSUB_STR word start\n\
+         INIT_VAR char first\nTO_CHAR text first\nINIT_VAR bool upper\nIS_UPPER first upper\n\
+         INIT_VAR string capital\nSET_VAR capital П\nTO_CHAR capital first\nIS_UPPER first upper\n",
+        "",
+    )
+    .unwrap();
+
+    assert_eq!(outcome.globals["size"].as_int().unwrap(), 11);
+    assert_eq!(outcome.globals["second"].as_char().unwrap(), 'р');
+    assert_eq!(outcome.globals["chars_size"].as_int().unwrap(), 11);
+    assert_eq!(outcome.globals["word"].as_str().unwrap(), "мир");
+    assert!(outcome.globals["upper"].as_bool().unwrap());
+
+    let error = run_with_input(
+        "INIT_VAR string text\nSET_VAR text ёж\nINIT_VAR int index\nSET_VAR index 2\nINIT_VAR char c\nGET_SYMBOL text index c\n",
+        "",
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(
+        error,
+        ScriptError::IndexOutOfBoundsError { index: 2, len: 2 }
+    ));
+}
+
+#[test]
+fn chars_are_unicode_scalar_values() {
+    let outcome = run_with_input(
+        "INIT_VAR char code\nSET_VAR code 1099\nINIT_VAR char symbol\nSET_VAR symbol ы\n\
+         INIT_VAR bool same\nEQUALS code symbol same\nINIT_VAR char emoji\nREAD_CHAR emoji cin\n\
+         INIT_VAR char next\nREAD_CHAR next cin\nINIT_VAR string text\nSET_VAR text >\nADD_STR text emoji\n",
+        "🦀a",
+    )
+    .unwrap();
+
+    assert!(outcome.globals["same"].as_bool().unwrap());
+    assert_eq!(outcome.globals["emoji"].as_char().unwrap(), '🦀');
+    assert_eq!(outcome.globals["next"].as_char().unwrap(), 'a');
+    assert_eq!(outcome.globals["text"].as_str().unwrap(), ">🦀");
+
+    let error = run_with_input("INIT_VAR char code\nSET_VAR code 55296\n", "")
+        .err()
+        .unwrap();
+    assert!(matches!(error, ScriptError::ParseVarError));
+}
+
+#[test]
+fn string_and_map_sizes() {
+    let outcome = run_with_input(
+        "INIT_VAR string text\nSET_VAR text e\u{301}ж🦀\nINIT_VAR int chars\nSTRING_SIZE text chars\n\
+         INIT_VAR int bytes\nSTRING_SIZE_BYTES text bytes\nINIT_VAR int graphemes\nSTRING_SIZE_GRAPHEMES text graphemes\n\
+         INIT_VAR string json\nSET_VAR json {\"a\": 1, \"b\": 2}\nINIT_VAR map[string,int] map\nJSON_PARSE json map\n\
+         INIT_VAR int map_size\nMAP_SIZE map map_size\n",
+        "",
+    )
+    .unwrap();
+
+    assert_eq!(outcome.globals["chars"].as_int().unwrap(), 4);
+    assert_eq!(outcome.globals["bytes"].as_int().unwrap(), 9);
+    assert_eq!(outcome.globals["graphemes"].as_int().unwrap(), 3);
+    assert_eq!(outcome.globals["map_size"].as_int().unwrap(), 2);
+}
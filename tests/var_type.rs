@@ -0,0 +1,49 @@
+use sustlang::{ScriptError, VarType};
+
+fn map(key: VarType, value: VarType) -> VarType {
+    VarType::Map(Box::new(key), Box::new(value))
+}
+
+#[test]
+fn nested_generics() {
+    assert_eq!(
+        VarType::from_name("map[string,map[string,int]]").unwrap(),
+        map(VarType::String, map(VarType::String, VarType::Integer))
+    );
+    assert_eq!(
+        VarType::from_name("list[ optional[ map[int, list[char]] ] ]").unwrap(),
+        VarType::List(Box::new(VarType::Optional(Box::new(map(
+            VarType::Integer,
+            VarType::List(Box::new(VarType::Char))
+        )))))
+    );
+}
+
+#[test]
+fn names_round_trip() {
+    let var_type = map(VarType::String, VarType::List(Box::new(VarType::Float)));
+    assert_eq!(VarType::from_name(&var_type.to_name()).unwrap(), var_type);
+}
+
+#[test]
+fn error_positions() {
+    for (name, position) in [
+        ("map[", 4),
+        ("map[int]", 7),
+        ("list[int", 8),
+        ("list[int]]", 9),
+        ("map[string, nope]", 12),
+        ("", 0),
+    ] {
+        match VarType::parse(name) {
+            Err((ScriptError::TypeUnknownError(_), pos)) => assert_eq!(pos, position, "{}", name),
+            _ => panic!("{} should not parse", name),
+        }
+    }
+}
+
+#[test]
+fn deep_nesting_is_rejected() {
+    let name = format!("{}int{}", "list[".repeat(10000), "]".repeat(10000));
+    assert!(VarType::from_name(&name).is_err());
+}
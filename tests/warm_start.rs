@@ -0,0 +1,53 @@
+use sustlang::{CompiledScript, ScriptError, VarType, Variable};
+
+const SCRIPT: &str = "FUNC int twice n int
+    COPY_VAR n result
+    ADD_INT result n
+FUNC_END
+INIT_VAR int doubled
+USE_FUNC twice doubled input
+USE_FUNC check null doubled
+";
+
+fn run(compiled: &CompiledScript, input: isize, bytecode: bool) -> isize {
+    compiled
+        .builder()
+        .stdout(Vec::new())
+        .global_var("input", Variable::from_int(Some(input)))
+        .native_fn("check", vec![VarType::Integer], VarType::Null, |_| {
+            Ok(Variable::from_null())
+        })
+        .bytecode(bytecode)
+        .build()
+        .unwrap()
+        .run_with_result()
+        .unwrap_or_else(|_| panic!("run failed"))
+        .globals["doubled"]
+        .as_int()
+        .unwrap()
+}
+
+#[test]
+fn compiled_script_runs_many_times() {
+    let compiled =
+        CompiledScript::parse(SCRIPT.to_string()).unwrap_or_else(|_| panic!("parse failed"));
+
+    assert_eq!(run(&compiled, 2, true), 4);
+    assert_eq!(run(&compiled, 5, true), 10);
+    assert_eq!(run(&compiled, 7, false), 14);
+}
+
+#[test]
+fn instances_have_own_globals() {
+    let compiled = CompiledScript::parse("INIT_VAR int count\nSET_VAR count 1\n".to_string())
+        .unwrap_or_else(|_| panic!("parse failed"));
+
+    let first = compiled.instantiate();
+    let second = compiled.instantiate();
+    first.run().unwrap_or_else(|_| panic!("run failed"));
+
+    assert!(matches!(
+        second.get_var("count".to_string(), &mut Default::default()),
+        Err(ScriptError::UnknownVarError(_))
+    ));
+}